@@ -0,0 +1,572 @@
+//! Structured `cargo audit` results
+//!
+//! `audit` runs `cargo audit --json` and parses the report into one [`Advisory`] per
+//! vulnerability or warning (unmaintained, unsound, yanked). Severity is derived from the
+//! advisory's CVSS v3 vector, since cargo-audit only reports the vector. Each advisory gets the
+//! dependency path that pulls the crate in (from `cargo tree -i`) and a suggestion for the
+//! smallest change that picks up a patched release.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// CVSS qualitative severity rating
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    None,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn from_score(score: f64) -> Self {
+        match score {
+            s if s >= 9.0 => Severity::Critical,
+            s if s >= 7.0 => Severity::High,
+            s if s >= 4.0 => Severity::Medium,
+            s if s > 0.0 => Severity::Low,
+            _ => Severity::None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::None => "none",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+/// One vulnerability or warning for one locked package
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Advisory {
+    /// `RUSTSEC-2020-0071`, or `yanked` for yanked crates without an advisory
+    pub id: String,
+    /// `vulnerability`, `unmaintained`, `unsound`, `yanked` or `notice`
+    pub kind: String,
+    pub package: String,
+    pub version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// CVE and GHSA identifiers
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// Version requirements of releases with the fix, e.g. `>=0.2.23`
+    pub patched: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cvss: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cvss_score: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+    /// From a workspace member down to the affected package
+    pub dependency_path: Vec<String>,
+    pub suggestion: String,
+}
+
+impl Advisory {
+    pub fn is_vulnerability(&self) -> bool {
+        self.kind == "vulnerability"
+    }
+
+    fn render(&self) -> String {
+        let mut out = format!("  {} {} {}", self.id, self.package, self.version);
+        if !self.is_vulnerability() {
+            out.push_str(&format!(" ({})", self.kind));
+        }
+        if let Some(title) = &self.title {
+            out.push_str(&format!(": {title}"));
+        }
+        let mut tags: Vec<String> = Vec::new();
+        if let (Some(severity), Some(score)) = (self.severity, self.cvss_score) {
+            tags.push(format!("{} {score:.1}", severity.as_str()));
+        }
+        tags.extend(
+            self.aliases
+                .iter()
+                .filter(|a| a.starts_with("CVE-"))
+                .cloned(),
+        );
+        if !tags.is_empty() {
+            out.push_str(&format!(" [{}]", tags.join(", ")));
+        }
+        if !self.dependency_path.is_empty() {
+            out.push_str(&format!(
+                "\n    path: {}",
+                self.dependency_path.join(" -> ")
+            ));
+        }
+        out.push_str(&format!("\n    fix: {}", self.suggestion));
+        out
+    }
+}
+
+/// Everything parsed from one `cargo audit --json` run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuditReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dependency_count: Option<usize>,
+    /// Vulnerabilities among `advisories`
+    pub vulnerabilities: usize,
+    /// Unmaintained, unsound and yanked warnings among `advisories`
+    pub warnings: usize,
+    /// Advisories hidden by `min_severity`
+    pub filtered_out: usize,
+    pub advisories: Vec<Advisory>,
+}
+
+impl AuditReport {
+    /// Drop vulnerabilities rated below `min_severity`. Vulnerabilities without a CVSS vector
+    /// cannot be ranked and are kept, as are warnings, which have no severity.
+    pub fn filter_min_severity(&mut self, min_severity: Severity) {
+        let before = self.advisories.len();
+        self.advisories.retain(|a| {
+            !a.is_vulnerability() || a.severity.is_none_or(|severity| severity >= min_severity)
+        });
+        self.filtered_out += before - self.advisories.len();
+        self.recount();
+    }
+
+    fn recount(&mut self) {
+        self.vulnerabilities = self
+            .advisories
+            .iter()
+            .filter(|a| a.is_vulnerability())
+            .count();
+        self.warnings = self.advisories.len() - self.vulnerabilities;
+    }
+
+    /// `Audit:` line with counts by severity, then each advisory with its path and fix
+    pub fn render(&self) -> String {
+        let mut by_severity: BTreeMap<Severity, usize> = BTreeMap::new();
+        for advisory in self.advisories.iter().filter(|a| a.is_vulnerability()) {
+            if let Some(severity) = advisory.severity {
+                *by_severity.entry(severity).or_default() += 1;
+            }
+        }
+        let mut out = format!("Audit: {} vulnerabilit", self.vulnerabilities);
+        out.push_str(if self.vulnerabilities == 1 {
+            "y"
+        } else {
+            "ies"
+        });
+        if !by_severity.is_empty() {
+            let counts: Vec<String> = by_severity
+                .iter()
+                .rev()
+                .map(|(severity, count)| format!("{count} {}", severity.as_str()))
+                .collect();
+            out.push_str(&format!(" ({})", counts.join(", ")));
+        }
+        out.push_str(&format!(", {} warning(s)", self.warnings));
+        if let Some(count) = self.dependency_count {
+            out.push_str(&format!(" in {count} dependencies"));
+        }
+        if self.filtered_out > 0 {
+            out.push_str(&format!(
+                ", {} below min_severity not shown",
+                self.filtered_out
+            ));
+        }
+        for advisory in &self.advisories {
+            out.push('\n');
+            out.push_str(&advisory.render());
+        }
+        out
+    }
+}
+
+#[derive(Deserialize)]
+struct RawReport {
+    lockfile: Option<RawLockfile>,
+    vulnerabilities: RawVulnerabilities,
+    #[serde(default)]
+    warnings: BTreeMap<String, Vec<RawFinding>>,
+}
+
+#[derive(Deserialize)]
+struct RawLockfile {
+    #[serde(rename = "dependency-count")]
+    dependency_count: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct RawVulnerabilities {
+    #[serde(default)]
+    list: Vec<RawFinding>,
+}
+
+#[derive(Deserialize)]
+struct RawFinding {
+    #[serde(default)]
+    kind: Option<String>,
+    advisory: Option<RawAdvisory>,
+    versions: Option<RawVersions>,
+    package: RawPackage,
+}
+
+#[derive(Deserialize)]
+struct RawAdvisory {
+    id: String,
+    title: Option<String>,
+    url: Option<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    cvss: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawVersions {
+    #[serde(default)]
+    patched: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct RawPackage {
+    name: String,
+    version: String,
+}
+
+/// Parse the JSON report of `cargo audit --json`. Dependency paths and suggestions are left
+/// empty; see [`parse_inverted_tree`] and [`upgrade_suggestion`].
+pub fn parse_audit_json(stdout: &str) -> Result<AuditReport, String> {
+    let json = stdout
+        .lines()
+        .find(|l| l.trim_start().starts_with('{'))
+        .ok_or_else(|| "cargo audit printed no JSON report".to_string())?;
+    let raw: RawReport =
+        serde_json::from_str(json).map_err(|e| format!("Failed to parse cargo audit JSON: {e}"))?;
+
+    let findings = raw
+        .vulnerabilities
+        .list
+        .into_iter()
+        .map(|finding| ("vulnerability".to_string(), finding))
+        .chain(raw.warnings.into_iter().flat_map(|(kind, findings)| {
+            findings.into_iter().map(move |finding| {
+                (
+                    finding.kind.clone().unwrap_or_else(|| kind.clone()),
+                    finding,
+                )
+            })
+        }));
+
+    let mut report = AuditReport {
+        dependency_count: raw.lockfile.and_then(|l| l.dependency_count),
+        ..Default::default()
+    };
+    for (kind, finding) in findings {
+        let advisory = finding.advisory;
+        let cvss = advisory.as_ref().and_then(|a| a.cvss.clone());
+        let cvss_score = cvss.as_deref().and_then(cvss_base_score);
+        report.advisories.push(Advisory {
+            id: advisory
+                .as_ref()
+                .map_or_else(|| kind.clone(), |a| a.id.clone()),
+            kind,
+            package: finding.package.name,
+            version: finding.package.version,
+            title: advisory.as_ref().and_then(|a| a.title.clone()),
+            url: advisory.as_ref().and_then(|a| a.url.clone()),
+            aliases: advisory.map(|a| a.aliases).unwrap_or_default(),
+            patched: finding.versions.map(|v| v.patched).unwrap_or_default(),
+            cvss,
+            cvss_score,
+            severity: cvss_score.map(Severity::from_score),
+            dependency_path: Vec::new(),
+            suggestion: String::new(),
+        });
+    }
+    // Most severe first; warnings after vulnerabilities
+    report.advisories.sort_by(|a, b| {
+        b.is_vulnerability().cmp(&a.is_vulnerability()).then(
+            b.cvss_score
+                .unwrap_or(0.0)
+                .total_cmp(&a.cvss_score.unwrap_or(0.0)),
+        )
+    });
+    report.recount();
+    Ok(report)
+}
+
+/// CVSS v3.x base score of a vector such as `CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H`
+pub fn cvss_base_score(vector: &str) -> Option<f64> {
+    let mut parts = vector.split('/');
+    if !parts.next()?.starts_with("CVSS:3") {
+        return None;
+    }
+    let metrics: BTreeMap<&str, &str> = parts.filter_map(|m| m.split_once(':')).collect();
+    let metric = |name: &str| metrics.get(name).copied();
+    let changed = metric("S")? == "C";
+    let attack_vector = match metric("AV")? {
+        "N" => 0.85,
+        "A" => 0.62,
+        "L" => 0.55,
+        "P" => 0.2,
+        _ => return None,
+    };
+    let attack_complexity = match metric("AC")? {
+        "L" => 0.77,
+        "H" => 0.44,
+        _ => return None,
+    };
+    let privileges = match (metric("PR")?, changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        ("H", false) => 0.27,
+        ("H", true) => 0.5,
+        _ => return None,
+    };
+    let interaction = match metric("UI")? {
+        "N" => 0.85,
+        "R" => 0.62,
+        _ => return None,
+    };
+    let impact_of = |name: &str| match metric(name) {
+        Some("H") => Some(0.56),
+        Some("L") => Some(0.22),
+        Some("N") => Some(0.0),
+        _ => None,
+    };
+    let (c, i, a) = (impact_of("C")?, impact_of("I")?, impact_of("A")?);
+
+    let iss: f64 = 1.0 - (1.0 - c) * (1.0 - i) * (1.0 - a);
+    let impact = if changed {
+        7.52 * (iss - 0.029) - 3.25 * (iss - 0.02).powi(15)
+    } else {
+        6.42 * iss
+    };
+    if impact <= 0.0 {
+        return Some(0.0);
+    }
+    let exploitability = 8.22 * attack_vector * attack_complexity * privileges * interaction;
+    let score = if changed {
+        (1.08 * (impact + exploitability)).min(10.0)
+    } else {
+        (impact + exploitability).min(10.0)
+    };
+    Some(round_up(score))
+}
+
+/// CVSS v3.1 Roundup: the smallest number with one decimal that is at least `value`
+fn round_up(value: f64) -> f64 {
+    let scaled = (value * 100_000.0).round() as u64;
+    if scaled.is_multiple_of(10_000) {
+        scaled as f64 / 100_000.0
+    } else {
+        (scaled / 10_000 + 1) as f64 / 10.0
+    }
+}
+
+/// First path of `cargo tree -i <pkg> --prefix depth`, as `name version` from a workspace
+/// member down to the package
+pub fn parse_inverted_tree(output: &str) -> Vec<String> {
+    let mut path: Vec<String> = Vec::new();
+    for line in output.lines() {
+        let depth_len = line.chars().take_while(|c| c.is_ascii_digit()).count();
+        let Ok(depth) = line[..depth_len].parse::<usize>() else {
+            continue;
+        };
+        // The first entry at each new depth continues the first chain
+        if depth != path.len() {
+            if depth < path.len() {
+                break;
+            }
+            continue;
+        }
+        let mut words = line[depth_len..].split_whitespace();
+        let (Some(name), Some(version)) = (words.next(), words.next()) else {
+            break;
+        };
+        path.push(format!("{name} {}", version.trim_start_matches('v')));
+    }
+    path.reverse();
+    path
+}
+
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version.trim().split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+    Some((
+        parts.next()??,
+        parts.next()??,
+        parts.next().flatten().unwrap_or(0),
+    ))
+}
+
+/// Versions that `cargo update` may move between without touching Cargo.toml
+fn semver_compatible(a: (u64, u64, u64), b: (u64, u64, u64)) -> bool {
+    match (a.0, b.0) {
+        (0, 0) if a.1 == 0 && b.1 == 0 => a.2 == b.2,
+        (0, 0) => a.1 == b.1,
+        (x, y) => x == y,
+    }
+}
+
+/// Lowest version a patched requirement such as `>=0.2.23` or `^1.4.2, <2` admits
+fn requirement_floor(requirement: &str) -> Option<(String, (u64, u64, u64))> {
+    let first = requirement.split(',').next()?.trim();
+    if first.starts_with('<') {
+        return None;
+    }
+    let version = first.trim_start_matches(['>', '=', '^', '~', ' ']);
+    parse_version(version).map(|parsed| (version.to_string(), parsed))
+}
+
+/// The smallest change that moves `advisory`'s package to a patched release: `cargo update`
+/// when a patched release is semver-compatible with the locked one, otherwise raising the
+/// requirement of the crate that depends on it
+pub fn upgrade_suggestion(advisory: &Advisory) -> String {
+    let package = &advisory.package;
+    let installed = parse_version(&advisory.version);
+    let mut floors: Vec<(String, (u64, u64, u64))> = advisory
+        .patched
+        .iter()
+        .filter_map(|r| requirement_floor(r))
+        .filter(|(_, floor)| installed.is_none_or(|v| *floor > v))
+        .collect();
+    floors.sort_by_key(|(_, floor)| *floor);
+
+    let Some((lowest, _)) = floors.first() else {
+        return match advisory.kind.as_str() {
+            "unmaintained" => format!("{package} is unmaintained; consider an alternative crate"),
+            "yanked" => format!(
+                "run `cargo update -p {package}@{}` to move off the yanked release",
+                advisory.version
+            ),
+            _ => format!(
+                "no patched release of {package}; remove or replace it, or audit with --ignore {} if it does not apply",
+                advisory.id
+            ),
+        };
+    };
+    if let Some(installed) = installed
+        && let Some((compatible, _)) = floors
+            .iter()
+            .find(|(_, floor)| semver_compatible(*floor, installed))
+    {
+        return format!(
+            "run `cargo update -p {package}@{}` (patched {compatible} is semver-compatible)",
+            advisory.version
+        );
+    }
+    match advisory.dependency_path.as_slice() {
+        [member, _] => {
+            format!(
+                "raise the {package} requirement in {member}'s Cargo.toml to {lowest} (see the upgrade tool)"
+            )
+        }
+        [.., parent, _] => format!(
+            "{package} comes in through {parent}; upgrade {parent} to a release that depends on {package} {lowest} or later"
+        ),
+        _ => format!("upgrade {package} to {lowest} or later"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPORT: &str = r#"{"database":{"advisory-count":870,"last-commit":"a1b2c3","last-updated":"2026-10-01T00:00:00Z"},"lockfile":{"dependency-count":42},"settings":{"target_arch":[],"target_os":[],"severity":null,"ignore":[],"informational_warnings":["unmaintained","unsound","notice"]},"vulnerabilities":{"found":true,"count":2,"list":[{"advisory":{"id":"RUSTSEC-2020-0071","package":"time","title":"Potential segfault in the time crate","description":"...","date":"2020-11-18","aliases":["CVE-2020-26235","GHSA-wcg3-cvx6-7396"],"related":[],"collection":"crates","categories":["code-execution","memory-corruption"],"keywords":["segfault"],"cvss":"CVSS:3.1/AV:N/AC:H/PR:N/UI:N/S:U/C:N/I:N/A:H","informational":null,"references":[],"source":null,"url":"https://github.com/time-rs/time/issues/293","withdrawn":null,"license":"CC0-1.0"},"versions":{"patched":[">=0.2.23"],"unaffected":["=0.2.0"]},"affected":{"arch":[],"os":[],"functions":{}},"package":{"name":"time","version":"0.1.45","source":"registry+https://github.com/rust-lang/crates.io-index","checksum":"x","dependencies":[],"replace":null}},{"advisory":{"id":"RUSTSEC-2024-0001","package":"demo-http","title":"Request smuggling","description":"...","date":"2024-01-01","aliases":[],"related":[],"collection":"crates","categories":[],"keywords":[],"cvss":"CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H","informational":null,"references":[],"source":null,"url":null,"withdrawn":null,"license":"CC0-1.0"},"versions":{"patched":[">=1.4.2, <2.0.0",">=2.1.0"],"unaffected":[]},"affected":null,"package":{"name":"demo-http","version":"1.4.0","source":"registry+https://github.com/rust-lang/crates.io-index","checksum":"y","dependencies":[],"replace":null}}]},"warnings":{"unmaintained":[{"kind":"unmaintained","package":{"name":"atty","version":"0.2.14","source":"registry+https://github.com/rust-lang/crates.io-index"},"advisory":{"id":"RUSTSEC-2024-0375","package":"atty","title":"`atty` is unmaintained","description":"...","date":"2024-09-25","aliases":[],"related":[],"collection":"crates","categories":[],"keywords":[],"cvss":null,"informational":"unmaintained","references":[],"source":null,"url":null,"withdrawn":null,"license":"CC0-1.0"},"affected":null,"versions":{"patched":[],"unaffected":[]}}]}}"#;
+
+    #[test]
+    fn test_parse_audit_json() {
+        let report = parse_audit_json(REPORT).unwrap();
+        assert_eq!(report.dependency_count, Some(42));
+        assert_eq!((report.vulnerabilities, report.warnings), (2, 1));
+        let ids: Vec<&str> = report.advisories.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "RUSTSEC-2024-0001",
+                "RUSTSEC-2020-0071",
+                "RUSTSEC-2024-0375"
+            ]
+        );
+        let time = &report.advisories[1];
+        assert_eq!(time.cvss_score, Some(5.9));
+        assert_eq!(time.severity, Some(Severity::Medium));
+        assert_eq!(time.patched, vec![">=0.2.23"]);
+        assert_eq!(report.advisories[0].severity, Some(Severity::Critical));
+        assert_eq!(report.advisories[2].kind, "unmaintained");
+    }
+
+    #[test]
+    fn test_min_severity_filter() {
+        let mut report = parse_audit_json(REPORT).unwrap();
+        report.filter_min_severity(Severity::High);
+        assert_eq!(report.advisories.len(), 2);
+        assert_eq!(report.filtered_out, 1);
+        assert_eq!(report.advisories[1].kind, "unmaintained");
+        assert!(
+            report
+                .render()
+                .starts_with("Audit: 1 vulnerability (1 critical), 1 warning(s) in 42 dependencies, 1 below min_severity not shown")
+        );
+    }
+
+    #[test]
+    fn test_cvss_base_scores() {
+        assert_eq!(
+            cvss_base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"),
+            Some(9.8)
+        );
+        assert_eq!(
+            cvss_base_score("CVSS:3.1/AV:N/AC:L/PR:L/UI:N/S:C/C:L/I:L/A:N"),
+            Some(6.4)
+        );
+        assert_eq!(
+            cvss_base_score("CVSS:3.0/AV:L/AC:L/PR:N/UI:R/S:U/C:N/I:N/A:N"),
+            Some(0.0)
+        );
+        assert_eq!(cvss_base_score("CVSS:4.0/AV:N/AC:L"), None);
+    }
+
+    #[test]
+    fn test_inverted_tree_path() {
+        let tree = "0time v0.1.45\n1chrono v0.4.19\n2app v0.1.0 (/tmp/app)\n2tool v0.1.0 (/tmp/tool)\n1app v0.1.0 (/tmp/app) (*)\n";
+        assert_eq!(
+            parse_inverted_tree(tree),
+            vec!["app 0.1.0", "chrono 0.4.19", "time 0.1.45"]
+        );
+    }
+
+    #[test]
+    fn test_upgrade_suggestions() {
+        let mut report = parse_audit_json(REPORT).unwrap();
+        let http = &mut report.advisories[0];
+        assert_eq!(
+            upgrade_suggestion(http),
+            "run `cargo update -p demo-http@1.4.0` (patched 1.4.2 is semver-compatible)"
+        );
+        let time = &mut report.advisories[1];
+        time.dependency_path = vec![
+            "app 0.1.0".into(),
+            "chrono 0.4.19".into(),
+            "time 0.1.45".into(),
+        ];
+        assert_eq!(
+            upgrade_suggestion(time),
+            "time comes in through chrono 0.4.19; upgrade chrono 0.4.19 to a release that depends on time 0.2.23 or later"
+        );
+        time.dependency_path = vec!["app 0.1.0".into(), "time 0.1.45".into()];
+        assert_eq!(
+            upgrade_suggestion(time),
+            "raise the time requirement in app 0.1.0's Cargo.toml to 0.2.23 (see the upgrade tool)"
+        );
+        assert_eq!(
+            upgrade_suggestion(&report.advisories[2]),
+            "atty is unmaintained; consider an alternative crate"
+        );
+    }
+}
@@ -1,18 +1,37 @@
 use crate::callback_system::{CallbackSender, ProgressUpdate, no_callback};
+use crate::condense::{CondenseOptions, Verbosity};
+use crate::diagnostics::{
+    CargoMessageParser, CargoReport, DiagnosticScope, DiagnosticsStore, MESSAGE_FORMAT_ARG,
+    has_message_format,
+};
+use crate::fingerprint::RequestFingerprint;
 use crate::mcp_callback::mcp_callback;
 use crate::operation_monitor::OperationMonitor;
-use crate::shell_pool::{ShellCommand, ShellPoolConfig, ShellPoolManager};
+use crate::output_store::OutputStore;
+use crate::result_cache::{CachedResult, ResultCache};
+use crate::shell_pool::{ShellPoolConfig, ShellPoolManager};
+use crate::speculative::{SpeculativeChecks, SpeculativeResult};
+use crate::suggestions::{Applicability, SuggestionBackups, SuggestionFilter};
+use crate::target_isolation::TargetIsolationConfig;
 use crate::terminal_output::TerminalOutput;
+use crate::test_results::{
+    FlakyReport, LIBTEST_JSON_ARGS, TestCase, TestReport, exact_test_args, nextest_filterset,
+    parse_nextest_list, parse_panics, parse_test_list, parse_test_output, render_test_list,
+    supports_libtest_json, wants_default_format, without_filterset,
+};
 use crate::timestamp;
+use crate::watch::{StepResult, WatchRun, WatchSession, WatchSessions, WatchStep};
 use rmcp::{
-    ErrorData, RoleServer, ServerHandler, handler::server::router::tool::ToolRouter, model::*,
-    schemars, service::RequestContext, tool, tool_handler, tool_router,
+    ErrorData, RoleServer, ServerHandler, handler::server::router::tool::ToolRouter,
+    handler::server::tool::ToolCallContext, model::*, schemars, service::RequestContext, tool,
+    tool_router,
 };
 // Updated for rmcp 0.6.3: Parameters moved (previous tool::Parameters is now private)
 // Use public re-exported Parameters wrapper (module path changed in rmcp 0.6.x)
 use rmcp::handler::server::wrapper::Parameters;
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Instant;
@@ -39,6 +58,54 @@ fn merge_outputs(stdout: &str, stderr: &str, empty_placeholder: &str) -> String
     format!("{s}\n\n{e}")
 }
 
+/// Expose the condensation fields of requests whose output can be condensed
+macro_rules! impl_condense_options {
+    ($($request:ty),* $(,)?) => {
+        $(
+            impl $request {
+                pub fn condense_options(&self) -> CondenseOptions {
+                    CondenseOptions::new(self.verbosity, self.max_output_tokens)
+                }
+            }
+        )*
+    };
+}
+
+impl_condense_options!(
+    BuildRequest,
+    RunRequest,
+    TestRequest,
+    CheckRequest,
+    DocRequest,
+    ClippyRequest,
+    NextestRequest,
+    FixRequest,
+    BenchRequest,
+);
+
+/// Key the result cache uses for the arguments of a request: everything except how the result is
+/// presented and whether to bypass the cache
+macro_rules! impl_cache_key {
+    ($($request:ty),* $(,)?) => {
+        $(
+            impl $request {
+                pub fn cache_key(&self) -> String {
+                    let key = Self {
+                        verbosity: None,
+                        max_output_tokens: None,
+                        enable_async_notification: None,
+                        force: None,
+                        ..self.clone()
+                    };
+                    format!("{key:?}")
+                }
+            }
+        )*
+    };
+}
+
+impl_cache_key!(TestRequest, CheckRequest, ClippyRequest);
+
 /// Dependency section specification for cargo add/remove commands
 #[derive(Debug, Clone, PartialEq, serde::Deserialize, schemars::JsonSchema)]
 pub enum DependencySection {
@@ -158,7 +225,7 @@ pub struct RemoveDependencyRequest {
     pub enable_async_notification: Option<bool>,
 }
 
-#[derive(Debug, Clone, serde::Deserialize, schemars::JsonSchema)]
+#[derive(Debug, Clone, Default, serde::Deserialize, schemars::JsonSchema)]
 pub struct BuildRequest {
     pub working_directory: String,
     /// Optional binary name to build (--bin parameter)
@@ -191,7 +258,7 @@ pub struct BuildRequest {
     pub release: Option<bool>,
     /// Build artifacts with the specified profile
     pub profile: Option<String>,
-    /// Number of parallel jobs, defaults to # of CPUs
+    /// Number of parallel jobs, defaults to # of CPUs (capped by the server's --jobs-budget)
     pub jobs: Option<u32>,
     /// Build for the target triple
     pub target: Option<String>,
@@ -201,6 +268,12 @@ pub struct BuildRequest {
     pub manifest_path: Option<String>,
     /// Additional arguments to pass to build
     pub args: Option<Vec<String>>,
+    /// Pass --timings and report total time, slowest units, frontend/codegen split and critical path
+    pub timings: Option<bool>,
+    /// Output detail: "summary", "normal" or "full" (defaults to the server's --verbosity)
+    pub verbosity: Option<Verbosity>,
+    /// Approximate token budget for the result; output is condensed further until it fits
+    pub max_output_tokens: Option<usize>,
     /// Enable async callback notifications for operation progress
     pub enable_async_notification: Option<bool>,
 }
@@ -230,11 +303,17 @@ pub struct RunRequest {
     pub manifest_path: Option<String>,
     /// Additional cargo arguments
     pub cargo_args: Option<Vec<String>>,
+    /// Run with RUST_BACKTRACE=1 so panics report failure locations (default: true unless the server's environment sets RUST_BACKTRACE)
+    pub backtrace: Option<bool>,
+    /// Output detail: "summary", "normal" or "full" (defaults to the server's --verbosity)
+    pub verbosity: Option<Verbosity>,
+    /// Approximate token budget for the result; output is condensed further until it fits
+    pub max_output_tokens: Option<usize>,
     /// Enable async callback notifications for operation progress
     pub enable_async_notification: Option<bool>,
 }
 
-#[derive(Debug, Clone, serde::Deserialize, schemars::JsonSchema)]
+#[derive(Debug, Clone, Default, serde::Deserialize, schemars::JsonSchema)]
 pub struct TestRequest {
     pub working_directory: String,
     /// Test name filter - if specified, only run tests containing this string
@@ -277,7 +356,7 @@ pub struct TestRequest {
     pub release: Option<bool>,
     /// Build artifacts with the specified profile
     pub profile: Option<String>,
-    /// Number of parallel jobs, defaults to # of CPUs
+    /// Number of parallel jobs, defaults to # of CPUs (capped by the server's --jobs-budget)
     pub jobs: Option<u32>,
     /// Build for the target triple
     pub target: Option<String>,
@@ -287,13 +366,29 @@ pub struct TestRequest {
     pub no_fail_fast: Option<bool>,
     /// Path to Cargo.toml
     pub manifest_path: Option<String>,
+    /// Run with RUST_BACKTRACE=1 so panics report failure locations (default: true unless the server's environment sets RUST_BACKTRACE)
+    pub backtrace: Option<bool>,
+    /// Run even if an identical earlier request's result is cached for unchanged sources
+    pub force: Option<bool>,
+    /// Output detail: "summary", "normal" or "full" (defaults to the server's --verbosity)
+    pub verbosity: Option<Verbosity>,
+    /// Approximate token budget for the result; output is condensed further until it fits
+    pub max_output_tokens: Option<usize>,
     /// Enable async callback notifications for operation progress
     pub enable_async_notification: Option<bool>,
 }
 
-#[derive(Debug, Clone, serde::Deserialize, schemars::JsonSchema)]
+#[derive(Debug, Clone, Default, serde::Deserialize, schemars::JsonSchema)]
 pub struct CheckRequest {
     pub working_directory: String,
+    /// Pass --timings and report total time, slowest units, frontend/codegen split and critical path
+    pub timings: Option<bool>,
+    /// Run even if an identical earlier request's result is cached for unchanged sources
+    pub force: Option<bool>,
+    /// Output detail: "summary", "normal" or "full" (defaults to the server's --verbosity)
+    pub verbosity: Option<Verbosity>,
+    /// Approximate token budget for the result; output is condensed further until it fits
+    pub max_output_tokens: Option<usize>,
     /// Enable async callback notifications for operation progress
     pub enable_async_notification: Option<bool>,
 }
@@ -306,14 +401,26 @@ pub struct UpdateRequest {
 #[derive(Debug, Clone, serde::Deserialize, schemars::JsonSchema)]
 pub struct DocRequest {
     pub working_directory: String,
+    /// Output detail: "summary", "normal" or "full" (defaults to the server's --verbosity)
+    pub verbosity: Option<Verbosity>,
+    /// Approximate token budget for the result; output is condensed further until it fits
+    pub max_output_tokens: Option<usize>,
     /// Enable async callback notifications for operation progress
     pub enable_async_notification: Option<bool>,
 }
-#[derive(Debug, Clone, serde::Deserialize, schemars::JsonSchema)]
+#[derive(Debug, Clone, Default, serde::Deserialize, schemars::JsonSchema)]
 pub struct ClippyRequest {
     pub working_directory: String,
     /// Additional arguments to pass to clippy (e.g., ["--fix", "--allow-dirty"])
     pub args: Option<Vec<String>>,
+    /// Also propose a `[lints]` table allowing the noisy lints ("lints_table") or an order to fix lints in ("fix_priority")
+    pub lint_advice: Option<crate::lints::LintAdvice>,
+    /// Run even if an identical earlier request's result is cached for unchanged sources
+    pub force: Option<bool>,
+    /// Output detail: "summary", "normal" or "full" (defaults to the server's --verbosity)
+    pub verbosity: Option<Verbosity>,
+    /// Approximate token budget for the result; output is condensed further until it fits
+    pub max_output_tokens: Option<usize>,
     /// Enable async callback notifications for operation progress
     pub enable_async_notification: Option<bool>,
 }
@@ -323,6 +430,12 @@ pub struct NextestRequest {
     pub working_directory: String,
     /// Additional arguments to pass to nextest (e.g., ["--all-features"])
     pub args: Option<Vec<String>>,
+    /// Run with RUST_BACKTRACE=1 so panics report failure locations (default: true unless the server's environment sets RUST_BACKTRACE)
+    pub backtrace: Option<bool>,
+    /// Output detail: "summary", "normal" or "full" (defaults to the server's --verbosity)
+    pub verbosity: Option<Verbosity>,
+    /// Approximate token budget for the result; output is condensed further until it fits
+    pub max_output_tokens: Option<usize>,
     /// Enable async callback notifications for operation progress
     pub enable_async_notification: Option<bool>,
 }
@@ -339,6 +452,10 @@ pub struct FixRequest {
     pub working_directory: String,
     /// Additional arguments to pass to fix (e.g., ["--allow-dirty"])
     pub args: Option<Vec<String>>,
+    /// Output detail: "summary", "normal" or "full" (defaults to the server's --verbosity)
+    pub verbosity: Option<Verbosity>,
+    /// Approximate token budget for the result; output is condensed further until it fits
+    pub max_output_tokens: Option<usize>,
     /// Enable async callback notifications for operation progress
     pub enable_async_notification: Option<bool>,
 }
@@ -357,6 +474,10 @@ pub struct BenchRequest {
     pub working_directory: String,
     /// Additional arguments to pass to bench
     pub args: Option<Vec<String>>,
+    /// Output detail: "summary", "normal" or "full" (defaults to the server's --verbosity)
+    pub verbosity: Option<Verbosity>,
+    /// Approximate token budget for the result; output is condensed further until it fits
+    pub max_output_tokens: Option<usize>,
     /// Enable async callback notifications for operation progress
     pub enable_async_notification: Option<bool>,
 }
@@ -390,8 +511,12 @@ pub struct UpgradeRequest {
 #[derive(Debug, Clone, serde::Deserialize, schemars::JsonSchema)]
 pub struct BumpVersionRequest {
     pub working_directory: String,
-    /// Version bump type: patch, minor, or major
+    /// Version bump type: patch, minor, major, or auto (the minimum bump required by a
+    /// semver_checks report)
     pub bump_type: String,
+    /// semver_checks operation whose report drives bump_type "auto" (default: the latest one
+    /// for this working directory)
+    pub semver_operation_id: Option<String>,
     /// Perform a dry run without making changes
     pub dry_run: Option<bool>,
     /// Modify all packages in the workspace
@@ -401,8 +526,13 @@ pub struct BumpVersionRequest {
 #[derive(Debug, Clone, serde::Deserialize, schemars::JsonSchema)]
 pub struct AuditRequest {
     pub working_directory: String,
-    /// Output format (default, json, yaml)
+    /// Output format (default, json, yaml). The default and json are parsed into structured
+    /// advisories; other formats are passed through as cargo-audit prints them.
     pub format: Option<String>,
+    /// Only report vulnerabilities rated at least this severity (low, medium, high, critical).
+    /// Vulnerabilities without a CVSS score and warnings, which have no severity, are always
+    /// reported.
+    pub min_severity: Option<crate::audit::Severity>,
     /// Show only vulnerable dependencies
     pub vulnerabilities_only: Option<bool>,
     /// Deny warnings as errors
@@ -413,6 +543,120 @@ pub struct AuditRequest {
     pub enable_async_notification: Option<bool>,
 }
 
+#[derive(Debug, Clone, serde::Deserialize, schemars::JsonSchema)]
+pub struct DenyRequest {
+    pub working_directory: String,
+    /// Checks to run: "advisories", "bans", "licenses", "sources" (default: all four)
+    pub checks: Option<Vec<crate::deny::DenyCheck>>,
+    /// Write a default deny.toml to the working directory instead of checking
+    pub init: Option<bool>,
+    /// Additional arguments to pass to cargo deny check (e.g., ["--exclude-dev"])
+    pub args: Option<Vec<String>>,
+    /// Enable async callback notifications for operation progress
+    pub enable_async_notification: Option<bool>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, schemars::JsonSchema)]
+pub struct UnusedDepsRequest {
+    pub working_directory: String,
+    /// Detector: "machete" (default; fast, runs on stable) or "udeps" (accurate, builds with nightly)
+    pub tool: Option<crate::unused_deps::UnusedDepsTool>,
+    /// Remove the unused dependencies with cargo remove, then run check to confirm the build
+    pub fix: Option<bool>,
+    /// Additional arguments to pass to cargo machete or cargo udeps
+    pub args: Option<Vec<String>>,
+    /// Enable async callback notifications for operation progress
+    pub enable_async_notification: Option<bool>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, schemars::JsonSchema)]
+pub struct SemverChecksRequest {
+    pub working_directory: String,
+    /// Git revision to compare against (e.g. "v1.2.0", "origin/main")
+    pub baseline_rev: Option<String>,
+    /// Directory holding the baseline version of the crate
+    pub baseline_path: Option<String>,
+    /// Published version to compare against, fetched from the registry configured for cargo
+    /// (default when no baseline is given: the latest published version)
+    pub baseline_version: Option<String>,
+    /// Packages to check
+    pub package: Option<Vec<String>>,
+    /// Check all packages in the workspace
+    pub workspace: Option<bool>,
+    /// Space or comma separated list of features to activate
+    pub features: Option<Vec<String>>,
+    /// Activate all available features
+    pub all_features: Option<bool>,
+    /// Additional arguments to pass to cargo semver-checks (e.g., ["--release-type", "minor"])
+    pub args: Option<Vec<String>>,
+    /// Enable async callback notifications for operation progress
+    pub enable_async_notification: Option<bool>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, schemars::JsonSchema)]
+pub struct CoverageRequest {
+    pub working_directory: String,
+    /// Test name filter - if specified, only run tests containing this string
+    pub test_name: Option<String>,
+    /// Package to measure
+    pub package: Option<String>,
+    /// Measure all packages in the workspace
+    pub workspace: Option<bool>,
+    /// Exclude packages from the run
+    pub exclude: Option<Vec<String>>,
+    /// Test only this package's library
+    pub lib: Option<bool>,
+    /// Test all binaries
+    pub bins: Option<bool>,
+    /// Test only the specified binary
+    pub bin: Option<String>,
+    /// Test all test targets
+    pub tests: Option<bool>,
+    /// Test only the specified test target
+    pub test: Option<String>,
+    /// Space or comma separated list of features to activate
+    pub features: Option<Vec<String>>,
+    /// Activate all available features
+    pub all_features: Option<bool>,
+    /// Do not activate the `default` feature
+    pub no_default_features: Option<bool>,
+    /// Additional arguments to pass to cargo llvm-cov
+    pub args: Option<Vec<String>>,
+    /// List the uncovered lines of files whose path ends with (or contains) this, e.g. "src/parser.rs"
+    pub file: Option<String>,
+    /// Answer from the report of a previous coverage operation instead of running the tests again
+    pub operation_id: Option<String>,
+    /// Enable async callback notifications for operation progress
+    pub enable_async_notification: Option<bool>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, schemars::JsonSchema)]
+pub struct ExpandRequest {
+    pub working_directory: String,
+    /// Expand only this item, by path within the crate (e.g. "parser::Token" or "main")
+    pub item: Option<String>,
+    /// Package to expand
+    pub package: Option<String>,
+    /// Expand only this package's library
+    pub lib: Option<bool>,
+    /// Expand only the specified binary
+    pub bin: Option<String>,
+    /// Expand only the specified test target
+    pub test: Option<String>,
+    /// Space or comma separated list of features to activate
+    pub features: Option<Vec<String>>,
+    /// Activate all available features
+    pub all_features: Option<bool>,
+    /// Do not activate the `default` feature
+    pub no_default_features: Option<bool>,
+    /// Do not attempt to format the expanded code (--ugly)
+    pub ugly: Option<bool>,
+    /// Additional arguments to pass to cargo expand
+    pub args: Option<Vec<String>>,
+    /// Enable async callback notifications for operation progress
+    pub enable_async_notification: Option<bool>,
+}
+
 #[derive(Debug, Clone, serde::Deserialize, schemars::JsonSchema)]
 pub struct FmtRequest {
     pub working_directory: String,
@@ -525,6 +769,130 @@ pub struct StatusRequest {
     pub state_filter: Option<String>, // "active", "completed", "failed", etc.
 }
 
+/// Request to page through or search the full output of an operation
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetOutputRequest {
+    /// Operation ID whose full output to read (e.g. "op_test_3")
+    pub operation_id: String,
+    /// Byte offset to start reading from (default 0). Ignored when start_line or grep is set
+    pub offset: Option<usize>,
+    /// Maximum bytes to return when paging by offset (default 16384)
+    pub limit: Option<usize>,
+    /// First line to return (1-based, inclusive)
+    pub start_line: Option<usize>,
+    /// Last line to return (1-based, inclusive; default start_line + 199)
+    pub end_line: Option<usize>,
+    /// Return only lines containing this text (case-insensitive)
+    pub grep: Option<String>,
+    /// Lines of context around each grep match (default 2)
+    pub context: Option<usize>,
+}
+
+/// Request to apply compiler suggestions recorded by a previous build/check/clippy/test run
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ApplySuggestionsRequest {
+    pub working_directory: String,
+    /// Operation ID of the build/check/clippy/test run whose suggestions to apply
+    pub operation_id: String,
+    /// Only apply suggestions for these lints or error codes (e.g. ["unused_imports", "clippy::needless_return"])
+    pub lints: Option<Vec<String>>,
+    /// Only edit these files, as reported by rustc (e.g. ["src/main.rs"])
+    pub files: Option<Vec<String>>,
+    /// Least certain suggestions to accept (default: MachineApplicable)
+    pub applicability: Option<Applicability>,
+    /// Only show the diff; do not change any file
+    pub dry_run: Option<bool>,
+}
+
+/// Request to re-run the tests that failed in a previous test or nextest operation
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct RerunFailedRequest {
+    /// Operation ID of the test or nextest run whose failures to re-run
+    pub operation_id: String,
+    /// Enable async callback notifications for operation progress
+    pub enable_async_notification: Option<bool>,
+}
+
+/// Request to run the same tests repeatedly and report which ones are flaky
+#[derive(Debug, Clone, serde::Deserialize, schemars::JsonSchema)]
+pub struct DetectFlakyRequest {
+    pub working_directory: String,
+    /// Exact test names to repeat, e.g. ["tests::parses_input"] (default: all selected tests)
+    pub tests: Option<Vec<String>>,
+    /// Substring filter on test names, as for `test`
+    pub test_name: Option<String>,
+    /// Package to test
+    pub package: Option<String>,
+    /// Additional cargo test arguments (e.g., ["--lib", "--features", "foo"])
+    pub args: Option<Vec<String>>,
+    /// Number of runs (default 10, at most 100)
+    pub runs: Option<u32>,
+    /// Run the repetitions concurrently instead of one after another
+    pub parallel: Option<bool>,
+    /// Enable async callback notifications for operation progress
+    pub enable_async_notification: Option<bool>,
+}
+
+/// Request for the full explanation of a rustc error code
+#[derive(Debug, Clone, serde::Deserialize, schemars::JsonSchema)]
+pub struct ExplainRequest {
+    /// Directory whose toolchain explains the code
+    pub working_directory: String,
+    /// Error code such as "E0502" (the "E" is optional)
+    pub code: String,
+}
+
+/// Request to list the tests of a project without running them
+#[derive(Debug, Clone, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListTestsRequest {
+    pub working_directory: String,
+    /// Only tests whose name contains this substring
+    pub filter: Option<String>,
+    /// Only tests of this package
+    pub package: Option<String>,
+    /// Additional cargo arguments selecting targets or features (e.g., ["--lib", "--features", "foo"])
+    pub args: Option<Vec<String>>,
+    /// List with `cargo nextest list` instead of the libtest harness
+    pub nextest: Option<bool>,
+    /// Enable async callback notifications for operation progress
+    pub enable_async_notification: Option<bool>,
+}
+
+/// Request to revert files changed by apply_suggestions
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct UndoSuggestionsRequest {
+    /// apply_id returned by apply_suggestions (default: the most recent apply)
+    pub apply_id: Option<String>,
+    /// Restore files even if they were edited after the suggestions were applied
+    pub force: Option<bool>,
+}
+
+/// Request to start re-running a command chain whenever files in a directory change
+#[derive(Debug, Clone, serde::Deserialize, schemars::JsonSchema)]
+pub struct WatchStartRequest {
+    pub working_directory: String,
+    /// Commands to run in order after each change, stopping at the first failure (default ["check", "test"])
+    pub steps: Option<Vec<WatchStep>>,
+    /// Milliseconds without further changes before the chain runs (default 500)
+    pub debounce_ms: Option<u64>,
+    /// Run the chain once right away instead of waiting for the first change (default true)
+    pub run_on_start: Option<bool>,
+}
+
+/// Request for the latest results of watch sessions
+#[derive(Debug, Clone, serde::Deserialize, schemars::JsonSchema)]
+pub struct WatchStatusRequest {
+    /// session_id returned by watch_start (default: list all sessions)
+    pub session_id: Option<String>,
+}
+
+/// Request to end a watch session
+#[derive(Debug, Clone, serde::Deserialize, schemars::JsonSchema)]
+pub struct WatchStopRequest {
+    /// session_id returned by watch_start
+    pub session_id: String,
+}
+
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum CargoLockAction {
@@ -648,6 +1016,211 @@ pub struct AsyncCargo {
     disabled_tools: std::collections::HashSet<String>,
     // Track status calls per operation to detect polling patterns
     status_call_counts: Arc<AsyncRwLock<HashMap<String, u32>>>,
+    // Which tools build into their own CARGO_TARGET_DIR to avoid target lock contention
+    target_isolation: Arc<TargetIsolationConfig>,
+    // Spills oversized operation output to disk and keeps responses bounded
+    output_store: Arc<OutputStore>,
+    // Structured compiler diagnostics of recent build/check/clippy/test runs
+    diagnostics: Arc<DiagnosticsStore>,
+    // Originals of files changed by apply_suggestions, for undo_suggestions
+    suggestion_backups: Arc<SuggestionBackups>,
+    // Requests of recent test/nextest runs, so rerun_failed can repeat their selection
+    test_runs: Arc<AsyncRwLock<VecDeque<(String, TestRun)>>>,
+    // Directories watched by watch_start and the latest results of their command chains
+    watch_sessions: Arc<WatchSessions>,
+    // Background checks of the workspaces named in requests, reused by check when sources match
+    speculative: Arc<SpeculativeChecks>,
+    // Results of check/clippy/test by request fingerprint, returned again for identical requests
+    result_cache: Arc<ResultCache>,
+}
+
+/// Number of test runs kept for rerun_failed
+const MAX_STORED_TEST_RUNS: usize = 64;
+
+/// detect_flaky repetitions when the request does not say, and the most it accepts
+const DEFAULT_FLAKY_RUNS: u32 = 10;
+const MAX_FLAKY_RUNS: u32 = 100;
+
+/// The request behind a test or nextest operation
+#[derive(Debug, Clone)]
+enum TestRun {
+    Cargo(Box<TestRequest>),
+    Nextest(NextestRequest),
+}
+
+/// Drives one watch session: waits for debounced changes and runs the session's chain
+struct WatchRunner {
+    session_id: String,
+    /// Canonical form of `working_directory`, as the file watcher reports paths
+    root: PathBuf,
+    working_directory: String,
+    steps: Vec<WatchStep>,
+    debounce: std::time::Duration,
+    sessions: Arc<WatchSessions>,
+    diagnostics: Arc<DiagnosticsStore>,
+    output_store: Arc<OutputStore>,
+    target_isolation: Arc<TargetIsolationConfig>,
+    callback: Box<dyn CallbackSender>,
+}
+
+impl WatchRunner {
+    /// Run the chain after each batch of changes until the watcher goes away or the session is
+    /// stopped, notifying the client when a run ends differently from the previous one
+    async fn run(
+        self,
+        mut changes: tokio::sync::mpsc::UnboundedReceiver<PathBuf>,
+        run_on_start: bool,
+    ) {
+        let mut number = 0;
+        if run_on_start {
+            number += 1;
+            self.run_and_report(number, Vec::new()).await;
+        }
+        while let Some(paths) = crate::watch::next_batch(&mut changes, self.debounce).await {
+            number += 1;
+            let changed = paths
+                .iter()
+                .map(|p| {
+                    p.strip_prefix(&self.root)
+                        .unwrap_or(p)
+                        .display()
+                        .to_string()
+                })
+                .collect();
+            self.run_and_report(number, changed).await;
+        }
+    }
+
+    async fn run_and_report(&self, number: u64, changed: Vec<String>) {
+        self.sessions.set_running(&self.session_id, true).await;
+        let run = self.run_chain(number, changed).await;
+        let summary = run.summary();
+        let previous = self
+            .sessions
+            .record_run(&self.session_id, run.clone())
+            .await;
+        if run.outcome_changed(previous.as_ref()) {
+            let _ = self
+                .callback
+                .send_progress(ProgressUpdate::Progress {
+                    operation_id: self.session_id.clone(),
+                    message: format!("Watch session {}: {summary}", self.session_id),
+                    percentage: None,
+                    current_step: Some(crate::watch::render_chain(&self.steps)),
+                })
+                .await;
+        }
+    }
+
+    /// Run the steps in order, skipping the rest after the first failure
+    async fn run_chain(&self, number: u64, changed: Vec<String>) -> WatchRun {
+        let started_at = Instant::now();
+        let mut steps: Vec<StepResult> = Vec::new();
+        let mut skipped = Vec::new();
+        for &step in &self.steps {
+            if steps.last().is_some_and(|s| !s.success) {
+                skipped.push(step);
+                continue;
+            }
+            let operation_id = format!("{}_run{number}_{}", self.session_id, step.as_str());
+            let result = self.run_step(step, &operation_id).await;
+            let success = result.is_ok();
+            let output = self.output_store.finish(
+                &operation_id,
+                result.unwrap_or_else(|e| e),
+                &CondenseOptions::new(Some(Verbosity::Summary), None),
+            );
+            let report = self.diagnostics.get(&operation_id).await;
+            steps.push(StepResult::new(
+                step,
+                &operation_id,
+                success,
+                output,
+                report,
+            ));
+        }
+        WatchRun {
+            number,
+            changed,
+            finished_at: timestamp::format_current_time(),
+            duration_ms: started_at.elapsed().as_millis() as u64,
+            steps,
+            skipped,
+        }
+    }
+
+    /// Run one step with the default options of its tool
+    async fn run_step(&self, step: WatchStep, operation_id: &str) -> Result<String, String> {
+        let working_directory = self.working_directory.clone();
+        let target_dir = AsyncCargo::isolated_target_dir(
+            &self.target_isolation,
+            &working_directory,
+            step.as_str(),
+        );
+        let result = match step {
+            WatchStep::Check => {
+                let req = CheckRequest {
+                    working_directory,
+                    ..Default::default()
+                };
+                AsyncCargo::check_implementation(
+                    &req,
+                    target_dir.as_deref(),
+                    &self.diagnostics,
+                    &self.output_store,
+                    operation_id,
+                )
+                .await
+            }
+            WatchStep::Clippy => {
+                let req = ClippyRequest {
+                    working_directory,
+                    ..Default::default()
+                };
+                AsyncCargo::clippy_implementation(
+                    &req,
+                    target_dir.as_deref(),
+                    &self.diagnostics,
+                    &self.output_store,
+                    operation_id,
+                )
+                .await
+            }
+            WatchStep::Build => {
+                let req = BuildRequest {
+                    working_directory,
+                    ..Default::default()
+                };
+                AsyncCargo::build_implementation(
+                    &req,
+                    &self.diagnostics,
+                    &self.output_store,
+                    operation_id,
+                )
+                .await
+            }
+            WatchStep::Test => {
+                let req = TestRequest {
+                    working_directory,
+                    ..Default::default()
+                };
+                AsyncCargo::test_implementation(
+                    &req,
+                    &self.diagnostics,
+                    &self.output_store,
+                    operation_id,
+                )
+                .await
+            }
+        };
+        AsyncCargo::prune_isolated_target_dirs(
+            &self.target_isolation,
+            &self.working_directory,
+            step.as_str(),
+        )
+        .await;
+        result
+    }
 }
 
 /// Apply dependency section arguments to a cargo command
@@ -660,6 +1233,156 @@ fn apply_dependency_section_args(
     }
 }
 
+/// Create a `cargo` command that shares the server-wide jobserver, if a jobs budget is set
+fn cargo_command() -> tokio::process::Command {
+    let mut cmd = tokio::process::Command::new("cargo");
+    crate::cpu_budget::configure_command(&mut cmd);
+    cmd
+}
+
+/// Make panics in the tests or program print a short backtrace. A RUST_BACKTRACE already set in
+/// the server's environment is kept unless the request turns backtraces off.
+fn set_rust_backtrace(cmd: &mut tokio::process::Command, backtrace: Option<bool>) {
+    match backtrace {
+        Some(false) => {
+            cmd.env("RUST_BACKTRACE", "0");
+        }
+        _ if std::env::var_os("RUST_BACKTRACE").is_none() => {
+            cmd.env("RUST_BACKTRACE", "1");
+        }
+        _ => {}
+    }
+}
+
+/// Record the report parsed from cargo's JSON stdout for `operation_id` and return `text`, the
+/// human-readable stdout shown in place of the raw JSON, with the report's summary appended
+async fn record_cargo_messages(
+    diagnostics: &DiagnosticsStore,
+    scope: &DiagnosticScope,
+    operation_id: &str,
+    mut report: CargoReport,
+    text: &[u8],
+) -> String {
+    let text = String::from_utf8_lossy(text).trim_end().to_string();
+    if report.is_empty() {
+        return text;
+    }
+    report.delta = diagnostics.delta(scope, operation_id, &report).await;
+    diagnostics
+        .fill_explanations(&scope.working_directory, &mut report)
+        .await;
+    let fixes: usize = report.diagnostics.iter().map(|d| d.suggestions.len()).sum();
+    let summary = (report.errors + report.warnings > 0).then(|| {
+        if fixes > 0 {
+            format!(
+                "Diagnostics: {} ({fixes} suggested fix(es): apply_suggestions with operation_id=\"{operation_id}\")",
+                report.summary()
+            )
+        } else {
+            format!("Diagnostics: {}", report.summary())
+        }
+    });
+    let sections: Vec<String> = summary
+        .into_iter()
+        .chain(report.delta.as_ref().map(|delta| delta.render()))
+        .chain(
+            report
+                .explanations
+                .iter()
+                .map(|(code, excerpt)| format!("Error code {code}: {excerpt}")),
+        )
+        .collect();
+    diagnostics.record(operation_id, report).await;
+    match sections.join("\n") {
+        sections if sections.is_empty() => text,
+        sections if text.trim().is_empty() => sections,
+        sections => format!("{text}\n\n{sections}"),
+    }
+}
+
+/// Summarize the lints recorded for `operation_id`, attach the summary to its report and
+/// return its rendering; `None` when the run reported no lints
+async fn record_lint_summary(
+    diagnostics: &DiagnosticsStore,
+    working_directory: &str,
+    operation_id: &str,
+    advice: Option<crate::lints::LintAdvice>,
+) -> Option<String> {
+    let report = diagnostics.get(operation_id).await?;
+    let info = crate::lints::lint_info(working_directory).await;
+    let mut summary = crate::lints::LintSummary::from_report(&report, &info);
+    if summary.lints.is_empty() {
+        return None;
+    }
+    if let Some(advice) = advice {
+        let manifest = tokio::fs::read_to_string(Path::new(working_directory).join("Cargo.toml"))
+            .await
+            .unwrap_or_default();
+        let workspace = manifest.lines().any(|l| l.trim() == "[workspace]");
+        summary.advise(advice, workspace);
+    }
+    let rendered = summary.render();
+    diagnostics.record_lints(operation_id, summary).await;
+    Some(rendered)
+}
+
+/// Read the `--timings` report cargo named on stderr, record it for `operation_id` and return
+/// its rendering
+async fn record_timings(
+    diagnostics: &DiagnosticsStore,
+    working_directory: &str,
+    operation_id: &str,
+    stderr: &str,
+) -> Option<String> {
+    let path = Path::new(working_directory).join(crate::timings::report_path(stderr)?);
+    let html = tokio::fs::read_to_string(&path).await.ok()?;
+    let mut report = crate::timings::parse_timing_html(&html)?;
+    report.html_path = Some(path.display().to_string());
+    let rendered = report.render();
+    diagnostics.record_timings(operation_id, report).await;
+    Some(rendered)
+}
+
+/// Parse libtest/nextest output, record the per-test results for `operation_id` and return the
+/// text with JSON events rendered and a test summary appended, naming the operation to pass to
+/// rerun_failed when tests failed and marking failures detect_flaky found to be flaky
+async fn record_test_results(
+    diagnostics: &DiagnosticsStore,
+    working_directory: &str,
+    operation_id: &str,
+    stdout: &str,
+    stderr: &str,
+) -> String {
+    let (mut report, text) = parse_test_output(stdout, stderr);
+    if report.is_empty() {
+        return text;
+    }
+    diagnostics
+        .annotate_flaky(working_directory, &mut report)
+        .await;
+    let mut summary = report.render();
+    if report.failed > 0 {
+        let line_end = summary.find('\n').unwrap_or(summary.len());
+        summary.insert_str(
+            line_end,
+            &format!(" (rerun_failed with operation_id=\"{operation_id}\")"),
+        );
+    }
+    diagnostics.record_tests(operation_id, report).await;
+    if text.trim().is_empty() {
+        summary
+    } else {
+        format!("{}\n\n{summary}", text.trim_end())
+    }
+}
+
+/// Point a cargo command at an isolated target directory, if one was assigned
+fn apply_target_dir(cmd: &mut tokio::process::Command, target_dir: Option<&Path>) {
+    if let Some(dir) = target_dir {
+        cmd.env("CARGO_TARGET_DIR", dir);
+    }
+}
+
 impl Default for AsyncCargo {
     fn default() -> Self {
         use crate::operation_monitor::MonitorConfig;
@@ -761,6 +1484,14 @@ impl AsyncCargo {
             per_dir_mutex: Arc::new(AsyncRwLock::new(HashMap::new())),
             disabled_tools: Default::default(),
             status_call_counts: Arc::new(AsyncRwLock::new(HashMap::new())),
+            target_isolation: Arc::new(TargetIsolationConfig::default()),
+            output_store: Arc::new(OutputStore::default()),
+            diagnostics: Arc::new(DiagnosticsStore::new()),
+            suggestion_backups: Arc::new(SuggestionBackups::default()),
+            test_runs: Arc::new(AsyncRwLock::new(VecDeque::new())),
+            watch_sessions: Arc::new(WatchSessions::new()),
+            speculative: Arc::new(SpeculativeChecks::disabled()),
+            result_cache: Arc::new(ResultCache::default()),
         }
     }
 
@@ -777,6 +1508,14 @@ impl AsyncCargo {
             per_dir_mutex: Arc::new(AsyncRwLock::new(HashMap::new())),
             disabled_tools: Default::default(),
             status_call_counts: Arc::new(AsyncRwLock::new(HashMap::new())),
+            target_isolation: Arc::new(TargetIsolationConfig::default()),
+            output_store: Arc::new(OutputStore::default()),
+            diagnostics: Arc::new(DiagnosticsStore::new()),
+            suggestion_backups: Arc::new(SuggestionBackups::default()),
+            test_runs: Arc::new(AsyncRwLock::new(VecDeque::new())),
+            watch_sessions: Arc::new(WatchSessions::new()),
+            speculative: Arc::new(SpeculativeChecks::disabled()),
+            result_cache: Arc::new(ResultCache::default()),
         }
     }
 
@@ -809,7 +1548,80 @@ impl AsyncCargo {
             per_dir_mutex: Arc::new(AsyncRwLock::new(HashMap::new())),
             disabled_tools,
             status_call_counts: Arc::new(AsyncRwLock::new(HashMap::new())),
+            target_isolation: Arc::new(TargetIsolationConfig::default()),
+            output_store: Arc::new(OutputStore::default()),
+            diagnostics: Arc::new(DiagnosticsStore::new()),
+            suggestion_backups: Arc::new(SuggestionBackups::default()),
+            test_runs: Arc::new(AsyncRwLock::new(VecDeque::new())),
+            watch_sessions: Arc::new(WatchSessions::new()),
+            speculative: Arc::new(SpeculativeChecks::disabled()),
+            result_cache: Arc::new(ResultCache::default()),
+        }
+    }
+
+    /// Give the configured tools their own target directory under `target/async_cargo_mcp/<tool>`
+    pub fn with_target_isolation(mut self, config: TargetIsolationConfig) -> Self {
+        self.target_isolation = Arc::new(config);
+        self
+    }
+
+    /// Bound inline operation output, spilling anything larger to the store's directory
+    pub fn with_output_store(mut self, store: OutputStore) -> Self {
+        self.output_store = Arc::new(store);
+        self
+    }
+
+    /// Check the workspaces named in requests in the background after they change
+    pub fn with_speculative_checks(mut self, speculative: SpeculativeChecks) -> Self {
+        self.speculative = Arc::new(speculative);
+        self
+    }
+
+    /// Reuse results of repeated requests to the tools `cache` is enabled for
+    pub fn with_result_cache(mut self, cache: ResultCache) -> Self {
+        self.result_cache = Arc::new(cache);
+        self
+    }
+
+    /// Isolated target directory for `tool` in `working_directory`, if isolation is enabled for it
+    fn isolated_target_dir(
+        config: &TargetIsolationConfig,
+        working_directory: &str,
+        tool: &str,
+    ) -> Option<PathBuf> {
+        let dir = config.target_dir_for(working_directory, tool)?;
+        crate::target_isolation::mark_used(&dir);
+        Some(dir)
+    }
+
+    /// Record an operation's isolated target directory so `status` can report it
+    async fn record_target_dir(&self, operation_id: &str, target_dir: &Option<PathBuf>) {
+        if let Some(dir) = target_dir {
+            let _ = self
+                .monitor
+                .set_target_directory(operation_id, dir.display().to_string())
+                .await;
+        }
+    }
+
+    /// Keep isolated target dirs of a working directory within the configured disk budget
+    async fn prune_isolated_target_dirs(
+        config: &TargetIsolationConfig,
+        working_directory: &str,
+        tool: &str,
+    ) {
+        let Some(max_total_bytes) = config.max_total_bytes else {
+            return;
+        };
+        if !config.is_isolated(tool) {
+            return;
         }
+        let wd = working_directory.to_string();
+        let tool = tool.to_string();
+        let _ = tokio::task::spawn_blocking(move || {
+            crate::target_isolation::enforce_disk_budget(&wd, &tool, max_total_bytes)
+        })
+        .await;
     }
 
     fn is_tool_disabled(&self, name: &str) -> bool {
@@ -836,128 +1648,10 @@ impl AsyncCargo {
         self.ensure_enabled(name)
     }
 
-    /// Execute a cargo command using a pre-warmed shell from the pool, with graceful fallback to direct spawn
-    async fn execute_cargo_command(
-        &self,
-        command: String,
-        working_directory: Option<String>,
-        operation_id: &str,
-    ) -> Result<std::process::Output, Box<dyn std::error::Error + Send + Sync>> {
-        use std::path::PathBuf;
+    /// Check availability of optional cargo components
+    pub async fn check_component_availability() -> HashMap<String, bool> {
         use tokio::process::Command;
-
-        // Try shell pool first if we have a working directory
-        if let Some(ref working_dir) = working_directory {
-            let working_dir_path = PathBuf::from(working_dir);
-
-            tracing::debug!(
-                operation_id = operation_id,
-                command = %command,
-                working_dir = %working_dir,
-                "Attempting to use shell pool for cargo command"
-            );
-
-            // Try to get a shell from the pool
-            if let Some(mut shell) = self.shell_pool_manager.get_shell(&working_dir_path).await {
-                // Create shell command for the pool
-                let shell_command = ShellCommand {
-                    id: uuid::Uuid::new_v4().to_string(),
-                    command: vec!["bash".to_string(), "-c".to_string(), command.clone()],
-                    working_dir: working_dir.clone(),
-                    timeout_ms: 300_000, // 5 minute timeout
-                };
-                tracing::info!(
-                    operation_id = operation_id,
-                    "Sending command to shell pool shell_id={} cmd={}",
-                    shell.id(),
-                    command
-                );
-                let exec_result = shell.execute_command(shell_command).await;
-                let shell_id = shell.id().to_string();
-                match exec_result {
-                    Ok(shell_response) => {
-                        tracing::info!(
-                            operation_id = operation_id,
-                            shell_id = %shell_id,
-                            exit_code = shell_response.exit_code,
-                            stdout_len = shell_response.stdout.len(),
-                            stderr_len = shell_response.stderr.len(),
-                            duration_ms = shell_response.duration_ms,
-                            "Shell pool execution successful"
-                        );
-
-                        // Convert ShellResponse to std::process::Output
-                        use std::process::Output;
-
-                        // Get ExitStatus by running a simple command
-                        let exit_status = if shell_response.exit_code == 0 {
-                            Command::new("true").status().await.unwrap()
-                        } else {
-                            Command::new("false").status().await.unwrap()
-                        };
-
-                        let output = Output {
-                            status: exit_status,
-                            stdout: shell_response.stdout.into_bytes(),
-                            stderr: shell_response.stderr.into_bytes(),
-                        };
-
-                        // Return shell to pool before returning
-                        self.shell_pool_manager.return_shell(shell).await;
-                        return Ok(output);
-                    }
-                    Err(e) => {
-                        tracing::warn!(
-                            operation_id = operation_id,
-                            shell_id = %shell_id,
-                            error = %e,
-                            "Shell pool execution failed, will fall back"
-                        );
-                        // Attempt to return shell if it's still healthy
-                        if shell.is_healthy() {
-                            self.shell_pool_manager.return_shell(shell).await;
-                        }
-                        // Fall through to direct spawn
-                    }
-                }
-            } else {
-                tracing::debug!(
-                    operation_id = operation_id,
-                    "No shell available from pool, using direct spawn"
-                );
-            }
-        }
-
-        // Fallback to direct spawn (original behavior)
-        tracing::debug!(
-            operation_id = operation_id,
-            command = %command,
-            "Using direct spawn for cargo command"
-        );
-
-        let mut cmd = if cfg!(target_os = "windows") {
-            let mut cmd = Command::new("cmd");
-            cmd.args(["/C", &command]);
-            cmd
-        } else {
-            let mut cmd = Command::new("bash");
-            cmd.args(["-c", &command]);
-            cmd
-        };
-
-        if let Some(ref working_dir) = working_directory {
-            cmd.current_dir(working_dir);
-        }
-
-        cmd.output()
-            .await
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
-    }
-
-    /// Check availability of optional cargo components
-    pub async fn check_component_availability() -> HashMap<String, bool> {
-        use tokio::process::Command;
-        let mut availability = HashMap::new();
+        let mut availability = HashMap::new();
 
         // Check clippy
         let clippy_available = Command::new("cargo")
@@ -995,6 +1689,61 @@ impl AsyncCargo {
             .unwrap_or(false);
         availability.insert("cargo-audit".to_string(), cargo_audit_available);
 
+        // Check cargo-deny (deny command)
+        let cargo_deny_available = Command::new("cargo")
+            .args(["deny", "--version"])
+            .output()
+            .await
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+        availability.insert("cargo-deny".to_string(), cargo_deny_available);
+
+        // Check cargo-machete and cargo-udeps (unused_deps command)
+        let cargo_machete_available = Command::new("cargo")
+            .args(["machete", "--version"])
+            .output()
+            .await
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+        availability.insert("cargo-machete".to_string(), cargo_machete_available);
+        let cargo_udeps_available = Command::new("cargo")
+            .args(["+nightly", "udeps", "--version"])
+            .output()
+            .await
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+        availability.insert("cargo-udeps".to_string(), cargo_udeps_available);
+
+        // Check cargo-semver-checks (semver_checks command)
+        let cargo_semver_checks_available = Command::new("cargo")
+            .args(["semver-checks", "--version"])
+            .output()
+            .await
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+        availability.insert(
+            "cargo-semver-checks".to_string(),
+            cargo_semver_checks_available,
+        );
+
+        // Check cargo-llvm-cov (coverage command)
+        let cargo_llvm_cov_available = Command::new("cargo")
+            .args(["llvm-cov", "--version"])
+            .output()
+            .await
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+        availability.insert("cargo-llvm-cov".to_string(), cargo_llvm_cov_available);
+
+        // Check cargo-expand (expand command)
+        let cargo_expand_available = Command::new("cargo")
+            .args(["expand", "--version"])
+            .output()
+            .await
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+        availability.insert("cargo-expand".to_string(), cargo_expand_available);
+
         // Check rustfmt (for cargo fmt)
         let rustfmt_available = Command::new("rustfmt")
             .arg("--version")
@@ -1059,6 +1808,65 @@ impl AsyncCargo {
             );
         }
 
+        if *availability.get("cargo-deny").unwrap_or(&false) {
+            report.push_str(
+                "+ cargo-deny - Available (license, ban, source and advisory policy checks)\n",
+            );
+        } else {
+            report.push_str(
+                "- cargo-deny - Not available (install with: cargo install cargo-deny)\n",
+            );
+        }
+
+        if *availability.get("cargo-machete").unwrap_or(&false) {
+            report.push_str(
+                "+ cargo-machete - Available (fast unused dependency detection with the unused_deps command)\n",
+            );
+        } else {
+            report.push_str(
+                "- cargo-machete - Not available (install with: cargo install cargo-machete)\n",
+            );
+        }
+
+        if *availability.get("cargo-udeps").unwrap_or(&false) {
+            report.push_str(
+                "+ cargo-udeps - Available (accurate unused dependency detection on nightly with unused_deps tool \"udeps\")\n",
+            );
+        } else {
+            report.push_str(
+                "- cargo-udeps - Not available (install with: cargo install cargo-udeps; needs a nightly toolchain)\n",
+            );
+        }
+
+        if *availability.get("cargo-semver-checks").unwrap_or(&false) {
+            report.push_str(
+                "+ cargo-semver-checks - Available (API compatibility checks with the semver_checks command)\n",
+            );
+        } else {
+            report.push_str(
+                "- cargo-semver-checks - Not available (install with: cargo install cargo-semver-checks)\n",
+            );
+        }
+
+        if *availability.get("cargo-llvm-cov").unwrap_or(&false) {
+            report.push_str(
+                "+ cargo-llvm-cov - Available (test coverage with the coverage command)\n",
+            );
+        } else {
+            report.push_str(
+                "- cargo-llvm-cov - Not available (install with: cargo install cargo-llvm-cov)\n",
+            );
+        }
+
+        if *availability.get("cargo-expand").unwrap_or(&false) {
+            report
+                .push_str("+ cargo-expand - Available (macro expansion with the expand command)\n");
+        } else {
+            report.push_str(
+                "- cargo-expand - Not available (install with: cargo install cargo-expand)\n",
+            );
+        }
+
         if *availability.get("rustfmt").unwrap_or(&false) {
             report.push_str("+ rustfmt - Available (code formatting with cargo fmt)\n");
         } else {
@@ -1080,6 +1888,21 @@ impl AsyncCargo {
         report.push_str(
             "* Use 'audit' for security vulnerability scanning if cargo-audit is available\n",
         );
+        report.push_str(
+            "* Use 'deny' to enforce license, ban and source policies if cargo-deny is available\n",
+        );
+        report.push_str(
+            "* Use 'unused_deps' to find dependencies nothing uses if cargo-machete or cargo-udeps is available\n",
+        );
+        report.push_str(
+            "* Use 'semver_checks' before 'bump_version' to find the bump an API change requires if cargo-semver-checks is available\n",
+        );
+        report.push_str(
+            "* Use 'coverage' to find lines tests never execute if cargo-llvm-cov is available\n",
+        );
+        report.push_str(
+            "* Use 'expand' to see what derives and macros generate if cargo-expand is available\n",
+        );
         report.push_str(
             "* Enable async notifications (enable_async_notification=true) for long operations\n",
         );
@@ -1153,6 +1976,294 @@ impl AsyncCargo {
         }
     }
 
+    /// Start speculative checks of a workspace the first time a request names it
+    async fn observe_workspace(&self, working_directory: &str) {
+        if !self.speculative.is_enabled()
+            || self.speculative.is_watched(working_directory).await
+            || !Path::new(working_directory).join("Cargo.toml").is_file()
+        {
+            return;
+        }
+        let watching = std::fs::canonicalize(working_directory)
+            .and_then(|root| crate::watch::watch_directory(&root).map_err(std::io::Error::other));
+        let (watcher, changes) = match watching {
+            Ok(watching) => watching,
+            Err(e) => {
+                tracing::warn!("Not running speculative checks in {working_directory}: {e}");
+                return;
+            }
+        };
+        let working_directory_owned = working_directory.to_string();
+        let speculative = self.speculative.clone();
+        let monitor = self.monitor.clone();
+        let diagnostics = self.speculative.diagnostics();
+        let output_store = self.output_store.clone();
+        let started = self
+            .speculative
+            .watch(working_directory, move || {
+                tokio::spawn(async move {
+                    // The watcher stops delivering changes when dropped
+                    let _watcher = watcher;
+                    Self::speculative_check_loop(
+                        working_directory_owned,
+                        changes,
+                        speculative,
+                        monitor,
+                        diagnostics,
+                        output_store,
+                    )
+                    .await;
+                })
+            })
+            .await;
+        if started {
+            tracing::info!("Speculative checks enabled for {working_directory}");
+        }
+    }
+
+    /// Check `working_directory` in its own target dir after each debounced batch of changes,
+    /// once foreground operations there have finished and no other speculative check runs. A
+    /// foreground operation that starts meanwhile cancels the check, which then runs again once
+    /// the workspace is idle, so it never competes with the user's own builds for long.
+    async fn speculative_check_loop(
+        working_directory: String,
+        mut changes: tokio::sync::mpsc::UnboundedReceiver<PathBuf>,
+        speculative: Arc<SpeculativeChecks>,
+        monitor: Arc<OperationMonitor>,
+        diagnostics: Arc<DiagnosticsStore>,
+        output_store: Arc<OutputStore>,
+    ) {
+        let debounce =
+            std::time::Duration::from_millis(crate::speculative::SPECULATIVE_DEBOUNCE_MS);
+        let target_dir = crate::target_isolation::isolated_target_dir(
+            &working_directory,
+            crate::speculative::SPECULATIVE_TARGET_DIR,
+        );
+        let foreground_active = || async {
+            !monitor
+                .get_operations(|op| {
+                    op.is_active() && op.working_directory.as_deref() == Some(&working_directory)
+                })
+                .await
+                .is_empty()
+        };
+        let mut requeued = false;
+        loop {
+            if !requeued
+                && crate::watch::next_batch(&mut changes, debounce)
+                    .await
+                    .is_none()
+            {
+                break;
+            }
+            requeued = false;
+            while foreground_active().await {
+                tokio::time::sleep(debounce).await;
+            }
+            let _slot = speculative.acquire_slot().await;
+
+            let Some(fingerprint) =
+                crate::fingerprint::source_fingerprint(&working_directory).await
+            else {
+                continue;
+            };
+            if speculative
+                .cached(&working_directory, fingerprint)
+                .await
+                .is_some()
+            {
+                continue;
+            }
+
+            let operation_id = speculative.next_operation_id();
+            let req = CheckRequest {
+                working_directory: working_directory.clone(),
+                ..Default::default()
+            };
+            crate::target_isolation::mark_used(&target_dir);
+            let check = Self::check_implementation(
+                &req,
+                Some(&target_dir),
+                &diagnostics,
+                &output_store,
+                &operation_id,
+            );
+            let preempted = async {
+                while !foreground_active().await {
+                    tokio::time::sleep(debounce).await;
+                }
+            };
+            // Dropping the check kills its cargo process
+            let result = tokio::select! {
+                result = check => result,
+                () = preempted => {
+                    tracing::debug!(
+                        "Speculative check {operation_id} in {working_directory} cancelled by a foreground operation"
+                    );
+                    requeued = true;
+                    continue;
+                }
+            };
+            tracing::debug!(
+                "Speculative check {operation_id} in {working_directory} finished (success: {})",
+                result.is_ok()
+            );
+            speculative
+                .record(
+                    &working_directory,
+                    SpeculativeResult {
+                        operation_id,
+                        fingerprint,
+                        result,
+                        finished_at: timestamp::format_current_time(),
+                    },
+                )
+                .await;
+        }
+    }
+
+    /// The latest speculative check of `working_directory` if its sources have not changed since
+    async fn cached_speculative_check(&self, working_directory: &str) -> Option<SpeculativeResult> {
+        // Fingerprinting walks the tree, so skip it when there is nothing to compare with
+        self.speculative.latest(working_directory).await?;
+        let fingerprint = crate::fingerprint::source_fingerprint(working_directory).await?;
+        self.speculative
+            .cached(working_directory, fingerprint)
+            .await
+    }
+
+    /// Return a speculative check as the result of `check_id`. Its report moves into the user's
+    /// store, where the delta against the user's previous check is computed now, making it the
+    /// baseline for the next one.
+    async fn replay_speculative_check(
+        &self,
+        check_id: &str,
+        working_directory: &str,
+        cached: SpeculativeResult,
+        options: &CondenseOptions,
+    ) -> Result<CallToolResult, ErrorData> {
+        let note = format!(
+            "Sources unchanged since speculative check {} finished at {}; returning its result without running cargo.",
+            cached.operation_id, cached.finished_at
+        );
+        let mut result = cached.result;
+        let report = self
+            .speculative
+            .diagnostics()
+            .get(&cached.operation_id)
+            .await;
+        if let Some(mut report) = report {
+            // Same scope as check_implementation gives the default request
+            let scope = DiagnosticScope::new("check", working_directory, [MESSAGE_FORMAT_ARG]);
+            report.delta = self.diagnostics.delta(&scope, check_id, &report).await;
+            if let Some(delta) = &report.delta {
+                let rendered = delta.render();
+                result = match result {
+                    Ok(text) => Ok(format!("{text}\n\n{rendered}")),
+                    Err(text) => Err(format!("{text}\n\n{rendered}")),
+                };
+            }
+            // The text names the speculative operation, e.g. for apply_suggestions
+            self.diagnostics
+                .record(&cached.operation_id, report.clone())
+                .await;
+            self.diagnostics.record(check_id, report).await;
+        }
+        let earlier = CachedResult {
+            operation_id: cached.operation_id,
+            result,
+            finished_at: cached.finished_at,
+        };
+        self.replay_result("check", check_id, &note, earlier, options)
+            .await
+    }
+
+    /// Fingerprint of a request to `tool` with the arguments `key`, if results of `tool` are cached
+    async fn result_fingerprint(
+        &self,
+        tool: &str,
+        working_directory: &str,
+        key: &str,
+    ) -> Option<RequestFingerprint> {
+        if !self.result_cache.is_enabled(tool) {
+            return None;
+        }
+        RequestFingerprint::compute(working_directory, tool, key).await
+    }
+
+    /// Keep the result of a fingerprinted request for identical later requests, under the
+    /// fingerprint the workspace has now that the request has run
+    async fn cache_result(
+        cache: &ResultCache,
+        fingerprint: Option<RequestFingerprint>,
+        operation_id: &str,
+        result: &Result<String, String>,
+    ) {
+        let Some(before) = fingerprint else {
+            return;
+        };
+        let Some(after) = before.after_run().await else {
+            return;
+        };
+        let cached = CachedResult {
+            operation_id: operation_id.to_string(),
+            result: result.clone(),
+            finished_at: timestamp::format_current_time(),
+        };
+        cache.store(after.key, cached).await;
+    }
+
+    /// Answer a request to `tool` with the result of an earlier operation, preceded by `note`.
+    /// The earlier operation's diagnostics are copied to `operation_id`.
+    async fn replay_result(
+        &self,
+        tool: &str,
+        operation_id: &str,
+        note: &str,
+        earlier: CachedResult,
+        options: &CondenseOptions,
+    ) -> Result<CallToolResult, ErrorData> {
+        if let Some(report) = self.diagnostics.get(&earlier.operation_id).await {
+            self.diagnostics.record(operation_id, report).await;
+        }
+        let result = match earlier.result {
+            Ok(text) => Ok(format!("{note}\n{text}")),
+            Err(text) => Err(format!("{note}\n{text}")),
+        };
+        let result = self
+            .output_store
+            .finish_result(operation_id, result, options);
+        let response = Self::handle_sync_result(
+            tool,
+            &format!("cargo {tool}"),
+            &format!("Reused result of {}", earlier.operation_id),
+            result,
+        );
+        self.with_diagnostics(operation_id, response).await
+    }
+
+    /// Keep the request of a test run for rerun_failed, evicting the oldest beyond the limit
+    async fn remember_test_run(&self, operation_id: &str, run: TestRun) {
+        let mut runs = self.test_runs.write().await;
+        runs.push_back((operation_id.to_string(), run));
+        while runs.len() > MAX_STORED_TEST_RUNS {
+            runs.pop_front();
+        }
+    }
+
+    /// Attach the structured diagnostics recorded for an operation as `structuredContent`
+    async fn with_diagnostics(
+        &self,
+        operation_id: &str,
+        result: Result<CallToolResult, ErrorData>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let mut result = result?;
+        if let Some(report) = self.diagnostics.get(operation_id).await {
+            result.structured_content = serde_json::to_value(report).ok();
+        }
+        Ok(result)
+    }
+
     /// Create a comprehensive final result for automatic push notifications
     pub fn create_final_result_update(
         operation_id: &str,
@@ -1280,6 +2391,7 @@ impl AsyncCargo {
                             first_wait_time: None,
                             timeout_duration: None,
                             working_directory: None,
+                            target_directory: None,
                             result: Some(Err(err)),
                             cancellation_token: tokio_util::sync::CancellationToken::new(),
                         };
@@ -1298,6 +2410,7 @@ impl AsyncCargo {
                             first_wait_time: None,
                             timeout_duration: None,
                             working_directory: None,
+                            target_directory: None,
                             result: Some(Err(msg)),
                             cancellation_token: tokio_util::sync::CancellationToken::new(),
                         };
@@ -1601,7 +2714,21 @@ impl AsyncCargo {
             TerminalOutput::display_wait_results(&terminal_results);
         }
 
-        Ok(CallToolResult::success(final_content))
+        // Structured diagnostics of build/check/clippy/test operations, keyed by operation ID
+        let mut structured = serde_json::Map::new();
+        for op_info in &results {
+            if let Some(report) = self.diagnostics.get(&op_info.id).await
+                && let Ok(value) = serde_json::to_value(report)
+            {
+                structured.insert(op_info.id.clone(), value);
+            }
+        }
+
+        let mut response = CallToolResult::success(final_content);
+        if !structured.is_empty() {
+            response.structured_content = Some(serde_json::json!({ "operations": structured }));
+        }
+        Ok(response)
     }
 
     #[tool(
@@ -1725,12 +2852,140 @@ impl AsyncCargo {
                     status_lines.push(format!("  {}", status_line));
                 }
             }
+
+            status_lines.extend(
+                self.format_shell_pool_status(req.working_directory.as_deref())
+                    .await,
+            );
         }
 
         let status_text = status_lines.join("\n");
         Ok(CallToolResult::success(vec![Content::text(status_text)]))
     }
 
+    #[tool(
+        description = "GET OUTPUT: Read the full output of an operation whose result was truncated (results above the inline limit show only head and tail). Page by byte offset (offset/limit), by line range (start_line/end_line), or grep for text with context lines. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal."
+    )]
+    async fn get_output(
+        &self,
+        Parameters(req): Parameters<GetOutputRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        use crate::output_store::{DEFAULT_PAGE_BYTES, grep_lines, lines_in_range, page_by_bytes};
+
+        let operation_id = req.operation_id.trim();
+        let full_output = match self.output_store.read_spilled(operation_id) {
+            Some(text) => Some(text),
+            None => self
+                .find_operation(operation_id)
+                .await
+                .and_then(|op| op.result)
+                .map(|result| match result {
+                    Ok(text) | Err(text) => text,
+                }),
+        };
+        let Some(text) = full_output else {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "No output found for operation '{operation_id}'. It may still be running, may not exist, or its output was never recorded."
+            ))]));
+        };
+
+        let response = if let Some(pattern) = req.grep.as_deref().filter(|p| !p.is_empty()) {
+            const MAX_MATCHES: usize = 200;
+            let matched = grep_lines(&text, pattern, req.context.unwrap_or(2), MAX_MATCHES);
+            if matched.is_empty() {
+                format!(
+                    "Output of {operation_id}: no lines contain '{pattern}' ({} lines searched)",
+                    text.lines().count()
+                )
+            } else {
+                format!(
+                    "Output of {operation_id}: lines containing '{pattern}' (first {MAX_MATCHES} matches at most, ':' marks a match):\n{}",
+                    matched.join("\n")
+                )
+            }
+        } else if req.start_line.is_some() || req.end_line.is_some() {
+            let total_lines = text.lines().count();
+            let start = req.start_line.unwrap_or(1).max(1);
+            let end = req.end_line.unwrap_or(start + 199).min(total_lines);
+            let lines = lines_in_range(&text, start, end);
+            format!(
+                "Output of {operation_id} (lines {start}-{end} of {total_lines}):\n{}",
+                lines.join("\n")
+            )
+        } else {
+            let page = page_by_bytes(
+                &text,
+                req.offset.unwrap_or(0),
+                req.limit.unwrap_or(DEFAULT_PAGE_BYTES),
+            );
+            let next = if page.end < page.total_bytes {
+                format!(
+                    "\n[More output available: call get_output with offset={}]",
+                    page.end
+                )
+            } else {
+                String::new()
+            };
+            format!(
+                "Output of {operation_id} (bytes {start}-{end} of {total}):\n{text}{next}",
+                start = page.start,
+                end = page.end,
+                total = page.total_bytes,
+                text = page.text,
+            )
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(response)]))
+    }
+
+    /// Look up an operation among active and completed operations
+    async fn find_operation(
+        &self,
+        operation_id: &str,
+    ) -> Option<crate::operation_monitor::OperationInfo> {
+        if let Some(op) = self.monitor.get_operation(operation_id).await {
+            return Some(op);
+        }
+        self.monitor
+            .get_completed_operations()
+            .await
+            .into_iter()
+            .find(|op| op.id == operation_id)
+    }
+
+    /// Format shell pool usage for the status overview, optionally limited to one directory
+    async fn format_shell_pool_status(&self, working_directory: Option<&str>) -> Vec<String> {
+        if !self.shell_pool_manager.config().enabled {
+            return Vec::new();
+        }
+        let stats = self.shell_pool_manager.get_stats().await;
+        let mut lines = vec![format!(
+            "Shell pools: {pools} pools, {in_use}/{max} shells in use",
+            pools = stats.total_pools,
+            in_use = stats.total_shells,
+            max = stats.max_shells,
+        )];
+        for dir in stats.directories.iter().filter(|d| {
+            working_directory.is_none_or(|filter| d.working_dir.to_string_lossy().contains(filter))
+        }) {
+            lines.push(format!(
+                "  {path}: {idle} idle, {in_use} in use (peak {peak}), target {target}{prewarmed} | hits {hits}, misses {misses}, fallbacks {fallbacks} ({rate:.0}% hit rate), avg wait {wait}ms",
+                path = dir.working_dir.display(),
+                idle = dir.idle_shells,
+                in_use = dir.in_use,
+                peak = dir.peak_in_use,
+                target = dir.target_size,
+                prewarmed = if dir.prewarmed { ", prewarmed" } else { "" },
+                hits = dir.hits,
+                misses = dir.misses,
+                fallbacks = dir.fallbacks,
+                rate = dir.hit_rate() * 100.0,
+                wait = dir.avg_wait.as_millis(),
+            ));
+        }
+        lines
+    }
+
     /// Format a single operation's status for display
     fn format_operation_status(
         &self,
@@ -1758,8 +3013,14 @@ impl AsyncCargo {
             String::new()
         };
 
+        let target_info = operation
+            .target_directory
+            .as_deref()
+            .map(|dir| format!(" [target dir: {dir}]"))
+            .unwrap_or_default();
+
         format!(
-            "[{id}] {state_text} ({command}) - {duration_str} in {working_dir}{concurrency_info}",
+            "[{id}] {state_text} ({command}) - {duration_str} in {working_dir}{target_info}{concurrency_info}",
             id = operation.id,
             command = operation.command,
         )
@@ -1837,671 +3098,2007 @@ impl AsyncCargo {
     }
 
     #[tool(
-        description = "Attempt remediation for a stale Cargo lock file. Options: A = delete target/.cargo-lock then cargo clean; B = only delete .cargo-lock; C = do nothing. Cancels active jobs for the directory before deletion. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal."
+        description = "APPLY SUGGESTIONS: Apply the fixes rustc/clippy suggested in a previous build, check, clippy or test run (by operation_id). Filter by lints, files and applicability (default MachineApplicable only). Shows a unified diff; all files are written or none. Use dry_run=true to preview and undo_suggestions to revert. Finer-grained than 'fix'. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal."
     )]
-    async fn cargo_lock_remediation(
+    async fn apply_suggestions(
         &self,
-        Parameters(req): Parameters<CargoLockRemediationRequest>,
-        _context: RequestContext<RoleServer>,
+        Parameters(req): Parameters<ApplySuggestionsRequest>,
     ) -> Result<CallToolResult, ErrorData> {
-        let dir = req.working_directory.clone();
-        let action = req.action.clone();
-        let summary = self
-            .perform_cargo_lock_remediation(&dir, action)
+        self.ensure_enabled("apply_suggestions")?;
+        let operation_id = req.operation_id.trim();
+        let Some((report, recorded_at)) = self.diagnostics.get_with_time(operation_id).await else {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "No diagnostics recorded for operation '{operation_id}'. Run build, check, clippy or test first and pass its operation_id."
+            ))]));
+        };
+
+        let filter = SuggestionFilter {
+            lints: req.lints,
+            files: req.files,
+            applicability: req.applicability.unwrap_or_default(),
+        };
+        let working_directory = req.working_directory.clone();
+        let plan = tokio::task::spawn_blocking(move || {
+            crate::suggestions::plan_edits(
+                &report,
+                Path::new(&working_directory),
+                &filter,
+                recorded_at,
+            )
+        })
+        .await
+        .map_err(|e| ErrorData::internal_error(format!("Planning edits failed: {e}"), None))?;
+
+        let skipped = if plan.skipped.is_empty() {
+            String::new()
+        } else {
+            format!("\nSkipped:\n  - {}", plan.skipped.join("\n  - "))
+        };
+        if plan.files.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "No suggestions from {operation_id} match the filter.{skipped}"
+            ))]));
+        }
+
+        let summary = format!(
+            "{} suggestion(s) in {} file(s) from {operation_id}",
+            plan.replacements(),
+            plan.files.len()
+        );
+        let diff = plan.diff();
+        if req.dry_run.unwrap_or(false) {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "Dry run: would apply {summary}. No files were changed.{skipped}\n\n```diff\n{diff}```"
+            ))]));
+        }
+
+        let apply_id = self.generate_operation_id_for("apply_suggestions");
+        let backups = self.suggestion_backups.clone();
+        let id = apply_id.clone();
+        let applied = tokio::task::spawn_blocking(move || backups.apply(&id, &plan))
             .await
-            .map_err(|e| ErrorData::internal_error(e, None))?;
+            .map_err(|e| ErrorData::internal_error(format!("Applying edits failed: {e}"), None))?;
+        let text = match applied {
+            Ok(()) => format!(
+                "Applied {summary} (apply_id={apply_id}). Revert with undo_suggestions apply_id=\"{apply_id}\". Rerun check to refresh diagnostics.{skipped}\n\n```diff\n{diff}```"
+            ),
+            Err(e) => format!("- Failed to apply suggestions; no files were changed: {e}"),
+        };
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
 
-        let explanation = format!(
-            "A Cargo internal lock file can persist at <dir>/target/.cargo-lock when previous commands crashed or were interrupted, causing new cargo invocations to block.\nDirectory: {dir}\nResult: {summary}"
+    #[tool(
+        description = "UNDO SUGGESTIONS: Restore the files changed by apply_suggestions (default: the most recent apply). Refuses if the files were edited since, unless force=true. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal."
+    )]
+    async fn undo_suggestions(
+        &self,
+        Parameters(req): Parameters<UndoSuggestionsRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.ensure_enabled("undo_suggestions")?;
+        let backups = self.suggestion_backups.clone();
+        let undone = tokio::task::spawn_blocking(move || {
+            backups.undo(req.apply_id.as_deref(), req.force.unwrap_or(false))
+        })
+        .await
+        .map_err(|e| ErrorData::internal_error(format!("Undo failed: {e}"), None))?;
+        let text = match undone {
+            Ok((apply_id, files)) => format!(
+                "Reverted {apply_id}: restored {}",
+                files
+                    .iter()
+                    .map(|f| f.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Err(e) => format!("- Nothing reverted: {e}"),
+        };
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    #[tool(
+        description = "RERUN FAILED: Re-run only the tests that failed in a previous test or nextest operation, with exact name filters and the same package, target and feature selection. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+    )]
+    async fn rerun_failed(
+        &self,
+        Parameters(req): Parameters<RerunFailedRequest>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.ensure_enabled("rerun_failed")?;
+        let run = self
+            .test_runs
+            .read()
+            .await
+            .iter()
+            .find(|(id, _)| *id == req.operation_id)
+            .map(|(_, run)| run.clone());
+        let Some(run) = run else {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "- Cannot rerun: no test or nextest run recorded for operation_id=\"{}\".",
+                req.operation_id
+            ))]));
+        };
+        let failures: Vec<TestCase> = self
+            .diagnostics
+            .get(&req.operation_id)
+            .await
+            .and_then(|report| report.tests)
+            .map(|tests| tests.failures().cloned().collect())
+            .unwrap_or_default();
+        if failures.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "No failed tests recorded for {}; nothing to rerun. If the run is still in progress, wait for it first.",
+                req.operation_id
+            ))]));
+        }
+
+        let mut names: Vec<String> = Vec::new();
+        for test in &failures {
+            if !names.contains(&test.name) {
+                names.push(test.name.clone());
+            }
+        }
+        let header = format!(
+            "Re-running {} failed test(s) from {}: {}",
+            names.len(),
+            req.operation_id,
+            names.join(", ")
         );
-        Ok(CallToolResult::success(vec![Content::text(explanation)]))
+
+        let mut result = match run {
+            TestRun::Cargo(original) => {
+                let rerun = TestRequest {
+                    test_name: None,
+                    test_args: Some(exact_test_args(
+                        original.test_args.as_deref().unwrap_or_default(),
+                        &names,
+                    )),
+                    no_run: None,
+                    force: Some(true),
+                    enable_async_notification: req.enable_async_notification,
+                    ..*original
+                };
+                self.test(Parameters(rerun), context).await?
+            }
+            TestRun::Nextest(original) => {
+                let mut args = without_filterset(original.args.as_deref().unwrap_or_default());
+                let failed: Vec<&TestCase> = failures.iter().collect();
+                args.extend(["-E".to_string(), nextest_filterset(&failed)]);
+                let rerun = NextestRequest {
+                    args: Some(args),
+                    enable_async_notification: req.enable_async_notification,
+                    ..original
+                };
+                self.nextest(Parameters(rerun), context).await?
+            }
+        };
+        if let Some(RawContent::Text(first)) = result.content.first_mut().map(|c| &mut c.raw) {
+            first.text = format!("{header}\n{}", first.text);
+        }
+        Ok(result)
     }
 
     #[tool(
-        description = "CARGO BUILD: Faster than terminal cargo. Use enable_async_notification=true for builds >1s to multitask. Structured output with isolation. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+        description = "DETECT FLAKY: Run the selected tests N times (sequentially or in parallel) and report each test's pass rate and distinct failure messages. Tests that both pass and fail are flagged and marked as known-flaky in later test results. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
     )]
-    async fn build(
+    async fn detect_flaky(
         &self,
-        Parameters(req): Parameters<BuildRequest>,
+        Parameters(req): Parameters<DetectFlakyRequest>,
         context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.ensure_enabled("build")?;
-        let build_id = self.generate_operation_id_for("build");
+        self.ensure_enabled("detect_flaky")?;
+        let flaky_id = self.generate_operation_id_for("detect_flaky");
 
-        // Check if async notifications are enabled and not in synchronous mode
         if self.should_run_synchronously(req.enable_async_notification) {
-            // Synchronous operation for when async notifications are disabled or synchronous mode is enabled
-            let result = self.build_implementation(&req, "sync_build").await;
-            return Self::handle_sync_result(
-                "build",
-                "cargo build",
-                "Synchronous build operation",
-                result,
-            );
+            let response = match self.output_store.bound_result(
+                &flaky_id,
+                Self::detect_flaky_implementation(
+                    &req,
+                    &self.diagnostics,
+                    &self.output_store,
+                    &flaky_id,
+                )
+                .await,
+            ) {
+                Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
+                Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
+            };
+            self.with_diagnostics(&flaky_id, response).await
         } else {
-            // TRUE 2-STAGE ASYNC PATTERN:
-            // 1. Send immediate response that operation has started
-            // 2. Spawn background task to do actual work and send notifications
-
             let peer = context.peer.clone();
             let req_clone = req.clone();
-            let build_id_clone = build_id.clone();
+            let flaky_id_clone = flaky_id.clone();
             let monitor = self.monitor.clone();
-            let shell_pool_manager = self.shell_pool_manager.clone();
+            let output_store = self.output_store.clone();
+            let diagnostics = self.diagnostics.clone();
 
-            // Register operation BEFORE spawning so wait() can find it immediately
             self.register_async_operation(
-                &build_id,
-                "cargo build",
-                "Building project in the background",
+                &flaky_id,
+                "cargo test (repeated)",
+                "Running tests repeatedly to detect flakiness in the background",
                 Some(req.working_directory.clone()),
             )
             .await;
 
-            // Spawn background task for actual build work
             tokio::spawn(async move {
-                // Create MCP callback sender to notify the LLM client
-                let callback = mcp_callback(peer.clone(), build_id_clone.clone());
+                let callback = mcp_callback(peer, flaky_id_clone.clone());
 
-                // Send started notification immediately
                 let _ = callback
                     .send_progress(ProgressUpdate::Started {
-                        operation_id: build_id_clone.clone(),
-                        command: "cargo build".to_string(),
-                        description: "Building project in the background".to_string(),
+                        operation_id: flaky_id_clone.clone(),
+                        command: "cargo test (repeated)".to_string(),
+                        description:
+                            "Running tests repeatedly to detect flakiness in the background"
+                                .to_string(),
                     })
                     .await;
 
                 let started_at = Instant::now();
-                // Do the actual build work
-                let result = Self::build_implementation_static(
+                let result = Self::detect_flaky_implementation(
                     &req_clone,
-                    &build_id_clone,
-                    shell_pool_manager,
+                    &diagnostics,
+                    &output_store,
+                    &flaky_id_clone,
                 )
                 .await;
-
-                // Store the result in the operation monitor for later retrieval by wait
-                // This ensures the full output (stdout/stderr) is available to `wait`
+                let result = output_store.bound_result(&flaky_id_clone, result);
                 let _ = monitor
-                    .complete_operation(&build_id_clone, result.clone())
+                    .complete_operation(&flaky_id_clone, result.clone())
                     .await;
 
-                // Send completion notification with measured duration
                 let duration_ms = started_at.elapsed().as_millis() as u64;
-
-                // Send brief completion update for legacy support
                 let completion_update = match result {
-                    Ok(ref msg) => ProgressUpdate::Completed {
-                        operation_id: build_id_clone.clone(),
-                        message: msg.clone(),
+                    Ok(msg) => ProgressUpdate::Completed {
+                        operation_id: flaky_id_clone,
+                        message: msg,
                         duration_ms,
                     },
-                    Err(ref err) => ProgressUpdate::Failed {
-                        operation_id: build_id_clone.clone(),
-                        error: err.clone(),
+                    Err(err) => ProgressUpdate::Failed {
+                        operation_id: flaky_id_clone,
+                        error: err,
                         duration_ms,
                     },
                 };
 
-                if let Err(e) = callback.send_progress(completion_update).await {
-                    tracing::error!("Failed to send build completion progress update: {e:?}");
-                }
-
-                // Send comprehensive final result for AI consumption
-                let final_result_update = Self::create_final_result_update(
-                    &build_id_clone,
-                    "cargo build",
-                    "Building project in the background",
-                    &req_clone.working_directory,
-                    &result,
-                    duration_ms,
-                );
-
-                if let Err(e) = callback.send_progress(final_result_update).await {
-                    tracing::error!("Failed to send build final result update: {e:?}");
-                }
+                let _ = callback.send_progress(completion_update).await;
             });
 
-            // Return immediate response to LLM - this is the "first stage"
-            let tool_hint = self.generate_tool_hint(&build_id, "build");
+            let tool_hint = self.generate_tool_hint(&flaky_id, "flaky test detection");
             let timestamp = timestamp::format_current_time();
             Ok(CallToolResult::success(vec![Content::text(format!(
-                "Build operation {build_id} started at {timestamp} in the background.{tool_hint}"
+                "Flaky test detection {flaky_id} started at {timestamp} in the background.{tool_hint}"
             ))]))
         }
     }
 
-    /// Internal implementation of build logic using shell pool
-    async fn build_implementation(
-        &self,
-        req: &BuildRequest,
+    /// Build the tests once, run them `runs` times and aggregate per-test pass rates
+    async fn detect_flaky_implementation(
+        req: &DetectFlakyRequest,
+        diagnostics: &DiagnosticsStore,
+        output_store: &OutputStore,
         operation_id: &str,
     ) -> Result<String, String> {
-        let mut cmd_args = vec!["cargo".to_string(), "build".to_string()];
-
-        // Add package selection
-        if req.workspace.unwrap_or(false) {
-            cmd_args.push("--workspace".to_string());
-        }
-
-        if let Some(exclude) = &req.exclude {
-            for package in exclude {
-                cmd_args.extend(vec!["--exclude".to_string(), package.clone()]);
-            }
+        let runs = req
+            .runs
+            .unwrap_or(DEFAULT_FLAKY_RUNS)
+            .clamp(1, MAX_FLAKY_RUNS) as usize;
+        let parallel = req.parallel.unwrap_or(false);
+        let working_directory = req.working_directory.clone();
+
+        let mut cargo_args = vec!["test".to_string()];
+        if let Some(package) = &req.package {
+            cargo_args.extend(["--package".to_string(), package.clone()]);
         }
-
-        // Add target selection
-        if req.lib.unwrap_or(false) {
-            cmd_args.push("--lib".to_string());
+        cargo_args.extend(req.args.iter().flatten().cloned());
+        if let Some(test_name) = &req.test_name {
+            cargo_args.push(test_name.clone());
         }
+        let test_args = match &req.tests {
+            Some(tests) if !tests.is_empty() => exact_test_args(&[], tests),
+            _ => Vec::new(),
+        };
 
-        if req.bins.unwrap_or(false) {
-            cmd_args.push("--bins".to_string());
+        // Compile once so that the runs do not race to build or report compile errors N times
+        let mut build = cargo_command();
+        build
+            .args(&cargo_args)
+            .arg("--no-run")
+            .current_dir(&working_directory);
+        let build = output_store
+            .capture(operation_id, &mut build)
+            .await
+            .map_err(|e| format!("Failed to execute cargo test: {e}"))?;
+        if !build.status.success() {
+            return Err(format!(
+                "- Flaky test detection {operation_id} failed in {working_directory}: the tests did not compile.\nErrors: {}",
+                String::from_utf8_lossy(&build.stderr)
+            ));
         }
 
-        if let Some(bin_name) = &req.bin_name {
-            cmd_args.extend(vec!["--bin".to_string(), bin_name.clone()]);
-        }
+        // Each run streams to a log of its own, named after the operation and the run number
+        let run_once = move |output_store: OutputStore,
+                             run_id: String,
+                             cargo_args: Vec<String>,
+                             test_args: Vec<String>,
+                             dir: String| async move {
+            let mut cmd = cargo_command();
+            cmd.args(&cargo_args)
+                .arg("--no-fail-fast")
+                .arg("--")
+                .args(&test_args)
+                .current_dir(&dir);
+            let output = output_store.capture(&run_id, &mut cmd).await.ok()?;
+            let (report, _) = parse_test_output(
+                &String::from_utf8_lossy(&output.stdout),
+                &String::from_utf8_lossy(&output.stderr),
+            );
+            (!report.is_empty()).then_some(report)
+        };
 
-        if req.examples.unwrap_or(false) {
-            cmd_args.push("--examples".to_string());
+        let mut outcomes: Vec<Option<TestReport>> = Vec::with_capacity(runs);
+        if parallel {
+            let mut set = tokio::task::JoinSet::new();
+            for run in 1..=runs {
+                set.spawn(run_once(
+                    output_store.clone(),
+                    format!("{operation_id}_run{run}"),
+                    cargo_args.clone(),
+                    test_args.clone(),
+                    working_directory.clone(),
+                ));
+            }
+            while let Some(outcome) = set.join_next().await {
+                outcomes.push(outcome.ok().flatten());
+            }
+        } else {
+            for run in 1..=runs {
+                outcomes.push(
+                    run_once(
+                        output_store.clone(),
+                        format!("{operation_id}_run{run}"),
+                        cargo_args.clone(),
+                        test_args.clone(),
+                        working_directory.clone(),
+                    )
+                    .await,
+                );
+            }
         }
 
-        if let Some(example) = &req.example {
-            cmd_args.extend(vec!["--example".to_string(), example.clone()]);
+        let run_errors = outcomes.iter().filter(|o| o.is_none()).count();
+        let reports: Vec<TestReport> = outcomes.into_iter().flatten().collect();
+        let report = FlakyReport::from_runs(&reports, parallel, run_errors);
+        if report.tests.is_empty() {
+            return Err(format!(
+                "- Flaky test detection {operation_id} found no tests to run in {working_directory}; check the test names and filters."
+            ));
         }
+        let summary = report.render();
+        diagnostics
+            .record_flaky(&working_directory, operation_id, report)
+            .await;
+        Ok(format!(
+            "Flaky test detection {operation_id} completed in {working_directory}.\n{summary}"
+        ))
+    }
 
-        if req.tests.unwrap_or(false) {
-            cmd_args.push("--tests".to_string());
-        }
+    #[tool(
+        description = "EXPLAIN: Full `rustc --explain` text for an error code (e.g. E0502) from the working directory's toolchain, with erroneous and fixed examples. Build, check, clippy and test results already include a one-paragraph excerpt per error code; use this for the rest. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal."
+    )]
+    async fn explain(
+        &self,
+        Parameters(req): Parameters<ExplainRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.ensure_enabled("explain")?;
+        let Some(code) = crate::explain::normalize_error_code(&req.code) else {
+            return Err(ErrorData::invalid_params(
+                format!(
+                    "'{}' is not a rustc error code; expected a code such as E0502",
+                    req.code
+                ),
+                None,
+            ));
+        };
+        let result_msg = match self
+            .diagnostics
+            .explain(&req.working_directory, &code)
+            .await
+        {
+            Ok(explanation) => format!("{code}:\n\n{explanation}"),
+            Err(error) => format!("- No explanation for {code}: {error}"),
+        };
+        Ok(CallToolResult::success(vec![Content::text(result_msg)]))
+    }
 
-        if let Some(test) = &req.test {
-            cmd_args.extend(vec!["--test".to_string(), test.clone()]);
-        }
+    #[tool(
+        description = "LIST TESTS: List all tests without running them (builds test targets): name, binary/target, package, ignored flag and doc-test location. Filter by name substring or package, then pass exact names to test (with --exact) or nextest. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+    )]
+    async fn list_tests(
+        &self,
+        Parameters(req): Parameters<ListTestsRequest>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.ensure_enabled("list_tests")?;
+        let list_id = self.generate_operation_id_for("list_tests");
 
-        if req.all_targets.unwrap_or(false) {
-            cmd_args.push("--all-targets".to_string());
-        }
+        if self.should_run_synchronously(req.enable_async_notification) {
+            let response = match self.output_store.bound_result(
+                &list_id,
+                Self::list_tests_implementation(
+                    &req,
+                    &self.diagnostics,
+                    &self.output_store,
+                    &list_id,
+                )
+                .await,
+            ) {
+                Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
+                Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
+            };
+            self.with_diagnostics(&list_id, response).await
+        } else {
+            let peer = context.peer.clone();
+            let req_clone = req.clone();
+            let list_id_clone = list_id.clone();
+            let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
+            let diagnostics = self.diagnostics.clone();
 
-        // Add feature selection
-        if let Some(features) = &req.features
-            && !features.is_empty()
-        {
-            // Filter out literal "default" which causes error if not declared explicitly
-            let filtered: Vec<String> = features
-                .iter()
-                .filter(|f| f.as_str() != "default")
-                .cloned()
-                .collect();
-            if !filtered.is_empty() {
-                cmd_args.extend(vec!["--features".to_string(), filtered.join(",")]);
-            }
-        }
+            self.register_async_operation(
+                &list_id,
+                "cargo test -- --list",
+                "Listing tests in the background",
+                Some(req.working_directory.clone()),
+            )
+            .await;
 
-        if req.all_features.unwrap_or(false) {
-            cmd_args.push("--all-features".to_string());
-        }
+            tokio::spawn(async move {
+                let callback = mcp_callback(peer, list_id_clone.clone());
 
-        if req.no_default_features.unwrap_or(false) {
-            cmd_args.push("--no-default-features".to_string());
-        }
+                let _ = callback
+                    .send_progress(ProgressUpdate::Started {
+                        operation_id: list_id_clone.clone(),
+                        command: "cargo test -- --list".to_string(),
+                        description: "Listing tests in the background".to_string(),
+                    })
+                    .await;
 
-        // Add compilation options
+                let started_at = Instant::now();
+                let result = Self::list_tests_implementation(
+                    &req_clone,
+                    &diagnostics,
+                    &output_store,
+                    &list_id_clone,
+                )
+                .await;
+                let result = output_store.bound_result(&list_id_clone, result);
+                let _ = monitor
+                    .complete_operation(&list_id_clone, result.clone())
+                    .await;
+
+                let duration_ms = started_at.elapsed().as_millis() as u64;
+                let completion_update = match result {
+                    Ok(msg) => ProgressUpdate::Completed {
+                        operation_id: list_id_clone,
+                        message: msg,
+                        duration_ms,
+                    },
+                    Err(err) => ProgressUpdate::Failed {
+                        operation_id: list_id_clone,
+                        error: err,
+                        duration_ms,
+                    },
+                };
+
+                let _ = callback.send_progress(completion_update).await;
+            });
+
+            let tool_hint = self.generate_tool_hint(&list_id, "test listing");
+            let timestamp = timestamp::format_current_time();
+            Ok(CallToolResult::success(vec![Content::text(format!(
+                "Test listing {list_id} started at {timestamp} in the background.{tool_hint}"
+            ))]))
+        }
+    }
+
+    /// List tests with libtest's `--list` (or `cargo nextest list`), then filter them
+    async fn list_tests_implementation(
+        req: &ListTestsRequest,
+        diagnostics: &DiagnosticsStore,
+        output_store: &OutputStore,
+        operation_id: &str,
+    ) -> Result<String, String> {
+        let working_directory = &req.working_directory;
+        let mut selection: Vec<String> = Vec::new();
+        if let Some(package) = &req.package {
+            selection.extend(["--package".to_string(), package.clone()]);
+        }
+        selection.extend(req.args.iter().flatten().cloned());
+
+        let mut tests = if req.nextest.unwrap_or(false) {
+            let mut cmd = cargo_command();
+            cmd.args(["nextest", "list", "--message-format", "json"])
+                .args(&selection)
+                .current_dir(working_directory);
+            let output = output_store
+                .capture(operation_id, &mut cmd)
+                .await
+                .map_err(|e| format!("Failed to execute cargo nextest list: {e}"))?;
+            if !output.status.success() {
+                return Err(format!(
+                    "- Test listing {operation_id} failed in {working_directory}.\nErrors: {}",
+                    String::from_utf8_lossy(&output.stderr)
+                ));
+            }
+            parse_nextest_list(&String::from_utf8_lossy(&output.stdout))
+        } else {
+            // Nightly reports ignored flags and source locations in one JSON listing; on stable
+            // a second listing with --ignored marks the ignored tests
+            let json_events = supports_libtest_json(working_directory).await;
+            let list = |ignored_only: bool| {
+                let mut cmd = cargo_command();
+                cmd.args(["test", MESSAGE_FORMAT_ARG])
+                    .args(&selection)
+                    .args(["--", "--list"])
+                    .current_dir(working_directory);
+                if json_events {
+                    cmd.args(["-Z", "unstable-options", "--format", "json"]);
+                }
+                if ignored_only {
+                    cmd.arg("--ignored");
+                }
+                // The --ignored listing gets a log of its own so it does not replace the first
+                let log_id = match ignored_only {
+                    true => format!("{operation_id}_ignored"),
+                    false => operation_id.to_string(),
+                };
+                async move {
+                    let mut messages = CargoMessageParser::default();
+                    let output = output_store
+                        .capture_with(&log_id, &mut cmd, |line| messages.line(line))
+                        .await
+                        .map_err(|e| format!("Failed to execute cargo test --list: {e}"))?;
+                    let report = messages.finish();
+                    let text = String::from_utf8_lossy(&output.stdout);
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    if !output.status.success() {
+                        return Err(format!(
+                            "- Test listing {operation_id} failed in {working_directory}.\nErrors: {stderr}\nOutput: {text}"
+                        ));
+                    }
+                    Ok(parse_test_list(&text, &stderr, &report.artifacts))
+                }
+            };
+            let mut tests = list(false).await?;
+            if !json_events {
+                let ignored = list(true).await?;
+                for test in &mut tests {
+                    test.ignored = ignored
+                        .iter()
+                        .any(|i| i.name == test.name && i.binary == test.binary);
+                }
+            }
+            tests
+        };
+
+        let total = tests.len();
+        if let Some(filter) = &req.filter {
+            tests.retain(|t| t.name.contains(filter.as_str()));
+        }
+        if let Some(package) = &req.package {
+            tests.retain(|t| t.package.as_deref().is_none_or(|p| p == package));
+        }
+        let binaries = {
+            let mut seen: Vec<&Option<String>> = Vec::new();
+            for test in &tests {
+                if !seen.contains(&&test.binary) {
+                    seen.push(&test.binary);
+                }
+            }
+            seen.len()
+        };
+        let ignored = tests.iter().filter(|t| t.ignored).count();
+        let mut header = format!(
+            "Test listing {operation_id} in {working_directory}: {} test(s) in {binaries} binar{} ({ignored} ignored)",
+            tests.len(),
+            if binaries == 1 { "y" } else { "ies" },
+        );
+        if tests.len() < total {
+            header.push_str(&format!(", {} of {total} match the filters", tests.len()));
+        }
+        let listing = render_test_list(&tests);
+        diagnostics
+            .record(
+                operation_id,
+                crate::diagnostics::CargoReport {
+                    test_list: Some(tests),
+                    ..Default::default()
+                },
+            )
+            .await;
+        if listing.is_empty() {
+            Ok(header)
+        } else {
+            Ok(format!("{header}\n{listing}"))
+        }
+    }
+
+    #[tool(
+        description = "Attempt remediation for a stale Cargo lock file. Options: A = delete target/.cargo-lock then cargo clean; B = only delete .cargo-lock; C = do nothing. Cancels active jobs for the directory before deletion. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal."
+    )]
+    async fn cargo_lock_remediation(
+        &self,
+        Parameters(req): Parameters<CargoLockRemediationRequest>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let dir = req.working_directory.clone();
+        let action = req.action.clone();
+        let summary = self
+            .perform_cargo_lock_remediation(&dir, action)
+            .await
+            .map_err(|e| ErrorData::internal_error(e, None))?;
+
+        let explanation = format!(
+            "A Cargo internal lock file can persist at <dir>/target/.cargo-lock when previous commands crashed or were interrupted, causing new cargo invocations to block.\nDirectory: {dir}\nResult: {summary}"
+        );
+        Ok(CallToolResult::success(vec![Content::text(explanation)]))
+    }
+
+    #[tool(
+        description = "CARGO BUILD: Faster than terminal cargo. Use enable_async_notification=true for builds >1s to multitask. Structured output with isolation. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+    )]
+    async fn build(
+        &self,
+        Parameters(req): Parameters<BuildRequest>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.ensure_enabled("build")?;
+        let build_id = self.generate_operation_id_for("build");
+
+        // Check if async notifications are enabled and not in synchronous mode
+        if self.should_run_synchronously(req.enable_async_notification) {
+            // Synchronous operation for when async notifications are disabled or synchronous mode is enabled
+            let result =
+                Self::build_implementation(&req, &self.diagnostics, &self.output_store, &build_id)
+                    .await;
+            let result =
+                self.output_store
+                    .finish_result(&build_id, result, &req.condense_options());
+            let response = Self::handle_sync_result(
+                "build",
+                "cargo build",
+                "Synchronous build operation",
+                result,
+            );
+            return self.with_diagnostics(&build_id, response).await;
+        } else {
+            // TRUE 2-STAGE ASYNC PATTERN:
+            // 1. Send immediate response that operation has started
+            // 2. Spawn background task to do actual work and send notifications
+
+            let peer = context.peer.clone();
+            let req_clone = req.clone();
+            let build_id_clone = build_id.clone();
+            let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
+            let diagnostics = self.diagnostics.clone();
+
+            // Register operation BEFORE spawning so wait() can find it immediately
+            self.register_async_operation(
+                &build_id,
+                "cargo build",
+                "Building project in the background",
+                Some(req.working_directory.clone()),
+            )
+            .await;
+
+            // Spawn background task for actual build work
+            tokio::spawn(async move {
+                // Create MCP callback sender to notify the LLM client
+                let callback = mcp_callback(peer.clone(), build_id_clone.clone());
+
+                // Send started notification immediately
+                let _ = callback
+                    .send_progress(ProgressUpdate::Started {
+                        operation_id: build_id_clone.clone(),
+                        command: "cargo build".to_string(),
+                        description: "Building project in the background".to_string(),
+                    })
+                    .await;
+
+                let started_at = Instant::now();
+                // Do the actual build work
+                let result = Self::build_implementation(
+                    &req_clone,
+                    &diagnostics,
+                    &output_store,
+                    &build_id_clone,
+                )
+                .await;
+                let result = output_store.finish_result(
+                    &build_id_clone,
+                    result,
+                    &req_clone.condense_options(),
+                );
+
+                // Store the result in the operation monitor for later retrieval by wait
+                // This ensures the full output (stdout/stderr) is available to `wait`
+                let _ = monitor
+                    .complete_operation(&build_id_clone, result.clone())
+                    .await;
+
+                // Send completion notification with measured duration
+                let duration_ms = started_at.elapsed().as_millis() as u64;
+
+                // Send brief completion update for legacy support
+                let completion_update = match result {
+                    Ok(ref msg) => ProgressUpdate::Completed {
+                        operation_id: build_id_clone.clone(),
+                        message: msg.clone(),
+                        duration_ms,
+                    },
+                    Err(ref err) => ProgressUpdate::Failed {
+                        operation_id: build_id_clone.clone(),
+                        error: err.clone(),
+                        duration_ms,
+                    },
+                };
+
+                if let Err(e) = callback.send_progress(completion_update).await {
+                    tracing::error!("Failed to send build completion progress update: {e:?}");
+                }
+
+                // Send comprehensive final result for AI consumption
+                let final_result_update = Self::create_final_result_update(
+                    &build_id_clone,
+                    "cargo build",
+                    "Building project in the background",
+                    &req_clone.working_directory,
+                    &result,
+                    duration_ms,
+                );
+
+                if let Err(e) = callback.send_progress(final_result_update).await {
+                    tracing::error!("Failed to send build final result update: {e:?}");
+                }
+            });
+
+            // Return immediate response to LLM - this is the "first stage"
+            let tool_hint = self.generate_tool_hint(&build_id, "build");
+            let timestamp = timestamp::format_current_time();
+            Ok(CallToolResult::success(vec![Content::text(format!(
+                "Build operation {build_id} started at {timestamp} in the background.{tool_hint}"
+            ))]))
+        }
+    }
+
+    /// Run cargo build, streaming its JSON messages through the diagnostics parser
+    async fn build_implementation(
+        req: &BuildRequest,
+        diagnostics: &DiagnosticsStore,
+        output_store: &OutputStore,
+        operation_id: &str,
+    ) -> Result<String, String> {
+        let mut cmd_args = vec!["cargo".to_string(), "build".to_string()];
+        if !has_message_format(&req.args) {
+            cmd_args.push(MESSAGE_FORMAT_ARG.to_string());
+        }
+        if req.timings.unwrap_or(false) {
+            cmd_args.push("--timings".to_string());
+        }
+
+        // Add package selection
+        if req.workspace.unwrap_or(false) {
+            cmd_args.push("--workspace".to_string());
+        }
+
+        if let Some(exclude) = &req.exclude {
+            for package in exclude {
+                cmd_args.extend(vec!["--exclude".to_string(), package.clone()]);
+            }
+        }
+
+        // Add target selection
+        if req.lib.unwrap_or(false) {
+            cmd_args.push("--lib".to_string());
+        }
+
+        if req.bins.unwrap_or(false) {
+            cmd_args.push("--bins".to_string());
+        }
+
+        if let Some(bin_name) = &req.bin_name {
+            cmd_args.extend(vec!["--bin".to_string(), bin_name.clone()]);
+        }
+
+        if req.examples.unwrap_or(false) {
+            cmd_args.push("--examples".to_string());
+        }
+
+        if let Some(example) = &req.example {
+            cmd_args.extend(vec!["--example".to_string(), example.clone()]);
+        }
+
+        if req.tests.unwrap_or(false) {
+            cmd_args.push("--tests".to_string());
+        }
+
+        if let Some(test) = &req.test {
+            cmd_args.extend(vec!["--test".to_string(), test.clone()]);
+        }
+
+        if req.all_targets.unwrap_or(false) {
+            cmd_args.push("--all-targets".to_string());
+        }
+
+        // Add feature selection
+        if let Some(features) = &req.features
+            && !features.is_empty()
+        {
+            // Filter out literal "default" which causes error if not declared explicitly
+            let filtered: Vec<String> = features
+                .iter()
+                .filter(|f| f.as_str() != "default")
+                .cloned()
+                .collect();
+            if !filtered.is_empty() {
+                cmd_args.extend(vec!["--features".to_string(), filtered.join(",")]);
+            }
+        }
+
+        if req.all_features.unwrap_or(false) {
+            cmd_args.push("--all-features".to_string());
+        }
+
+        if req.no_default_features.unwrap_or(false) {
+            cmd_args.push("--no-default-features".to_string());
+        }
+
+        // Add compilation options
         if req.release.unwrap_or(false) {
             cmd_args.push("--release".to_string());
         }
 
-        if let Some(profile) = &req.profile {
-            cmd_args.extend(vec!["--profile".to_string(), profile.clone()]);
+        if let Some(profile) = &req.profile {
+            cmd_args.extend(vec!["--profile".to_string(), profile.clone()]);
+        }
+
+        if let Some(jobs) = req.jobs {
+            let jobs = crate::cpu_budget::cap_jobs(jobs);
+            cmd_args.extend(vec!["--jobs".to_string(), jobs.to_string()]);
+        }
+
+        // Validate target if provided (avoid failing build for cross targets not installed)
+        let mut target_note: Option<String> = None;
+        if let Some(target) = &req.target {
+            let target_installed = {
+                use std::process::Command as StdCommand;
+                // Use rustup to list targets; fallback to assuming installed if rustup not available
+                if let Ok(output) = StdCommand::new("rustup")
+                    .args(["target", "list", "--installed"])
+                    .output()
+                {
+                    let list = String::from_utf8_lossy(&output.stdout);
+                    list.lines().any(|l| l.trim() == target)
+                } else {
+                    true
+                }
+            };
+            if target_installed {
+                cmd_args.extend(vec!["--target".to_string(), target.clone()]);
+            } else {
+                target_note = Some(format!(
+                    "[info] Requested target '{target}' not installed; building with host target instead."
+                ));
+            }
+        }
+
+        if let Some(target_dir) = &req.target_dir {
+            cmd_args.extend(vec!["--target-dir".to_string(), target_dir.clone()]);
+        }
+
+        // Add manifest options
+        if let Some(manifest_path) = &req.manifest_path {
+            cmd_args.extend(vec!["--manifest-path".to_string(), manifest_path.clone()]);
+        }
+
+        // Add additional arguments
+        if let Some(args) = &req.args {
+            for arg in args {
+                cmd_args.push(arg.clone());
+            }
+        }
+
+        let mut cmd = cargo_command();
+        cmd.args(&cmd_args[1..]).current_dir(&req.working_directory);
+        let mut messages = CargoMessageParser::default();
+        let output = output_store
+            .capture_with(operation_id, &mut cmd, |line| messages.line(line))
+            .await
+            .map_err(|e| {
+                format!(
+                    "- Build operation failed in {}.\nError: Failed to execute cargo build: {}",
+                    &req.working_directory, e
+                )
+            })?;
+
+        let scope = DiagnosticScope::new("build", &req.working_directory, &cmd_args[2..]);
+        let stdout = record_cargo_messages(
+            diagnostics,
+            &scope,
+            operation_id,
+            messages.finish(),
+            &output.stdout,
+        )
+        .await;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let timings = match req.timings {
+            Some(true) => {
+                record_timings(diagnostics, &req.working_directory, operation_id, &stderr).await
+            }
+            _ => None,
+        };
+
+        let working_dir_msg = format!(" in {}", &req.working_directory);
+        let bin_msg = if let Some(bin_name) = &req.bin_name {
+            format!(" (binary: {bin_name})")
+        } else {
+            String::new()
+        };
+
+        // For successful builds, treat only cargo lock-wait noise as empty so placeholder still appears.
+        let lock_wait_prefix = "Blocking waiting for file lock";
+        let stdout_trim = stdout.trim();
+        let stderr_lines: Vec<&str> = stderr.lines().collect();
+        let meaningful_stderr: Vec<&str> = stderr_lines
+            .iter()
+            .copied()
+            .filter(|l| {
+                let t = l.trim();
+                !(t.is_empty() || t.starts_with(lock_wait_prefix))
+            })
+            .collect();
+        let stdout_display = if output.status.success() {
+            if stdout_trim.is_empty() && meaningful_stderr.is_empty() {
+                Self::no_output_placeholder("build")
+            } else if stdout_trim.is_empty() {
+                stderr.to_string()
+            } else if meaningful_stderr.is_empty() {
+                stdout.to_string()
+            } else {
+                format!("{stdout}\n\n{stderr}")
+            }
+        } else {
+            merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("build"))
+        };
+        if output.status.success() {
+            let mut msg = format!(
+                "+ Build completed successfully{working_dir_msg}{bin_msg}.\nOutput: {stdout_display}"
+            );
+            if let Some(note) = target_note {
+                msg.push('\n');
+                msg.push_str(&note);
+            }
+            if let Some(timings) = timings {
+                msg.push_str(&format!("\n\n{timings}"));
+            }
+            Ok(msg)
+        } else {
+            // Keep Error: section (tests rely on it) but also include merged content in Output
+            let timings = timings.map(|t| format!("\n\n{t}")).unwrap_or_default();
+            Err(format!(
+                "- Build failed{working_dir_msg}{bin_msg}.\nError: {stderr}\nOutput: {stdout_display}{timings}"
+            ))
+        }
+    }
+
+    #[tool(
+        description = "CARGO RUN: Faster than terminal cargo. Use enable_async_notification=true for long-running apps to multitask. Structured output with isolation. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+    )]
+    async fn run(
+        &self,
+        Parameters(req): Parameters<RunRequest>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let run_id = self.generate_operation_id_for("run");
+
+        // Check if we should run synchronously or use async notifications
+        if self.should_run_synchronously(req.enable_async_notification) {
+            // Synchronous operation for when async notifications are disabled
+            let response = match self.output_store.finish_result(
+                &run_id,
+                Self::run_implementation(&req, &self.diagnostics, &self.output_store, &run_id)
+                    .await,
+                &req.condense_options(),
+            ) {
+                Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
+                Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
+            };
+            self.with_diagnostics(&run_id, response).await
+        } else {
+            // TRUE 2-STAGE ASYNC PATTERN:
+            // 1. Send immediate response that operation has started
+            // 2. Spawn background task to do actual work and send notifications
+
+            let peer = context.peer.clone();
+            let req_clone = req.clone();
+            let run_id_clone = run_id.clone();
+            let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
+            let diagnostics = self.diagnostics.clone();
+
+            // Register operation before spawn so wait() can find it immediately
+            self.register_async_operation(
+                &run_id,
+                "cargo run",
+                "Running application in the background",
+                Some(req.working_directory.clone()),
+            )
+            .await;
+
+            // Spawn background task for actual run work
+            tokio::spawn(async move {
+                // Create MCP callback sender to notify the LLM client
+                let callback = mcp_callback(peer, run_id_clone.clone());
+
+                // Send started notification immediately
+                let _ = callback
+                    .send_progress(ProgressUpdate::Started {
+                        operation_id: run_id_clone.clone(),
+                        command: "cargo run".to_string(),
+                        description: "Running application in the background".to_string(),
+                    })
+                    .await;
+
+                // Do the actual run work
+                let started_at = Instant::now();
+                let result = Self::run_implementation(
+                    &req_clone,
+                    &diagnostics,
+                    &output_store,
+                    &run_id_clone,
+                )
+                .await;
+                let result = output_store.finish_result(
+                    &run_id_clone,
+                    result,
+                    &req_clone.condense_options(),
+                );
+
+                // Store result for wait()
+                let _ = monitor
+                    .complete_operation(&run_id_clone, result.clone())
+                    .await;
+
+                // Send completion notification
+                let duration_ms = started_at.elapsed().as_millis() as u64;
+                let completion_update = match result {
+                    Ok(msg) => ProgressUpdate::Completed {
+                        operation_id: run_id_clone,
+                        message: msg,
+                        duration_ms,
+                    },
+                    Err(err) => ProgressUpdate::Failed {
+                        operation_id: run_id_clone,
+                        error: err,
+                        duration_ms,
+                    },
+                };
+
+                let _ = callback.send_progress(completion_update).await;
+            });
+
+            // Return immediate response to LLM - this is the "first stage"
+            let tool_hint = self.generate_tool_hint(&run_id, "run");
+            let timestamp = timestamp::format_current_time();
+            Ok(CallToolResult::success(vec![Content::text(format!(
+                "Run operation {run_id} started at {timestamp} in the background.{tool_hint}"
+            ))]))
+        }
+    }
+
+    /// Internal implementation of run logic
+    async fn run_implementation(
+        req: &RunRequest,
+        diagnostics: &DiagnosticsStore,
+        output_store: &OutputStore,
+        operation_id: &str,
+    ) -> Result<String, String> {
+        let mut cmd = cargo_command();
+        cmd.arg("run");
+
+        // Add feature selection
+        if let Some(features) = &req.features
+            && !features.is_empty()
+        {
+            cmd.arg("--features").arg(features.join(","));
+        }
+
+        if req.all_features.unwrap_or(false) {
+            cmd.arg("--all-features");
+        }
+
+        if req.no_default_features.unwrap_or(false) {
+            cmd.arg("--no-default-features");
+        }
+
+        // Add compilation options
+        if req.release.unwrap_or(false) {
+            cmd.arg("--release");
+        }
+
+        if let Some(profile) = &req.profile {
+            cmd.arg("--profile").arg(profile);
+        }
+
+        if let Some(target) = &req.target {
+            cmd.arg("--target").arg(target);
+        }
+
+        if let Some(jobs) = req.jobs {
+            cmd.arg("--jobs").arg(jobs.to_string());
+        }
+
+        // Add manifest path
+        if let Some(manifest_path) = &req.manifest_path {
+            cmd.arg("--manifest-path").arg(manifest_path);
+        }
+
+        // Add --bin parameter if specified
+        if let Some(bin_name) = &req.bin_name {
+            cmd.arg("--bin").arg(bin_name);
+        }
+
+        // Add additional cargo arguments
+        if let Some(cargo_args) = &req.cargo_args {
+            for arg in cargo_args {
+                cmd.arg(arg);
+            }
+        }
+
+        // Add binary arguments after -- separator
+        if let Some(binary_args) = &req.binary_args
+            && !binary_args.is_empty()
+        {
+            tracing::debug!("Adding binary args: {binary_args:?}");
+            cmd.arg("--");
+            for arg in binary_args {
+                cmd.arg(arg);
+                tracing::debug!("Added binary arg: {arg}");
+            }
         }
 
-        if let Some(jobs) = req.jobs {
-            cmd_args.extend(vec!["--jobs".to_string(), jobs.to_string()]);
+        set_rust_backtrace(&mut cmd, req.backtrace);
+
+        // Set working directory
+        cmd.current_dir(&req.working_directory);
+
+        let output = output_store
+            .capture(operation_id, &mut cmd)
+            .await
+            .map_err(|e| format!("Run operation failed: Failed to execute cargo run: {e}"))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        let working_dir_msg = format!(" in {}", &req.working_directory);
+        let bin_msg = if let Some(bin_name) = &req.bin_name {
+            format!(" (binary: {bin_name})")
+        } else {
+            String::new()
+        };
+
+        let args_msg = if let Some(binary_args) = &req.binary_args {
+            if !binary_args.is_empty() {
+                format!(" with args: [{}]", binary_args.join(", "))
+            } else {
+                String::new()
+            }
+        } else {
+            String::new()
+        };
+
+        let panics = parse_panics(&stderr);
+        let panic_summary: String = panics
+            .iter()
+            .map(|panic| format!("\n\n{}", panic.render()))
+            .collect();
+        if !panics.is_empty() {
+            diagnostics
+                .record(
+                    operation_id,
+                    crate::diagnostics::CargoReport {
+                        success: Some(output.status.success()),
+                        panics,
+                        ..Default::default()
+                    },
+                )
+                .await;
         }
 
-        // Validate target if provided (avoid failing build for cross targets not installed)
-        let mut target_note: Option<String> = None;
-        if let Some(target) = &req.target {
-            let target_installed = {
-                use std::process::Command as StdCommand;
-                // Use rustup to list targets; fallback to assuming installed if rustup not available
-                if let Ok(output) = StdCommand::new("rustup")
-                    .args(["target", "list", "--installed"])
-                    .output()
+        if output.status.success() {
+            // Merge stdout+stderr so compile lines (on stderr) always appear in Output section.
+            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("run"));
+            Ok(format!(
+                "+ Run operation completed successfully{working_dir_msg}{bin_msg}{args_msg}.\nOutput: {merged}{panic_summary}"
+            ))
+        } else {
+            Err(format!(
+                "- Run operation failed{working_dir_msg}{bin_msg}{args_msg}.\nErrors: {stderr}\nOutput: {stdout}{panic_summary}"
+            ))
+        }
+    }
+
+    #[tool(
+        description = "CARGO TEST: Faster than terminal cargo. Provides complete error output but runs slower than nextest. Use when debugging test failures, expecting failures, or running specific tests that need detailed output. ALWAYS use enable_async_notification=true for test suites to multitask. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+    )]
+    async fn test(
+        &self,
+        Parameters(req): Parameters<TestRequest>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let test_id = self.generate_operation_id_for("test");
+        self.remember_test_run(&test_id, TestRun::Cargo(Box::new(req.clone())))
+            .await;
+
+        let fingerprint = self
+            .result_fingerprint("test", &req.working_directory, &req.cache_key())
+            .await;
+        if req.force != Some(true)
+            && let Some(fingerprint) = &fingerprint
+            && let Some(cached) = self.result_cache.get(fingerprint.key).await
+        {
+            return self
+                .replay_result(
+                    "test",
+                    &test_id,
+                    &cached.note(),
+                    cached,
+                    &req.condense_options(),
+                )
+                .await;
+        }
+
+        // Check if async notifications are enabled and not in synchronous mode
+        if self.should_run_synchronously(req.enable_async_notification) {
+            // Synchronous operation
+            let result =
+                Self::test_implementation(&req, &self.diagnostics, &self.output_store, &test_id)
+                    .await;
+            Self::cache_result(&self.result_cache, fingerprint, &test_id, &result).await;
+            let response =
+                match self
+                    .output_store
+                    .finish_result(&test_id, result, &req.condense_options())
                 {
-                    let list = String::from_utf8_lossy(&output.stdout);
-                    list.lines().any(|l| l.trim() == target)
-                } else {
-                    true
-                }
-            };
-            if target_installed {
-                cmd_args.extend(vec!["--target".to_string(), target.clone()]);
-            } else {
-                target_note = Some(format!(
-                    "[info] Requested target '{target}' not installed; building with host target instead."
-                ));
+                    Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
+                    Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
+                };
+            self.with_diagnostics(&test_id, response).await
+        } else {
+            // TRUE 2-STAGE ASYNC PATTERN:
+            // 1. Send immediate response that operation has started
+            // 2. Spawn background task to do actual work and send notifications
+
+            let peer = context.peer.clone();
+            let req_clone = req.clone();
+            let test_id_clone = test_id.clone();
+            let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
+            let diagnostics = self.diagnostics.clone();
+            let result_cache = self.result_cache.clone();
+
+            // Register operation before spawn
+            self.register_async_operation(
+                &test_id,
+                "cargo test",
+                "Running test suite in the background",
+                Some(req.working_directory.clone()),
+            )
+            .await;
+
+            // Spawn background task for actual test work
+            tokio::spawn(async move {
+                // Create MCP callback sender to notify the LLM client
+                let callback = mcp_callback(peer, test_id_clone.clone());
+
+                // Send started notification immediately
+                let _ = callback
+                    .send_progress(ProgressUpdate::Started {
+                        operation_id: test_id_clone.clone(),
+                        command: "cargo test".to_string(),
+                        description: "Running test suite in the background".to_string(),
+                    })
+                    .await;
+
+                // Do the actual test work
+                let started_at = Instant::now();
+                let result = Self::test_implementation(
+                    &req_clone,
+                    &diagnostics,
+                    &output_store,
+                    &test_id_clone,
+                )
+                .await;
+                Self::cache_result(&result_cache, fingerprint, &test_id_clone, &result).await;
+                let result = output_store.finish_result(
+                    &test_id_clone,
+                    result,
+                    &req_clone.condense_options(),
+                );
+                // Store for wait
+                let _ = monitor
+                    .complete_operation(&test_id_clone, result.clone())
+                    .await;
+
+                // Send completion notification
+                let duration_ms = started_at.elapsed().as_millis() as u64;
+                let completion_update = match result {
+                    Ok(msg) => ProgressUpdate::Completed {
+                        operation_id: test_id_clone,
+                        message: msg,
+                        duration_ms,
+                    },
+                    Err(err) => ProgressUpdate::Failed {
+                        operation_id: test_id_clone,
+                        error: err,
+                        duration_ms,
+                    },
+                };
+
+                let _ = callback.send_progress(completion_update).await;
+            });
+
+            // Return immediate response to LLM - this is the "first stage"
+            let tool_hint = self.generate_tool_hint(&test_id, "test");
+            let timestamp = timestamp::format_current_time();
+            Ok(CallToolResult::success(vec![Content::text(format!(
+                "Test operation {test_id} started at {timestamp} in the background.{tool_hint}"
+            ))]))
+        }
+    }
+
+    /// Internal implementation of test logic
+    pub async fn test_implementation(
+        req: &TestRequest,
+        diagnostics: &DiagnosticsStore,
+        output_store: &OutputStore,
+        operation_id: &str,
+    ) -> Result<String, String> {
+        let test_id = uuid::Uuid::new_v4().to_string()[..8].to_string();
+
+        let mut cmd = cargo_command();
+        cmd.arg("test");
+        if !has_message_format(&req.args) {
+            cmd.arg(MESSAGE_FORMAT_ARG);
+        }
+
+        // Add package selection
+        if let Some(package) = &req.package {
+            cmd.arg("--package").arg(package);
+        }
+
+        if req.workspace.unwrap_or(false) {
+            cmd.arg("--workspace");
+        }
+
+        if let Some(exclude) = &req.exclude {
+            for pkg in exclude {
+                cmd.arg("--exclude").arg(pkg);
             }
         }
 
-        if let Some(target_dir) = &req.target_dir {
-            cmd_args.extend(vec!["--target-dir".to_string(), target_dir.clone()]);
+        // Add target selection
+        if req.lib.unwrap_or(false) {
+            cmd.arg("--lib");
+        }
+
+        if req.bins.unwrap_or(false) {
+            cmd.arg("--bins");
+        }
+
+        if let Some(bin) = &req.bin {
+            cmd.arg("--bin").arg(bin);
+        }
+
+        if req.examples.unwrap_or(false) {
+            cmd.arg("--examples");
+        }
+
+        if let Some(example) = &req.example {
+            cmd.arg("--example").arg(example);
+        }
+
+        if req.tests.unwrap_or(false) {
+            cmd.arg("--tests");
+        }
+
+        if let Some(test) = &req.test {
+            cmd.arg("--test").arg(test);
+        }
+
+        if req.all_targets.unwrap_or(false) {
+            cmd.arg("--all-targets");
+        }
+
+        if req.doc.unwrap_or(false) {
+            cmd.arg("--doc");
+        }
+
+        // Add feature selection
+        if let Some(features) = &req.features
+            && !features.is_empty()
+        {
+            cmd.arg("--features").arg(features.join(","));
+        }
+
+        if req.all_features.unwrap_or(false) {
+            cmd.arg("--all-features");
+        }
+
+        if req.no_default_features.unwrap_or(false) {
+            cmd.arg("--no-default-features");
+        }
+
+        // Add compilation options
+        if req.release.unwrap_or(false) {
+            cmd.arg("--release");
+        }
+
+        if let Some(profile) = &req.profile {
+            cmd.arg("--profile").arg(profile);
+        }
+
+        if let Some(jobs) = req.jobs {
+            cmd.arg("--jobs")
+                .arg(crate::cpu_budget::cap_jobs(jobs).to_string());
+        }
+
+        if let Some(target) = &req.target {
+            cmd.arg("--target").arg(target);
+        }
+
+        // Add test options
+        if req.no_run.unwrap_or(false) {
+            cmd.arg("--no-run");
+        }
+
+        if req.no_fail_fast.unwrap_or(false) {
+            cmd.arg("--no-fail-fast");
         }
 
         // Add manifest options
         if let Some(manifest_path) = &req.manifest_path {
-            cmd_args.extend(vec!["--manifest-path".to_string(), manifest_path.clone()]);
+            cmd.arg("--manifest-path").arg(manifest_path);
         }
 
-        // Add additional arguments
-        if let Some(args) = &req.args {
-            for arg in args {
-                cmd_args.push(arg.clone());
+        // Add additional cargo arguments; anything after a `--` in them goes to the test binary
+        // with test_args, since a second `--` would make libtest read what follows as filters
+        let args = req.args.as_deref().unwrap_or_default();
+        let (cargo_args, binary_args) = match args.iter().position(|a| a == "--") {
+            Some(separator) => (&args[..separator], &args[separator + 1..]),
+            None => (args, &[][..]),
+        };
+        cmd.args(cargo_args);
+
+        // Add test name filter as positional argument
+        if let Some(test_name) = &req.test_name {
+            cmd.arg(test_name);
+        }
+
+        let test_args: Vec<String> = binary_args
+            .iter()
+            .chain(req.test_args.as_deref().unwrap_or_default())
+            .cloned()
+            .collect();
+
+        // Ask for libtest's JSON events where the toolchain supports them
+        let json_events = !req.no_run.unwrap_or(false)
+            && wants_default_format(&test_args)
+            && supports_libtest_json(&req.working_directory).await;
+
+        // Add test arguments after -- separator
+        if json_events || !test_args.is_empty() {
+            cmd.arg("--");
+            if json_events {
+                cmd.args(LIBTEST_JSON_ARGS);
             }
+            cmd.args(&test_args);
         }
 
-        // Build the command string and execute using shell pool
-        let command = cmd_args.join(" ");
-        tracing::info!(
-            operation_id = operation_id,
-            "Invoking execute_cargo_command for build in {}",
-            req.working_directory
-        );
-        let output = match self
-            .execute_cargo_command(command, Some(req.working_directory.clone()), operation_id)
+        set_rust_backtrace(&mut cmd, req.backtrace);
+
+        // Set working directory
+        cmd.current_dir(&req.working_directory);
+
+        let mut messages = CargoMessageParser::default();
+        let output = output_store
+            .capture_with(operation_id, &mut cmd, |line| messages.line(line))
             .await
-        {
-            Ok(o) => o,
-            Err(e) => {
-                tracing::error!(operation_id = operation_id, error=%e, "execute_cargo_command returned error for build");
-                return Err(format!(
-                    "- Build operation failed in {}.\nError: Failed to execute cargo build: {}",
-                    &req.working_directory, e
-                ));
-            }
-        };
-        tracing::info!(operation_id = operation_id, status = %output.status, "execute_cargo_command returned output");
+            .map_err(|e| format!("Failed to execute cargo test: {e}"))?;
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
+        let scope = DiagnosticScope::new(
+            "test",
+            &req.working_directory,
+            cmd.as_std().get_args().skip(1).map(|a| a.to_string_lossy()),
+        );
+        let stdout = record_cargo_messages(
+            diagnostics,
+            &scope,
+            operation_id,
+            messages.finish(),
+            &output.stdout,
+        )
+        .await;
         let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = record_test_results(
+            diagnostics,
+            &req.working_directory,
+            operation_id,
+            &stdout,
+            &stderr,
+        )
+        .await;
 
         let working_dir_msg = format!(" in {}", &req.working_directory);
-        let bin_msg = if let Some(bin_name) = &req.bin_name {
-            format!(" (binary: {bin_name})")
+        let test_filter_msg = if let Some(test_name) = &req.test_name {
+            format!(" (filter: {test_name})")
         } else {
             String::new()
         };
 
-        // For successful builds, treat only cargo lock-wait noise as empty so placeholder still appears.
-        let lock_wait_prefix = "Blocking waiting for file lock";
-        let stdout_trim = stdout.trim();
-        let stderr_lines: Vec<&str> = stderr.lines().collect();
-        let meaningful_stderr: Vec<&str> = stderr_lines
-            .iter()
-            .copied()
-            .filter(|l| {
-                let t = l.trim();
-                !(t.is_empty() || t.starts_with(lock_wait_prefix))
-            })
-            .collect();
-        let stdout_display = if output.status.success() {
-            if stdout_trim.is_empty() && meaningful_stderr.is_empty() {
-                Self::no_output_placeholder("build")
-            } else if stdout_trim.is_empty() {
-                stderr.to_string()
-            } else if meaningful_stderr.is_empty() {
-                stdout.to_string()
-            } else {
-                format!("{stdout}\n\n{stderr}")
-            }
-        } else {
-            merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("build"))
-        };
         if output.status.success() {
-            let mut msg = format!(
-                "+ Build completed successfully{working_dir_msg}{bin_msg}.\nOutput: {stdout_display}"
-            );
-            if let Some(note) = target_note {
-                msg.push('\n');
-                msg.push_str(&note);
-            }
-            Ok(msg)
+            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("test"));
+            Ok(format!(
+                "Test operation #{test_id} completed successfully{working_dir_msg}{test_filter_msg}.\nOutput: {merged}"
+            ))
         } else {
-            // Keep Error: section (tests rely on it) but also include merged content in Output
             Err(format!(
-                "- Build failed{working_dir_msg}{bin_msg}.\nError: {stderr}\nOutput: {stdout_display}"
+                "- Test operation #{test_id} failed{working_dir_msg}{test_filter_msg}.\nErrors: {stderr}\nOutput: {stdout}"
             ))
         }
     }
 
-    /// Static version of build_implementation for use in async contexts
-    async fn build_implementation_static(
-        req: &BuildRequest,
+    #[tool(
+        description = "CARGO CHECK: Faster than terminal cargo. Fast validation - async optional for large projects. Quick compile check. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+    )]
+    async fn check(
+        &self,
+        Parameters(req): Parameters<CheckRequest>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let check_id = self.generate_operation_id_for("check");
+        let target_dir =
+            Self::isolated_target_dir(&self.target_isolation, &req.working_directory, "check");
+
+        let force = req.force == Some(true);
+        // Speculative checks run the default request, so only an equivalent one may reuse them
+        let default_request = CheckRequest {
+            working_directory: req.working_directory.clone(),
+            ..Default::default()
+        };
+        if !force
+            && req.cache_key() == default_request.cache_key()
+            && let Some(cached) = self.cached_speculative_check(&req.working_directory).await
+        {
+            return self
+                .replay_speculative_check(
+                    &check_id,
+                    &req.working_directory,
+                    cached,
+                    &req.condense_options(),
+                )
+                .await;
+        }
+        let fingerprint = self
+            .result_fingerprint("check", &req.working_directory, &req.cache_key())
+            .await;
+        if !force
+            && let Some(fingerprint) = &fingerprint
+            && let Some(cached) = self.result_cache.get(fingerprint.key).await
+        {
+            return self
+                .replay_result(
+                    "check",
+                    &check_id,
+                    &cached.note(),
+                    cached,
+                    &req.condense_options(),
+                )
+                .await;
+        }
+
+        // Check if we should run synchronously or use async notifications
+        if self.should_run_synchronously(req.enable_async_notification) {
+            // Synchronous operation for when async notifications are disabled
+            let result = Self::check_implementation(
+                &req,
+                target_dir.as_deref(),
+                &self.diagnostics,
+                &self.output_store,
+                &check_id,
+            )
+            .await;
+            Self::cache_result(&self.result_cache, fingerprint, &check_id, &result).await;
+            let result =
+                self.output_store
+                    .finish_result(&check_id, result, &req.condense_options());
+            Self::prune_isolated_target_dirs(
+                &self.target_isolation,
+                &req.working_directory,
+                "check",
+            )
+            .await;
+            let response = Self::handle_sync_result(
+                "check",
+                "cargo check",
+                "Synchronous check operation",
+                result,
+            );
+            return self.with_diagnostics(&check_id, response).await;
+        } else {
+            // TRUE 2-STAGE ASYNC PATTERN:
+            // 1. Send immediate response that operation has started
+            // 2. Spawn background task to do actual work and send notifications
+
+            let peer = context.peer.clone();
+            let req_clone = req.clone();
+            let check_id_clone = check_id.clone();
+            let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
+            let diagnostics = self.diagnostics.clone();
+            let isolation = self.target_isolation.clone();
+            let result_cache = self.result_cache.clone();
+
+            // Register operation before spawn
+            self.register_async_operation(
+                &check_id,
+                "cargo check",
+                "Checking project in the background",
+                Some(req.working_directory.clone()),
+            )
+            .await;
+            self.record_target_dir(&check_id, &target_dir).await;
+
+            // Spawn background task for actual check work
+            tokio::spawn(async move {
+                // Create MCP callback sender to notify the LLM client
+                let callback = mcp_callback(peer, check_id_clone.clone());
+
+                // Send started notification immediately
+                let _ = callback
+                    .send_progress(ProgressUpdate::Started {
+                        operation_id: check_id_clone.clone(),
+                        command: "cargo check".to_string(),
+                        description: "Checking project in the background".to_string(),
+                    })
+                    .await;
+
+                // Do the actual check work
+                let started_at = Instant::now();
+                let result = Self::check_implementation(
+                    &req_clone,
+                    target_dir.as_deref(),
+                    &diagnostics,
+                    &output_store,
+                    &check_id_clone,
+                )
+                .await;
+                Self::cache_result(&result_cache, fingerprint, &check_id_clone, &result).await;
+                let result = output_store.finish_result(
+                    &check_id_clone,
+                    result,
+                    &req_clone.condense_options(),
+                );
+                Self::prune_isolated_target_dirs(&isolation, &req_clone.working_directory, "check")
+                    .await;
+                // Store for wait
+                let _ = monitor
+                    .complete_operation(&check_id_clone, result.clone())
+                    .await;
+
+                // Send completion notification
+                let duration_ms = started_at.elapsed().as_millis() as u64;
+                let completion_update = match result {
+                    Ok(msg) => ProgressUpdate::Completed {
+                        operation_id: check_id_clone,
+                        message: msg,
+                        duration_ms,
+                    },
+                    Err(err) => ProgressUpdate::Failed {
+                        operation_id: check_id_clone,
+                        error: err,
+                        duration_ms,
+                    },
+                };
+
+                let _ = callback.send_progress(completion_update).await;
+            });
+
+            // Return immediate response to LLM - this is the "first stage"
+            let tool_hint = self.generate_tool_hint(&check_id, "check");
+            let timestamp = timestamp::format_current_time();
+            Ok(CallToolResult::success(vec![Content::text(format!(
+                "+ Check operation {check_id} started at {timestamp} in the background.{tool_hint}"
+            ))]))
+        }
+    }
+
+    /// Internal implementation of check logic
+    async fn check_implementation(
+        req: &CheckRequest,
+        target_dir: Option<&Path>,
+        diagnostics: &DiagnosticsStore,
+        output_store: &OutputStore,
         operation_id: &str,
-        shell_pool_manager: Arc<ShellPoolManager>,
     ) -> Result<String, String> {
-        let mut cmd_args = vec!["cargo".to_string(), "build".to_string()];
-
-        // Add package selection
-        if req.workspace.unwrap_or(false) {
-            cmd_args.push("--workspace".to_string());
+        let mut cmd = cargo_command();
+        cmd.arg("check").arg(MESSAGE_FORMAT_ARG).kill_on_drop(true);
+        apply_target_dir(&mut cmd, target_dir);
+        if req.timings.unwrap_or(false) {
+            cmd.arg("--timings");
         }
 
-        if let Some(exclude) = &req.exclude {
-            for package in exclude {
-                cmd_args.extend(vec!["--exclude".to_string(), package.clone()]);
+        // Set working directory
+        cmd.current_dir(&req.working_directory);
+
+        let mut messages = CargoMessageParser::default();
+        let output = output_store
+            .capture_with(operation_id, &mut cmd, |line| messages.line(line))
+            .await
+            .map_err(|e| {
+                format!(
+                    "- Check operation failed in {}.\nError: Failed to execute cargo check: {}",
+                    &req.working_directory, e
+                )
+            })?;
+
+        let scope = DiagnosticScope::new(
+            "check",
+            &req.working_directory,
+            cmd.as_std().get_args().skip(1).map(|a| a.to_string_lossy()),
+        );
+        let stdout = record_cargo_messages(
+            diagnostics,
+            &scope,
+            operation_id,
+            messages.finish(),
+            &output.stdout,
+        )
+        .await;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let timings = match req.timings {
+            Some(true) => {
+                record_timings(diagnostics, &req.working_directory, operation_id, &stderr).await
             }
+            _ => None,
         }
+        .map(|t| format!("\n\n{t}"))
+        .unwrap_or_default();
 
-        // Add target selection
-        if req.lib.unwrap_or(false) {
-            cmd_args.push("--lib".to_string());
-        }
+        let working_dir_msg = format!(" in {}", &req.working_directory);
 
-        if req.bins.unwrap_or(false) {
-            cmd_args.push("--bins".to_string());
+        if output.status.success() {
+            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("check"));
+            Ok(format!(
+                "+ Check operation completed successfully{working_dir_msg}.\nOutput: {merged}{timings}"
+            ))
+        } else {
+            Err(format!(
+                "- Check operation failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {stdout}{timings}"
+            ))
         }
+    }
 
-        if let Some(bin_name) = &req.bin_name {
-            cmd_args.extend(vec!["--bin".to_string(), bin_name.clone()]);
-        }
+    #[tool(
+        description = "CARGO ADD: Faster than terminal cargo. Synchronous operation for Cargo.toml modifications. Handles version conflicts. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+    )]
+    async fn add(
+        &self,
+        Parameters(req): Parameters<DependencyRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let add_id = self.generate_operation_id_for("add");
 
-        if req.examples.unwrap_or(false) {
-            cmd_args.push("--examples".to_string());
-        }
+        // Always use synchronous execution for Cargo.toml modifications
 
-        if let Some(example) = &req.example {
-            cmd_args.extend(vec!["--example".to_string(), example.clone()]);
-        }
+        let mut cmd = cargo_command();
 
-        if req.tests.unwrap_or(false) {
-            cmd_args.push("--tests".to_string());
-        }
+        // Build the dependency specification
+        let dep_spec = if let Some(version) = &req.version {
+            format!("{}@{}", req.name, version)
+        } else {
+            req.name.clone()
+        };
 
-        if let Some(test) = &req.test {
-            cmd_args.extend(vec!["--test".to_string(), test.clone()]);
-        }
+        cmd.arg("add").arg(&dep_spec);
 
-        if req.all_targets.unwrap_or(false) {
-            cmd_args.push("--all-targets".to_string());
-        }
+        // Set working directory
+        cmd.current_dir(&req.working_directory);
 
-        // Add feature selection
+        // Add optional features
         if let Some(features) = &req.features
             && !features.is_empty()
         {
-            // Filter out literal "default" which causes error if not declared explicitly
-            let filtered: Vec<String> = features
-                .iter()
-                .filter(|f| f.as_str() != "default")
-                .cloned()
-                .collect();
-            if !filtered.is_empty() {
-                cmd_args.extend(vec!["--features".to_string(), filtered.join(",")]);
-            }
-        }
-
-        if req.all_features.unwrap_or(false) {
-            cmd_args.push("--all-features".to_string());
+            cmd.arg("--features").arg(features.join(","));
         }
 
+        // Add no-default-features flag
         if req.no_default_features.unwrap_or(false) {
-            cmd_args.push("--no-default-features".to_string());
+            cmd.arg("--no-default-features");
         }
 
-        // Add compilation options
-        if req.release.unwrap_or(false) {
-            cmd_args.push("--release".to_string());
+        // Add optional flag
+        if req.optional.unwrap_or(false) {
+            cmd.arg("--optional");
         }
 
-        if let Some(profile) = &req.profile {
-            cmd_args.extend(vec!["--profile".to_string(), profile.clone()]);
-        }
+        // Apply dependency section arguments
+        apply_dependency_section_args(&mut cmd, &req.section);
 
-        if let Some(jobs) = req.jobs {
-            cmd_args.extend(vec!["--jobs".to_string(), jobs.to_string()]);
-        }
+        let output = cmd.output().await.map_err(|e| {
+            ErrorData::internal_error(format!("Failed to execute cargo add: {e}"), None)
+        })?;
 
-        // Validate target if provided (avoid failing build for cross targets not installed)
-        let mut target_note: Option<String> = None;
-        if let Some(target) = &req.target {
-            let target_installed = {
-                use std::process::Command as StdCommand;
-                // Use rustup to list targets; fallback to assuming installed if rustup not available
-                if let Ok(output) = StdCommand::new("rustup")
-                    .args(["target", "list", "--installed"])
-                    .output()
-                {
-                    let list = String::from_utf8_lossy(&output.stdout);
-                    list.lines().any(|l| l.trim() == target)
-                } else {
-                    true
-                }
-            };
-            if target_installed {
-                cmd_args.extend(vec!["--target".to_string(), target.clone()]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        let working_dir_msg = format!(" in {}", &req.working_directory);
+
+        let result_msg = if output.status.success() {
+            format!(
+                "+ Add operation #{add_id} completed successfully{working_dir_msg}.\nAdded dependency: {}\nOutput: {stdout}",
+                req.name
+            )
+        } else {
+            format!(
+                "- Add operation #{add_id} failed{working_dir_msg}.\nDependency: {}\nError: {stderr}\nOutput: {stdout}",
+                req.name
+            )
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(result_msg)]))
+    }
+
+    #[tool(
+        description = "CARGO REMOVE: Faster than terminal cargo. Synchronous operation for Cargo.toml modifications. Prevents Cargo.toml corruption. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+    )]
+    async fn remove(
+        &self,
+        Parameters(mut req): Parameters<RemoveDependencyRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let remove_id = self.generate_operation_id_for("remove");
+
+        // Handle backward compatibility: if name is provided and names is empty, use name
+        if req.names.is_empty() {
+            if let Some(name) = &req.name {
+                req.names = vec![name.clone()];
             } else {
-                target_note = Some(format!(
-                    "[info] Requested target '{target}' not installed; building with host target instead."
+                return Err(ErrorData::invalid_params(
+                    "Either 'name' or 'names' must be provided",
+                    None,
                 ));
             }
         }
 
-        if let Some(target_dir) = &req.target_dir {
-            cmd_args.extend(vec!["--target-dir".to_string(), target_dir.clone()]);
-        }
+        // Always use synchronous execution for Cargo.toml modifications
+        let result_msg = match Self::remove_implementation(&req, &remove_id).await {
+            Ok(result_msg) | Err(result_msg) => result_msg,
+        };
 
-        // Add manifest options
-        if let Some(manifest_path) = &req.manifest_path {
-            cmd_args.extend(vec!["--manifest-path".to_string(), manifest_path.clone()]);
-        }
+        Ok(CallToolResult::success(vec![Content::text(result_msg)]))
+    }
 
-        // Add additional arguments
-        if let Some(args) = &req.args {
-            for arg in args {
-                cmd_args.push(arg.clone());
-            }
+    /// Internal implementation of remove logic
+    async fn remove_implementation(
+        req: &RemoveDependencyRequest,
+        remove_id: &str,
+    ) -> Result<String, String> {
+        let mut cmd = cargo_command();
+        cmd.arg("remove");
+
+        // Add all dependency names
+        for name in &req.names {
+            cmd.arg(name);
         }
 
-        // Build the command string and execute using shell pool
-        let command = cmd_args.join(" ");
-        let output = Self::execute_cargo_command_static(
-            command,
-            Some(req.working_directory.clone()),
-            operation_id,
-            shell_pool_manager,
-        )
-        .await
-        .map_err(|e| {
-            format!(
-                "- Build operation failed in {}.\nError: Failed to execute cargo build: {}",
-                &req.working_directory, e
-            )
-        })?;
+        // Apply dependency section arguments
+        apply_dependency_section_args(&mut cmd, &req.section);
+
+        // Set working directory
+        cmd.current_dir(&req.working_directory);
+
+        let output = cmd
+            .output()
+            .await
+            .map_err(|e| format!("Failed to execute cargo remove: {e}"))?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
 
         let working_dir_msg = format!(" in {}", &req.working_directory);
-        let bin_msg = if let Some(bin_name) = &req.bin_name {
-            format!(" (binary: {bin_name})")
-        } else {
-            String::new()
-        };
+        let deps_list = req.names.join(", ");
 
-        // For successful builds, treat only cargo lock-wait noise as empty so placeholder still appears.
-        let lock_wait_prefix = "Blocking waiting for file lock";
-        let stdout_trim = stdout.trim();
-        let stderr_lines: Vec<&str> = stderr.lines().collect();
-        let meaningful_stderr: Vec<&str> = stderr_lines
-            .iter()
-            .copied()
-            .filter(|l| {
-                let t = l.trim();
-                !(t.is_empty() || t.starts_with(lock_wait_prefix))
-            })
-            .collect();
-        let stdout_display = if output.status.success() {
-            if stdout_trim.is_empty() && meaningful_stderr.is_empty() {
-                Self::no_output_placeholder("build")
-            } else if stdout_trim.is_empty() {
-                stderr.to_string()
-            } else if meaningful_stderr.is_empty() {
-                stdout.to_string()
-            } else {
-                format!("{stdout}\n\n{stderr}")
-            }
-        } else {
-            merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("build"))
-        };
+        let merged = merge_outputs(&stdout, &stderr, "(no remove output captured)");
         if output.status.success() {
-            let mut msg = format!(
-                "+ Build completed successfully{working_dir_msg}{bin_msg}.\nOutput: {stdout_display}"
-            );
-            if let Some(note) = target_note {
-                msg.push('\n');
-                msg.push_str(&note);
-            }
-            Ok(msg)
+            Ok(format!(
+                "- Remove operation #{remove_id} completed successfully{working_dir_msg}.\nRemoved dependencies: {}\nOutput: {merged}",
+                deps_list
+            ))
         } else {
-            // Keep Error: section (tests rely on it) but also include merged content in Output
             Err(format!(
-                "- Build failed{working_dir_msg}{bin_msg}.\nError: {stderr}\nOutput: {stdout_display}"
+                "- Remove operation #{remove_id} failed{working_dir_msg}.\nDependencies: {}\nErrors: {stderr}\nOutput: {merged}",
+                deps_list
             ))
         }
     }
 
-    /// Static version of execute_cargo_command for use in async contexts
-    async fn execute_cargo_command_static(
-        command: String,
-        working_directory: Option<String>,
-        operation_id: &str,
-        shell_pool_manager: Arc<ShellPoolManager>,
-    ) -> Result<std::process::Output, Box<dyn std::error::Error + Send + Sync>> {
-        use std::path::PathBuf;
-        use tokio::process::Command;
-
-        // Try shell pool first if we have a working directory
-        if let Some(ref working_dir) = working_directory {
-            let working_dir_path = PathBuf::from(working_dir);
-
-            tracing::debug!(
-                operation_id = operation_id,
-                command = %command,
-                working_dir = %working_dir,
-                "Attempting to use shell pool for cargo command"
-            );
-
-            // Try to get a shell from the pool
-            if let Some(mut shell) = shell_pool_manager.get_shell(&working_dir_path).await {
-                // Create shell command for the pool
-                let shell_command = ShellCommand {
-                    id: uuid::Uuid::new_v4().to_string(),
-                    command: vec!["bash".to_string(), "-c".to_string(), command.clone()],
-                    working_dir: working_dir.clone(),
-                    timeout_ms: 300_000, // 5 minute timeout
-                };
-                tracing::info!(
-                    operation_id = operation_id,
-                    "[static] Sending command to shell pool shell_id={} cmd={}",
-                    shell.id(),
-                    command
-                );
-                let exec_result = shell.execute_command(shell_command).await;
-                let shell_id = shell.id().to_string();
-                match exec_result {
-                    Ok(shell_response) => {
-                        tracing::info!(
-                            operation_id = operation_id,
-                            shell_id = %shell_id,
-                            exit_code = shell_response.exit_code,
-                            stdout_len = shell_response.stdout.len(),
-                            stderr_len = shell_response.stderr.len(),
-                            duration_ms = shell_response.duration_ms,
-                            "Shell pool execution successful"
-                        );
+    #[tool(
+        description = "CARGO UPDATE: Faster than terminal cargo. Synchronous operation - returns results immediately once cargo lock is acquired. Updates dependencies to latest compatible versions. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal."
+    )]
+    async fn update(
+        &self,
+        Parameters(req): Parameters<UpdateRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        // Always use synchronous execution for dependency updates
+        match Self::update_implementation(&req).await {
+            Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
+            Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
+        }
+    }
 
-                        // Convert ShellResponse to std::process::Output
-                        use std::process::Output;
+    /// Internal implementation of update logic
+    async fn update_implementation(req: &UpdateRequest) -> Result<String, String> {
+        let mut cmd = cargo_command();
+        cmd.arg("update");
 
-                        // Get ExitStatus by running a simple command
-                        let exit_status = if shell_response.exit_code == 0 {
-                            Command::new("true").status().await.unwrap()
-                        } else {
-                            Command::new("false").status().await.unwrap()
-                        };
+        // Set working directory
+        cmd.current_dir(&req.working_directory);
 
-                        let output = Output {
-                            status: exit_status,
-                            stdout: shell_response.stdout.into_bytes(),
-                            stderr: shell_response.stderr.into_bytes(),
-                        };
+        let output = cmd
+            .output()
+            .await
+            .map_err(|e| format!("Update operation failed: Failed to execute cargo update: {e}"))?;
 
-                        shell_pool_manager.return_shell(shell).await;
-                        return Ok(output);
-                    }
-                    Err(e) => {
-                        tracing::warn!(
-                            operation_id = operation_id,
-                            shell_id = %shell_id,
-                            error = %e,
-                            "Shell pool execution failed, will fall back"
-                        );
-                        if shell.is_healthy() {
-                            shell_pool_manager.return_shell(shell).await;
-                        }
-                        // Fall through
-                    }
-                }
-            } else {
-                tracing::debug!(
-                    operation_id = operation_id,
-                    "No shell available from pool, using direct spawn"
-                );
-            }
-        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
 
-        // Fallback to direct spawn (original behavior)
-        tracing::debug!(
-            operation_id = operation_id,
-            command = %command,
-            "Using direct spawn for cargo command"
-        );
+        let working_dir_msg = format!(" in {}", &req.working_directory);
+        let merged = merge_outputs(&stdout, &stderr, "(no update output captured)");
 
-        let mut cmd = if cfg!(target_os = "windows") {
-            let mut cmd = Command::new("cmd");
-            cmd.args(["/C", &command]);
-            cmd
+        if output.status.success() {
+            Ok(format!(
+                "Update operation completed successfully{working_dir_msg}.\nOutput: {merged}"
+            ))
         } else {
-            let mut cmd = Command::new("bash");
-            cmd.args(["-c", &command]);
-            cmd
-        };
-
-        if let Some(ref working_dir) = working_directory {
-            cmd.current_dir(working_dir);
+            Err(format!(
+                "- Update operation failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {merged}"
+            ))
         }
-
-        cmd.output()
-            .await
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
     }
 
     #[tool(
-        description = "CARGO RUN: Faster than terminal cargo. Use enable_async_notification=true for long-running apps to multitask. Structured output with isolation. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+        description = "CARGO DOC: Faster than terminal cargo. Use enable_async_notification=true for large codebases to multitask. Creates LLM-friendly API reference. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
     )]
-    async fn run(
+    async fn doc(
         &self,
-        Parameters(req): Parameters<RunRequest>,
+        Parameters(req): Parameters<DocRequest>,
         context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        let run_id = self.generate_operation_id_for("run");
+        let doc_id = self.generate_operation_id_for("doc");
+        let target_dir =
+            Self::isolated_target_dir(&self.target_isolation, &req.working_directory, "doc");
 
         // Check if we should run synchronously or use async notifications
         if self.should_run_synchronously(req.enable_async_notification) {
             // Synchronous operation for when async notifications are disabled
-            match Self::run_implementation(&req).await {
+            let result =
+                Self::doc_implementation(&req, target_dir.as_deref(), &self.output_store, &doc_id)
+                    .await;
+            let result = self
+                .output_store
+                .finish_result(&doc_id, result, &req.condense_options());
+            Self::prune_isolated_target_dirs(&self.target_isolation, &req.working_directory, "doc")
+                .await;
+            match result {
                 Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
                 Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
             }
@@ -2512,51 +5109,66 @@ impl AsyncCargo {
 
             let peer = context.peer.clone();
             let req_clone = req.clone();
-            let run_id_clone = run_id.clone();
+            let doc_id_clone = doc_id.clone();
             let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
+            let isolation = self.target_isolation.clone();
 
-            // Register operation before spawn so wait() can find it immediately
+            // Register operation before spawn
             self.register_async_operation(
-                &run_id,
-                "cargo run",
-                "Running application in the background",
+                &doc_id,
+                "cargo doc",
+                "Generating documentation in the background",
                 Some(req.working_directory.clone()),
             )
             .await;
+            self.record_target_dir(&doc_id, &target_dir).await;
 
-            // Spawn background task for actual run work
+            // Spawn background task for actual doc generation work
             tokio::spawn(async move {
                 // Create MCP callback sender to notify the LLM client
-                let callback = mcp_callback(peer, run_id_clone.clone());
+                let callback = mcp_callback(peer, doc_id_clone.clone());
 
                 // Send started notification immediately
                 let _ = callback
                     .send_progress(ProgressUpdate::Started {
-                        operation_id: run_id_clone.clone(),
-                        command: "cargo run".to_string(),
-                        description: "Running application in the background".to_string(),
+                        operation_id: doc_id_clone.clone(),
+                        command: "cargo doc".to_string(),
+                        description: "Generating documentation in the background".to_string(),
                     })
                     .await;
 
-                // Do the actual run work
+                // Do the actual doc generation work
                 let started_at = Instant::now();
-                let result = Self::run_implementation(&req_clone).await;
-
-                // Store result for wait()
+                let result = Self::doc_implementation(
+                    &req_clone,
+                    target_dir.as_deref(),
+                    &output_store,
+                    &doc_id_clone,
+                )
+                .await;
+                let result = output_store.finish_result(
+                    &doc_id_clone,
+                    result,
+                    &req_clone.condense_options(),
+                );
+                Self::prune_isolated_target_dirs(&isolation, &req_clone.working_directory, "doc")
+                    .await;
+                // Store for wait
                 let _ = monitor
-                    .complete_operation(&run_id_clone, result.clone())
+                    .complete_operation(&doc_id_clone, result.clone())
                     .await;
 
                 // Send completion notification
                 let duration_ms = started_at.elapsed().as_millis() as u64;
                 let completion_update = match result {
                     Ok(msg) => ProgressUpdate::Completed {
-                        operation_id: run_id_clone,
+                        operation_id: doc_id_clone,
                         message: msg,
                         duration_ms,
                     },
                     Err(err) => ProgressUpdate::Failed {
-                        operation_id: run_id_clone,
+                        operation_id: doc_id_clone,
                         error: err,
                         duration_ms,
                     },
@@ -2566,140 +5178,349 @@ impl AsyncCargo {
             });
 
             // Return immediate response to LLM - this is the "first stage"
-            let tool_hint = self.generate_tool_hint(&run_id, "run");
+            let tool_hint = self.generate_tool_hint(&doc_id, "documentation generation");
             let timestamp = timestamp::format_current_time();
             Ok(CallToolResult::success(vec![Content::text(format!(
-                "Run operation {run_id} started at {timestamp} in the background.{tool_hint}"
+                "Documentation generation {doc_id} started at {timestamp} in the background.{tool_hint}"
             ))]))
         }
     }
 
-    /// Internal implementation of run logic
-    async fn run_implementation(req: &RunRequest) -> Result<String, String> {
-        use tokio::process::Command;
+    // (HTML content parsing removed; doc tool reports path only)
 
-        let mut cmd = Command::new("cargo");
-        cmd.arg("run");
+    /// Internal implementation of doc generation logic
+    async fn doc_implementation(
+        req: &DocRequest,
+        target_dir: Option<&Path>,
+        output_store: &OutputStore,
+        operation_id: &str,
+    ) -> Result<String, String> {
+        let mut cmd = cargo_command();
+        cmd.arg("doc").arg("--no-deps");
+        apply_target_dir(&mut cmd, target_dir);
 
-        // Add feature selection
-        if let Some(features) = &req.features
-            && !features.is_empty()
+        // Set working directory
+        cmd.current_dir(&req.working_directory);
+
+        let output = output_store
+            .capture(operation_id, &mut cmd)
+            .await
+            .map_err(|e| {
+                format!("Documentation generation failed: Failed to execute cargo doc: {e}")
+            })?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        let working_dir_msg = format!(" in {}", &req.working_directory);
+
+        if output.status.success() {
+            // Try to determine the crate name for the documentation path
+            let crate_name = {
+                let cargo_toml_path = format!("{}/Cargo.toml", &req.working_directory);
+                match tokio::fs::read_to_string(&cargo_toml_path).await {
+                    Ok(content) => content
+                        .lines()
+                        .find(|line| line.trim().starts_with("name"))
+                        .and_then(|line| {
+                            line.split('=')
+                                .nth(1)?
+                                .trim()
+                                .trim_matches('"')
+                                .split(' ')
+                                .next()
+                                .map(|s| s.replace('-', "_"))
+                        })
+                        .unwrap_or_else(|| "unknown_crate".to_string()),
+                    Err(_) => "unknown_crate".to_string(),
+                }
+            };
+
+            let doc_root = match target_dir {
+                Some(dir) => dir.display().to_string(),
+                None => format!("{}/target", &req.working_directory),
+            };
+            let doc_path = format!("{doc_root}/doc/{crate_name}/index.html");
+
+            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("doc"));
+            Ok(format!(
+                "Documentation generation completed successfully{working_dir_msg}.\nDocumentation generated at: {doc_path}\nThe generated documentation provides comprehensive API information that can be used by LLMs for more accurate and up-to-date project understanding.\nTip: Use this documentation to get the latest API details, examples, and implementation notes that complement the source code.\n\nOutput: {merged}"
+            ))
+        } else {
+            Err(format!(
+                "- Documentation generation failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {stdout}"
+            ))
+        }
+    }
+    #[tool(
+        description = "CARGO CLIPPY: Faster than terminal cargo. Supports --fix via args=['--fix','--allow-dirty']. Fast operation - async optional. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+    )]
+    async fn clippy(
+        &self,
+        Parameters(req): Parameters<ClippyRequest>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let clippy_id = self.generate_operation_id_for("clippy");
+        let target_dir =
+            Self::isolated_target_dir(&self.target_isolation, &req.working_directory, "clippy");
+
+        // `--fix` edits sources, so its result says nothing about a repeat
+        let fixes = req
+            .args
+            .as_ref()
+            .is_some_and(|args| args.iter().any(|a| a == "--fix"));
+        let fingerprint = if fixes {
+            None
+        } else {
+            self.result_fingerprint("clippy", &req.working_directory, &req.cache_key())
+                .await
+        };
+        if req.force != Some(true)
+            && let Some(fingerprint) = &fingerprint
+            && let Some(cached) = self.result_cache.get(fingerprint.key).await
         {
-            cmd.arg("--features").arg(features.join(","));
+            return self
+                .replay_result(
+                    "clippy",
+                    &clippy_id,
+                    &cached.note(),
+                    cached,
+                    &req.condense_options(),
+                )
+                .await;
         }
 
-        if req.all_features.unwrap_or(false) {
-            cmd.arg("--all-features");
-        }
+        // Check if we should run synchronously or use async notifications
+        if self.should_run_synchronously(req.enable_async_notification) {
+            // Synchronous operation for when async notifications are disabled
+            let result = Self::clippy_implementation(
+                &req,
+                target_dir.as_deref(),
+                &self.diagnostics,
+                &self.output_store,
+                &clippy_id,
+            )
+            .await;
+            Self::cache_result(&self.result_cache, fingerprint, &clippy_id, &result).await;
+            let result =
+                self.output_store
+                    .finish_result(&clippy_id, result, &req.condense_options());
+            Self::prune_isolated_target_dirs(
+                &self.target_isolation,
+                &req.working_directory,
+                "clippy",
+            )
+            .await;
+            let response = match result {
+                Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
+                Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
+            };
+            self.with_diagnostics(&clippy_id, response).await
+        } else {
+            // TRUE 2-STAGE ASYNC PATTERN:
+            // 1. Send immediate response that operation has started
+            // 2. Spawn background task to do actual work and send notifications
 
-        if req.no_default_features.unwrap_or(false) {
-            cmd.arg("--no-default-features");
-        }
+            let peer = context.peer.clone();
+            let req_clone = req.clone();
+            let clippy_id_clone = clippy_id.clone();
+            let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
+            let diagnostics = self.diagnostics.clone();
+            let isolation = self.target_isolation.clone();
+            let result_cache = self.result_cache.clone();
 
-        // Add compilation options
-        if req.release.unwrap_or(false) {
-            cmd.arg("--release");
-        }
+            // Register operation before spawn
+            self.register_async_operation(
+                &clippy_id,
+                "cargo clippy",
+                "Running linter in the background",
+                Some(req.working_directory.clone()),
+            )
+            .await;
+            self.record_target_dir(&clippy_id, &target_dir).await;
 
-        if let Some(profile) = &req.profile {
-            cmd.arg("--profile").arg(profile);
-        }
+            // Spawn background task for actual clippy work
+            tokio::spawn(async move {
+                // Create MCP callback sender to notify the LLM client
+                let callback = mcp_callback(peer, clippy_id_clone.clone());
 
-        if let Some(target) = &req.target {
-            cmd.arg("--target").arg(target);
-        }
+                // Send started notification immediately
+                let _ = callback
+                    .send_progress(ProgressUpdate::Started {
+                        operation_id: clippy_id_clone.clone(),
+                        command: "cargo clippy".to_string(),
+                        description: "Running linter in the background".to_string(),
+                    })
+                    .await;
 
-        if let Some(jobs) = req.jobs {
-            cmd.arg("--jobs").arg(jobs.to_string());
-        }
+                // Do the actual clippy work
+                let started_at = Instant::now();
+                let result = Self::clippy_implementation(
+                    &req_clone,
+                    target_dir.as_deref(),
+                    &diagnostics,
+                    &output_store,
+                    &clippy_id_clone,
+                )
+                .await;
+                Self::cache_result(&result_cache, fingerprint, &clippy_id_clone, &result).await;
+                let result = output_store.finish_result(
+                    &clippy_id_clone,
+                    result,
+                    &req_clone.condense_options(),
+                );
+                Self::prune_isolated_target_dirs(
+                    &isolation,
+                    &req_clone.working_directory,
+                    "clippy",
+                )
+                .await;
+                // Store for wait
+                let _ = monitor
+                    .complete_operation(&clippy_id_clone, result.clone())
+                    .await;
 
-        // Add manifest path
-        if let Some(manifest_path) = &req.manifest_path {
-            cmd.arg("--manifest-path").arg(manifest_path);
-        }
+                // Send completion notification
+                let duration_ms = started_at.elapsed().as_millis() as u64;
+                let completion_update = match result {
+                    Ok(msg) => ProgressUpdate::Completed {
+                        operation_id: clippy_id_clone,
+                        message: msg,
+                        duration_ms,
+                    },
+                    Err(err) => ProgressUpdate::Failed {
+                        operation_id: clippy_id_clone,
+                        error: err,
+                        duration_ms,
+                    },
+                };
 
-        // Add --bin parameter if specified
-        if let Some(bin_name) = &req.bin_name {
-            cmd.arg("--bin").arg(bin_name);
+                let _ = callback.send_progress(completion_update).await;
+            });
+
+            // Return immediate response to LLM - this is the "first stage"
+            let tool_hint = self.generate_tool_hint(&clippy_id, "clippy linting");
+            let timestamp = timestamp::format_current_time();
+            Ok(CallToolResult::success(vec![Content::text(format!(
+                "Clippy operation {clippy_id} started at {timestamp} in the background.{tool_hint}"
+            ))]))
         }
+    }
 
-        // Add additional cargo arguments
-        if let Some(cargo_args) = &req.cargo_args {
-            for arg in cargo_args {
-                cmd.arg(arg);
-            }
+    /// Internal implementation of clippy logic
+    async fn clippy_implementation(
+        req: &ClippyRequest,
+        target_dir: Option<&Path>,
+        diagnostics: &DiagnosticsStore,
+        output_store: &OutputStore,
+        operation_id: &str,
+    ) -> Result<String, String> {
+        let mut cmd = cargo_command();
+        cmd.arg("clippy");
+        if !has_message_format(&req.args) {
+            cmd.arg(MESSAGE_FORMAT_ARG);
         }
+        apply_target_dir(&mut cmd, target_dir);
 
-        // Add binary arguments after -- separator
-        if let Some(binary_args) = &req.binary_args
-            && !binary_args.is_empty()
-        {
-            tracing::debug!("Adding binary args: {binary_args:?}");
-            cmd.arg("--");
-            for arg in binary_args {
-                cmd.arg(arg);
-                tracing::debug!("Added binary arg: {arg}");
-            }
+        // Add any additional arguments passed to clippy
+        if let Some(args) = &req.args {
+            cmd.args(args);
         }
 
-        // Set working directory
         cmd.current_dir(&req.working_directory);
 
-        let output = cmd
-            .output()
+        let mut messages = CargoMessageParser::default();
+        let output = output_store
+            .capture_with(operation_id, &mut cmd, |line| messages.line(line))
             .await
-            .map_err(|e| format!("Run operation failed: Failed to execute cargo run: {e}"))?;
+            .map_err(|e| format!("Clippy operation failed: Failed to execute cargo clippy: {e}"))?;
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
+        let scope = DiagnosticScope::new(
+            "clippy",
+            &req.working_directory,
+            cmd.as_std().get_args().skip(1).map(|a| a.to_string_lossy()),
+        );
+        let mut stdout = record_cargo_messages(
+            diagnostics,
+            &scope,
+            operation_id,
+            messages.finish(),
+            &output.stdout,
+        )
+        .await;
+        if let Some(lints) = record_lint_summary(
+            diagnostics,
+            &req.working_directory,
+            operation_id,
+            req.lint_advice,
+        )
+        .await
+        {
+            stdout = format!("{stdout}\n\n{lints}");
+        }
         let stderr = String::from_utf8_lossy(&output.stderr);
 
         let working_dir_msg = format!(" in {}", &req.working_directory);
-        let bin_msg = if let Some(bin_name) = &req.bin_name {
-            format!(" (binary: {bin_name})")
-        } else {
-            String::new()
-        };
-
-        let args_msg = if let Some(binary_args) = &req.binary_args {
-            if !binary_args.is_empty() {
-                format!(" with args: [{}]", binary_args.join(", "))
-            } else {
-                String::new()
-            }
-        } else {
-            String::new()
-        };
 
         if output.status.success() {
-            // Merge stdout+stderr so compile lines (on stderr) always appear in Output section.
-            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("run"));
+            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("clippy"));
             Ok(format!(
-                "+ Run operation completed successfully{working_dir_msg}{bin_msg}{args_msg}.\nOutput: {merged}"
+                "Clippy operation passed with no warnings{working_dir_msg}.\nOutput: {merged}",
             ))
         } else {
+            // Even on failure, ensure stderr also visible in Output (besides Errors) for parity with other commands
+            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("clippy"));
             Err(format!(
-                "- Run operation failed{working_dir_msg}{bin_msg}{args_msg}.\nErrors: {stderr}\nOutput: {stdout}"
+                "- Clippy operation failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {merged}",
             ))
         }
     }
 
     #[tool(
-        description = "CARGO TEST: Faster than terminal cargo. Provides complete error output but runs slower than nextest. Use when debugging test failures, expecting failures, or running specific tests that need detailed output. ALWAYS use enable_async_notification=true for test suites to multitask. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+        description = "CARGO NEXTEST: Faster than terminal cargo. Faster test runner - preferred when expecting success or checking full test suite. Use 'test' when debugging failures or need detailed error output. ALWAYS use enable_async_notification=true for test suites to multitask. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
     )]
-    async fn test(
+    async fn nextest(
         &self,
-        Parameters(req): Parameters<TestRequest>,
+        Parameters(req): Parameters<NextestRequest>,
         context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        let test_id = self.generate_operation_id_for("test");
+        let nextest_id = self.generate_operation_id_for("nextest");
+        self.remember_test_run(&nextest_id, TestRun::Nextest(req.clone()))
+            .await;
 
-        // Check if async notifications are enabled and not in synchronous mode
+        // First check if nextest is available
+        let nextest_check = tokio::process::Command::new("cargo")
+            .args(["nextest", "--version"])
+            .output()
+            .await;
+
+        if nextest_check.is_err() || !nextest_check.unwrap().status.success() {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                r#"- Nextest operation #{nextest_id} failed: cargo-nextest is not installed. 
+Install with: cargo install cargo-nextest
+Falling back to regular cargo test is recommended."#
+            ))]));
+        }
+
+        // Check if we should run synchronously or use async notifications
         if self.should_run_synchronously(req.enable_async_notification) {
-            // Synchronous operation
-            match Self::test_implementation(&req).await {
+            // Synchronous operation for when async notifications are disabled
+            let result = match self.output_store.finish_result(
+                &nextest_id,
+                Self::nextest_implementation(
+                    &req,
+                    &self.diagnostics,
+                    &self.output_store,
+                    &nextest_id,
+                )
+                .await,
+                &req.condense_options(),
+            ) {
                 Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
                 Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
-            }
+            };
+            self.with_diagnostics(&nextest_id, result).await
         } else {
             // TRUE 2-STAGE ASYNC PATTERN:
             // 1. Send immediate response that operation has started
@@ -2707,50 +5528,63 @@ impl AsyncCargo {
 
             let peer = context.peer.clone();
             let req_clone = req.clone();
-            let test_id_clone = test_id.clone();
+            let nextest_id_clone = nextest_id.clone();
             let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
+            let diagnostics = self.diagnostics.clone();
 
             // Register operation before spawn
             self.register_async_operation(
-                &test_id,
-                "cargo test",
-                "Running test suite in the background",
+                &nextest_id,
+                "cargo nextest run",
+                "Running fast test suite in the background",
                 Some(req.working_directory.clone()),
             )
             .await;
 
-            // Spawn background task for actual test work
+            // Spawn background task for actual nextest work
             tokio::spawn(async move {
                 // Create MCP callback sender to notify the LLM client
-                let callback = mcp_callback(peer, test_id_clone.clone());
+                let callback = mcp_callback(peer, nextest_id_clone.clone());
 
                 // Send started notification immediately
                 let _ = callback
                     .send_progress(ProgressUpdate::Started {
-                        operation_id: test_id_clone.clone(),
-                        command: "cargo test".to_string(),
-                        description: "Running test suite in the background".to_string(),
+                        operation_id: nextest_id_clone.clone(),
+                        command: "cargo nextest run".to_string(),
+                        description: "Running fast test suite in the background".to_string(),
                     })
                     .await;
 
-                // Do the actual test work
+                // Do the actual nextest work
                 let started_at = Instant::now();
-                let result = Self::test_implementation(&req_clone).await;
+                let result = Self::nextest_implementation(
+                    &req_clone,
+                    &diagnostics,
+                    &output_store,
+                    &nextest_id_clone,
+                )
+                .await;
+                let result = output_store.finish_result(
+                    &nextest_id_clone,
+                    result,
+                    &req_clone.condense_options(),
+                );
                 // Store for wait
                 let _ = monitor
-                    .complete_operation(&test_id_clone, result.clone())
+                    .complete_operation(&nextest_id_clone, result.clone())
                     .await;
 
                 // Send completion notification
                 let duration_ms = started_at.elapsed().as_millis() as u64;
                 let completion_update = match result {
                     Ok(msg) => ProgressUpdate::Completed {
-                        operation_id: test_id_clone,
+                        operation_id: nextest_id_clone,
                         message: msg,
                         duration_ms,
                     },
                     Err(err) => ProgressUpdate::Failed {
-                        operation_id: test_id_clone,
+                        operation_id: nextest_id_clone,
                         error: err,
                         duration_ms,
                     },
@@ -2760,193 +5594,123 @@ impl AsyncCargo {
             });
 
             // Return immediate response to LLM - this is the "first stage"
-            let tool_hint = self.generate_tool_hint(&test_id, "test");
+            let tool_hint = self.generate_tool_hint(&nextest_id, "nextest");
             let timestamp = timestamp::format_current_time();
             Ok(CallToolResult::success(vec![Content::text(format!(
-                "Test operation {test_id} started at {timestamp} in the background.{tool_hint}"
+                "Nextest operation {nextest_id} started at {timestamp} in the background.{tool_hint}"
             ))]))
         }
     }
 
-    /// Internal implementation of test logic
-    pub async fn test_implementation(req: &TestRequest) -> Result<String, String> {
-        use tokio::process::Command;
-
-        let test_id = uuid::Uuid::new_v4().to_string()[..8].to_string();
-
-        let mut cmd = Command::new("cargo");
-        cmd.arg("test");
-
-        // Add package selection
-        if let Some(package) = &req.package {
-            cmd.arg("--package").arg(package);
-        }
-
-        if req.workspace.unwrap_or(false) {
-            cmd.arg("--workspace");
-        }
-
-        if let Some(exclude) = &req.exclude {
-            for pkg in exclude {
-                cmd.arg("--exclude").arg(pkg);
-            }
-        }
-
-        // Add target selection
-        if req.lib.unwrap_or(false) {
-            cmd.arg("--lib");
-        }
-
-        if req.bins.unwrap_or(false) {
-            cmd.arg("--bins");
-        }
-
-        if let Some(bin) = &req.bin {
-            cmd.arg("--bin").arg(bin);
-        }
-
-        if req.examples.unwrap_or(false) {
-            cmd.arg("--examples");
-        }
-
-        if let Some(example) = &req.example {
-            cmd.arg("--example").arg(example);
-        }
-
-        if req.tests.unwrap_or(false) {
-            cmd.arg("--tests");
-        }
-
-        if let Some(test) = &req.test {
-            cmd.arg("--test").arg(test);
-        }
-
-        if req.all_targets.unwrap_or(false) {
-            cmd.arg("--all-targets");
-        }
-
-        if req.doc.unwrap_or(false) {
-            cmd.arg("--doc");
-        }
-
-        // Add feature selection
-        if let Some(features) = &req.features
-            && !features.is_empty()
-        {
-            cmd.arg("--features").arg(features.join(","));
-        }
-
-        if req.all_features.unwrap_or(false) {
-            cmd.arg("--all-features");
-        }
-
-        if req.no_default_features.unwrap_or(false) {
-            cmd.arg("--no-default-features");
-        }
-
-        // Add compilation options
-        if req.release.unwrap_or(false) {
-            cmd.arg("--release");
-        }
-
-        if let Some(profile) = &req.profile {
-            cmd.arg("--profile").arg(profile);
-        }
-
-        if let Some(jobs) = req.jobs {
-            cmd.arg("--jobs").arg(jobs.to_string());
-        }
-
-        if let Some(target) = &req.target {
-            cmd.arg("--target").arg(target);
-        }
-
-        // Add test options
-        if req.no_run.unwrap_or(false) {
-            cmd.arg("--no-run");
-        }
+    /// Internal implementation of nextest logic
+    async fn nextest_implementation(
+        req: &NextestRequest,
+        diagnostics: &DiagnosticsStore,
+        output_store: &OutputStore,
+        operation_id: &str,
+    ) -> Result<String, String> {
+        let nextest_id = uuid::Uuid::new_v4().to_string()[..8].to_string();
 
-        if req.no_fail_fast.unwrap_or(false) {
-            cmd.arg("--no-fail-fast");
-        }
+        let mut cmd = cargo_command();
+        cmd.args(["nextest", "run"]);
 
-        // Add manifest options
-        if let Some(manifest_path) = &req.manifest_path {
-            cmd.arg("--manifest-path").arg(manifest_path);
+        // Per-test results as libtest JSON events on stdout; the human report stays on stderr
+        if !has_message_format(&req.args) {
+            cmd.env("NEXTEST_EXPERIMENTAL_LIBTEST_JSON", "1")
+                .args(["--message-format", "libtest-json-plus"]);
         }
 
-        // Add additional cargo arguments
+        // Add any additional arguments
         if let Some(args) = &req.args {
-            for arg in args {
-                cmd.arg(arg);
-            }
-        }
-
-        // Add test name filter as positional argument
-        if let Some(test_name) = &req.test_name {
-            cmd.arg(test_name);
-        }
-
-        // Add test arguments after -- separator
-        if let Some(test_args) = &req.test_args
-            && !test_args.is_empty()
-        {
-            cmd.arg("--");
-            for arg in test_args {
-                cmd.arg(arg);
-            }
+            cmd.args(args);
         }
 
-        // Set working directory
+        set_rust_backtrace(&mut cmd, req.backtrace);
         cmd.current_dir(&req.working_directory);
 
-        let output = cmd
-            .output()
+        let output = output_store
+            .capture(operation_id, &mut cmd)
             .await
-            .map_err(|e| format!("Failed to execute cargo test: {e}"))?;
+            .map_err(|e| format!("Failed to execute cargo nextest: {e}"))?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
-
-        let working_dir_msg = format!(" in {}", &req.working_directory);
-        let test_filter_msg = if let Some(test_name) = &req.test_name {
-            format!(" (filter: {test_name})")
+        let (mut tests, _) = parse_test_output(&stdout, "");
+        let (stdout, stderr) = if tests.is_empty() {
+            (stdout.to_string(), stderr.to_string())
         } else {
-            String::new()
+            diagnostics
+                .annotate_flaky(&req.working_directory, &mut tests)
+                .await;
+            let summary = tests.render();
+            diagnostics.record_tests(operation_id, tests).await;
+            let printed: Vec<&str> = stdout
+                .lines()
+                .filter(|l| !l.trim_start().starts_with("{\"type\""))
+                .collect();
+            (
+                printed.join("\n"),
+                format!("{}\n\n{summary}", stderr.trim_end()),
+            )
         };
 
+        let working_dir_msg = format!(" in {}", &req.working_directory);
+
         if output.status.success() {
-            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("test"));
+            // Many nextest summaries are emitted to stderr (progress + final report). If stdout is empty but stderr has content, treat stderr as primary.
+            let combined = if stdout.trim().is_empty() && !stderr.trim().is_empty() {
+                stderr.to_string()
+            } else if !stdout.trim().is_empty() && !stderr.trim().is_empty() {
+                format!("{stdout}\n{stderr}")
+            } else {
+                stdout.to_string()
+            };
+            let final_output = if combined.trim().is_empty() {
+                Self::no_output_placeholder("nextest")
+            } else {
+                combined
+            };
             Ok(format!(
-                "Test operation #{test_id} completed successfully{working_dir_msg}{test_filter_msg}.\nOutput: {merged}"
+                "Nextest operation #{nextest_id} completed successfully{working_dir_msg}.\nOutput: {final_output}"
             ))
         } else {
+            // Failure: include full stderr + stdout (stderr first for clarity)
+            let mut err_block = String::new();
+            if !stderr.trim().is_empty() {
+                err_block.push_str(&stderr);
+            }
+            if !stdout.trim().is_empty() {
+                if !err_block.is_empty() {
+                    err_block.push_str("\n--- stdout ---\n");
+                }
+                err_block.push_str(&stdout);
+            }
             Err(format!(
-                "- Test operation #{test_id} failed{working_dir_msg}{test_filter_msg}.\nErrors: {stderr}\nOutput: {stdout}"
+                "- Nextest operation #{nextest_id} failed{working_dir_msg}.\nErrors: {err_block}"
             ))
         }
     }
 
     #[tool(
-        description = "CARGO CHECK: Faster than terminal cargo. Fast validation - async optional for large projects. Quick compile check. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+        description = "CARGO CLEAN: Faster than terminal cargo. Fast operation - async not needed. Frees disk space. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
     )]
-    async fn check(
+    async fn clean(
         &self,
-        Parameters(req): Parameters<CheckRequest>,
+        Parameters(req): Parameters<CleanRequest>,
         context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        let check_id = self.generate_operation_id_for("check");
+        let clean_id = self.generate_operation_id_for("clean");
 
         // Check if we should run synchronously or use async notifications
         if self.should_run_synchronously(req.enable_async_notification) {
             // Synchronous operation for when async notifications are disabled
-            let result = Self::check_implementation(&req).await;
-            return Self::handle_sync_result(
-                "check",
-                "cargo check",
-                "Synchronous check operation",
-                result,
-            );
+            match self
+                .output_store
+                .bound_result(&clean_id, Self::clean_implementation(&req).await)
+            {
+                Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
+                Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
+            }
         } else {
             // TRUE 2-STAGE ASYNC PATTERN:
             // 1. Send immediate response that operation has started
@@ -2954,50 +5718,52 @@ impl AsyncCargo {
 
             let peer = context.peer.clone();
             let req_clone = req.clone();
-            let check_id_clone = check_id.clone();
+            let clean_id_clone = clean_id.clone();
             let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
 
             // Register operation before spawn
             self.register_async_operation(
-                &check_id,
-                "cargo check",
-                "Checking project in the background",
+                &clean_id,
+                "cargo clean",
+                "Cleaning build artifacts in the background",
                 Some(req.working_directory.clone()),
             )
             .await;
 
-            // Spawn background task for actual check work
+            // Spawn background task for actual clean work
             tokio::spawn(async move {
                 // Create MCP callback sender to notify the LLM client
-                let callback = mcp_callback(peer, check_id_clone.clone());
+                let callback = mcp_callback(peer, clean_id_clone.clone());
 
                 // Send started notification immediately
                 let _ = callback
                     .send_progress(ProgressUpdate::Started {
-                        operation_id: check_id_clone.clone(),
-                        command: "cargo check".to_string(),
-                        description: "Checking project in the background".to_string(),
+                        operation_id: clean_id_clone.clone(),
+                        command: "cargo clean".to_string(),
+                        description: "Cleaning build artifacts in the background".to_string(),
                     })
                     .await;
 
-                // Do the actual check work
+                // Do the actual clean work
                 let started_at = Instant::now();
-                let result = Self::check_implementation(&req_clone).await;
+                let result = Self::clean_implementation(&req_clone).await;
+                let result = output_store.bound_result(&clean_id_clone, result);
                 // Store for wait
                 let _ = monitor
-                    .complete_operation(&check_id_clone, result.clone())
+                    .complete_operation(&clean_id_clone, result.clone())
                     .await;
 
                 // Send completion notification
                 let duration_ms = started_at.elapsed().as_millis() as u64;
                 let completion_update = match result {
                     Ok(msg) => ProgressUpdate::Completed {
-                        operation_id: check_id_clone,
+                        operation_id: clean_id_clone,
                         message: msg,
                         duration_ms,
                     },
                     Err(err) => ProgressUpdate::Failed {
-                        operation_id: check_id_clone,
+                        operation_id: clean_id_clone,
                         error: err,
                         duration_ms,
                     },
@@ -3007,27 +5773,24 @@ impl AsyncCargo {
             });
 
             // Return immediate response to LLM - this is the "first stage"
-            let tool_hint = self.generate_tool_hint(&check_id, "check");
+            let tool_hint = self.generate_tool_hint(&clean_id, "clean");
             let timestamp = timestamp::format_current_time();
             Ok(CallToolResult::success(vec![Content::text(format!(
-                "+ Check operation {check_id} started at {timestamp} in the background.{tool_hint}"
+                "Clean operation {clean_id} started at {timestamp} in the background.{tool_hint}"
             ))]))
         }
     }
 
-    /// Internal implementation of check logic
-    async fn check_implementation(req: &CheckRequest) -> Result<String, String> {
-        use tokio::process::Command;
-
-        let mut cmd = Command::new("cargo");
-        cmd.arg("check");
+    /// Internal implementation of clean logic
+    async fn clean_implementation(req: &CleanRequest) -> Result<String, String> {
+        let mut cmd = cargo_command();
+        cmd.arg("clean");
 
-        // Set working directory
         cmd.current_dir(&req.working_directory);
 
         let output = cmd.output().await.map_err(|e| {
             format!(
-                "- Check operation failed in {}.\nError: Failed to execute cargo check: {}",
+                "- Clean operation failed in {}.\nError: Failed to execute cargo clean: {}",
                 &req.working_directory, e
             )
         })?;
@@ -3038,211 +5801,306 @@ impl AsyncCargo {
         let working_dir_msg = format!(" in {}", &req.working_directory);
 
         if output.status.success() {
-            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("check"));
+            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("clean"));
             Ok(format!(
-                "+ Check operation completed successfully{working_dir_msg}.\nOutput: {merged}"
+                "Clean operation completed successfully{working_dir_msg}.\nOutput: {merged}"
             ))
         } else {
+            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("clean"));
             Err(format!(
-                "- Check operation failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {stdout}"
+                "- Clean operation failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {merged}"
             ))
         }
     }
 
     #[tool(
-        description = "CARGO ADD: Faster than terminal cargo. Synchronous operation for Cargo.toml modifications. Handles version conflicts. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+        description = "CARGO FIX: Faster than terminal cargo. Automatically fix compiler warnings. Supports --allow-dirty via args. Use async for large codebases. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
     )]
-    async fn add(
+    async fn fix(
         &self,
-        Parameters(req): Parameters<DependencyRequest>,
+        Parameters(req): Parameters<FixRequest>,
+        context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        let add_id = self.generate_operation_id_for("add");
+        let fix_id = self.generate_operation_id_for("fix");
 
-        // Always use synchronous execution for Cargo.toml modifications
-        use tokio::process::Command;
+        // Check if we should run synchronously or use async notifications
+        if self.should_run_synchronously(req.enable_async_notification) {
+            // Synchronous operation for when async notifications are disabled
+            match self.output_store.finish_result(
+                &fix_id,
+                Self::fix_implementation(&req, &self.output_store, &fix_id).await,
+                &req.condense_options(),
+            ) {
+                Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
+                Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
+            }
+        } else {
+            // TRUE 2-STAGE ASYNC PATTERN:
+            // 1. Send immediate response that operation has started
+            // 2. Spawn background task to do actual work and send notifications
 
-        let mut cmd = Command::new("cargo");
+            let peer = context.peer.clone();
+            let req_clone = req.clone();
+            let fix_id_clone = fix_id.clone();
+            let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
 
-        // Build the dependency specification
-        let dep_spec = if let Some(version) = &req.version {
-            format!("{}@{}", req.name, version)
-        } else {
-            req.name.clone()
-        };
+            // Register operation before spawn
+            self.register_async_operation(
+                &fix_id,
+                "cargo fix",
+                "Fixing compiler warnings in the background",
+                Some(req.working_directory.clone()),
+            )
+            .await;
 
-        cmd.arg("add").arg(&dep_spec);
+            // Spawn background task for actual fix work
+            tokio::spawn(async move {
+                // Create MCP callback sender to notify the LLM client
+                let callback = mcp_callback(peer, fix_id_clone.clone());
 
-        // Set working directory
-        cmd.current_dir(&req.working_directory);
+                // Send started notification immediately
+                let _ = callback
+                    .send_progress(ProgressUpdate::Started {
+                        operation_id: fix_id_clone.clone(),
+                        command: "cargo fix".to_string(),
+                        description: "Fixing compiler warnings in the background".to_string(),
+                    })
+                    .await;
 
-        // Add optional features
-        if let Some(features) = &req.features
-            && !features.is_empty()
-        {
-            cmd.arg("--features").arg(features.join(","));
-        }
+                // Do the actual fix work
+                let started_at = Instant::now();
+                let result =
+                    Self::fix_implementation(&req_clone, &output_store, &fix_id_clone).await;
+                let result = output_store.finish_result(
+                    &fix_id_clone,
+                    result,
+                    &req_clone.condense_options(),
+                );
+                // Store for wait
+                let _ = monitor
+                    .complete_operation(&fix_id_clone, result.clone())
+                    .await;
 
-        // Add no-default-features flag
-        if req.no_default_features.unwrap_or(false) {
-            cmd.arg("--no-default-features");
+                // Send completion notification
+                let duration_ms = started_at.elapsed().as_millis() as u64;
+                let completion_update = match result {
+                    Ok(msg) => ProgressUpdate::Completed {
+                        operation_id: fix_id_clone,
+                        message: msg,
+                        duration_ms,
+                    },
+                    Err(err) => ProgressUpdate::Failed {
+                        operation_id: fix_id_clone,
+                        error: err,
+                        duration_ms,
+                    },
+                };
+
+                let _ = callback.send_progress(completion_update).await;
+            });
+
+            // Return immediate response to LLM - this is the "first stage"
+            let tool_hint = self.generate_tool_hint(&fix_id, "fix");
+            let timestamp = timestamp::format_current_time();
+            Ok(CallToolResult::success(vec![Content::text(format!(
+                "Fix operation {fix_id} started at {timestamp} in the background.{tool_hint}"
+            ))]))
         }
+    }
 
-        // Add optional flag
-        if req.optional.unwrap_or(false) {
-            cmd.arg("--optional");
+    /// Internal implementation of fix logic
+    async fn fix_implementation(
+        req: &FixRequest,
+        output_store: &OutputStore,
+        operation_id: &str,
+    ) -> Result<String, String> {
+        let mut cmd = cargo_command();
+        cmd.arg("fix");
+
+        // Add any additional arguments
+        if let Some(args) = &req.args {
+            cmd.args(args);
+        } else {
+            // Provide safe defaults to allow running in temp test project without VCS
+            cmd.arg("--allow-dirty").arg("--allow-no-vcs");
         }
 
-        // Apply dependency section arguments
-        apply_dependency_section_args(&mut cmd, &req.section);
+        cmd.current_dir(&req.working_directory);
 
-        let output = cmd.output().await.map_err(|e| {
-            ErrorData::internal_error(format!("Failed to execute cargo add: {e}"), None)
-        })?;
+        let output = output_store
+            .capture(operation_id, &mut cmd)
+            .await
+            .map_err(|e| {
+                format!(
+                    "- Fix operation failed in {}.\nError: Failed to execute cargo fix: {}",
+                    &req.working_directory, e
+                )
+            })?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
 
         let working_dir_msg = format!(" in {}", &req.working_directory);
 
-        let result_msg = if output.status.success() {
-            format!(
-                "+ Add operation #{add_id} completed successfully{working_dir_msg}.\nAdded dependency: {}\nOutput: {stdout}",
-                req.name
-            )
+        if output.status.success() {
+            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("fix"));
+            Ok(format!(
+                "Fix operation completed successfully{working_dir_msg}.\nOutput: {merged}"
+            ))
         } else {
-            format!(
-                "- Add operation #{add_id} failed{working_dir_msg}.\nDependency: {}\nError: {stderr}\nOutput: {stdout}",
-                req.name
-            )
-        };
-
-        Ok(CallToolResult::success(vec![Content::text(result_msg)]))
+            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("fix"));
+            Err(format!(
+                "- Fix operation failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {merged}"
+            ))
+        }
     }
 
     #[tool(
-        description = "CARGO REMOVE: Faster than terminal cargo. Synchronous operation for Cargo.toml modifications. Prevents Cargo.toml corruption. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+        description = "CARGO SEARCH: Faster than terminal cargo. Search for crates on crates.io. Fast operation - async not needed unless searching many terms. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
     )]
-    async fn remove(
+    async fn search(
         &self,
-        Parameters(mut req): Parameters<RemoveDependencyRequest>,
+        Parameters(req): Parameters<SearchRequest>,
+        context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        let remove_id = self.generate_operation_id_for("remove");
-
-        // Handle backward compatibility: if name is provided and names is empty, use name
-        if req.names.is_empty() {
-            if let Some(name) = &req.name {
-                req.names = vec![name.clone()];
-            } else {
-                return Err(ErrorData::invalid_params(
-                    "Either 'name' or 'names' must be provided",
-                    None,
-                ));
-            }
-        }
-
-        // Always use synchronous execution for Cargo.toml modifications
-        use tokio::process::Command;
-
-        let mut cmd = Command::new("cargo");
-        cmd.arg("remove");
+        let search_id = self.generate_operation_id_for("search");
 
-        // Add all dependency names
-        for name in &req.names {
-            cmd.arg(name);
-        }
+        // Check if we should run synchronously or use async notifications
+        if self.should_run_synchronously(req.enable_async_notification) {
+            // Synchronous operation for when async notifications are disabled
+            match self
+                .output_store
+                .bound_result(&search_id, Self::search_implementation(&req).await)
+            {
+                Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
+                Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
+            }
+        } else {
+            // TRUE 2-STAGE ASYNC PATTERN:
+            // 1. Send immediate response that operation has started
+            // 2. Spawn background task to do actual work and send notifications
 
-        // Apply dependency section arguments
-        apply_dependency_section_args(&mut cmd, &req.section);
+            let peer = context.peer.clone();
+            let req_clone = req.clone();
+            let search_id_clone = search_id.clone();
+            let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
 
-        // Set working directory
-        cmd.current_dir(&req.working_directory);
+            // Register operation before spawn
+            self.register_async_operation(
+                &search_id,
+                "cargo search",
+                &format!("Searching crates.io for '{}' in the background", req.query),
+                None,
+            )
+            .await;
 
-        let output = cmd.output().await.map_err(|e| {
-            ErrorData::internal_error(format!("Failed to execute cargo remove: {e}"), None)
-        })?;
+            // Spawn background task for actual search work
+            tokio::spawn(async move {
+                // Create MCP callback sender to notify the LLM client
+                let callback = mcp_callback(peer, search_id_clone.clone());
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
+                // Send started notification immediately
+                let _ = callback
+                    .send_progress(ProgressUpdate::Started {
+                        operation_id: search_id_clone.clone(),
+                        command: "cargo search".to_string(),
+                        description: format!(
+                            "Searching crates.io for '{}' in the background",
+                            req_clone.query
+                        ),
+                    })
+                    .await;
 
-        let working_dir_msg = format!(" in {}", &req.working_directory);
-        let deps_list = req.names.join(", ");
+                // Do the actual search work
+                let started_at = Instant::now();
+                let result = Self::search_implementation(&req_clone).await;
+                let result = output_store.bound_result(&search_id_clone, result);
+                // Store for wait
+                let _ = monitor
+                    .complete_operation(&search_id_clone, result.clone())
+                    .await;
 
-        let merged = merge_outputs(&stdout, &stderr, "(no remove output captured)");
-        let result_msg = if output.status.success() {
-            format!(
-                "- Remove operation #{remove_id} completed successfully{working_dir_msg}.\nRemoved dependencies: {}\nOutput: {merged}",
-                deps_list
-            )
-        } else {
-            format!(
-                "- Remove operation #{remove_id} failed{working_dir_msg}.\nDependencies: {}\nErrors: {stderr}\nOutput: {merged}",
-                deps_list
-            )
-        };
+                // Send completion notification
+                let duration_ms = started_at.elapsed().as_millis() as u64;
+                let completion_update = match result {
+                    Ok(msg) => ProgressUpdate::Completed {
+                        operation_id: search_id_clone,
+                        message: msg,
+                        duration_ms,
+                    },
+                    Err(err) => ProgressUpdate::Failed {
+                        operation_id: search_id_clone,
+                        error: err,
+                        duration_ms,
+                    },
+                };
 
-        Ok(CallToolResult::success(vec![Content::text(result_msg)]))
-    }
+                let _ = callback.send_progress(completion_update).await;
+            });
 
-    #[tool(
-        description = "CARGO UPDATE: Faster than terminal cargo. Synchronous operation - returns results immediately once cargo lock is acquired. Updates dependencies to latest compatible versions. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal."
-    )]
-    async fn update(
-        &self,
-        Parameters(req): Parameters<UpdateRequest>,
-    ) -> Result<CallToolResult, ErrorData> {
-        // Always use synchronous execution for dependency updates
-        match Self::update_implementation(&req).await {
-            Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
-            Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
+            // Return immediate response to LLM - this is the "first stage"
+            let tool_hint = self.generate_tool_hint(&search_id, "search");
+            let timestamp = timestamp::format_current_time();
+            Ok(CallToolResult::success(vec![Content::text(format!(
+                "Search operation {} started at {} in the background. Searching crates.io for '{}'.{}",
+                search_id, timestamp, req.query, tool_hint
+            ))]))
         }
     }
 
-    /// Internal implementation of update logic
-    async fn update_implementation(req: &UpdateRequest) -> Result<String, String> {
-        use tokio::process::Command;
-
-        let mut cmd = Command::new("cargo");
-        cmd.arg("update");
+    /// Internal implementation of search logic
+    async fn search_implementation(req: &SearchRequest) -> Result<String, String> {
+        let mut cmd = cargo_command();
+        cmd.arg("search").arg(&req.query);
 
-        // Set working directory
-        cmd.current_dir(&req.working_directory);
+        if let Some(limit) = req.limit {
+            cmd.args(["--limit", &limit.to_string()]);
+        }
 
-        let output = cmd
-            .output()
-            .await
-            .map_err(|e| format!("Update operation failed: Failed to execute cargo update: {e}"))?;
+        let output = cmd.output().await.map_err(|e| {
+            format!(
+                "- Search operation failed for query '{}'.\nError: Failed to execute cargo search: {}",
+                req.query, e
+            )
+        })?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
-
-        let working_dir_msg = format!(" in {}", &req.working_directory);
-        let merged = merge_outputs(&stdout, &stderr, "(no update output captured)");
+        let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("search"));
 
         if output.status.success() {
             Ok(format!(
-                "Update operation completed successfully{working_dir_msg}.\nOutput: {merged}"
+                "Search operation completed successfully.\nQuery: {}\nResults:\n{merged}",
+                req.query
             ))
         } else {
             Err(format!(
-                "- Update operation failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {merged}"
+                "- Search operation failed.\nQuery: {}\nErrors: {stderr}\nOutput: {merged}",
+                req.query
             ))
         }
     }
 
     #[tool(
-        description = "CARGO DOC: Faster than terminal cargo. Use enable_async_notification=true for large codebases to multitask. Creates LLM-friendly API reference. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+        description = "CARGO BENCH: Faster than terminal cargo. ALWAYS use enable_async_notification=true for benchmark suites to multitask. Performance testing. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
     )]
-    async fn doc(
+    async fn bench(
         &self,
-        Parameters(req): Parameters<DocRequest>,
+        Parameters(req): Parameters<BenchRequest>,
         context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        let doc_id = self.generate_operation_id_for("doc");
+        let bench_id = self.generate_operation_id_for("bench");
 
         // Check if we should run synchronously or use async notifications
         if self.should_run_synchronously(req.enable_async_notification) {
             // Synchronous operation for when async notifications are disabled
-            match Self::doc_implementation(&req).await {
+            match self.output_store.finish_result(
+                &bench_id,
+                Self::bench_implementation(&req, &self.output_store, &bench_id).await,
+                &req.condense_options(),
+            ) {
                 Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
                 Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
             }
@@ -3253,50 +6111,57 @@ impl AsyncCargo {
 
             let peer = context.peer.clone();
             let req_clone = req.clone();
-            let doc_id_clone = doc_id.clone();
+            let bench_id_clone = bench_id.clone();
             let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
 
             // Register operation before spawn
             self.register_async_operation(
-                &doc_id,
-                "cargo doc",
-                "Generating documentation in the background",
+                &bench_id,
+                "cargo bench",
+                "Running benchmarks in the background",
                 Some(req.working_directory.clone()),
             )
             .await;
 
-            // Spawn background task for actual doc generation work
+            // Spawn background task for actual bench work
             tokio::spawn(async move {
                 // Create MCP callback sender to notify the LLM client
-                let callback = mcp_callback(peer, doc_id_clone.clone());
+                let callback = mcp_callback(peer, bench_id_clone.clone());
 
                 // Send started notification immediately
                 let _ = callback
                     .send_progress(ProgressUpdate::Started {
-                        operation_id: doc_id_clone.clone(),
-                        command: "cargo doc".to_string(),
-                        description: "Generating documentation in the background".to_string(),
+                        operation_id: bench_id_clone.clone(),
+                        command: "cargo bench".to_string(),
+                        description: "Running benchmarks in the background".to_string(),
                     })
                     .await;
 
-                // Do the actual doc generation work
+                // Do the actual bench work
                 let started_at = Instant::now();
-                let result = Self::doc_implementation(&req_clone).await;
+                let result =
+                    Self::bench_implementation(&req_clone, &output_store, &bench_id_clone).await;
+                let result = output_store.finish_result(
+                    &bench_id_clone,
+                    result,
+                    &req_clone.condense_options(),
+                );
                 // Store for wait
                 let _ = monitor
-                    .complete_operation(&doc_id_clone, result.clone())
+                    .complete_operation(&bench_id_clone, result.clone())
                     .await;
 
                 // Send completion notification
                 let duration_ms = started_at.elapsed().as_millis() as u64;
                 let completion_update = match result {
                     Ok(msg) => ProgressUpdate::Completed {
-                        operation_id: doc_id_clone,
+                        operation_id: bench_id_clone,
                         message: msg,
                         duration_ms,
                     },
                     Err(err) => ProgressUpdate::Failed {
-                        operation_id: doc_id_clone,
+                        operation_id: bench_id_clone,
                         error: err,
                         duration_ms,
                     },
@@ -3306,29 +6171,39 @@ impl AsyncCargo {
             });
 
             // Return immediate response to LLM - this is the "first stage"
-            let tool_hint = self.generate_tool_hint(&doc_id, "documentation generation");
+            let tool_hint = self.generate_tool_hint(&bench_id, "benchmark");
             let timestamp = timestamp::format_current_time();
             Ok(CallToolResult::success(vec![Content::text(format!(
-                "Documentation generation {doc_id} started at {timestamp} in the background.{tool_hint}"
+                "Benchmark operation {bench_id} started at {timestamp} in the background.{tool_hint}"
             ))]))
         }
     }
 
-    // (HTML content parsing removed; doc tool reports path only)
-
-    /// Internal implementation of doc generation logic
-    async fn doc_implementation(req: &DocRequest) -> Result<String, String> {
-        use tokio::process::Command;
+    /// Internal implementation of bench logic
+    async fn bench_implementation(
+        req: &BenchRequest,
+        output_store: &OutputStore,
+        operation_id: &str,
+    ) -> Result<String, String> {
+        let mut cmd = cargo_command();
+        cmd.arg("bench");
 
-        let mut cmd = Command::new("cargo");
-        cmd.arg("doc").arg("--no-deps");
+        // Add any additional arguments
+        if let Some(args) = &req.args {
+            cmd.args(args);
+        }
 
-        // Set working directory
         cmd.current_dir(&req.working_directory);
 
-        let output = cmd.output().await.map_err(|e| {
-            format!("Documentation generation failed: Failed to execute cargo doc: {e}")
-        })?;
+        let output = output_store
+            .capture(operation_id, &mut cmd)
+            .await
+            .map_err(|e| {
+                format!(
+                    "- Benchmark operation failed in {}.\nError: Failed to execute cargo bench: {}",
+                    &req.working_directory, e
+                )
+            })?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -3336,110 +6211,87 @@ impl AsyncCargo {
         let working_dir_msg = format!(" in {}", &req.working_directory);
 
         if output.status.success() {
-            // Try to determine the crate name for the documentation path
-            let crate_name = {
-                let cargo_toml_path = format!("{}/Cargo.toml", &req.working_directory);
-                match tokio::fs::read_to_string(&cargo_toml_path).await {
-                    Ok(content) => content
-                        .lines()
-                        .find(|line| line.trim().starts_with("name"))
-                        .and_then(|line| {
-                            line.split('=')
-                                .nth(1)?
-                                .trim()
-                                .trim_matches('"')
-                                .split(' ')
-                                .next()
-                                .map(|s| s.replace('-', "_"))
-                        })
-                        .unwrap_or_else(|| "unknown_crate".to_string()),
-                    Err(_) => "unknown_crate".to_string(),
-                }
-            };
-
-            let doc_path = format!(
-                "{}/target/doc/{}/index.html",
-                &req.working_directory, crate_name
-            );
-
-            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("doc"));
+            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("bench"));
             Ok(format!(
-                "Documentation generation completed successfully{working_dir_msg}.\nDocumentation generated at: {doc_path}\nThe generated documentation provides comprehensive API information that can be used by LLMs for more accurate and up-to-date project understanding.\nTip: Use this documentation to get the latest API details, examples, and implementation notes that complement the source code.\n\nOutput: {merged}"
+                "Benchmark operation completed successfully{working_dir_msg}.\nOutput: {merged}"
             ))
         } else {
+            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("bench"));
             Err(format!(
-                "- Documentation generation failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {stdout}"
+                "- Benchmark operation failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {merged}"
             ))
         }
     }
+
     #[tool(
-        description = "CARGO CLIPPY: Faster than terminal cargo. Supports --fix via args=['--fix','--allow-dirty']. Fast operation - async optional. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+        description = "CARGO INSTALL: Faster than terminal cargo. Use enable_async_notification=true for large packages to multitask. Global tool installation. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
     )]
-    async fn clippy(
+    async fn install(
         &self,
-        Parameters(req): Parameters<ClippyRequest>,
+        Parameters(req): Parameters<InstallRequest>,
         context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        let clippy_id = self.generate_operation_id_for("clippy");
+        let install_id = self.generate_operation_id_for("install");
 
         // Check if we should run synchronously or use async notifications
         if self.should_run_synchronously(req.enable_async_notification) {
-            // Synchronous operation for when async notifications are disabled
-            match Self::clippy_implementation(&req).await {
+            match self.output_store.bound_result(
+                &install_id,
+                Self::install_implementation(&req, &self.output_store, &install_id).await,
+            ) {
                 Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
                 Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
             }
         } else {
-            // TRUE 2-STAGE ASYNC PATTERN:
-            // 1. Send immediate response that operation has started
-            // 2. Spawn background task to do actual work and send notifications
-
             let peer = context.peer.clone();
             let req_clone = req.clone();
-            let clippy_id_clone = clippy_id.clone();
+            let install_id_clone = install_id.clone();
             let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
 
             // Register operation before spawn
             self.register_async_operation(
-                &clippy_id,
-                "cargo clippy",
-                "Running linter in the background",
+                &install_id,
+                "cargo install",
+                &format!("Installing package '{}' in the background", req.package),
                 Some(req.working_directory.clone()),
             )
             .await;
 
-            // Spawn background task for actual clippy work
+            // Spawn background task for actual install work
             tokio::spawn(async move {
-                // Create MCP callback sender to notify the LLM client
-                let callback = mcp_callback(peer, clippy_id_clone.clone());
+                let callback = mcp_callback(peer, install_id_clone.clone());
 
-                // Send started notification immediately
                 let _ = callback
                     .send_progress(ProgressUpdate::Started {
-                        operation_id: clippy_id_clone.clone(),
-                        command: "cargo clippy".to_string(),
-                        description: "Running linter in the background".to_string(),
+                        operation_id: install_id_clone.clone(),
+                        command: "cargo install".to_string(),
+                        description: format!(
+                            "Installing package '{}' in the background",
+                            req_clone.package
+                        ),
                     })
                     .await;
 
-                // Do the actual clippy work
                 let started_at = Instant::now();
-                let result = Self::clippy_implementation(&req_clone).await;
-                // Store for wait
+                let result =
+                    Self::install_implementation(&req_clone, &output_store, &install_id_clone)
+                        .await;
+                let result = output_store.bound_result(&install_id_clone, result);
+
                 let _ = monitor
-                    .complete_operation(&clippy_id_clone, result.clone())
+                    .complete_operation(&install_id_clone, result.clone())
                     .await;
 
-                // Send completion notification
                 let duration_ms = started_at.elapsed().as_millis() as u64;
                 let completion_update = match result {
                     Ok(msg) => ProgressUpdate::Completed {
-                        operation_id: clippy_id_clone,
+                        operation_id: install_id_clone,
                         message: msg,
                         duration_ms,
                     },
                     Err(err) => ProgressUpdate::Failed {
-                        operation_id: clippy_id_clone,
+                        operation_id: install_id_clone,
                         error: err,
                         duration_ms,
                     },
@@ -3448,164 +6300,273 @@ impl AsyncCargo {
                 let _ = callback.send_progress(completion_update).await;
             });
 
-            // Return immediate response to LLM - this is the "first stage"
-            let tool_hint = self.generate_tool_hint(&clippy_id, "clippy linting");
+            let tool_hint = self.generate_tool_hint(&install_id, "install");
             let timestamp = timestamp::format_current_time();
             Ok(CallToolResult::success(vec![Content::text(format!(
-                "Clippy operation {clippy_id} started at {timestamp} in the background.{tool_hint}"
+                "Install operation {} started at {} in the background. Installing package '{}'.{}",
+                install_id, timestamp, req.package, tool_hint
             ))]))
         }
     }
 
-    /// Internal implementation of clippy logic
-    async fn clippy_implementation(req: &ClippyRequest) -> Result<String, String> {
-        use tokio::process::Command;
-
-        let mut cmd = Command::new("cargo");
-        cmd.arg("clippy");
+    /// Internal implementation of install logic
+    async fn install_implementation(
+        req: &InstallRequest,
+        output_store: &OutputStore,
+        operation_id: &str,
+    ) -> Result<String, String> {
+        let mut cmd = cargo_command();
+        cmd.arg("install");
 
-        // Add any additional arguments passed to clippy
-        if let Some(args) = &req.args {
-            cmd.args(args);
-        }
+        let package_spec = if let Some(version) = &req.version {
+            format!("{}@{}", req.package, version)
+        } else {
+            req.package.clone()
+        };
 
+        cmd.arg(&package_spec);
         cmd.current_dir(&req.working_directory);
 
-        let output = cmd
-            .output()
+        let output = output_store
+            .capture(operation_id, &mut cmd)
             .await
-            .map_err(|e| format!("Clippy operation failed: Failed to execute cargo clippy: {e}"))?;
+            .map_err(|e| {
+                format!(
+                    "- Install operation failed in {}.\nError: Failed to execute cargo install: {}",
+                    &req.working_directory, e
+                )
+            })?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
-
         let working_dir_msg = format!(" in {}", &req.working_directory);
+        let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("install"));
 
         if output.status.success() {
-            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("clippy"));
             Ok(format!(
-                "Clippy operation passed with no warnings{working_dir_msg}.\nOutput: {merged}",
+                "Install operation completed successfully{working_dir_msg}.\nInstalled package: {}\nOutput: {merged}",
+                req.package
             ))
         } else {
-            // Even on failure, ensure stderr also visible in Output (besides Errors) for parity with other commands
-            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("clippy"));
             Err(format!(
-                "- Clippy operation failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {merged}",
+                "- Install operation failed{working_dir_msg}.\nPackage: {}\nErrors: {stderr}\nOutput: {merged}",
+                req.package
             ))
         }
     }
 
     #[tool(
-        description = "CARGO NEXTEST: Faster than terminal cargo. Faster test runner - preferred when expecting success or checking full test suite. Use 'test' when debugging failures or need detailed error output. ALWAYS use enable_async_notification=true for test suites to multitask. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+        description = "CARGO UPGRADE: Faster than terminal cargo. Synchronous operation - returns results immediately once cargo lock is acquired. Updates dependencies to latest versions using cargo-edit. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal."
     )]
-    async fn nextest(
+    async fn upgrade(
         &self,
-        Parameters(req): Parameters<NextestRequest>,
-        context: RequestContext<RoleServer>,
+        Parameters(req): Parameters<UpgradeRequest>,
+        _context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        let nextest_id = self.generate_operation_id_for("nextest");
+        let upgrade_id = self.generate_operation_id_for("upgrade");
 
-        // First check if nextest is available
-        let nextest_check = tokio::process::Command::new("cargo")
-            .args(["nextest", "--version"])
+        // First check if cargo-edit (upgrade command) is available
+        if let Err(error_msg) = Self::check_cargo_edit_command("upgrade").await {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "- Upgrade operation #{upgrade_id} failed: {}
+Falling back to regular cargo update is recommended.",
+                error_msg
+            ))]));
+        }
+
+        // Always use synchronous execution for Cargo.toml modifications
+        match self
+            .output_store
+            .bound_result(&upgrade_id, Self::upgrade_implementation(&req).await)
+        {
+            Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
+            Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
+        }
+    }
+
+    /// Check if cargo-edit is installed by checking if a specific command is available
+    async fn check_cargo_edit_command(command: &str) -> Result<(), String> {
+        let check_result = tokio::process::Command::new("cargo")
+            .args([command, "--version"])
             .output()
             .await;
 
-        if nextest_check.is_err() || !nextest_check.unwrap().status.success() {
-            return Ok(CallToolResult::success(vec![Content::text(format!(
-                r#"- Nextest operation #{nextest_id} failed: cargo-nextest is not installed. 
-Install with: cargo install cargo-nextest
-Falling back to regular cargo test is recommended."#
-            ))]));
+        match check_result {
+            Ok(output) if output.status.success() => Ok(()),
+            Ok(_) => Err(format!(
+                "cargo-edit with {} command is not installed. Install with: cargo install cargo-edit",
+                command
+            )),
+            Err(_) => Err(format!(
+                "cargo-edit with {} command is not installed. Install with: cargo install cargo-edit",
+                command
+            )),
         }
+    }
 
-        // Check if we should run synchronously or use async notifications
-        if self.should_run_synchronously(req.enable_async_notification) {
-            // Synchronous operation for when async notifications are disabled
-            match Self::nextest_implementation(&req).await {
-                Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
-                Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
+    /// Internal implementation of upgrade logic
+    async fn upgrade_implementation(req: &UpgradeRequest) -> Result<String, String> {
+        let upgrade_id = uuid::Uuid::new_v4().to_string()[..8].to_string();
+
+        let mut cmd = cargo_command();
+        cmd.arg("upgrade");
+
+        // Add incompatible flag if requested
+        if req.incompatible.unwrap_or(false) {
+            cmd.arg("--incompatible");
+        }
+
+        // Add pinned flag if requested
+        if req.pinned.unwrap_or(false) {
+            cmd.arg("--pinned");
+        }
+
+        // Add dry run flag if requested
+        if req.dry_run.unwrap_or(false) {
+            cmd.arg("--dry-run");
+        }
+
+        // Add specific packages to upgrade
+        if let Some(packages) = &req.packages {
+            for package in packages {
+                cmd.args(["--package", package]);
             }
-        } else {
-            // TRUE 2-STAGE ASYNC PATTERN:
-            // 1. Send immediate response that operation has started
-            // 2. Spawn background task to do actual work and send notifications
+        }
 
-            let peer = context.peer.clone();
-            let req_clone = req.clone();
-            let nextest_id_clone = nextest_id.clone();
-            let monitor = self.monitor.clone();
+        // Add packages to exclude
+        if let Some(exclude) = &req.exclude {
+            for package in exclude {
+                cmd.args(["--exclude", package]);
+            }
+        }
 
-            // Register operation before spawn
-            self.register_async_operation(
-                &nextest_id,
-                "cargo nextest run",
-                "Running fast test suite in the background",
-                Some(req.working_directory.clone()),
-            )
-            .await;
+        // Add any additional arguments
+        if let Some(args) = &req.args {
+            cmd.args(args);
+        }
 
-            // Spawn background task for actual nextest work
-            tokio::spawn(async move {
-                // Create MCP callback sender to notify the LLM client
-                let callback = mcp_callback(peer, nextest_id_clone.clone());
+        cmd.current_dir(&req.working_directory);
 
-                // Send started notification immediately
-                let _ = callback
-                    .send_progress(ProgressUpdate::Started {
-                        operation_id: nextest_id_clone.clone(),
-                        command: "cargo nextest run".to_string(),
-                        description: "Running fast test suite in the background".to_string(),
-                    })
-                    .await;
+        let output = cmd
+            .output()
+            .await
+            .map_err(|e| format!("Failed to execute cargo upgrade: {e}"))?;
 
-                // Do the actual nextest work
-                let started_at = Instant::now();
-                let result = Self::nextest_implementation(&req_clone).await;
-                // Store for wait
-                let _ = monitor
-                    .complete_operation(&nextest_id_clone, result.clone())
-                    .await;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
 
-                // Send completion notification
-                let duration_ms = started_at.elapsed().as_millis() as u64;
-                let completion_update = match result {
-                    Ok(msg) => ProgressUpdate::Completed {
-                        operation_id: nextest_id_clone,
-                        message: msg,
-                        duration_ms,
-                    },
-                    Err(err) => ProgressUpdate::Failed {
-                        operation_id: nextest_id_clone,
-                        error: err,
-                        duration_ms,
-                    },
-                };
+        let working_dir_msg = format!(" in {}", &req.working_directory);
+        let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("upgrade"));
 
-                let _ = callback.send_progress(completion_update).await;
-            });
+        if output.status.success() {
+            let dry_run_msg = if req.dry_run.unwrap_or(false) {
+                " (dry run - no changes made)"
+            } else {
+                ""
+            };
+            Ok(format!(
+                "Upgrade operation #{upgrade_id} completed successfully{working_dir_msg}{dry_run_msg}.\nOutput: {merged}"
+            ))
+        } else {
+            Err(format!(
+                "- Upgrade operation #{upgrade_id} failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {merged}"
+            ))
+        }
+    }
 
-            // Return immediate response to LLM - this is the "first stage"
-            let tool_hint = self.generate_tool_hint(&nextest_id, "nextest");
-            let timestamp = timestamp::format_current_time();
-            Ok(CallToolResult::success(vec![Content::text(format!(
-                "Nextest operation {nextest_id} started at {timestamp} in the background.{tool_hint}"
-            ))]))
+    #[tool(
+        description = "CARGO BUMP-VERSION: Faster than terminal cargo. Synchronous operation - returns results immediately once cargo lock is acquired. Bumps package version using cargo-edit. Set bump_type \"auto\" to apply the minimum bump found by the latest semver_checks run. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal."
+    )]
+    async fn bump_version(
+        &self,
+        Parameters(req): Parameters<BumpVersionRequest>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let bump_id = self.generate_operation_id_for("bump-version");
+
+        // Validate bump_type before checking cargo-edit
+        if !["patch", "minor", "major", "auto"].contains(&req.bump_type.as_str()) {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "- Bump-version operation #{bump_id} failed: Invalid bump type '{}'. 
+Supported bump types: patch, minor, major, auto",
+                req.bump_type
+            ))]));
+        }
+
+        // Resolve "auto" to the bump the latest semver_checks report requires
+        let mut req = req;
+        let mut auto_msg = String::new();
+        if req.bump_type == "auto" {
+            let semver = match &req.semver_operation_id {
+                Some(operation_id) => self
+                    .diagnostics
+                    .get(operation_id)
+                    .await
+                    .and_then(|report| report.semver)
+                    .map(|semver| (operation_id.clone(), semver)),
+                None => self.diagnostics.latest_semver(&req.working_directory).await,
+            };
+            let Some((semver_id, semver)) = semver else {
+                return Ok(CallToolResult::success(vec![Content::text(format!(
+                    "- Bump-version operation #{bump_id} failed: bump_type \"auto\" needs a semver_checks report{}.
+Run semver_checks first, then bump_version again.",
+                    req.semver_operation_id
+                        .as_ref()
+                        .map_or(format!(" for {}", req.working_directory), |id| format!(
+                            " from operation {id}"
+                        ))
+                ))]));
+            };
+            let Some(level) = semver.set_version_level() else {
+                return Ok(CallToolResult::success(vec![Content::text(format!(
+                    "- Bump-version operation #{bump_id} failed: semver_checks operation {semver_id} did not determine a required bump."
+                ))]));
+            };
+            auto_msg = format!(
+                "\nBump type {level} chosen from semver_checks operation {semver_id} (minimum required: {}).",
+                semver.required_bump.map_or("unknown", |b| b.as_str())
+            );
+            req.bump_type = level.to_string();
+        }
+
+        // Check if cargo-edit (set-version command) is available
+        if let Err(error_msg) = Self::check_cargo_edit_command("set-version").await {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "- Bump-version operation #{bump_id} failed: {}
+The bump-version tool requires cargo-edit to be installed.",
+                error_msg
+            ))]));
+        }
+
+        // Always use synchronous execution for Cargo.toml modifications
+        match self
+            .output_store
+            .bound_result(&bump_id, Self::bump_version_implementation(&req).await)
+        {
+            Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(
+                result_msg + &auto_msg,
+            )])),
+            Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(
+                error_msg + &auto_msg,
+            )])),
         }
     }
 
-    /// Internal implementation of nextest logic
-    async fn nextest_implementation(req: &NextestRequest) -> Result<String, String> {
-        use tokio::process::Command;
+    /// Internal implementation of bump-version logic
+    async fn bump_version_implementation(req: &BumpVersionRequest) -> Result<String, String> {
+        let bump_id = uuid::Uuid::new_v4().to_string()[..8].to_string();
 
-        let nextest_id = uuid::Uuid::new_v4().to_string()[..8].to_string();
+        let mut cmd = cargo_command();
+        cmd.arg("set-version").args(["--bump", &req.bump_type]);
 
-        let mut cmd = Command::new("cargo");
-        cmd.args(["nextest", "run"]);
+        // Add workspace flag if requested
+        if req.workspace.unwrap_or(false) {
+            cmd.arg("--workspace");
+        }
 
-        // Add any additional arguments
-        if let Some(args) = &req.args {
-            cmd.args(args);
+        // Add dry run flag if requested
+        if req.dry_run.unwrap_or(false) {
+            cmd.arg("--dry-run");
         }
 
         cmd.current_dir(&req.working_directory);
@@ -3613,116 +6574,145 @@ Falling back to regular cargo test is recommended."#
         let output = cmd
             .output()
             .await
-            .map_err(|e| format!("Failed to execute cargo nextest: {e}"))?;
+            .map_err(|e| format!("Failed to execute cargo set-version: {e}"))?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
 
         let working_dir_msg = format!(" in {}", &req.working_directory);
+        let merged = merge_outputs(
+            &stdout,
+            &stderr,
+            &Self::no_output_placeholder("bump-version"),
+        );
 
         if output.status.success() {
-            // Many nextest summaries are emitted to stderr (progress + final report). If stdout is empty but stderr has content, treat stderr as primary.
-            let combined = if stdout.trim().is_empty() && !stderr.trim().is_empty() {
-                stderr.to_string()
-            } else if !stdout.trim().is_empty() && !stderr.trim().is_empty() {
-                format!("{stdout}\n{stderr}")
+            let dry_run_msg = if req.dry_run.unwrap_or(false) {
+                " (dry run - no changes made)"
             } else {
-                stdout.to_string()
+                ""
             };
-            let final_output = if combined.trim().is_empty() {
-                Self::no_output_placeholder("nextest")
+            let workspace_msg = if req.workspace.unwrap_or(false) {
+                " (workspace-wide)"
             } else {
-                combined
+                ""
             };
             Ok(format!(
-                "Nextest operation #{nextest_id} completed successfully{working_dir_msg}.\nOutput: {final_output}"
+                "Bump-version operation #{bump_id} completed successfully{working_dir_msg}{workspace_msg}{dry_run_msg}.\nOutput: {merged}"
             ))
         } else {
-            // Failure: include full stderr + stdout (stderr first for clarity)
-            let mut err_block = String::new();
-            if !stderr.trim().is_empty() {
-                err_block.push_str(&stderr);
-            }
-            if !stdout.trim().is_empty() {
-                if !err_block.is_empty() {
-                    err_block.push_str("\n--- stdout ---\n");
-                }
-                err_block.push_str(&stdout);
-            }
             Err(format!(
-                "- Nextest operation #{nextest_id} failed{working_dir_msg}.\nErrors: {err_block}"
+                "- Bump-version operation #{bump_id} failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {merged}"
             ))
         }
     }
 
     #[tool(
-        description = "CARGO CLEAN: Faster than terminal cargo. Fast operation - async not needed. Frees disk space. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+        description = "CARGO UNUSED-DEPS: Find dependencies that are declared but never used, per package with their section (normal, dev, build), via cargo-machete (default; fast) or cargo-udeps (tool \"udeps\"; accurate, needs nightly). Set fix=true to remove them with cargo remove and run check to confirm the build still compiles. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
     )]
-    async fn clean(
+    async fn unused_deps(
         &self,
-        Parameters(req): Parameters<CleanRequest>,
+        Parameters(req): Parameters<UnusedDepsRequest>,
         context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        let clean_id = self.generate_operation_id_for("clean");
+        use crate::unused_deps::UnusedDepsTool;
+        self.ensure_enabled("unused_deps")?;
+        let unused_id = self.generate_operation_id_for("unused_deps");
+
+        // First check if the selected detector is available
+        let tool = req.tool.unwrap_or_default();
+        let version_args: &[&str] = match tool {
+            UnusedDepsTool::Machete => &["machete", "--version"],
+            UnusedDepsTool::Udeps => &["+nightly", "udeps", "--version"],
+        };
+        let tool_check = tokio::process::Command::new("cargo")
+            .args(version_args)
+            .output()
+            .await;
+
+        if tool_check.is_err() || !tool_check.unwrap().status.success() {
+            let install = match tool {
+                UnusedDepsTool::Machete => "cargo install cargo-machete",
+                UnusedDepsTool::Udeps => {
+                    "cargo install cargo-udeps (and rustup toolchain install nightly)"
+                }
+            };
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "- Unused-deps operation #{unused_id} failed: {} is not installed.
+Install with: {install}
+This tool finds dependencies that are declared but never used.",
+                tool.as_str()
+            ))]));
+        }
 
-        // Check if we should run synchronously or use async notifications
         if self.should_run_synchronously(req.enable_async_notification) {
-            // Synchronous operation for when async notifications are disabled
-            match Self::clean_implementation(&req).await {
+            let response = match self.output_store.bound_result(
+                &unused_id,
+                Self::unused_deps_implementation(
+                    &req,
+                    &self.diagnostics,
+                    &self.output_store,
+                    &unused_id,
+                )
+                .await,
+            ) {
                 Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
                 Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
-            }
+            };
+            self.with_diagnostics(&unused_id, response).await
         } else {
-            // TRUE 2-STAGE ASYNC PATTERN:
-            // 1. Send immediate response that operation has started
-            // 2. Spawn background task to do actual work and send notifications
-
             let peer = context.peer.clone();
             let req_clone = req.clone();
-            let clean_id_clone = clean_id.clone();
+            let unused_id_clone = unused_id.clone();
             let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
+            let diagnostics = self.diagnostics.clone();
+            let command = format!(
+                "cargo {}",
+                &version_args[..version_args.len() - 1].join(" ")
+            );
 
-            // Register operation before spawn
             self.register_async_operation(
-                &clean_id,
-                "cargo clean",
-                "Cleaning build artifacts in the background",
+                &unused_id,
+                &command,
+                "Finding unused dependencies in the background",
                 Some(req.working_directory.clone()),
             )
             .await;
 
-            // Spawn background task for actual clean work
             tokio::spawn(async move {
-                // Create MCP callback sender to notify the LLM client
-                let callback = mcp_callback(peer, clean_id_clone.clone());
+                let callback = mcp_callback(peer, unused_id_clone.clone());
 
-                // Send started notification immediately
                 let _ = callback
                     .send_progress(ProgressUpdate::Started {
-                        operation_id: clean_id_clone.clone(),
-                        command: "cargo clean".to_string(),
-                        description: "Cleaning build artifacts in the background".to_string(),
+                        operation_id: unused_id_clone.clone(),
+                        command,
+                        description: "Finding unused dependencies in the background".to_string(),
                     })
                     .await;
 
-                // Do the actual clean work
                 let started_at = Instant::now();
-                let result = Self::clean_implementation(&req_clone).await;
-                // Store for wait
+                let result = Self::unused_deps_implementation(
+                    &req_clone,
+                    &diagnostics,
+                    &output_store,
+                    &unused_id_clone,
+                )
+                .await;
+                let result = output_store.bound_result(&unused_id_clone, result);
                 let _ = monitor
-                    .complete_operation(&clean_id_clone, result.clone())
+                    .complete_operation(&unused_id_clone, result.clone())
                     .await;
 
-                // Send completion notification
                 let duration_ms = started_at.elapsed().as_millis() as u64;
                 let completion_update = match result {
                     Ok(msg) => ProgressUpdate::Completed {
-                        operation_id: clean_id_clone,
+                        operation_id: unused_id_clone,
                         message: msg,
                         duration_ms,
                     },
                     Err(err) => ProgressUpdate::Failed {
-                        operation_id: clean_id_clone,
+                        operation_id: unused_id_clone,
                         error: err,
                         duration_ms,
                     },
@@ -3731,117 +6721,268 @@ Falling back to regular cargo test is recommended."#
                 let _ = callback.send_progress(completion_update).await;
             });
 
-            // Return immediate response to LLM - this is the "first stage"
-            let tool_hint = self.generate_tool_hint(&clean_id, "clean");
+            let tool_hint = self.generate_tool_hint(&unused_id, "unused_deps");
             let timestamp = timestamp::format_current_time();
             Ok(CallToolResult::success(vec![Content::text(format!(
-                "Clean operation {clean_id} started at {timestamp} in the background.{tool_hint}"
+                "Unused-deps operation {unused_id} started at {timestamp} in the background.{tool_hint}"
             ))]))
         }
     }
 
-    /// Internal implementation of clean logic
-    async fn clean_implementation(req: &CleanRequest) -> Result<String, String> {
-        use tokio::process::Command;
-
-        let mut cmd = Command::new("cargo");
-        cmd.arg("clean");
-
+    /// Run cargo-machete or cargo-udeps, look up each finding's section in `cargo metadata`,
+    /// and with `fix` remove them and run check
+    async fn unused_deps_implementation(
+        req: &UnusedDepsRequest,
+        diagnostics: &DiagnosticsStore,
+        output_store: &OutputStore,
+        operation_id: &str,
+    ) -> Result<String, String> {
+        use crate::unused_deps::{
+            DependencyKind, UnusedDepsReport, UnusedDepsTool, parse_machete_output,
+            parse_udeps_json, resolve_sections,
+        };
+        let tool = req.tool.unwrap_or_default();
+        let mut cmd = cargo_command();
+        match tool {
+            UnusedDepsTool::Machete => {
+                cmd.arg("machete");
+            }
+            UnusedDepsTool::Udeps => {
+                cmd.args(["+nightly", "udeps", "--output", "json", "--workspace"]);
+                cmd.arg("--all-targets");
+            }
+        }
+        if let Some(args) = &req.args {
+            cmd.args(args);
+        }
         cmd.current_dir(&req.working_directory);
 
-        let output = cmd.output().await.map_err(|e| {
-            format!(
-                "- Clean operation failed in {}.\nError: Failed to execute cargo clean: {}",
-                &req.working_directory, e
-            )
-        })?;
+        let output = output_store
+            .capture(operation_id, &mut cmd)
+            .await
+            .map_err(|e| format!("Failed to execute {}: {e}", tool.as_str()))?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
-
         let working_dir_msg = format!(" in {}", &req.working_directory);
 
-        if output.status.success() {
-            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("clean"));
-            Ok(format!(
-                "Clean operation completed successfully{working_dir_msg}.\nOutput: {merged}"
-            ))
-        } else {
-            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("clean"));
-            Err(format!(
-                "- Clean operation failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {merged}"
-            ))
+        // Both detectors exit nonzero when they find unused dependencies
+        let findings = match tool {
+            UnusedDepsTool::Machete => Some(parse_machete_output(&stdout))
+                .filter(|findings| output.status.success() || !findings.is_empty()),
+            UnusedDepsTool::Udeps => parse_udeps_json(&stdout),
+        };
+        let Some(findings) = findings else {
+            let merged = merge_outputs(
+                &stdout,
+                &stderr,
+                &Self::no_output_placeholder("unused-deps"),
+            );
+            return Err(format!(
+                "- Unused-deps operation #{operation_id} failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {merged}"
+            ));
+        };
+
+        let metadata = cargo_command()
+            .args(["metadata", "--no-deps", "--format-version", "1"])
+            .current_dir(&req.working_directory)
+            .output()
+            .await
+            .ok()
+            .and_then(|output| serde_json::from_slice::<serde_json::Value>(&output.stdout).ok());
+        let report = UnusedDepsReport {
+            tool,
+            dependencies: resolve_sections(findings, metadata.as_ref()),
+        };
+        let rendered = report.render();
+
+        if !req.fix.unwrap_or(false) || report.dependencies.is_empty() {
+            diagnostics.record_unused_deps(operation_id, report).await;
+            return Ok(format!(
+                "+ Unused-deps operation #{operation_id} completed successfully{working_dir_msg}.\n{rendered}"
+            ));
+        }
+
+        // One cargo remove per manifest and section; cargo remove cannot select a target table
+        // of dev or build dependencies, so those are left for the agent
+        let mut groups: Vec<(String, Option<DependencySection>, Vec<String>)> = Vec::new();
+        let mut skipped = Vec::new();
+        for dep in &report.dependencies {
+            let section = match (dep.kind, &dep.target) {
+                (DependencyKind::Normal, None) => None,
+                (DependencyKind::Normal, Some(target)) => {
+                    Some(DependencySection::Target(target.clone()))
+                }
+                (DependencyKind::Dev, None) => Some(DependencySection::Dev),
+                (DependencyKind::Build, None) => Some(DependencySection::Build),
+                (_, Some(target)) => {
+                    skipped.push(format!(
+                        "{} ({} dependency for target {target} in {})",
+                        dep.name,
+                        dep.kind.as_str(),
+                        dep.manifest_path
+                    ));
+                    continue;
+                }
+            };
+            let directory = Path::new(&dep.manifest_path).parent().map_or_else(
+                || req.working_directory.clone(),
+                |p| p.display().to_string(),
+            );
+            match groups
+                .iter_mut()
+                .find(|(d, s, _)| *d == directory && *s == section)
+            {
+                Some((_, _, names)) => names.push(dep.name.clone()),
+                None => groups.push((directory, section, vec![dep.name.clone()])),
+            }
+        }
+
+        let mut removals = Vec::new();
+        let mut removal_failed = false;
+        for (directory, section, names) in groups {
+            let remove_req = RemoveDependencyRequest {
+                name: None,
+                names,
+                section,
+                working_directory: directory,
+                enable_async_notification: None,
+            };
+            match Self::remove_implementation(&remove_req, operation_id).await {
+                Ok(msg) => removals.push(msg),
+                Err(msg) => {
+                    removal_failed = true;
+                    removals.push(msg);
+                }
+            }
+        }
+        let mut text = format!("{rendered}\n{}", removals.join("\n"));
+        if !skipped.is_empty() {
+            text.push_str(&format!(
+                "\nNot removed (remove by hand): {}",
+                skipped.join(", ")
+            ));
+        }
+
+        // Confirm the build still compiles; check records its diagnostics under this operation,
+        // so the unused dependency report is attached afterwards
+        let check_req = CheckRequest {
+            working_directory: req.working_directory.clone(),
+            ..Default::default()
+        };
+        let check =
+            Self::check_implementation(&check_req, None, diagnostics, output_store, operation_id)
+                .await;
+        diagnostics.record_unused_deps(operation_id, report).await;
+        match check {
+            Ok(check_msg) if !removal_failed => Ok(format!(
+                "+ Unused-deps operation #{operation_id} removed the unused dependencies{working_dir_msg} and check passed.\n{text}\nCheck: {check_msg}"
+            )),
+            Ok(check_msg) => Err(format!(
+                "- Unused-deps operation #{operation_id} could not remove every unused dependency{working_dir_msg}.\n{text}\nCheck: {check_msg}"
+            )),
+            Err(check_msg) => Err(format!(
+                "- Unused-deps operation #{operation_id} removed dependencies{working_dir_msg} but check failed; a removed dependency may still be used (e.g. only inside a macro), add it back with add.\n{text}\nCheck: {check_msg}"
+            )),
         }
     }
 
     #[tool(
-        description = "CARGO FIX: Faster than terminal cargo. Automatically fix compiler warnings. Supports --allow-dirty via args. Use async for large codebases. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+        description = "CARGO SEMVER-CHECKS: Compare the public API against a baseline (baseline_rev git revision, baseline_path local checkout, or baseline_version from the registry; default: the latest published version) via cargo-semver-checks. Returns the breaking changes found, each with its lint, the affected items and a semver reference, and the minimum required bump (patch, minor, major). Run before bump_version, then use bump_type \"auto\". Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
     )]
-    async fn fix(
+    async fn semver_checks(
         &self,
-        Parameters(req): Parameters<FixRequest>,
+        Parameters(req): Parameters<SemverChecksRequest>,
         context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        let fix_id = self.generate_operation_id_for("fix");
+        self.ensure_enabled("semver_checks")?;
+        let semver_id = self.generate_operation_id_for("semver_checks");
+
+        let baselines = [&req.baseline_rev, &req.baseline_path, &req.baseline_version];
+        if baselines.iter().filter(|b| b.is_some()).count() > 1 {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "- Semver-checks operation #{semver_id} failed: set only one of baseline_rev, baseline_path and baseline_version."
+            ))]));
+        }
+
+        // First check if cargo-semver-checks is available
+        let semver_check = tokio::process::Command::new("cargo")
+            .args(["semver-checks", "--version"])
+            .output()
+            .await;
+
+        if semver_check.is_err() || !semver_check.unwrap().status.success() {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "- Semver-checks operation #{semver_id} failed: cargo-semver-checks is not installed.
+Install with: cargo install cargo-semver-checks
+This tool finds breaking API changes and the version bump they require."
+            ))]));
+        }
 
-        // Check if we should run synchronously or use async notifications
         if self.should_run_synchronously(req.enable_async_notification) {
-            // Synchronous operation for when async notifications are disabled
-            match Self::fix_implementation(&req).await {
+            let response = match self.output_store.bound_result(
+                &semver_id,
+                Self::semver_checks_implementation(
+                    &req,
+                    &self.diagnostics,
+                    &self.output_store,
+                    &semver_id,
+                )
+                .await,
+            ) {
                 Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
                 Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
-            }
+            };
+            self.with_diagnostics(&semver_id, response).await
         } else {
-            // TRUE 2-STAGE ASYNC PATTERN:
-            // 1. Send immediate response that operation has started
-            // 2. Spawn background task to do actual work and send notifications
-
             let peer = context.peer.clone();
             let req_clone = req.clone();
-            let fix_id_clone = fix_id.clone();
+            let semver_id_clone = semver_id.clone();
             let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
+            let diagnostics = self.diagnostics.clone();
 
-            // Register operation before spawn
             self.register_async_operation(
-                &fix_id,
-                "cargo fix",
-                "Fixing compiler warnings in the background",
+                &semver_id,
+                "cargo semver-checks",
+                "Checking API compatibility in the background",
                 Some(req.working_directory.clone()),
             )
             .await;
 
-            // Spawn background task for actual fix work
             tokio::spawn(async move {
-                // Create MCP callback sender to notify the LLM client
-                let callback = mcp_callback(peer, fix_id_clone.clone());
+                let callback = mcp_callback(peer, semver_id_clone.clone());
 
-                // Send started notification immediately
                 let _ = callback
                     .send_progress(ProgressUpdate::Started {
-                        operation_id: fix_id_clone.clone(),
-                        command: "cargo fix".to_string(),
-                        description: "Fixing compiler warnings in the background".to_string(),
+                        operation_id: semver_id_clone.clone(),
+                        command: "cargo semver-checks".to_string(),
+                        description: "Checking API compatibility in the background".to_string(),
                     })
                     .await;
 
-                // Do the actual fix work
                 let started_at = Instant::now();
-                let result = Self::fix_implementation(&req_clone).await;
-                // Store for wait
+                let result = Self::semver_checks_implementation(
+                    &req_clone,
+                    &diagnostics,
+                    &output_store,
+                    &semver_id_clone,
+                )
+                .await;
+                let result = output_store.bound_result(&semver_id_clone, result);
                 let _ = monitor
-                    .complete_operation(&fix_id_clone, result.clone())
+                    .complete_operation(&semver_id_clone, result.clone())
                     .await;
 
-                // Send completion notification
                 let duration_ms = started_at.elapsed().as_millis() as u64;
                 let completion_update = match result {
                     Ok(msg) => ProgressUpdate::Completed {
-                        operation_id: fix_id_clone,
+                        operation_id: semver_id_clone,
                         message: msg,
                         duration_ms,
                     },
                     Err(err) => ProgressUpdate::Failed {
-                        operation_id: fix_id_clone,
+                        operation_id: semver_id_clone,
                         error: err,
                         duration_ms,
                     },
@@ -3850,74 +6991,130 @@ Falling back to regular cargo test is recommended."#
                 let _ = callback.send_progress(completion_update).await;
             });
 
-            // Return immediate response to LLM - this is the "first stage"
-            let tool_hint = self.generate_tool_hint(&fix_id, "fix");
+            let tool_hint = self.generate_tool_hint(&semver_id, "semver_checks");
             let timestamp = timestamp::format_current_time();
             Ok(CallToolResult::success(vec![Content::text(format!(
-                "Fix operation {fix_id} started at {timestamp} in the background.{tool_hint}"
+                "Semver-checks operation {semver_id} started at {timestamp} in the background.{tool_hint}"
             ))]))
         }
     }
 
-    /// Internal implementation of fix logic
-    async fn fix_implementation(req: &FixRequest) -> Result<String, String> {
-        use tokio::process::Command;
-
-        let mut cmd = Command::new("cargo");
-        cmd.arg("fix");
-
-        // Add any additional arguments
-        if let Some(args) = &req.args {
-            cmd.args(args);
-        } else {
-            // Provide safe defaults to allow running in temp test project without VCS
-            cmd.arg("--allow-dirty").arg("--allow-no-vcs");
+    /// Run `cargo semver-checks` against the requested baseline and record the parsed report
+    async fn semver_checks_implementation(
+        req: &SemverChecksRequest,
+        diagnostics: &DiagnosticsStore,
+        output_store: &OutputStore,
+        operation_id: &str,
+    ) -> Result<String, String> {
+        let mut cmd = cargo_command();
+        cmd.args(["semver-checks", "--color", "never"]);
+        if let Some(rev) = &req.baseline_rev {
+            cmd.args(["--baseline-rev", rev]);
         }
-
-        cmd.current_dir(&req.working_directory);
-
-        let output = cmd.output().await.map_err(|e| {
-            format!(
-                "- Fix operation failed in {}.\nError: Failed to execute cargo fix: {}",
-                &req.working_directory, e
-            )
-        })?;
+        if let Some(path) = &req.baseline_path {
+            cmd.args(["--baseline-root", path]);
+        }
+        if let Some(version) = &req.baseline_version {
+            cmd.args(["--baseline-version", version]);
+        }
+        if let Some(packages) = &req.package {
+            for package in packages {
+                cmd.args(["--package", package]);
+            }
+        }
+        if req.workspace.unwrap_or(false) {
+            cmd.arg("--workspace");
+        }
+        if let Some(features) = &req.features {
+            cmd.args(["--features", &features.join(",")]);
+        }
+        if req.all_features.unwrap_or(false) {
+            cmd.arg("--all-features");
+        }
+        if let Some(args) = &req.args {
+            cmd.args(args);
+        }
+        cmd.current_dir(&req.working_directory);
+
+        let output = output_store
+            .capture(operation_id, &mut cmd)
+            .await
+            .map_err(|e| format!("Failed to execute cargo semver-checks: {e}"))?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
-
         let working_dir_msg = format!(" in {}", &req.working_directory);
 
-        if output.status.success() {
-            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("fix"));
-            Ok(format!(
-                "Fix operation completed successfully{working_dir_msg}.\nOutput: {merged}"
-            ))
-        } else {
-            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("fix"));
-            Err(format!(
-                "- Fix operation failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {merged}"
-            ))
+        // Progress and summaries go to stderr, lint reports to stdout
+        let text = format!("{stderr}\n{stdout}");
+        match crate::semver::parse_semver_output(&text, &req.working_directory) {
+            Some(report) => {
+                let rendered = report.render();
+                diagnostics.record_semver(operation_id, report).await;
+                if output.status.success() {
+                    Ok(format!(
+                        "+ Semver-checks operation #{operation_id} completed successfully{working_dir_msg}.\n{rendered}"
+                    ))
+                } else {
+                    Err(format!(
+                        "- Semver-checks operation #{operation_id} found changes the current version does not allow{working_dir_msg}.\n{rendered}"
+                    ))
+                }
+            }
+            None => {
+                let merged = merge_outputs(
+                    &stdout,
+                    &stderr,
+                    &Self::no_output_placeholder("semver-checks"),
+                );
+                if output.status.success() {
+                    Ok(format!(
+                        "+ Semver-checks operation #{operation_id} completed successfully{working_dir_msg}.\nOutput: {merged}"
+                    ))
+                } else {
+                    Err(format!(
+                        "- Semver-checks operation #{operation_id} failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {merged}"
+                    ))
+                }
+            }
         }
     }
 
     #[tool(
-        description = "CARGO SEARCH: Faster than terminal cargo. Search for crates on crates.io. Fast operation - async not needed unless searching many terms. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+        description = "CARGO AUDIT: Faster than terminal cargo. Security vulnerability scanning. Use enable_async_notification=true for large projects to multitask. Identifies known security vulnerabilities. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
     )]
-    async fn search(
+    async fn audit(
         &self,
-        Parameters(req): Parameters<SearchRequest>,
+        Parameters(req): Parameters<AuditRequest>,
         context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        let search_id = self.generate_operation_id_for("search");
+        let audit_id = self.generate_operation_id_for("audit");
+
+        // First check if cargo-audit is available
+        let audit_check = tokio::process::Command::new("cargo")
+            .args(["audit", "--version"])
+            .output()
+            .await;
+
+        if audit_check.is_err() || !audit_check.unwrap().status.success() {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "- Audit operation #{audit_id} failed: cargo-audit is not installed. 
+Install with: cargo install cargo-audit
+This tool scans for known security vulnerabilities in dependencies."
+            ))]));
+        }
 
         // Check if we should run synchronously or use async notifications
         if self.should_run_synchronously(req.enable_async_notification) {
             // Synchronous operation for when async notifications are disabled
-            match Self::search_implementation(&req).await {
+            let response = match self.output_store.bound_result(
+                &audit_id,
+                Self::audit_implementation(&req, &self.diagnostics, &audit_id).await,
+            ) {
                 Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
                 Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
-            }
+            };
+            self.with_diagnostics(&audit_id, response).await
         } else {
             // TRUE 2-STAGE ASYNC PATTERN:
             // 1. Send immediate response that operation has started
@@ -3925,53 +7122,55 @@ Falling back to regular cargo test is recommended."#
 
             let peer = context.peer.clone();
             let req_clone = req.clone();
-            let search_id_clone = search_id.clone();
+            let audit_id_clone = audit_id.clone();
             let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
+            let diagnostics = self.diagnostics.clone();
 
             // Register operation before spawn
             self.register_async_operation(
-                &search_id,
-                "cargo search",
-                &format!("Searching crates.io for '{}' in the background", req.query),
-                None,
+                &audit_id,
+                "cargo audit",
+                "Scanning for security vulnerabilities in the background",
+                Some(req.working_directory.clone()),
             )
             .await;
 
-            // Spawn background task for actual search work
+            // Spawn background task for actual audit work
             tokio::spawn(async move {
                 // Create MCP callback sender to notify the LLM client
-                let callback = mcp_callback(peer, search_id_clone.clone());
+                let callback = mcp_callback(peer, audit_id_clone.clone());
 
                 // Send started notification immediately
                 let _ = callback
                     .send_progress(ProgressUpdate::Started {
-                        operation_id: search_id_clone.clone(),
-                        command: "cargo search".to_string(),
-                        description: format!(
-                            "Searching crates.io for '{}' in the background",
-                            req_clone.query
-                        ),
+                        operation_id: audit_id_clone.clone(),
+                        command: "cargo audit".to_string(),
+                        description: "Scanning for security vulnerabilities in the background"
+                            .to_string(),
                     })
                     .await;
 
-                // Do the actual search work
+                // Do the actual audit work
                 let started_at = Instant::now();
-                let result = Self::search_implementation(&req_clone).await;
+                let result =
+                    Self::audit_implementation(&req_clone, &diagnostics, &audit_id_clone).await;
+                let result = output_store.bound_result(&audit_id_clone, result);
                 // Store for wait
                 let _ = monitor
-                    .complete_operation(&search_id_clone, result.clone())
+                    .complete_operation(&audit_id_clone, result.clone())
                     .await;
 
                 // Send completion notification
                 let duration_ms = started_at.elapsed().as_millis() as u64;
                 let completion_update = match result {
                     Ok(msg) => ProgressUpdate::Completed {
-                        operation_id: search_id_clone,
+                        operation_id: audit_id_clone,
                         message: msg,
                         duration_ms,
                     },
                     Err(err) => ProgressUpdate::Failed {
-                        operation_id: search_id_clone,
+                        operation_id: audit_id_clone,
                         error: err,
                         duration_ms,
                     },
@@ -3981,118 +7180,215 @@ Falling back to regular cargo test is recommended."#
             });
 
             // Return immediate response to LLM - this is the "first stage"
-            let tool_hint = self.generate_tool_hint(&search_id, "search");
+            let tool_hint = self.generate_tool_hint(&audit_id, "audit");
             let timestamp = timestamp::format_current_time();
             Ok(CallToolResult::success(vec![Content::text(format!(
-                "Search operation {} started at {} in the background. Searching crates.io for '{}'.{}",
-                search_id, timestamp, req.query, tool_hint
+                "Audit operation {audit_id} started at {timestamp} in the background.{tool_hint}"
             ))]))
         }
     }
 
-    /// Internal implementation of search logic
-    async fn search_implementation(req: &SearchRequest) -> Result<String, String> {
-        use tokio::process::Command;
+    /// Internal implementation of audit logic
+    async fn audit_implementation(
+        req: &AuditRequest,
+        diagnostics: &DiagnosticsStore,
+        operation_id: &str,
+    ) -> Result<String, String> {
+        let audit_id = uuid::Uuid::new_v4().to_string()[..8].to_string();
 
-        let mut cmd = Command::new("cargo");
-        cmd.arg("search").arg(&req.query);
+        let mut cmd = cargo_command();
+        cmd.arg("audit");
 
-        if let Some(limit) = req.limit {
-            cmd.args(["--limit", &limit.to_string()]);
+        // Parse the JSON report unless another format was asked for
+        let structured = matches!(req.format.as_deref(), None | Some("json"));
+        if structured {
+            cmd.arg("--json");
+        } else if let Some(format) = &req.format {
+            cmd.args(["--format", format]);
         }
 
-        let output = cmd.output().await.map_err(|e| {
-            format!(
-                "- Search operation failed for query '{}'.\nError: Failed to execute cargo search: {}",
-                req.query, e
-            )
-        })?;
+        // Add vulnerabilities-only flag if requested
+        if req.vulnerabilities_only.unwrap_or(false) {
+            cmd.arg("--vulnerabilities");
+        }
+
+        // Add deny warnings flag if requested
+        if req.deny_warnings.unwrap_or(false) {
+            cmd.arg("--deny-warnings");
+        }
+
+        // Add any additional arguments
+        if let Some(args) = &req.args {
+            cmd.args(args);
+        }
+
+        cmd.current_dir(&req.working_directory);
+
+        let output = cmd
+            .output()
+            .await
+            .map_err(|e| format!("Failed to execute cargo audit: {e}"))?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
-        let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("search"));
+
+        let working_dir_msg = format!(" in {}", &req.working_directory);
+
+        if structured && let Ok(mut report) = crate::audit::parse_audit_json(&stdout) {
+            // Filter first so cargo tree only runs for advisories that will be shown
+            if let Some(min_severity) = req.min_severity {
+                report.filter_min_severity(min_severity);
+            }
+            for advisory in &mut report.advisories {
+                advisory.dependency_path = Self::audit_dependency_path(
+                    &req.working_directory,
+                    &advisory.package,
+                    &advisory.version,
+                )
+                .await;
+                advisory.suggestion = crate::audit::upgrade_suggestion(advisory);
+            }
+            let rendered = report.render();
+            let failed = report.vulnerabilities > 0
+                || (req.deny_warnings.unwrap_or(false) && report.warnings > 0);
+            diagnostics.record_audit(operation_id, report).await;
+            return if failed {
+                Err(format!(
+                    "Audit operation #{audit_id} found security vulnerabilities{working_dir_msg}.\n{rendered}"
+                ))
+            } else {
+                Ok(format!(
+                    "Audit operation #{audit_id} completed successfully{working_dir_msg}.\nNo known vulnerabilities found.\n{rendered}"
+                ))
+            };
+        }
+
+        let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("audit"));
 
         if output.status.success() {
             Ok(format!(
-                "Search operation completed successfully.\nQuery: {}\nResults:\n{merged}",
-                req.query
+                "Audit operation #{audit_id} completed successfully{working_dir_msg}.\nNo known vulnerabilities found.\nOutput: {merged}"
             ))
         } else {
-            Err(format!(
-                "- Search operation failed.\nQuery: {}\nErrors: {stderr}\nOutput: {merged}",
-                req.query
-            ))
+            // Check if it's a vulnerability warning (exit code 1) vs actual error
+            let vulnerability_detected = output.status.code() == Some(1) && !stdout.is_empty();
+
+            if vulnerability_detected {
+                Err(format!(
+                    "Audit operation #{audit_id} found security vulnerabilities{working_dir_msg}.\nVulnerabilities detected:\n{stdout}\nErrors: {stderr}\nOutput: {merged}"
+                ))
+            } else {
+                Err(format!(
+                    "- Audit operation #{audit_id} failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {merged}"
+                ))
+            }
+        }
+    }
+
+    /// How a workspace member depends on `package@version`, from `cargo tree --invert`
+    async fn audit_dependency_path(
+        working_directory: &str,
+        package: &str,
+        version: &str,
+    ) -> Vec<String> {
+        let output = cargo_command()
+            .args(["tree", "--invert"])
+            .arg(format!("{package}@{version}"))
+            .args(["--prefix", "depth", "--target", "all", "--offline"])
+            .current_dir(working_directory)
+            .output()
+            .await;
+        match output {
+            Ok(output) if output.status.success() => {
+                crate::audit::parse_inverted_tree(&String::from_utf8_lossy(&output.stdout))
+            }
+            _ => Vec::new(),
         }
     }
 
     #[tool(
-        description = "CARGO BENCH: Faster than terminal cargo. ALWAYS use enable_async_notification=true for benchmark suites to multitask. Performance testing. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+        description = "CARGO DENY: Check dependencies against the license, ban, source and advisory policy in deny.toml via cargo-deny. Select checks (advisories, bans, licenses, sources); violations come back structured with the offending crate and the dependency path that pulls it in. Set init=true to scaffold a deny.toml. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
     )]
-    async fn bench(
+    async fn deny(
         &self,
-        Parameters(req): Parameters<BenchRequest>,
+        Parameters(req): Parameters<DenyRequest>,
         context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        let bench_id = self.generate_operation_id_for("bench");
+        self.ensure_enabled("deny")?;
+        let deny_id = self.generate_operation_id_for("deny");
+
+        // First check if cargo-deny is available
+        let deny_check = tokio::process::Command::new("cargo")
+            .args(["deny", "--version"])
+            .output()
+            .await;
+
+        if deny_check.is_err() || !deny_check.unwrap().status.success() {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "- Deny operation #{deny_id} failed: cargo-deny is not installed.
+Install with: cargo install cargo-deny
+This tool enforces license, ban, source and advisory policies on dependencies."
+            ))]));
+        }
+
+        if req.init.unwrap_or(false) {
+            let result = Self::deny_init(&req.working_directory, &deny_id).await;
+            return Self::handle_sync_result("deny", "cargo deny init", "Deny init", result);
+        }
 
-        // Check if we should run synchronously or use async notifications
         if self.should_run_synchronously(req.enable_async_notification) {
-            // Synchronous operation for when async notifications are disabled
-            match Self::bench_implementation(&req).await {
+            let response = match self.output_store.bound_result(
+                &deny_id,
+                Self::deny_implementation(&req, &self.diagnostics, &deny_id).await,
+            ) {
                 Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
                 Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
-            }
+            };
+            self.with_diagnostics(&deny_id, response).await
         } else {
-            // TRUE 2-STAGE ASYNC PATTERN:
-            // 1. Send immediate response that operation has started
-            // 2. Spawn background task to do actual work and send notifications
-
             let peer = context.peer.clone();
             let req_clone = req.clone();
-            let bench_id_clone = bench_id.clone();
+            let deny_id_clone = deny_id.clone();
             let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
+            let diagnostics = self.diagnostics.clone();
 
-            // Register operation before spawn
             self.register_async_operation(
-                &bench_id,
-                "cargo bench",
-                "Running benchmarks in the background",
+                &deny_id,
+                "cargo deny check",
+                "Checking dependency policies in the background",
                 Some(req.working_directory.clone()),
             )
             .await;
 
-            // Spawn background task for actual bench work
             tokio::spawn(async move {
-                // Create MCP callback sender to notify the LLM client
-                let callback = mcp_callback(peer, bench_id_clone.clone());
+                let callback = mcp_callback(peer, deny_id_clone.clone());
 
-                // Send started notification immediately
                 let _ = callback
                     .send_progress(ProgressUpdate::Started {
-                        operation_id: bench_id_clone.clone(),
-                        command: "cargo bench".to_string(),
-                        description: "Running benchmarks in the background".to_string(),
+                        operation_id: deny_id_clone.clone(),
+                        command: "cargo deny check".to_string(),
+                        description: "Checking dependency policies in the background".to_string(),
                     })
                     .await;
 
-                // Do the actual bench work
                 let started_at = Instant::now();
-                let result = Self::bench_implementation(&req_clone).await;
-                // Store for wait
+                let result =
+                    Self::deny_implementation(&req_clone, &diagnostics, &deny_id_clone).await;
+                let result = output_store.bound_result(&deny_id_clone, result);
                 let _ = monitor
-                    .complete_operation(&bench_id_clone, result.clone())
+                    .complete_operation(&deny_id_clone, result.clone())
                     .await;
 
-                // Send completion notification
                 let duration_ms = started_at.elapsed().as_millis() as u64;
                 let completion_update = match result {
                     Ok(msg) => ProgressUpdate::Completed {
-                        operation_id: bench_id_clone,
+                        operation_id: deny_id_clone,
                         message: msg,
                         duration_ms,
                     },
                     Err(err) => ProgressUpdate::Failed {
-                        operation_id: bench_id_clone,
+                        operation_id: deny_id_clone,
                         error: err,
                         duration_ms,
                     },
@@ -4101,116 +7397,222 @@ Falling back to regular cargo test is recommended."#
                 let _ = callback.send_progress(completion_update).await;
             });
 
-            // Return immediate response to LLM - this is the "first stage"
-            let tool_hint = self.generate_tool_hint(&bench_id, "benchmark");
+            let tool_hint = self.generate_tool_hint(&deny_id, "deny");
             let timestamp = timestamp::format_current_time();
             Ok(CallToolResult::success(vec![Content::text(format!(
-                "Benchmark operation {bench_id} started at {timestamp} in the background.{tool_hint}"
+                "Deny operation {deny_id} started at {timestamp} in the background.{tool_hint}"
             ))]))
         }
     }
 
-    /// Internal implementation of bench logic
-    async fn bench_implementation(req: &BenchRequest) -> Result<String, String> {
-        use tokio::process::Command;
+    /// Scaffold a deny.toml with `cargo deny init`, refusing to replace an existing one
+    async fn deny_init(working_directory: &str, operation_id: &str) -> Result<String, String> {
+        let config = Path::new(working_directory).join("deny.toml");
+        if config.exists() {
+            return Err(format!(
+                "- Deny init #{operation_id} skipped: {} already exists. Edit it, or remove it first to start over.",
+                config.display()
+            ));
+        }
+        let output = cargo_command()
+            .args(["deny", "init"])
+            .current_dir(working_directory)
+            .output()
+            .await
+            .map_err(|e| format!("Failed to execute cargo deny init: {e}"))?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if output.status.success() {
+            Ok(format!(
+                "+ Deny init #{operation_id} created {}. Review its [licenses] allow list, [bans] and [sources] sections, then run deny to check.",
+                config.display()
+            ))
+        } else {
+            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("deny"));
+            Err(format!(
+                "- Deny init #{operation_id} failed in {working_directory}.\nErrors: {stderr}\nOutput: {merged}"
+            ))
+        }
+    }
 
-        let mut cmd = Command::new("cargo");
-        cmd.arg("bench");
+    /// Run `cargo deny --format json check` and record the parsed violations
+    async fn deny_implementation(
+        req: &DenyRequest,
+        diagnostics: &DiagnosticsStore,
+        operation_id: &str,
+    ) -> Result<String, String> {
+        use crate::deny::DenyCheck;
+        let mut checks = req
+            .checks
+            .clone()
+            .filter(|checks| !checks.is_empty())
+            .unwrap_or_else(|| {
+                vec![
+                    DenyCheck::Advisories,
+                    DenyCheck::Bans,
+                    DenyCheck::Licenses,
+                    DenyCheck::Sources,
+                ]
+            });
+        checks.sort();
+        checks.dedup();
 
-        // Add any additional arguments
+        let mut cmd = cargo_command();
+        cmd.args(["deny", "--format", "json", "--color", "never", "check"]);
         if let Some(args) = &req.args {
             cmd.args(args);
         }
-
+        cmd.args(checks.iter().map(DenyCheck::as_str));
         cmd.current_dir(&req.working_directory);
 
-        let output = cmd.output().await.map_err(|e| {
-            format!(
-                "- Benchmark operation failed in {}.\nError: Failed to execute cargo bench: {}",
-                &req.working_directory, e
-            )
-        })?;
+        let output = cmd
+            .output()
+            .await
+            .map_err(|e| format!("Failed to execute cargo deny: {e}"))?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
-
         let working_dir_msg = format!(" in {}", &req.working_directory);
 
-        if output.status.success() {
-            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("bench"));
-            Ok(format!(
-                "Benchmark operation completed successfully{working_dir_msg}.\nOutput: {merged}"
-            ))
-        } else {
-            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("bench"));
-            Err(format!(
-                "- Benchmark operation failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {merged}"
-            ))
+        // Diagnostics go to stderr; read both in case that changes
+        let json = format!("{stderr}\n{stdout}");
+        match crate::deny::parse_deny_json(&json, &checks) {
+            Ok(report) => {
+                let rendered = report.render();
+                let failed = report.errors > 0 || !output.status.success();
+                diagnostics.record_deny(operation_id, report).await;
+                if failed {
+                    Err(format!(
+                        "- Deny operation #{operation_id} found policy violations{working_dir_msg}.\n{rendered}"
+                    ))
+                } else {
+                    Ok(format!(
+                        "+ Deny operation #{operation_id} completed successfully{working_dir_msg}.\n{rendered}"
+                    ))
+                }
+            }
+            Err(_) => {
+                let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("deny"));
+                if output.status.success() {
+                    Ok(format!(
+                        "+ Deny operation #{operation_id} completed successfully{working_dir_msg}.\nOutput: {merged}"
+                    ))
+                } else {
+                    Err(format!(
+                        "- Deny operation #{operation_id} failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {merged}"
+                    ))
+                }
+            }
         }
     }
 
     #[tool(
-        description = "CARGO INSTALL: Faster than terminal cargo. Use enable_async_notification=true for large packages to multitask. Global tool installation. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+        description = "CARGO LLVM-COV: Run tests under cargo llvm-cov and report line, region and function coverage per file, least covered first, with uncovered line ranges. Set file to list the uncovered lines of one file; pass operation_id of an earlier coverage run to query it again without rerunning tests. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
     )]
-    async fn install(
+    async fn coverage(
         &self,
-        Parameters(req): Parameters<InstallRequest>,
+        Parameters(req): Parameters<CoverageRequest>,
         context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        let install_id = self.generate_operation_id_for("install");
+        self.ensure_enabled("coverage")?;
+
+        // Queries against an earlier report need no test run
+        if let Some(operation_id) = &req.operation_id {
+            let text = match self
+                .diagnostics
+                .get(operation_id)
+                .await
+                .and_then(|report| report.coverage)
+            {
+                Some(coverage) => match &req.file {
+                    Some(file) => coverage.render_uncovered(file),
+                    None => coverage.render(),
+                },
+                None => format!("- No coverage report recorded for operation {operation_id}"),
+            };
+            return Ok(CallToolResult::success(vec![Content::text(text)]));
+        }
+
+        let coverage_id = self.generate_operation_id_for("coverage");
+
+        // First check if cargo-llvm-cov is available
+        let llvm_cov_check = tokio::process::Command::new("cargo")
+            .args(["llvm-cov", "--version"])
+            .output()
+            .await;
+
+        if llvm_cov_check.is_err() || !llvm_cov_check.unwrap().status.success() {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "- Coverage operation #{coverage_id} failed: cargo-llvm-cov is not installed.
+Install with: cargo install cargo-llvm-cov (it also needs: rustup component add llvm-tools-preview)
+This tool measures which lines the tests execute."
+            ))]));
+        }
 
-        // Check if we should run synchronously or use async notifications
         if self.should_run_synchronously(req.enable_async_notification) {
-            match Self::install_implementation(&req).await {
+            let response = match self.output_store.bound_result(
+                &coverage_id,
+                Self::coverage_implementation(
+                    &req,
+                    &self.diagnostics,
+                    &self.output_store,
+                    &coverage_id,
+                )
+                .await,
+            ) {
                 Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
                 Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
-            }
+            };
+            self.with_diagnostics(&coverage_id, response).await
         } else {
             let peer = context.peer.clone();
             let req_clone = req.clone();
-            let install_id_clone = install_id.clone();
+            let coverage_id_clone = coverage_id.clone();
             let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
+            let diagnostics = self.diagnostics.clone();
 
-            // Register operation before spawn
             self.register_async_operation(
-                &install_id,
-                "cargo install",
-                &format!("Installing package '{}' in the background", req.package),
+                &coverage_id,
+                "cargo llvm-cov",
+                "Measuring test coverage in the background",
                 Some(req.working_directory.clone()),
             )
             .await;
 
-            // Spawn background task for actual install work
             tokio::spawn(async move {
-                let callback = mcp_callback(peer, install_id_clone.clone());
+                let callback = mcp_callback(peer, coverage_id_clone.clone());
 
                 let _ = callback
                     .send_progress(ProgressUpdate::Started {
-                        operation_id: install_id_clone.clone(),
-                        command: "cargo install".to_string(),
-                        description: format!(
-                            "Installing package '{}' in the background",
-                            req_clone.package
-                        ),
+                        operation_id: coverage_id_clone.clone(),
+                        command: "cargo llvm-cov".to_string(),
+                        description: "Measuring test coverage in the background".to_string(),
                     })
                     .await;
 
                 let started_at = Instant::now();
-                let result = Self::install_implementation(&req_clone).await;
-
+                let result = Self::coverage_implementation(
+                    &req_clone,
+                    &diagnostics,
+                    &output_store,
+                    &coverage_id_clone,
+                )
+                .await;
+                let result = output_store.bound_result(&coverage_id_clone, result);
                 let _ = monitor
-                    .complete_operation(&install_id_clone, result.clone())
+                    .complete_operation(&coverage_id_clone, result.clone())
                     .await;
 
                 let duration_ms = started_at.elapsed().as_millis() as u64;
                 let completion_update = match result {
                     Ok(msg) => ProgressUpdate::Completed {
-                        operation_id: install_id_clone,
+                        operation_id: coverage_id_clone,
                         message: msg,
                         duration_ms,
                     },
                     Err(err) => ProgressUpdate::Failed {
-                        operation_id: install_id_clone,
+                        operation_id: coverage_id_clone,
                         error: err,
                         duration_ms,
                     },
@@ -4219,349 +7621,184 @@ Falling back to regular cargo test is recommended."#
                 let _ = callback.send_progress(completion_update).await;
             });
 
-            let tool_hint = self.generate_tool_hint(&install_id, "install");
+            let tool_hint = self.generate_tool_hint(&coverage_id, "coverage");
             let timestamp = timestamp::format_current_time();
             Ok(CallToolResult::success(vec![Content::text(format!(
-                "Install operation {} started at {} in the background. Installing package '{}'.{}",
-                install_id, timestamp, req.package, tool_hint
+                "Coverage operation {coverage_id} started at {timestamp} in the background.{tool_hint}"
             ))]))
         }
     }
 
-    /// Internal implementation of install logic
-    async fn install_implementation(req: &InstallRequest) -> Result<String, String> {
-        use tokio::process::Command;
-
-        let mut cmd = Command::new("cargo");
-        cmd.arg("install");
-
-        let package_spec = if let Some(version) = &req.version {
-            format!("{}@{}", req.package, version)
-        } else {
-            req.package.clone()
-        };
-
-        cmd.arg(&package_spec);
-        cmd.current_dir(&req.working_directory);
-
-        let output = cmd.output().await.map_err(|e| {
-            format!(
-                "- Install operation failed in {}.\nError: Failed to execute cargo install: {}",
-                &req.working_directory, e
-            )
-        })?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let working_dir_msg = format!(" in {}", &req.working_directory);
-        let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("install"));
-
-        if output.status.success() {
-            Ok(format!(
-                "Install operation completed successfully{working_dir_msg}.\nInstalled package: {}\nOutput: {merged}",
-                req.package
-            ))
-        } else {
-            Err(format!(
-                "- Install operation failed{working_dir_msg}.\nPackage: {}\nErrors: {stderr}\nOutput: {merged}",
-                req.package
-            ))
-        }
-    }
-
-    #[tool(
-        description = "CARGO UPGRADE: Faster than terminal cargo. Synchronous operation - returns results immediately once cargo lock is acquired. Updates dependencies to latest versions using cargo-edit. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal."
-    )]
-    async fn upgrade(
-        &self,
-        Parameters(req): Parameters<UpgradeRequest>,
-        _context: RequestContext<RoleServer>,
-    ) -> Result<CallToolResult, ErrorData> {
-        let upgrade_id = self.generate_operation_id_for("upgrade");
-
-        // First check if cargo-edit (upgrade command) is available
-        if let Err(error_msg) = Self::check_cargo_edit_command("upgrade").await {
-            return Ok(CallToolResult::success(vec![Content::text(format!(
-                "- Upgrade operation #{upgrade_id} failed: {}
-Falling back to regular cargo update is recommended.",
-                error_msg
-            ))]));
-        }
-
-        // Always use synchronous execution for Cargo.toml modifications
-        match Self::upgrade_implementation(&req).await {
-            Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
-            Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
-        }
-    }
-
-    /// Check if cargo-edit is installed by checking if a specific command is available
-    async fn check_cargo_edit_command(command: &str) -> Result<(), String> {
-        let check_result = tokio::process::Command::new("cargo")
-            .args([command, "--version"])
-            .output()
-            .await;
+    /// Run the tests under `cargo llvm-cov`, writing the JSON export to a temporary file so it
+    /// does not mix with test output, and record the parsed report
+    async fn coverage_implementation(
+        req: &CoverageRequest,
+        diagnostics: &DiagnosticsStore,
+        output_store: &OutputStore,
+        operation_id: &str,
+    ) -> Result<String, String> {
+        let export_path =
+            std::env::temp_dir().join(format!("async_cargo_mcp_{operation_id}_llvm_cov.json"));
 
-        match check_result {
-            Ok(output) if output.status.success() => Ok(()),
-            Ok(_) => Err(format!(
-                "cargo-edit with {} command is not installed. Install with: cargo install cargo-edit",
-                command
-            )),
-            Err(_) => Err(format!(
-                "cargo-edit with {} command is not installed. Install with: cargo install cargo-edit",
-                command
-            )),
+        let mut cmd = cargo_command();
+        cmd.args(["llvm-cov", "--json", "--output-path"])
+            .arg(&export_path);
+        if let Some(package) = &req.package {
+            cmd.args(["--package", package]);
         }
-    }
-
-    /// Internal implementation of upgrade logic
-    async fn upgrade_implementation(req: &UpgradeRequest) -> Result<String, String> {
-        use tokio::process::Command;
-
-        let upgrade_id = uuid::Uuid::new_v4().to_string()[..8].to_string();
-
-        let mut cmd = Command::new("cargo");
-        cmd.arg("upgrade");
-
-        // Add incompatible flag if requested
-        if req.incompatible.unwrap_or(false) {
-            cmd.arg("--incompatible");
+        if req.workspace.unwrap_or(false) {
+            cmd.arg("--workspace");
         }
-
-        // Add pinned flag if requested
-        if req.pinned.unwrap_or(false) {
-            cmd.arg("--pinned");
+        for exclude in req.exclude.iter().flatten() {
+            cmd.args(["--exclude", exclude]);
         }
-
-        // Add dry run flag if requested
-        if req.dry_run.unwrap_or(false) {
-            cmd.arg("--dry-run");
+        if req.lib.unwrap_or(false) {
+            cmd.arg("--lib");
         }
-
-        // Add specific packages to upgrade
-        if let Some(packages) = &req.packages {
-            for package in packages {
-                cmd.args(["--package", package]);
-            }
+        if req.bins.unwrap_or(false) {
+            cmd.arg("--bins");
         }
-
-        // Add packages to exclude
-        if let Some(exclude) = &req.exclude {
-            for package in exclude {
-                cmd.args(["--exclude", package]);
-            }
+        if let Some(bin) = &req.bin {
+            cmd.args(["--bin", bin]);
         }
-
-        // Add any additional arguments
-        if let Some(args) = &req.args {
-            cmd.args(args);
+        if req.tests.unwrap_or(false) {
+            cmd.arg("--tests");
         }
-
-        cmd.current_dir(&req.working_directory);
-
-        let output = cmd
-            .output()
-            .await
-            .map_err(|e| format!("Failed to execute cargo upgrade: {e}"))?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-
-        let working_dir_msg = format!(" in {}", &req.working_directory);
-        let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("upgrade"));
-
-        if output.status.success() {
-            let dry_run_msg = if req.dry_run.unwrap_or(false) {
-                " (dry run - no changes made)"
-            } else {
-                ""
-            };
-            Ok(format!(
-                "Upgrade operation #{upgrade_id} completed successfully{working_dir_msg}{dry_run_msg}.\nOutput: {merged}"
-            ))
-        } else {
-            Err(format!(
-                "- Upgrade operation #{upgrade_id} failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {merged}"
-            ))
+        if let Some(test) = &req.test {
+            cmd.args(["--test", test]);
         }
-    }
-
-    #[tool(
-        description = "CARGO BUMP-VERSION: Faster than terminal cargo. Synchronous operation - returns results immediately once cargo lock is acquired. Bumps package version using cargo-edit. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal."
-    )]
-    async fn bump_version(
-        &self,
-        Parameters(req): Parameters<BumpVersionRequest>,
-        _context: RequestContext<RoleServer>,
-    ) -> Result<CallToolResult, ErrorData> {
-        let bump_id = self.generate_operation_id_for("bump-version");
-
-        // Validate bump_type before checking cargo-edit
-        if !["patch", "minor", "major"].contains(&req.bump_type.as_str()) {
-            return Ok(CallToolResult::success(vec![Content::text(format!(
-                "- Bump-version operation #{bump_id} failed: Invalid bump type '{}'. 
-Supported bump types: patch, minor, major",
-                req.bump_type
-            ))]));
+        if let Some(features) = &req.features {
+            cmd.args(["--features", &features.join(",")]);
         }
-
-        // Check if cargo-edit (set-version command) is available
-        if let Err(error_msg) = Self::check_cargo_edit_command("set-version").await {
-            return Ok(CallToolResult::success(vec![Content::text(format!(
-                "- Bump-version operation #{bump_id} failed: {}
-The bump-version tool requires cargo-edit to be installed.",
-                error_msg
-            ))]));
+        if req.all_features.unwrap_or(false) {
+            cmd.arg("--all-features");
         }
-
-        // Always use synchronous execution for Cargo.toml modifications
-        match Self::bump_version_implementation(&req).await {
-            Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
-            Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
+        if req.no_default_features.unwrap_or(false) {
+            cmd.arg("--no-default-features");
         }
-    }
-
-    /// Internal implementation of bump-version logic
-    async fn bump_version_implementation(req: &BumpVersionRequest) -> Result<String, String> {
-        use tokio::process::Command;
-
-        let bump_id = uuid::Uuid::new_v4().to_string()[..8].to_string();
-
-        let mut cmd = Command::new("cargo");
-        cmd.arg("set-version").args(["--bump", &req.bump_type]);
-
-        // Add workspace flag if requested
-        if req.workspace.unwrap_or(false) {
-            cmd.arg("--workspace");
+        if let Some(args) = &req.args {
+            cmd.args(args);
         }
-
-        // Add dry run flag if requested
-        if req.dry_run.unwrap_or(false) {
-            cmd.arg("--dry-run");
+        if let Some(test_name) = &req.test_name {
+            cmd.args(["--", test_name]);
         }
-
         cmd.current_dir(&req.working_directory);
 
-        let output = cmd
-            .output()
+        let output = output_store
+            .capture(operation_id, &mut cmd)
             .await
-            .map_err(|e| format!("Failed to execute cargo set-version: {e}"))?;
+            .map_err(|e| format!("Failed to execute cargo llvm-cov: {e}"))?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
-
         let working_dir_msg = format!(" in {}", &req.working_directory);
-        let merged = merge_outputs(
-            &stdout,
-            &stderr,
-            &Self::no_output_placeholder("bump-version"),
-        );
 
-        if output.status.success() {
-            let dry_run_msg = if req.dry_run.unwrap_or(false) {
-                " (dry run - no changes made)"
-            } else {
-                ""
-            };
-            let workspace_msg = if req.workspace.unwrap_or(false) {
-                " (workspace-wide)"
-            } else {
-                ""
-            };
-            Ok(format!(
-                "Bump-version operation #{bump_id} completed successfully{working_dir_msg}{workspace_msg}{dry_run_msg}.\nOutput: {merged}"
-            ))
-        } else {
-            Err(format!(
-                "- Bump-version operation #{bump_id} failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {merged}"
-            ))
+        let export = tokio::fs::read_to_string(&export_path).await;
+        let _ = tokio::fs::remove_file(&export_path).await;
+        if !output.status.success() {
+            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("coverage"));
+            return Err(format!(
+                "- Coverage operation #{operation_id} failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {merged}"
+            ));
+        }
+        let export = export.map_err(|e| {
+            format!("- Coverage operation #{operation_id} failed{working_dir_msg}: cargo llvm-cov wrote no JSON export ({e})")
+        })?;
+        let working_directory = std::fs::canonicalize(&req.working_directory)
+            .unwrap_or_else(|_| req.working_directory.clone().into());
+        let report =
+            crate::coverage::parse_llvm_cov_json(&export, &working_directory).map_err(|e| {
+                format!("- Coverage operation #{operation_id} failed{working_dir_msg}: {e}")
+            })?;
+
+        let mut text = format!(
+            "Coverage operation #{operation_id} completed successfully{working_dir_msg}.\n{}",
+            report.render()
+        );
+        if let Some(file) = &req.file {
+            text.push_str(&format!("\n\n{}", report.render_uncovered(file)));
         }
+        diagnostics.record_coverage(operation_id, report).await;
+        Ok(text)
     }
 
     #[tool(
-        description = "CARGO AUDIT: Faster than terminal cargo. Security vulnerability scanning. Use enable_async_notification=true for large projects to multitask. Identifies known security vulnerabilities. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
+        description = "CARGO EXPAND: Show the source after macro expansion (derives, macro_rules!, proc macros) via cargo-expand, to debug macro errors. Select package, lib/bin/test target and features; set item to a path like \"module::Type\" to expand only that item. Without item, large output is returned as an outline of items with line ranges, and the full source is available through get_output. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
     )]
-    async fn audit(
+    async fn expand(
         &self,
-        Parameters(req): Parameters<AuditRequest>,
+        Parameters(req): Parameters<ExpandRequest>,
         context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        let audit_id = self.generate_operation_id_for("audit");
+        self.ensure_enabled("expand")?;
+        let expand_id = self.generate_operation_id_for("expand");
 
-        // First check if cargo-audit is available
-        let audit_check = tokio::process::Command::new("cargo")
-            .args(["audit", "--version"])
+        // First check if cargo-expand is available
+        let expand_check = tokio::process::Command::new("cargo")
+            .args(["expand", "--version"])
             .output()
             .await;
 
-        if audit_check.is_err() || !audit_check.unwrap().status.success() {
+        if expand_check.is_err() || !expand_check.unwrap().status.success() {
             return Ok(CallToolResult::success(vec![Content::text(format!(
-                "- Audit operation #{audit_id} failed: cargo-audit is not installed. 
-Install with: cargo install cargo-audit
-This tool scans for known security vulnerabilities in dependencies."
+                "- Expand operation #{expand_id} failed: cargo-expand is not installed.
+Install with: cargo install cargo-expand
+This tool shows the code that macros and derives expand to."
             ))]));
         }
 
-        // Check if we should run synchronously or use async notifications
         if self.should_run_synchronously(req.enable_async_notification) {
-            // Synchronous operation for when async notifications are disabled
-            match Self::audit_implementation(&req).await {
-                Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
-                Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
-            }
+            let result = Self::expand_implementation(&req, &self.output_store, &expand_id).await;
+            let result = self.output_store.bound_result(&expand_id, result);
+            Self::handle_sync_result(
+                "expand",
+                "cargo expand",
+                "Synchronous expand operation",
+                result,
+            )
         } else {
-            // TRUE 2-STAGE ASYNC PATTERN:
-            // 1. Send immediate response that operation has started
-            // 2. Spawn background task to do actual work and send notifications
-
             let peer = context.peer.clone();
             let req_clone = req.clone();
-            let audit_id_clone = audit_id.clone();
+            let expand_id_clone = expand_id.clone();
             let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
 
-            // Register operation before spawn
             self.register_async_operation(
-                &audit_id,
-                "cargo audit",
-                "Scanning for security vulnerabilities in the background",
+                &expand_id,
+                "cargo expand",
+                "Expanding macros in the background",
                 Some(req.working_directory.clone()),
             )
             .await;
 
-            // Spawn background task for actual audit work
             tokio::spawn(async move {
-                // Create MCP callback sender to notify the LLM client
-                let callback = mcp_callback(peer, audit_id_clone.clone());
+                let callback = mcp_callback(peer, expand_id_clone.clone());
 
-                // Send started notification immediately
                 let _ = callback
                     .send_progress(ProgressUpdate::Started {
-                        operation_id: audit_id_clone.clone(),
-                        command: "cargo audit".to_string(),
-                        description: "Scanning for security vulnerabilities in the background"
-                            .to_string(),
+                        operation_id: expand_id_clone.clone(),
+                        command: "cargo expand".to_string(),
+                        description: "Expanding macros in the background".to_string(),
                     })
                     .await;
 
-                // Do the actual audit work
                 let started_at = Instant::now();
-                let result = Self::audit_implementation(&req_clone).await;
-                // Store for wait
+                let result =
+                    Self::expand_implementation(&req_clone, &output_store, &expand_id_clone).await;
+                let result = output_store.bound_result(&expand_id_clone, result);
                 let _ = monitor
-                    .complete_operation(&audit_id_clone, result.clone())
+                    .complete_operation(&expand_id_clone, result.clone())
                     .await;
 
-                // Send completion notification
                 let duration_ms = started_at.elapsed().as_millis() as u64;
                 let completion_update = match result {
                     Ok(msg) => ProgressUpdate::Completed {
-                        operation_id: audit_id_clone,
+                        operation_id: expand_id_clone,
                         message: msg,
                         duration_ms,
                     },
                     Err(err) => ProgressUpdate::Failed {
-                        operation_id: audit_id_clone,
+                        operation_id: expand_id_clone,
                         error: err,
                         duration_ms,
                     },
@@ -4570,77 +7807,241 @@ This tool scans for known security vulnerabilities in dependencies."
                 let _ = callback.send_progress(completion_update).await;
             });
 
-            // Return immediate response to LLM - this is the "first stage"
-            let tool_hint = self.generate_tool_hint(&audit_id, "audit");
+            let tool_hint = self.generate_tool_hint(&expand_id, "expand");
             let timestamp = timestamp::format_current_time();
             Ok(CallToolResult::success(vec![Content::text(format!(
-                "Audit operation {audit_id} started at {timestamp} in the background.{tool_hint}"
+                "Expand operation {expand_id} started at {timestamp} in the background.{tool_hint}"
             ))]))
         }
     }
 
-    /// Internal implementation of audit logic
-    async fn audit_implementation(req: &AuditRequest) -> Result<String, String> {
-        use tokio::process::Command;
-
-        let audit_id = uuid::Uuid::new_v4().to_string()[..8].to_string();
-
-        let mut cmd = Command::new("cargo");
-        cmd.arg("audit");
-
-        // Add format flag if specified
-        if let Some(format) = &req.format {
-            cmd.args(["--format", format]);
+    /// Run `cargo expand`. Large output for a whole target is saved for get_output and replaced
+    /// by an outline of its items.
+    async fn expand_implementation(
+        req: &ExpandRequest,
+        output_store: &OutputStore,
+        operation_id: &str,
+    ) -> Result<String, String> {
+        let mut cmd = cargo_command();
+        cmd.args(["expand", "--color", "never"]);
+        if let Some(package) = &req.package {
+            cmd.args(["--package", package]);
         }
-
-        // Add vulnerabilities-only flag if requested
-        if req.vulnerabilities_only.unwrap_or(false) {
-            cmd.arg("--vulnerabilities");
+        if req.lib.unwrap_or(false) {
+            cmd.arg("--lib");
         }
-
-        // Add deny warnings flag if requested
-        if req.deny_warnings.unwrap_or(false) {
-            cmd.arg("--deny-warnings");
+        if let Some(bin) = &req.bin {
+            cmd.args(["--bin", bin]);
+        }
+        if let Some(test) = &req.test {
+            cmd.args(["--test", test]);
+        }
+        if let Some(features) = &req.features {
+            cmd.args(["--features", &features.join(",")]);
+        }
+        if req.all_features.unwrap_or(false) {
+            cmd.arg("--all-features");
+        }
+        if req.no_default_features.unwrap_or(false) {
+            cmd.arg("--no-default-features");
+        }
+        if req.ugly.unwrap_or(false) {
+            cmd.arg("--ugly");
         }
-
-        // Add any additional arguments
         if let Some(args) = &req.args {
             cmd.args(args);
         }
-
+        if let Some(item) = &req.item {
+            cmd.arg(item);
+        }
         cmd.current_dir(&req.working_directory);
 
-        let output = cmd
-            .output()
+        let output = output_store
+            .capture(operation_id, &mut cmd)
             .await
-            .map_err(|e| format!("Failed to execute cargo audit: {e}"))?;
+            .map_err(|e| format!("Failed to execute cargo expand: {e}"))?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
-
         let working_dir_msg = format!(" in {}", &req.working_directory);
-        let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("audit"));
+        let item_msg = req
+            .item
+            .as_ref()
+            .map(|item| format!(" for item '{item}'"))
+            .unwrap_or_default();
+
+        if !output.status.success() {
+            let merged = merge_outputs(&stdout, &stderr, &Self::no_output_placeholder("expand"));
+            return Err(format!(
+                "- Expand operation #{operation_id} failed{working_dir_msg}{item_msg}.\nErrors: {stderr}\nOutput: {merged}"
+            ));
+        }
 
-        if output.status.success() {
-            Ok(format!(
-                "Audit operation #{audit_id} completed successfully{working_dir_msg}.\nNo known vulnerabilities found.\nOutput: {merged}"
-            ))
-        } else {
-            // Check if it's a vulnerability warning (exit code 1) vs actual error
-            let vulnerability_detected = output.status.code() == Some(1) && !stdout.is_empty();
+        let total_lines = stdout.lines().count();
+        if req.item.is_none() && total_lines > crate::expand::MAX_INLINE_EXPANDED_LINES {
+            let outline =
+                crate::expand::render_outline(&crate::expand::outline(&stdout), total_lines);
+            // An expansion past the capture limit keeps the log written while it ran, which
+            // also holds cargo's stderr and is the only place with the complete source
+            let location = if output_store.save(operation_id, &stdout) {
+                format!(
+                    "Read the full expanded source with get_output operation_id=\"{operation_id}\" (line ranges match the outline)."
+                )
+            } else {
+                "The full expanded source could not be saved; pass `item` to expand one item."
+                    .to_string()
+            };
+            return Ok(format!(
+                "+ Expand operation #{operation_id} completed successfully{working_dir_msg}.\n{outline}\n{location}"
+            ));
+        }
+        if stdout.trim().is_empty() && req.item.is_some() {
+            return Err(format!(
+                "- Expand operation #{operation_id} found nothing{working_dir_msg}{item_msg}. Expand without item to see an outline of the available items.\nErrors: {stderr}"
+            ));
+        }
+        Ok(format!(
+            "+ Expand operation #{operation_id} completed successfully{working_dir_msg}{item_msg}.\nOutput:\n{stdout}"
+        ))
+    }
 
-            if vulnerability_detected {
-                Err(format!(
-                    "Audit operation #{audit_id} found security vulnerabilities{working_dir_msg}.\nVulnerabilities detected:\n{stdout}\nErrors: {stderr}\nOutput: {merged}"
-                ))
+    #[tool(
+        description = "WATCH START: Start a watch session on a directory. Changes (ignoring target/, .git/ and .gitignore'd paths) are debounced, then a command chain such as check → test runs in the background, stopping at the first failing step. A progress notification is sent only when the outcome changes; call watch_status for the latest diagnostics and watch_stop to end the session. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal."
+    )]
+    async fn watch_start(
+        &self,
+        Parameters(req): Parameters<WatchStartRequest>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.ensure_enabled("watch_start")?;
+        let root = match std::fs::canonicalize(&req.working_directory) {
+            Ok(root) if root.is_dir() => root,
+            _ => {
+                return Ok(CallToolResult::success(vec![Content::text(format!(
+                    "- Cannot watch {}: not a directory",
+                    req.working_directory
+                ))]));
+            }
+        };
+        if let Some(existing) = self
+            .watch_sessions
+            .find_by_directory(&req.working_directory)
+            .await
+        {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "- {} is already watched by session {}; call watch_stop with session_id=\"{}\" first to change its settings.\n{}",
+                req.working_directory,
+                existing.session_id,
+                existing.session_id,
+                existing.summary()
+            ))]));
+        }
+
+        let steps = req
+            .steps
+            .clone()
+            .filter(|steps| !steps.is_empty())
+            .unwrap_or_else(WatchStep::default_chain);
+        let debounce_ms = req.debounce_ms.unwrap_or(crate::watch::DEFAULT_DEBOUNCE_MS);
+        let session_id = self.generate_operation_id_for("watch");
+        let (watcher, changes) = match crate::watch::watch_directory(&root) {
+            Ok(watching) => watching,
+            Err(e) => {
+                return Ok(CallToolResult::success(vec![Content::text(format!(
+                    "- Watch session {session_id} failed to start in {}: {e}",
+                    req.working_directory
+                ))]));
+            }
+        };
+
+        let session = WatchSession::new(
+            &session_id,
+            &req.working_directory,
+            steps.clone(),
+            debounce_ms,
+            timestamp::format_current_time(),
+        );
+        let summary = session.summary();
+        self.watch_sessions.insert(session).await;
+
+        let runner = WatchRunner {
+            session_id: session_id.clone(),
+            root,
+            working_directory: req.working_directory.clone(),
+            steps,
+            debounce: std::time::Duration::from_millis(debounce_ms),
+            sessions: self.watch_sessions.clone(),
+            diagnostics: self.diagnostics.clone(),
+            output_store: self.output_store.clone(),
+            target_isolation: self.target_isolation.clone(),
+            callback: mcp_callback(context.peer.clone(), session_id.clone()),
+        };
+        let run_on_start = req.run_on_start.unwrap_or(true);
+        let task = tokio::spawn(async move {
+            // The watcher stops delivering changes when dropped, so it lives as long as the task
+            let _watcher = watcher;
+            runner.run(changes, run_on_start).await;
+        });
+        self.watch_sessions.attach_task(&session_id, task).await;
+
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "+ Started {summary}.\nCall watch_status with session_id=\"{session_id}\" for the latest results and watch_stop to end the session."
+        ))]))
+    }
+
+    #[tool(
+        description = "WATCH STATUS: Latest run of a watch session started by watch_start: which files triggered it, each step's pass/fail summary and condensed diagnostics, with the structured diagnostics as structuredContent. Without session_id, lists all watch sessions. Non-blocking. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal."
+    )]
+    async fn watch_status(
+        &self,
+        Parameters(req): Parameters<WatchStatusRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.ensure_enabled("watch_status")?;
+        let Some(session_id) = &req.session_id else {
+            let sessions = self.watch_sessions.list().await;
+            let text = if sessions.is_empty() {
+                "No watch sessions. Start one with watch_start.".to_string()
             } else {
-                Err(format!(
-                    "- Audit operation #{audit_id} failed{working_dir_msg}.\nErrors: {stderr}\nOutput: {merged}"
-                ))
+                sessions
+                    .iter()
+                    .map(|s| s.summary())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+            let mut result = CallToolResult::success(vec![Content::text(text)]);
+            result.structured_content = Some(json!({ "watch_sessions": sessions }));
+            return Ok(result);
+        };
+        match self.watch_sessions.get(session_id).await {
+            Some(session) => {
+                let mut result = CallToolResult::success(vec![Content::text(session.render())]);
+                result.structured_content = Some(json!({ "watch": session }));
+                Ok(result)
             }
+            None => Ok(CallToolResult::success(vec![Content::text(format!(
+                "- No watch session {session_id}. It may have been stopped; call watch_status without session_id to list sessions."
+            ))])),
         }
     }
 
+    #[tool(
+        description = "WATCH STOP: End a watch session started by watch_start. A run in progress is cancelled and the directory is no longer watched. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal."
+    )]
+    async fn watch_stop(
+        &self,
+        Parameters(req): Parameters<WatchStopRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.ensure_enabled("watch_stop")?;
+        let text = match self.watch_sessions.stop(&req.session_id).await {
+            Some(session) => format!(
+                "+ Stopped watch session {} on {} after {} run(s)",
+                session.session_id, session.working_directory, session.runs
+            ),
+            None => format!("- No watch session {}", req.session_id),
+        };
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
     #[tool(
         description = "CARGO FMT: Faster than terminal cargo. Format Rust code using rustfmt. Use enable_async_notification=true for large projects to multitask while code is being formatted. Always use async_cargo_mcp MCP tools; do not run cargo in a terminal. For operations >1s, set enable_async_notification=true and call mcp_async_cargo_m_wait with specific operation_ids to collect results."
     )]
@@ -4654,7 +8055,10 @@ This tool scans for known security vulnerabilities in dependencies."
         // Check if we should run synchronously or use async notifications
         if self.should_run_synchronously(req.enable_async_notification) {
             // Synchronous operation for when async notifications are disabled
-            match Self::fmt_implementation(&req).await {
+            match self.output_store.bound_result(
+                &fmt_id,
+                Self::fmt_implementation(&req, &self.output_store, &fmt_id).await,
+            ) {
                 Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
                 Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
             }
@@ -4667,6 +8071,7 @@ This tool scans for known security vulnerabilities in dependencies."
             let req_clone = req.clone();
             let fmt_id_clone = fmt_id.clone();
             let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
 
             // Register operation before spawn
             self.register_async_operation(
@@ -4693,7 +8098,9 @@ This tool scans for known security vulnerabilities in dependencies."
 
                 // Do the actual format work
                 let started_at = Instant::now();
-                let result = Self::fmt_implementation(&req_clone).await;
+                let result =
+                    Self::fmt_implementation(&req_clone, &output_store, &fmt_id_clone).await;
+                let result = output_store.bound_result(&fmt_id_clone, result);
                 // Store for wait
                 let _ = monitor
                     .complete_operation(&fmt_id_clone, result.clone())
@@ -4727,7 +8134,11 @@ This tool scans for known security vulnerabilities in dependencies."
     }
 
     /// Internal implementation of fmt logic
-    async fn fmt_implementation(req: &FmtRequest) -> Result<String, String> {
+    async fn fmt_implementation(
+        req: &FmtRequest,
+        output_store: &OutputStore,
+        operation_id: &str,
+    ) -> Result<String, String> {
         use tokio::process::Command;
 
         // First check if rustfmt is available
@@ -4742,7 +8153,7 @@ This tool formats Rust code according to style guidelines.",
             ));
         }
 
-        let mut cmd = Command::new("cargo");
+        let mut cmd = cargo_command();
         cmd.arg("fmt");
 
         // Add check flag if requested (don't make changes, just check)
@@ -4762,12 +8173,15 @@ This tool formats Rust code according to style guidelines.",
 
         cmd.current_dir(&req.working_directory);
 
-        let output = cmd.output().await.map_err(|e| {
-            format!(
-                "- Format operation failed in {}.\nError: Failed to execute cargo fmt: {}",
-                &req.working_directory, e
-            )
-        })?;
+        let output = output_store
+            .capture(operation_id, &mut cmd)
+            .await
+            .map_err(|e| {
+                format!(
+                    "- Format operation failed in {}.\nError: Failed to execute cargo fmt: {}",
+                    &req.working_directory, e
+                )
+            })?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -4810,18 +8224,25 @@ This tool formats Rust code according to style guidelines.",
         Parameters(req): Parameters<TreeRequest>,
         _context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
+        let tree_id = self.generate_operation_id_for("tree");
+
         // Synchronous operation only
-        match Self::tree_implementation(&req).await {
+        match self.output_store.bound_result(
+            &tree_id,
+            Self::tree_implementation(&req, &self.output_store, &tree_id).await,
+        ) {
             Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
             Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
         }
     }
 
     /// Internal implementation of tree logic
-    async fn tree_implementation(req: &TreeRequest) -> Result<String, String> {
-        use tokio::process::Command;
-
-        let mut cmd = Command::new("cargo");
+    async fn tree_implementation(
+        req: &TreeRequest,
+        output_store: &OutputStore,
+        operation_id: &str,
+    ) -> Result<String, String> {
+        let mut cmd = cargo_command();
         cmd.arg("tree");
 
         // Add depth limit if specified
@@ -4858,12 +8279,15 @@ This tool formats Rust code according to style guidelines.",
 
         cmd.current_dir(&req.working_directory);
 
-        let output = cmd.output().await.map_err(|e| {
-            format!(
-                "- Tree operation failed in {}.\nError: Failed to execute cargo tree: {}",
-                &req.working_directory, e
-            )
-        })?;
+        let output = output_store
+            .capture(operation_id, &mut cmd)
+            .await
+            .map_err(|e| {
+                format!(
+                    "- Tree operation failed in {}.\nError: Failed to execute cargo tree: {}",
+                    &req.working_directory, e
+                )
+            })?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -4889,9 +8313,7 @@ This tool formats Rust code according to style guidelines.",
         &self,
         Parameters(req): Parameters<VersionRequest>,
     ) -> Result<CallToolResult, ErrorData> {
-        use tokio::process::Command;
-
-        let mut cmd = Command::new("cargo");
+        let mut cmd = cargo_command();
         cmd.arg("version");
 
         // Add verbose flag if requested
@@ -4931,7 +8353,10 @@ This tool formats Rust code according to style guidelines.",
         // Check if we should run synchronously or use async notifications
         if self.should_run_synchronously(req.enable_async_notification) {
             // Synchronous operation for when async notifications are disabled
-            match Self::fetch_implementation(&req).await {
+            match self.output_store.bound_result(
+                &fetch_id,
+                Self::fetch_implementation(&req, &self.output_store, &fetch_id).await,
+            ) {
                 Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
                 Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
             }
@@ -4944,6 +8369,7 @@ This tool formats Rust code according to style guidelines.",
             let req_clone = req.clone();
             let fetch_id_clone = fetch_id.clone();
             let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
 
             // Register operation before spawn
             self.register_async_operation(
@@ -4970,7 +8396,9 @@ This tool formats Rust code according to style guidelines.",
 
                 // Do the actual fetch work
                 let started_at = Instant::now();
-                let result = Self::fetch_implementation(&req_clone).await;
+                let result =
+                    Self::fetch_implementation(&req_clone, &output_store, &fetch_id_clone).await;
+                let result = output_store.bound_result(&fetch_id_clone, result);
                 // Store for wait
                 let _ = monitor
                     .complete_operation(&fetch_id_clone, result.clone())
@@ -5004,10 +8432,12 @@ This tool formats Rust code according to style guidelines.",
     }
 
     /// Internal implementation of fetch logic
-    async fn fetch_implementation(req: &FetchRequest) -> Result<String, String> {
-        use tokio::process::Command;
-
-        let mut cmd = Command::new("cargo");
+    async fn fetch_implementation(
+        req: &FetchRequest,
+        output_store: &OutputStore,
+        operation_id: &str,
+    ) -> Result<String, String> {
+        let mut cmd = cargo_command();
         cmd.arg("fetch");
 
         // Add target if specified
@@ -5039,12 +8469,15 @@ This tool formats Rust code according to style guidelines.",
 
         cmd.current_dir(&req.working_directory);
 
-        let output = cmd.output().await.map_err(|e| {
-            format!(
-                "- Fetch operation failed in {}.\nError: Failed to execute cargo fetch: {}",
-                &req.working_directory, e
-            )
-        })?;
+        let output = output_store
+            .capture(operation_id, &mut cmd)
+            .await
+            .map_err(|e| {
+                format!(
+                    "- Fetch operation failed in {}.\nError: Failed to execute cargo fetch: {}",
+                    &req.working_directory, e
+                )
+            })?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -5076,7 +8509,10 @@ This tool formats Rust code according to style guidelines.",
         // Check if we should run synchronously or use async notifications
         if self.should_run_synchronously(req.enable_async_notification) {
             // Synchronous operation for when async notifications are disabled
-            match Self::rustc_implementation(&req).await {
+            match self.output_store.bound_result(
+                &rustc_id,
+                Self::rustc_implementation(&req, &self.output_store, &rustc_id).await,
+            ) {
                 Ok(result_msg) => Ok(CallToolResult::success(vec![Content::text(result_msg)])),
                 Err(error_msg) => Ok(CallToolResult::success(vec![Content::text(error_msg)])),
             }
@@ -5089,6 +8525,7 @@ This tool formats Rust code according to style guidelines.",
             let req_clone = req.clone();
             let rustc_id_clone = rustc_id.clone();
             let monitor = self.monitor.clone();
+            let output_store = self.output_store.clone();
 
             // Register operation before spawn
             self.register_async_operation(
@@ -5116,7 +8553,9 @@ This tool formats Rust code according to style guidelines.",
 
                 // Do the actual rustc work
                 let started_at = Instant::now();
-                let result = Self::rustc_implementation(&req_clone).await;
+                let result =
+                    Self::rustc_implementation(&req_clone, &output_store, &rustc_id_clone).await;
+                let result = output_store.bound_result(&rustc_id_clone, result);
                 // Store for wait
                 let _ = monitor
                     .complete_operation(&rustc_id_clone, result.clone())
@@ -5150,12 +8589,14 @@ This tool formats Rust code according to style guidelines.",
     }
 
     /// Internal implementation of rustc logic
-    async fn rustc_implementation(req: &RustcRequest) -> Result<String, String> {
-        use tokio::process::Command;
-
+    async fn rustc_implementation(
+        req: &RustcRequest,
+        output_store: &OutputStore,
+        operation_id: &str,
+    ) -> Result<String, String> {
         let rustc_id = uuid::Uuid::new_v4().to_string()[..8].to_string();
 
-        let mut cmd = Command::new("cargo");
+        let mut cmd = cargo_command();
         cmd.arg("rustc");
 
         // Add cargo-specific arguments first
@@ -5173,8 +8614,8 @@ This tool formats Rust code according to style guidelines.",
 
         cmd.current_dir(&req.working_directory);
 
-        let output = cmd
-            .output()
+        let output = output_store
+            .capture(operation_id, &mut cmd)
             .await
             .map_err(|e| format!("Failed to execute cargo rustc: {e}"))?;
 
@@ -5202,11 +8643,9 @@ This tool formats Rust code according to style guidelines.",
         &self,
         Parameters(req): Parameters<MetadataRequest>,
     ) -> Result<CallToolResult, ErrorData> {
-        use tokio::process::Command;
-
         let metadata_id = self.generate_operation_id_for("metadata");
 
-        let mut cmd = Command::new("cargo");
+        let mut cmd = cargo_command();
         cmd.arg("metadata");
 
         // Cargo metadata defaults to JSON format, but we can specify it explicitly
@@ -5278,11 +8717,11 @@ This tool formats Rust code according to style guidelines.",
             )
         };
 
+        let result_msg = self.output_store.bound(&metadata_id, result_msg);
         Ok(CallToolResult::success(vec![Content::text(result_msg)]))
     }
 }
 
-#[tool_handler]
 impl ServerHandler for AsyncCargo {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
@@ -5297,6 +8736,31 @@ impl ServerHandler for AsyncCargo {
         }
     }
 
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if let Some(working_directory) = request
+            .arguments
+            .as_ref()
+            .and_then(|args| args.get("working_directory"))
+            .and_then(|dir| dir.as_str())
+        {
+            self.observe_workspace(working_directory).await;
+        }
+        let tcc = ToolCallContext::new(self, request, context);
+        self.tool_router.call(tcc).await
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        Ok(ListToolsResult::with_all_items(self.tool_router.list_all()))
+    }
+
     async fn list_resources(
         &self,
         _request: Option<PaginatedRequestParam>,
@@ -5481,8 +8945,6 @@ impl AsyncCargo {
         req: DependencyRequest,
         callback: Option<Box<dyn CallbackSender>>,
     ) -> Result<String, String> {
-        use tokio::process::Command;
-
         let operation_id = self.generate_operation_id_for("add");
         let start_time = Instant::now();
 
@@ -5517,7 +8979,7 @@ impl AsyncCargo {
             })
             .await;
 
-        let mut cmd = Command::new("cargo");
+        let mut cmd = cargo_command();
 
         // Build the dependency specification
         let dep_spec = if let Some(version) = &req.version {
@@ -5604,8 +9066,6 @@ impl AsyncCargo {
         mut req: RemoveDependencyRequest,
         callback: Option<Box<dyn CallbackSender>>,
     ) -> Result<String, String> {
-        use tokio::process::Command;
-
         // Handle backward compatibility: if name is provided and names is empty, use name
         if req.names.is_empty() {
             if let Some(name) = &req.name {
@@ -5639,7 +9099,7 @@ impl AsyncCargo {
             })
             .await;
 
-        let mut cmd = Command::new("cargo");
+        let mut cmd = cargo_command();
         cmd.arg("remove");
 
         // Add all dependency names
@@ -5706,7 +9166,6 @@ impl AsyncCargo {
         req: BuildRequest,
         callback: Option<Box<dyn CallbackSender>>,
     ) -> Result<String, String> {
-        use tokio::process::Command;
         let operation_id = self.generate_operation_id_for("build");
         let start_time = Instant::now();
 
@@ -5721,7 +9180,7 @@ impl AsyncCargo {
             })
             .await;
 
-        let mut cmd = Command::new("cargo");
+        let mut cmd = cargo_command();
         cmd.arg("build");
 
         // Set working directory
@@ -5817,7 +9276,7 @@ This tool scans for known security vulnerabilities in dependencies."#
             return Err(error_msg);
         }
 
-        let mut cmd = Command::new("cargo");
+        let mut cmd = cargo_command();
         cmd.arg("audit");
 
         // Add format flag if specified
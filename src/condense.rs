@@ -0,0 +1,518 @@
+//! Token-aware condensation of cargo output
+//!
+//! Raw cargo output spends most of its length on progress lines (`Compiling foo v1.0`) and on
+//! the same warning reported once per target. Condensation removes that noise before a result
+//! reaches the client while keeping every error block, with its source snippet and notes, intact.
+//!
+//! Three levels are available:
+//! - `full`: output is returned unchanged (the default)
+//! - `normal`: progress lines and duplicate diagnostic blocks are dropped
+//! - `summary`: additionally, warnings are reduced to one line plus location and grouped by
+//!   message, and only key lines (test results, panics, failures) are kept from other output
+//!
+//! With `max_output_tokens`, output that does not fit is escalated to the next level until it
+//! does; the caller applies a final head/tail cut if even `summary` is too large.
+
+use std::collections::{HashMap, HashSet};
+
+/// How much of an operation's output to return
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    serde::Deserialize,
+    schemars::JsonSchema,
+    clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Verbosity {
+    /// Errors in full, one line per distinct warning, and key result lines only
+    Summary,
+    /// Drop progress lines and duplicate diagnostics, keep everything else
+    Normal,
+    /// Return output unchanged
+    #[default]
+    Full,
+}
+
+impl Verbosity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Verbosity::Summary => "summary",
+            Verbosity::Normal => "normal",
+            Verbosity::Full => "full",
+        }
+    }
+
+    /// Next more aggressive level, if any
+    fn escalate(self) -> Option<Verbosity> {
+        match self {
+            Verbosity::Full => Some(Verbosity::Normal),
+            Verbosity::Normal => Some(Verbosity::Summary),
+            Verbosity::Summary => None,
+        }
+    }
+}
+
+/// Per-request condensation settings
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CondenseOptions {
+    /// Requested level; `None` uses the server default
+    pub verbosity: Option<Verbosity>,
+    /// Approximate token budget for the returned text
+    pub max_output_tokens: Option<usize>,
+}
+
+impl CondenseOptions {
+    pub fn new(verbosity: Option<Verbosity>, max_output_tokens: Option<usize>) -> Self {
+        Self {
+            verbosity,
+            max_output_tokens,
+        }
+    }
+}
+
+/// Condensed text plus counts of what was removed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condensed {
+    pub text: String,
+    /// Level that was finally applied
+    pub verbosity: Verbosity,
+    pub progress_lines: usize,
+    pub duplicate_diagnostics: usize,
+    /// Lines dropped from warning bodies and non-key output in summary mode
+    pub other_lines: usize,
+}
+
+impl Condensed {
+    /// Whether anything was removed
+    pub fn elided(&self) -> bool {
+        self.progress_lines + self.duplicate_diagnostics + self.other_lines > 0
+    }
+
+    /// One-line description of what was removed, e.g. "12 progress lines, 2 duplicate diagnostics"
+    pub fn describe(&self) -> String {
+        [
+            (self.progress_lines, "progress line"),
+            (self.duplicate_diagnostics, "duplicate diagnostic"),
+            (self.other_lines, "other line"),
+        ]
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, what)| format!("{count} {what}{}", if *count == 1 { "" } else { "s" }))
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+}
+
+/// Cargo status verbs that only report progress
+const PROGRESS_PREFIXES: &[&str] = &[
+    "Compiling ",
+    "Checking ",
+    "Documenting ",
+    "Downloaded ",
+    "Downloading ",
+    "Fresh ",
+    "Blocking waiting for file lock",
+    "Updating crates.io index",
+    "Updating git repository",
+    "Locking ",
+    "Adding ",
+    "Building [",
+];
+
+/// Lines kept in summary mode outside of diagnostics, with the lines of context after them
+const KEY_LINE_MARKERS: &[(&str, usize)] = &[
+    ("panicked at", 2),
+    ("test result:", 0),
+    ("FAILED", 0),
+    ("failures:", 0),
+    ("Finished ", 0),
+    ("error:", 2),
+    ("Error:", 2),
+    // Sections the server appends after cargo's own output
+    ("Diagnostics: ", 0),
+    ("Changes since ", 0),
+    ("Tests: ", 0),
+    ("Panic: ", 0),
+    ("Error code E", 0),
+    ("Timings: ", 0),
+    ("Lint summary: ", 0),
+];
+
+/// Rough token estimate (about four bytes per token for English text and code)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+/// Condense `text` at `verbosity`, escalating while it exceeds `max_output_tokens`.
+/// Returns `None` when the text is returned unchanged.
+pub fn condense_to_budget(
+    text: &str,
+    verbosity: Verbosity,
+    max_output_tokens: Option<usize>,
+) -> Option<Condensed> {
+    let mut level = verbosity;
+    let mut result = condense(text, level);
+    while let Some(budget) = max_output_tokens
+        && estimate_tokens(&result.text) > budget
+        && let Some(next) = level.escalate()
+    {
+        level = next;
+        result = condense(text, level);
+    }
+    result.elided().then_some(result)
+}
+
+/// Condense `text` at a fixed level
+pub fn condense(text: &str, verbosity: Verbosity) -> Condensed {
+    let mut out = Condensed {
+        text: String::new(),
+        verbosity,
+        progress_lines: 0,
+        duplicate_diagnostics: 0,
+        other_lines: 0,
+    };
+    if verbosity == Verbosity::Full {
+        out.text = text.to_string();
+        return out;
+    }
+
+    let lines = split_section_labels(text);
+    let mut kept: Vec<Entry> = Vec::new();
+    let mut seen_blocks: HashSet<String> = HashSet::new();
+    // Summary mode: warnings grouped by header, in order of first appearance
+    let mut warning_groups: Vec<WarningGroup> = Vec::new();
+    let mut group_index: HashMap<&str, usize> = HashMap::new();
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+
+        if is_progress_line(line) {
+            out.progress_lines += 1;
+            i += 1;
+            continue;
+        }
+
+        if let Some(kind) = diagnostic_kind(line) {
+            let end = diagnostic_block_end(&lines, i);
+            let block = &lines[i..end];
+            i = end;
+            if !seen_blocks.insert(block.join("\n")) {
+                out.duplicate_diagnostics += 1;
+                continue;
+            }
+
+            if verbosity == Verbosity::Summary && kind == DiagnosticKind::Warning {
+                let location = block.iter().find(|l| l.trim_start().starts_with("-->"));
+                out.other_lines += block.len() - 1 - usize::from(location.is_some());
+                let index = *group_index.entry(line).or_insert_with(|| {
+                    kept.push(Entry::Warnings(warning_groups.len()));
+                    warning_groups.push(WarningGroup {
+                        header: line,
+                        locations: Vec::new(),
+                        count: 0,
+                    });
+                    warning_groups.len() - 1
+                });
+                let group = &mut warning_groups[index];
+                group.count += 1;
+                if group.count > 1 {
+                    // The header line itself is not repeated
+                    out.other_lines += 1;
+                }
+                group.locations.extend(location.copied());
+            } else {
+                kept.extend(block.iter().map(|l| Entry::Line(l)));
+            }
+            continue;
+        }
+
+        if verbosity == Verbosity::Summary && i > 0 {
+            match key_line_context(line) {
+                Some(context) => {
+                    // Context stops early at anything that is classified on its own
+                    let mut end = i + 1;
+                    while end < lines.len()
+                        && end <= i + context
+                        && !lines[end].trim().is_empty()
+                        && !is_progress_line(lines[end])
+                        && diagnostic_kind(lines[end]).is_none()
+                    {
+                        end += 1;
+                    }
+                    kept.extend(lines[i..end].iter().map(|l| Entry::Line(l)));
+                    i = end;
+                }
+                None => {
+                    if !line.trim().is_empty() {
+                        out.other_lines += 1;
+                    }
+                    i += 1;
+                }
+            }
+            continue;
+        }
+
+        kept.push(Entry::Line(line));
+        i += 1;
+    }
+
+    let mut rendered: Vec<String> = Vec::with_capacity(kept.len());
+    for entry in kept {
+        match entry {
+            Entry::Line(line) => rendered.push(line.to_string()),
+            Entry::Warnings(index) => {
+                let group = &warning_groups[index];
+                if group.count > 1 {
+                    rendered.push(format!("{} (x{})", group.header, group.count));
+                } else {
+                    rendered.push(group.header.to_string());
+                }
+                rendered.extend(group.locations.iter().map(|l| l.to_string()));
+                rendered.push(String::new());
+            }
+        }
+    }
+
+    out.text = collapse_blank_lines(&rendered);
+    out
+}
+
+/// Output line, or the slot where a grouped warning is rendered in summary mode
+enum Entry<'a> {
+    Line(&'a str),
+    Warnings(usize),
+}
+
+struct WarningGroup<'a> {
+    header: &'a str,
+    locations: Vec<&'a str>,
+    count: usize,
+}
+
+/// Split `Output: ...` / `Error: ...` section labels onto their own line, so that cargo output
+/// starting right after the label is classified like any other line
+fn split_section_labels(text: &str) -> Vec<&str> {
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        match ["Output: ", "Error: "]
+            .iter()
+            .find(|label| line.starts_with(*label) && !line[label.len()..].trim().is_empty())
+        {
+            Some(label) => {
+                lines.push(&line[..label.len() - 1]);
+                lines.push(&line[label.len()..]);
+            }
+            None => lines.push(line),
+        }
+    }
+    lines
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DiagnosticKind {
+    Error,
+    Warning,
+}
+
+fn is_progress_line(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    PROGRESS_PREFIXES.iter().any(|p| trimmed.starts_with(p))
+}
+
+/// Classify rustc/cargo diagnostic headers such as `warning: unused variable` or `error[E0308]:`
+fn diagnostic_kind(line: &str) -> Option<DiagnosticKind> {
+    let (kind, rest) = if let Some(rest) = line.strip_prefix("error") {
+        (DiagnosticKind::Error, rest)
+    } else if let Some(rest) = line.strip_prefix("warning") {
+        (DiagnosticKind::Warning, rest)
+    } else {
+        return None;
+    };
+    let rest = match rest.strip_prefix('[') {
+        Some(code) => &code[code.find(']')? + 1..],
+        None => rest,
+    };
+    rest.starts_with(':').then_some(kind)
+}
+
+/// A diagnostic block runs from its header to the next blank line or the next header
+fn diagnostic_block_end(lines: &[&str], start: usize) -> usize {
+    let mut end = start + 1;
+    while end < lines.len()
+        && !lines[end].trim().is_empty()
+        && diagnostic_kind(lines[end]).is_none()
+    {
+        end += 1;
+    }
+    end
+}
+
+fn key_line_context(line: &str) -> Option<usize> {
+    // `---- tests::name stdout ----` introduces a failing test's captured output
+    if line.trim_start().starts_with("---- ") {
+        return Some(0);
+    }
+    KEY_LINE_MARKERS
+        .iter()
+        .find(|(marker, _)| line.contains(marker))
+        .map(|(_, context)| *context)
+}
+
+/// Join lines, squeezing runs of blank lines left behind by removed blocks
+fn collapse_blank_lines(lines: &[String]) -> String {
+    let mut out = String::new();
+    let mut previous_blank = false;
+    for line in lines {
+        let blank = line.trim().is_empty();
+        if blank && previous_blank {
+            continue;
+        }
+        previous_blank = blank;
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUILD_OUTPUT: &str = "+ Build completed successfully.
+Output:    Compiling libc v0.2.0
+   Compiling demo v0.1.0 (/tmp/demo)
+warning: unused variable: `x`
+ --> src/lib.rs:2:9
+  |
+2 |     let x = 1;
+  |         ^ help: if this is intentional, prefix it with an underscore: `_x`
+
+warning: unused variable: `x`
+ --> src/lib.rs:2:9
+  |
+2 |     let x = 1;
+  |         ^ help: if this is intentional, prefix it with an underscore: `_x`
+
+warning: unused variable: `y`
+ --> src/main.rs:3:9
+  |
+3 |     let y = 2;
+  |         ^
+
+warning: `demo` (lib) generated 1 warning
+    Finished `dev` profile [unoptimized + debuginfo] target(s) in 0.50s";
+
+    #[test]
+    fn test_full_is_unchanged() {
+        assert!(condense_to_budget(BUILD_OUTPUT, Verbosity::Full, None).is_none());
+    }
+
+    #[test]
+    fn test_normal_drops_progress_and_duplicates() {
+        let c = condense(BUILD_OUTPUT, Verbosity::Normal);
+        assert!(!c.text.contains("Compiling"));
+        assert_eq!(c.progress_lines, 2);
+        assert_eq!(c.duplicate_diagnostics, 1);
+        assert_eq!(c.text.matches("unused variable: `x`").count(), 1);
+        assert!(c.text.contains("let y = 2;"));
+        assert!(c.text.contains("Finished"));
+    }
+
+    #[test]
+    fn test_summary_keeps_errors_in_full() {
+        let text = "- Build failed.
+Error:    Compiling demo v0.1.0
+error[E0308]: mismatched types
+ --> src/main.rs:2:18
+  |
+2 |     let x: u32 = \"a\";
+  |            ---   ^^^ expected `u32`, found `&str`
+
+warning: unused import: `std::fs`
+ --> src/main.rs:1:5
+  |
+1 | use std::fs;
+  |     ^^^^^^^
+
+error: could not compile `demo` (bin \"demo\") due to 1 previous error";
+        let c = condense(text, Verbosity::Summary);
+        assert!(c.text.contains("expected `u32`, found `&str`"));
+        assert!(
+            c.text
+                .contains("warning: unused import: `std::fs`\n --> src/main.rs:1:5")
+        );
+        assert!(!c.text.contains("use std::fs;"));
+        assert!(c.text.contains("could not compile"));
+    }
+
+    #[test]
+    fn test_summary_groups_warnings_with_locations() {
+        let text = "warning: field is never read
+ --> src/a.rs:1:1
+  |
+
+warning: unused import
+ --> src/c.rs:9:9
+
+warning: field is never read
+ --> src/b.rs:5:5
+  |";
+        let c = condense(text, Verbosity::Summary);
+        assert_eq!(
+            c.text,
+            "warning: field is never read (x2)\n --> src/a.rs:1:1\n --> src/b.rs:5:5\n\nwarning: unused import\n --> src/c.rs:9:9"
+        );
+    }
+
+    #[test]
+    fn test_summary_keeps_test_failures() {
+        let text = "+ Tests completed.
+running 2 tests
+test tests::ok ... ok
+test tests::bad ... FAILED
+
+failures:
+
+---- tests::bad stdout ----
+
+thread 'tests::bad' panicked at src/lib.rs:10:9:
+assertion `left == right` failed
+  left: 1
+
+test result: FAILED. 1 passed; 1 failed";
+        let c = condense(text, Verbosity::Summary);
+        assert!(c.text.contains("test tests::bad ... FAILED"));
+        assert!(c.text.contains("panicked at src/lib.rs:10:9"));
+        assert!(c.text.contains("assertion `left == right` failed"));
+        assert!(c.text.contains("test result: FAILED"));
+        assert!(!c.text.contains("tests::ok ... ok"));
+    }
+
+    #[test]
+    fn test_budget_escalates_levels() {
+        let c = condense_to_budget(BUILD_OUTPUT, Verbosity::Full, Some(1)).unwrap();
+        assert_eq!(c.verbosity, Verbosity::Summary);
+        let fits = condense_to_budget(BUILD_OUTPUT, Verbosity::Full, Some(100_000));
+        assert!(fits.is_none());
+    }
+
+    #[test]
+    fn test_diagnostic_kind() {
+        assert_eq!(
+            diagnostic_kind("error[E0308]: mismatched types"),
+            Some(DiagnosticKind::Error)
+        );
+        assert_eq!(
+            diagnostic_kind("warning: unused"),
+            Some(DiagnosticKind::Warning)
+        );
+        assert_eq!(diagnostic_kind("errors: 3"), None);
+        assert_eq!(diagnostic_kind("warnings"), None);
+    }
+}
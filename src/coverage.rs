@@ -0,0 +1,379 @@
+//! Code coverage from `cargo llvm-cov`
+//!
+//! `coverage` runs the tests under `cargo llvm-cov --json` and parses the llvm-cov export: line,
+//! region and function totals per file, and the uncovered lines, computed from the coverage
+//! segments the way `llvm-cov report` counts lines. Reports are kept per operation so
+//! "uncovered lines in file X" can be answered again without rerunning the tests.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Covered and total count of one kind of coverage item
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CoverageCount {
+    pub covered: u64,
+    pub count: u64,
+}
+
+impl CoverageCount {
+    pub fn percent(&self) -> f64 {
+        if self.count == 0 {
+            100.0
+        } else {
+            self.covered as f64 * 100.0 / self.count as f64
+        }
+    }
+
+    fn render(&self) -> String {
+        format!("{:.1}% ({}/{})", self.percent(), self.covered, self.count)
+    }
+}
+
+/// Coverage of one source file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileCoverage {
+    /// Relative to the working directory when the file is inside it
+    pub file: String,
+    pub lines: CoverageCount,
+    pub regions: CoverageCount,
+    pub functions: CoverageCount,
+    /// Inclusive line ranges that are executable but never ran, e.g. `[[5, 7], [12, 12]]`
+    pub uncovered_lines: Vec<(u64, u64)>,
+}
+
+impl FileCoverage {
+    /// `5-7, 12`
+    pub fn uncovered_ranges(&self) -> String {
+        self.uncovered_lines
+            .iter()
+            .map(|&(start, end)| {
+                if start == end {
+                    start.to_string()
+                } else {
+                    format!("{start}-{end}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Everything parsed from one `cargo llvm-cov --json` export
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CoverageReport {
+    pub lines: CoverageCount,
+    pub regions: CoverageCount,
+    pub functions: CoverageCount,
+    /// Least covered first
+    pub files: Vec<FileCoverage>,
+}
+
+impl CoverageReport {
+    /// Files whose path ends with `query` (or contains it, if none ends with it)
+    pub fn find_files(&self, query: &str) -> Vec<&FileCoverage> {
+        let query = query.trim_start_matches("./");
+        let ending: Vec<&FileCoverage> = self
+            .files
+            .iter()
+            .filter(|f| f.file.ends_with(query))
+            .collect();
+        if !ending.is_empty() {
+            return ending;
+        }
+        self.files
+            .iter()
+            .filter(|f| f.file.contains(query))
+            .collect()
+    }
+
+    /// Uncovered line ranges of the files matching `query`, one line per file
+    pub fn render_uncovered(&self, query: &str) -> String {
+        let files = self.find_files(query);
+        if files.is_empty() {
+            return format!("No file matching '{query}' in the coverage report");
+        }
+        files
+            .iter()
+            .map(|f| {
+                let ranges = if f.uncovered_lines.is_empty() {
+                    "none".to_string()
+                } else {
+                    f.uncovered_ranges()
+                };
+                format!(
+                    "Uncovered lines in {} ({} of lines covered): {ranges}",
+                    f.file,
+                    f.lines.render()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// `Coverage:` totals line, then one line per file with its uncovered line ranges
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "Coverage: lines {}, regions {}, functions {} over {} file(s)",
+            self.lines.render(),
+            self.regions.render(),
+            self.functions.render(),
+            self.files.len()
+        );
+        for file in &self.files {
+            out.push_str(&format!(
+                "\n  {:>5.1}% lines {:>5.1}% regions {:>5.1}% functions  {}",
+                file.lines.percent(),
+                file.regions.percent(),
+                file.functions.percent(),
+                file.file
+            ));
+            if !file.uncovered_lines.is_empty() {
+                out.push_str(&format!(" (uncovered: {})", file.uncovered_ranges()));
+            }
+        }
+        out
+    }
+}
+
+#[derive(Deserialize)]
+struct RawExport {
+    data: Vec<RawData>,
+}
+
+#[derive(Deserialize)]
+struct RawData {
+    files: Vec<RawFile>,
+    totals: RawSummary,
+}
+
+#[derive(Deserialize)]
+struct RawFile {
+    filename: String,
+    #[serde(default)]
+    segments: Vec<Vec<serde_json::Value>>,
+    summary: RawSummary,
+}
+
+#[derive(Deserialize)]
+struct RawSummary {
+    lines: RawCount,
+    regions: RawCount,
+    functions: RawCount,
+}
+
+#[derive(Deserialize)]
+struct RawCount {
+    count: u64,
+    covered: u64,
+}
+
+impl From<RawCount> for CoverageCount {
+    fn from(raw: RawCount) -> Self {
+        CoverageCount {
+            covered: raw.covered,
+            count: raw.count,
+        }
+    }
+}
+
+/// One llvm-cov segment: `[line, column, count, has_count, is_region_entry, is_gap_region]`
+struct Segment {
+    line: u64,
+    count: u64,
+    has_count: bool,
+    is_region_entry: bool,
+    is_gap: bool,
+}
+
+impl Segment {
+    fn parse(raw: &[serde_json::Value]) -> Option<Self> {
+        Some(Segment {
+            line: raw.first()?.as_u64()?,
+            count: raw.get(2)?.as_u64()?,
+            has_count: raw.get(3)?.as_bool()?,
+            is_region_entry: raw.get(4)?.as_bool()?,
+            // Exports before LLVM 11 have no gap flag
+            is_gap: raw.get(5).and_then(|v| v.as_bool()).unwrap_or(false),
+        })
+    }
+
+    fn starts_region(&self) -> bool {
+        !self.is_gap && self.has_count && self.is_region_entry
+    }
+}
+
+/// Lines that are mapped to code but never executed, following llvm-cov's `LineCoverageStats`:
+/// a line is mapped if a region starts on it or a counted region wraps into it, and its count is
+/// the largest count among those regions
+fn uncovered_lines(segments: &[Segment]) -> Vec<(u64, u64)> {
+    let (Some(first), Some(last)) = (segments.first(), segments.last()) else {
+        return Vec::new();
+    };
+    let mut uncovered: Vec<(u64, u64)> = Vec::new();
+    let mut wrapped: Option<&Segment> = None;
+    let mut next = 0;
+    for line in first.line..=last.line {
+        let start = next;
+        while next < segments.len() && segments[next].line == line {
+            next += 1;
+        }
+        let on_line = &segments[start..next];
+        let region_starts = on_line.iter().filter(|s| s.starts_region()).count();
+        let skipped = on_line
+            .first()
+            .is_some_and(|s| !s.has_count && s.is_region_entry);
+        let mapped = !skipped && (wrapped.is_some_and(|w| w.has_count) || region_starts > 0);
+        if mapped {
+            let count = on_line
+                .iter()
+                .filter(|s| s.starts_region())
+                .map(|s| s.count)
+                .chain(wrapped.map(|w| w.count))
+                .max()
+                .unwrap_or(0);
+            if count == 0 {
+                match uncovered.last_mut() {
+                    Some((_, end)) if *end + 1 == line => *end = line,
+                    _ => uncovered.push((line, line)),
+                }
+            }
+        }
+        if let Some(last_on_line) = on_line.last() {
+            wrapped = Some(last_on_line);
+        }
+    }
+    uncovered
+}
+
+/// Parse an llvm-cov JSON export. File names are made relative to `working_directory`, and files
+/// outside it (dependencies, the standard library) are dropped.
+pub fn parse_llvm_cov_json(json: &str, working_directory: &Path) -> Result<CoverageReport, String> {
+    let export: RawExport = serde_json::from_str(json)
+        .map_err(|e| format!("Failed to parse llvm-cov JSON export: {e}"))?;
+    let data = export
+        .data
+        .into_iter()
+        .next()
+        .ok_or_else(|| "llvm-cov export contains no coverage data".to_string())?;
+
+    let mut files: Vec<FileCoverage> = data
+        .files
+        .into_iter()
+        .filter_map(|raw| {
+            let path = Path::new(&raw.filename);
+            let file = if path.is_absolute() {
+                path.strip_prefix(working_directory).ok()?
+            } else {
+                path
+            };
+            let segments: Vec<Segment> = raw
+                .segments
+                .iter()
+                .filter_map(|s| Segment::parse(s))
+                .collect();
+            Some(FileCoverage {
+                file: file.display().to_string(),
+                lines: raw.summary.lines.into(),
+                regions: raw.summary.regions.into(),
+                functions: raw.summary.functions.into(),
+                uncovered_lines: uncovered_lines(&segments),
+            })
+        })
+        .collect();
+    files.sort_by(|a, b| {
+        a.lines
+            .percent()
+            .total_cmp(&b.lines.percent())
+            .then_with(|| a.file.cmp(&b.file))
+    });
+
+    Ok(CoverageReport {
+        lines: data.totals.lines.into(),
+        regions: data.totals.regions.into(),
+        functions: data.totals.functions.into(),
+        files,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // src/lib.rs of a crate with `add` tested and `sub` untested:
+    //  1 pub fn add(a: i32, b: i32) -> i32 {
+    //  2     a + b
+    //  3 }
+    //  4
+    //  5 pub fn sub(a: i32, b: i32) -> i32 {
+    //  6     a - b
+    //  7 }
+    const EXPORT: &str = r#"{"data":[{"files":[
+      {"filename":"/work/demo/src/lib.rs","segments":[[1,1,1,true,true,false],[3,2,0,false,false,false],[5,1,0,true,true,false],[7,2,0,false,false,false]],"branches":[],"expansions":[],
+       "summary":{"branches":{"count":0,"covered":0,"notcovered":0,"percent":0},"functions":{"count":2,"covered":1,"percent":50},"instantiations":{"count":2,"covered":1,"percent":50},"lines":{"count":6,"covered":3,"percent":50},"regions":{"count":2,"covered":1,"notcovered":1,"percent":50}}},
+      {"filename":"/work/demo/src/main.rs","segments":[[1,1,1,true,true,false],[3,2,0,false,false,false]],"branches":[],"expansions":[],
+       "summary":{"branches":{"count":0,"covered":0,"notcovered":0,"percent":0},"functions":{"count":1,"covered":1,"percent":100},"instantiations":{"count":1,"covered":1,"percent":100},"lines":{"count":3,"covered":3,"percent":100},"regions":{"count":1,"covered":1,"notcovered":0,"percent":100}}},
+      {"filename":"/home/user/.cargo/registry/src/dep-1.0.0/src/lib.rs","segments":[],"branches":[],"expansions":[],
+       "summary":{"branches":{"count":0,"covered":0,"notcovered":0,"percent":0},"functions":{"count":1,"covered":0,"percent":0},"instantiations":{"count":1,"covered":0,"percent":0},"lines":{"count":3,"covered":0,"percent":0},"regions":{"count":1,"covered":0,"notcovered":1,"percent":0}}}
+    ],"functions":[],"totals":{"branches":{"count":0,"covered":0,"notcovered":0,"percent":0},"functions":{"count":3,"covered":2,"percent":66.6},"instantiations":{"count":3,"covered":2,"percent":66.6},"lines":{"count":9,"covered":6,"percent":66.6},"regions":{"count":3,"covered":2,"notcovered":1,"percent":66.6}}}],
+      "type":"llvm.coverage.json.export","version":"2.0.1"}"#;
+
+    #[test]
+    fn test_parse_llvm_cov_json() {
+        let report = parse_llvm_cov_json(EXPORT, Path::new("/work/demo")).unwrap();
+        assert_eq!(
+            report.lines,
+            CoverageCount {
+                covered: 6,
+                count: 9
+            }
+        );
+        let files: Vec<&str> = report.files.iter().map(|f| f.file.as_str()).collect();
+        assert_eq!(files, vec!["src/lib.rs", "src/main.rs"]);
+        assert_eq!(report.files[0].uncovered_lines, vec![(5, 7)]);
+        assert!(report.files[1].uncovered_lines.is_empty());
+        assert!(report.render().starts_with(
+            "Coverage: lines 66.7% (6/9), regions 66.7% (2/3), functions 66.7% (2/3) over 2 file(s)\n   50.0% lines  50.0% regions  50.0% functions  src/lib.rs (uncovered: 5-7)"
+        ));
+    }
+
+    #[test]
+    fn test_uncovered_lines_follow_wrapped_regions() {
+        let segment = |line, count, has_count, is_region_entry| Segment {
+            line,
+            count,
+            has_count,
+            is_region_entry,
+            is_gap: false,
+        };
+        // Lines 10-12 run; the body of the `if` opened on line 12 never does, so line 13 and
+        // the closing brace on 14 are uncovered; execution resumes after that brace until 16
+        let segments = vec![
+            segment(10, 4, true, true),
+            segment(12, 0, true, true),
+            segment(14, 4, true, false),
+            segment(16, 0, false, false),
+        ];
+        assert_eq!(uncovered_lines(&segments), vec![(13, 14)]);
+    }
+
+    #[test]
+    fn test_find_files() {
+        let report = parse_llvm_cov_json(EXPORT, Path::new("/work/demo")).unwrap();
+        let found: Vec<&str> = report
+            .find_files("lib.rs")
+            .iter()
+            .map(|f| f.file.as_str())
+            .collect();
+        assert_eq!(found, vec!["src/lib.rs"]);
+        assert_eq!(report.find_files("./src/main.rs").len(), 1);
+        assert_eq!(report.find_files("src").len(), 2);
+        assert_eq!(
+            report.render_uncovered("lib.rs"),
+            "Uncovered lines in src/lib.rs (50.0% (3/6) of lines covered): 5-7"
+        );
+        assert_eq!(
+            report.render_uncovered("parser.rs"),
+            "No file matching 'parser.rs' in the coverage report"
+        );
+    }
+}
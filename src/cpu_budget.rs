@@ -0,0 +1,127 @@
+//! Global CPU budget shared by all cargo invocations
+//!
+//! Every cargo process normally assumes it owns all cores, so three concurrent operations
+//! oversubscribe the machine threefold. When `--jobs-budget N` is set, the server creates a
+//! single GNU make jobserver and hands it to every cargo process it starts (via
+//! `CARGO_MAKEFLAGS`). Cargo then acquires a token from the shared pool before starting each
+//! rustc/build-script job, so all concurrent operations together stay within the budget.
+//!
+//! As with `make -jN`, each process also runs one job on its own implicit token, so the pool
+//! holds `N - 1` tokens. A budget of 1 needs no pool at all: cargo processes are started with
+//! `CARGO_BUILD_JOBS=1` instead, which keeps each of them to its implicit token.
+
+use std::io;
+use std::sync::OnceLock;
+use tracing::info;
+
+static JOB_BUDGET: OnceLock<JobBudget> = OnceLock::new();
+
+/// The process-wide jobserver and the budget it was created with
+#[derive(Debug)]
+struct JobBudget {
+    /// `None` for a budget of 1, which leaves no tokens to share
+    client: Option<jobserver::Client>,
+    jobs: u32,
+}
+
+impl JobBudget {
+    fn new(jobs: u32) -> io::Result<Self> {
+        let client = match tokens_for(jobs) {
+            0 => None,
+            tokens => Some(jobserver::Client::new(tokens)?),
+        };
+        Ok(Self { client, jobs })
+    }
+
+    fn configure(&self, cmd: &mut std::process::Command) {
+        match &self.client {
+            Some(client) => client.configure(cmd),
+            None => {
+                cmd.env("CARGO_BUILD_JOBS", "1");
+            }
+        }
+    }
+}
+
+/// Tokens in the shared pool for a budget of `jobs`; every cargo process adds its implicit one
+fn tokens_for(jobs: u32) -> usize {
+    (jobs.max(1) as usize).saturating_sub(1)
+}
+
+/// Create the shared jobserver with a budget of `jobs` parallel jobs.
+/// Must be called at most once, before any cargo command is spawned.
+pub fn init(jobs: u32) -> io::Result<()> {
+    let jobs = jobs.max(1);
+    let budget = JobBudget::new(jobs)?;
+    JOB_BUDGET
+        .set(budget)
+        .map_err(|_| io::Error::new(io::ErrorKind::AlreadyExists, "jobserver already set"))?;
+    info!("Shared jobserver created with a budget of {jobs} jobs");
+    Ok(())
+}
+
+/// The configured jobs budget, or `None` when every cargo process manages its own parallelism
+pub fn budget() -> Option<u32> {
+    JOB_BUDGET.get().map(|b| b.jobs)
+}
+
+/// Cap a requested `--jobs` value by the global budget
+pub fn cap_jobs(requested: u32) -> u32 {
+    cap_jobs_to(requested, budget())
+}
+
+fn cap_jobs_to(requested: u32, budget: Option<u32>) -> u32 {
+    match budget {
+        Some(max) => requested.min(max),
+        None => requested,
+    }
+}
+
+/// Hand the shared jobserver to a child process (no-op when no budget is configured).
+/// Children of that process, such as cargo launched from a pooled shell, inherit it too.
+pub fn configure_command(cmd: &mut tokio::process::Command) {
+    if let Some(b) = JOB_BUDGET.get() {
+        b.configure(cmd.as_std_mut());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cap_jobs_without_budget_is_identity() {
+        assert_eq!(cap_jobs_to(16, None), 16);
+    }
+
+    #[test]
+    fn test_cap_jobs_with_budget() {
+        assert_eq!(cap_jobs_to(16, Some(4)), 4);
+        assert_eq!(cap_jobs_to(2, Some(4)), 2);
+    }
+
+    #[test]
+    fn test_budget_of_one_runs_a_single_job() {
+        assert_eq!(tokens_for(1), 0);
+        assert_eq!(tokens_for(4), 3);
+
+        let budget = JobBudget::new(1).unwrap();
+        assert!(budget.client.is_none());
+        let mut cmd = std::process::Command::new("cargo");
+        budget.configure(&mut cmd);
+        let jobs = cmd
+            .get_envs()
+            .find(|(key, _)| *key == "CARGO_BUILD_JOBS")
+            .and_then(|(_, value)| value);
+        assert_eq!(jobs, Some(std::ffi::OsStr::new("1")));
+    }
+
+    #[test]
+    fn test_larger_budget_shares_a_jobserver() {
+        let budget = JobBudget::new(3).unwrap();
+        assert!(budget.client.is_some());
+        let mut cmd = std::process::Command::new("cargo");
+        budget.configure(&mut cmd);
+        assert!(cmd.get_envs().all(|(key, _)| key != "CARGO_BUILD_JOBS"));
+    }
+}
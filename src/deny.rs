@@ -0,0 +1,398 @@
+//! Structured `cargo deny check` results
+//!
+//! `deny` runs `cargo deny --format json check` for the selected checks. cargo-deny writes one
+//! JSON object per line to stderr; `diagnostic` lines are parsed into [`DenyViolation`]s with the
+//! check they belong to (derived from the diagnostic code), the offending crate, and the
+//! dependency path from a workspace member down to it, taken from the inclusion graph cargo-deny
+//! attaches to each diagnostic.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// One of the checks `cargo deny check` can run
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum DenyCheck {
+    Advisories,
+    Bans,
+    Licenses,
+    Sources,
+}
+
+impl DenyCheck {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DenyCheck::Advisories => "advisories",
+            DenyCheck::Bans => "bans",
+            DenyCheck::Licenses => "licenses",
+            DenyCheck::Sources => "sources",
+        }
+    }
+
+    /// The check that emits diagnostics with `code`, per cargo-deny's diagnostic codes
+    pub fn for_code(code: &str) -> Option<Self> {
+        match code {
+            "vulnerability"
+            | "notice"
+            | "unmaintained"
+            | "unsound"
+            | "yanked"
+            | "index-failure"
+            | "index-cache-load-failure"
+            | "advisory-ignored"
+            | "advisory-not-detected"
+            | "unknown-advisory" => Some(DenyCheck::Advisories),
+            "banned"
+            | "allowed"
+            | "not-allowed"
+            | "duplicate"
+            | "skipped"
+            | "wildcard"
+            | "unmatched-skip"
+            | "unmatched-skip-root"
+            | "build-script-not-allowed"
+            | "path-bypassed"
+            | "path-bypassed-by-glob"
+            | "checksum-match"
+            | "checksum-mismatch"
+            | "denied-by-extension"
+            | "detected-executable"
+            | "detected-executable-script"
+            | "unable-to-check-path"
+            | "feature-banned"
+            | "unknown-feature"
+            | "default-feature-enabled"
+            | "workspace-duplicate"
+            | "unresolved-workspace-dependency"
+            | "unused-workspace-dependency"
+            | "non-utf8-path"
+            | "unmatched-path-bypass"
+            | "unmatched-glob"
+            | "unused-wrapper" => Some(DenyCheck::Bans),
+            "rejected"
+            | "accepted"
+            | "unlicensed"
+            | "skipped-private-workspace-crate"
+            | "license-not-encountered"
+            | "license-exception-not-encountered"
+            | "missing-clarification-file"
+            | "parse-error"
+            | "empty-license-field"
+            | "no-license-field"
+            | "gather-failure" => Some(DenyCheck::Licenses),
+            "source-not-allowed"
+            | "unmatched-source"
+            | "unmatched-organization"
+            | "git-source-underspecified"
+            | "allowed-source"
+            | "allowed-by-organization" => Some(DenyCheck::Sources),
+            _ => None,
+        }
+    }
+}
+
+/// One error or warning reported by cargo-deny
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DenyViolation {
+    /// `advisories`, `bans`, `licenses` or `sources`; `other` for codes this server does not know
+    pub check: String,
+    /// `error` or `warning`
+    pub severity: String,
+    /// cargo-deny diagnostic code, e.g. `rejected`, `banned`, `duplicate`, `source-not-allowed`
+    pub code: String,
+    pub message: String,
+    /// `name version` of the offending crate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub krate: Option<String>,
+    /// From a workspace member down to the offending crate, as `name version`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependency_path: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub advisory_id: Option<String>,
+    /// Label and note text, e.g. the rejected license expression
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<String>,
+}
+
+impl DenyViolation {
+    fn render(&self) -> String {
+        let mut out = format!(
+            "- {}[{}/{}] {}",
+            self.severity, self.check, self.code, self.message
+        );
+        if let Some(krate) = &self.krate {
+            out.push_str(&format!(": {krate}"));
+        }
+        if let Some(id) = &self.advisory_id {
+            out.push_str(&format!(" ({id})"));
+        }
+        if self.dependency_path.len() > 1 {
+            out.push_str(&format!(
+                "\n    path: {}",
+                self.dependency_path.join(" -> ")
+            ));
+        }
+        for detail in &self.details {
+            out.push_str(&format!("\n    {detail}"));
+        }
+        out
+    }
+}
+
+/// Everything parsed from one `cargo deny check` run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DenyReport {
+    /// Checks that ran
+    pub checks: Vec<DenyCheck>,
+    pub errors: usize,
+    pub warnings: usize,
+    pub violations: Vec<DenyViolation>,
+}
+
+impl DenyReport {
+    /// `Deny:` line with counts per check, then each violation with its path
+    pub fn render(&self) -> String {
+        let mut per_check: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+        for violation in &self.violations {
+            let counts = per_check.entry(violation.check.as_str()).or_default();
+            if violation.severity == "error" {
+                counts.0 += 1;
+            } else {
+                counts.1 += 1;
+            }
+        }
+        let checks: Vec<&str> = self.checks.iter().map(DenyCheck::as_str).collect();
+        let mut out = format!(
+            "Deny: {} error(s), {} warning(s) from {}",
+            self.errors,
+            self.warnings,
+            checks.join(", ")
+        );
+        if !per_check.is_empty() {
+            let counts: Vec<String> = per_check
+                .iter()
+                .map(|(check, (errors, warnings))| {
+                    format!("{check}: {errors} error(s), {warnings} warning(s)")
+                })
+                .collect();
+            out.push_str(&format!(" ({})", counts.join("; ")));
+        }
+        for violation in &self.violations {
+            out.push('\n');
+            out.push_str(&violation.render());
+        }
+        out
+    }
+}
+
+#[derive(Deserialize)]
+struct RawLine {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    fields: Value,
+}
+
+#[derive(Deserialize)]
+struct RawDiagnostic {
+    code: Option<String>,
+    #[serde(default)]
+    severity: String,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    graphs: Vec<Value>,
+    #[serde(default)]
+    labels: Vec<RawLabel>,
+    #[serde(default)]
+    notes: Vec<String>,
+    advisory: Option<RawAdvisory>,
+}
+
+#[derive(Deserialize)]
+struct RawLabel {
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    span: String,
+}
+
+#[derive(Deserialize)]
+struct RawAdvisory {
+    id: String,
+}
+
+/// `name version` of an inclusion graph node, if it is a crate (feature nodes are not)
+fn node_krate(node: &Value) -> Option<String> {
+    let krate = node.get("Krate")?;
+    Some(format!(
+        "{} {}",
+        krate.get("name")?.as_str()?,
+        krate.get("version")?.as_str()?
+    ))
+}
+
+/// Follow the first parent of each node up to a root and return the crates from the root down
+fn graph_path(graph: &Value) -> Vec<String> {
+    let mut path = Vec::new();
+    let mut node = Some(graph);
+    while let Some(current) = node {
+        if let Some(krate) = node_krate(current)
+            && path.last() != Some(&krate)
+        {
+            path.push(krate);
+        }
+        node = current
+            .get("parents")
+            .and_then(Value::as_array)
+            .and_then(|parents| parents.first());
+    }
+    path.reverse();
+    path
+}
+
+/// Parse the JSON lines `cargo deny --format json check` prints. Only errors and warnings are
+/// kept, errors first.
+pub fn parse_deny_json(output: &str, checks: &[DenyCheck]) -> Result<DenyReport, String> {
+    let mut report = DenyReport {
+        checks: checks.to_vec(),
+        ..DenyReport::default()
+    };
+    let mut seen_json = false;
+    for line in output.lines().map(str::trim).filter(|l| l.starts_with('{')) {
+        let Ok(raw) = serde_json::from_str::<RawLine>(line) else {
+            continue;
+        };
+        seen_json = true;
+        if raw.kind != "diagnostic" {
+            continue;
+        }
+        let Ok(diagnostic) = serde_json::from_value::<RawDiagnostic>(raw.fields) else {
+            continue;
+        };
+        if diagnostic.severity != "error" && diagnostic.severity != "warning" {
+            continue;
+        }
+        let code = diagnostic.code.unwrap_or_default();
+        let check = DenyCheck::for_code(&code)
+            .or_else(|| (checks.len() == 1).then(|| checks[0]))
+            .map_or("other", |check| check.as_str());
+        let dependency_path = diagnostic
+            .graphs
+            .first()
+            .map(graph_path)
+            .unwrap_or_default();
+        let krate = diagnostic.graphs.first().and_then(node_krate);
+        let details = diagnostic
+            .labels
+            .into_iter()
+            .map(
+                |label| match (label.span.is_empty(), label.message.is_empty()) {
+                    (false, false) => format!("{} - {}", label.span, label.message),
+                    (false, true) => label.span,
+                    _ => label.message,
+                },
+            )
+            .filter(|text| !text.is_empty())
+            .chain(diagnostic.notes)
+            .collect();
+        report.violations.push(DenyViolation {
+            check: check.to_string(),
+            severity: diagnostic.severity,
+            code,
+            message: diagnostic.message,
+            krate,
+            dependency_path,
+            advisory_id: diagnostic.advisory.map(|a| a.id),
+            details,
+        });
+    }
+    if !seen_json {
+        return Err("cargo deny printed no JSON diagnostics".to_string());
+    }
+    report
+        .violations
+        .sort_by_key(|v| (v.severity != "error", v.check.clone()));
+    report.errors = report
+        .violations
+        .iter()
+        .filter(|v| v.severity == "error")
+        .count();
+    report.warnings = report.violations.len() - report.errors;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LICENSE: &str = r#"{"type":"diagnostic","fields":{"code":"rejected","graphs":[{"Krate":{"name":"gpl-crate","version":"1.2.0"},"parents":[{"Feature":{"crate_name":"gpl-crate","name":"default"},"parents":[{"Krate":{"name":"middle","version":"0.3.1"},"parents":[{"Krate":{"name":"app","version":"0.1.0"}}]}]}]}],"labels":[{"column":12,"line":4,"message":"rejected: license is not explicitly allowed","span":"GPL-3.0"}],"message":"failed to satisfy license requirements","notes":["GPL-3.0 - GNU General Public License v3.0 only"],"severity":"error"}}"#;
+    const DUPLICATE: &str = r#"{"type":"diagnostic","fields":{"code":"duplicate","graphs":[{"Krate":{"name":"syn","version":"1.0.109"},"parents":[{"Krate":{"name":"app","version":"0.1.0"}}]}],"labels":[],"message":"found 2 duplicate entries for crate 'syn'","notes":[],"severity":"warning"}}"#;
+    const ACCEPTED: &str = r#"{"type":"diagnostic","fields":{"code":"accepted","graphs":[],"labels":[],"message":"license requirements satisfied","notes":[],"severity":"help"}}"#;
+    const SOURCE: &str = r#"{"type":"diagnostic","fields":{"code":"source-not-allowed","graphs":[{"Krate":{"name":"forked","version":"0.1.0"}}],"labels":[{"message":"source","span":"git+https://example.com/forked"}],"message":"detected git source not specifically allowed","notes":[],"severity":"error"}}"#;
+    const LOG: &str =
+        r#"{"type":"log","fields":{"level":"WARN","message":"license-not-encountered"}}"#;
+    const SUMMARY: &str = r#"{"type":"summary","fields":{"bans":{"errors":0,"warnings":1}}}"#;
+
+    #[test]
+    fn test_parses_violations_with_paths() {
+        let output = [
+            DUPLICATE, LICENSE, ACCEPTED, SOURCE, LOG, SUMMARY, "not json",
+        ]
+        .join("\n");
+        let checks = [DenyCheck::Bans, DenyCheck::Licenses, DenyCheck::Sources];
+        let report = parse_deny_json(&output, &checks).unwrap();
+        assert_eq!((report.errors, report.warnings), (2, 1));
+        assert_eq!(report.violations.len(), 3);
+
+        let license = &report.violations[0];
+        assert_eq!(license.check, "licenses");
+        assert_eq!(license.code, "rejected");
+        assert_eq!(license.krate.as_deref(), Some("gpl-crate 1.2.0"));
+        assert_eq!(
+            license.dependency_path,
+            vec!["app 0.1.0", "middle 0.3.1", "gpl-crate 1.2.0"]
+        );
+        assert_eq!(
+            license.details,
+            vec![
+                "GPL-3.0 - rejected: license is not explicitly allowed",
+                "GPL-3.0 - GNU General Public License v3.0 only"
+            ]
+        );
+
+        assert_eq!(report.violations[1].check, "sources");
+        assert_eq!(report.violations[2].code, "duplicate");
+        assert_eq!(report.violations[2].check, "bans");
+    }
+
+    #[test]
+    fn test_renders_counts_per_check() {
+        let output = [LICENSE, DUPLICATE].join("\n");
+        let report = parse_deny_json(&output, &[DenyCheck::Bans, DenyCheck::Licenses]).unwrap();
+        let text = report.render();
+        assert!(
+            text.starts_with("Deny: 1 error(s), 1 warning(s) from bans, licenses (bans: 0 error(s), 1 warning(s); licenses: 1 error(s), 0 warning(s))"),
+            "{text}"
+        );
+        assert!(
+            text.contains(
+                "- error[licenses/rejected] failed to satisfy license requirements: gpl-crate 1.2.0\n    path: app 0.1.0 -> middle 0.3.1 -> gpl-crate 1.2.0"
+            ),
+            "{text}"
+        );
+    }
+
+    #[test]
+    fn test_unknown_codes_use_the_only_selected_check() {
+        let output = LICENSE.replace("\"rejected\"", "\"new-license-code\"");
+        let report = parse_deny_json(&output, &[DenyCheck::Licenses]).unwrap();
+        assert_eq!(report.violations[0].check, "licenses");
+        let report = parse_deny_json(&output, &[DenyCheck::Bans, DenyCheck::Licenses]).unwrap();
+        assert_eq!(report.violations[0].check, "other");
+        assert!(parse_deny_json("error: no deny.toml", &[DenyCheck::Bans]).is_err());
+    }
+}
@@ -0,0 +1,1024 @@
+//! Structured compiler diagnostics from cargo's JSON message stream
+//!
+//! `build`, `check`, `clippy` and `test` run cargo with `--message-format=json`. Cargo then
+//! writes one JSON record per line to stdout (`compiler-message`, `compiler-artifact`,
+//! `build-finished`) interleaved with ordinary output such as test results. This module turns
+//! those records into a [`CargoReport`] that is returned as MCP `structuredContent`, and rebuilds
+//! the human-readable text from each diagnostic's `rendered` field for clients that only read
+//! text.
+//!
+//! Each distinct rustc error code gets a one-paragraph excerpt of its explanation in
+//! [`CargoReport::explanations`]; see [`crate::explain`].
+//!
+//! The store also keeps the last diagnostic set per [`DiagnosticScope`] (working directory,
+//! tool and target selection) so each new result can report a [`DiagnosticDelta`]: which
+//! diagnostics were fixed, which are new and which remain.
+
+use crate::audit::AuditReport;
+use crate::coverage::CoverageReport;
+use crate::deny::DenyReport;
+use crate::explain::{ExplanationCache, excerpt, is_error_code};
+use crate::lints::LintSummary;
+use crate::semver::SemverReport;
+use crate::test_results::{FlakyReport, KnownFlaky, ListedTest, Panic, TestReport};
+use crate::timings::TimingReport;
+use crate::unused_deps::UnusedDepsReport;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::time::SystemTime;
+use tokio::sync::RwLock;
+
+/// Message format passed to cargo; `rendered` fields stay free of ANSI escapes
+pub const MESSAGE_FORMAT_ARG: &str = "--message-format=json";
+
+/// Number of reports kept for lookup by operation ID
+const MAX_STORED_REPORTS: usize = 64;
+
+/// Source location of a diagnostic or suggestion
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DiagnosticSpan {
+    pub file: String,
+    pub line_start: usize,
+    pub line_end: usize,
+    pub column_start: usize,
+    pub column_end: usize,
+    pub byte_start: usize,
+    pub byte_end: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// A replacement rustc proposes for a span
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Suggestion {
+    /// The help message the suggestion belongs to
+    pub message: String,
+    pub span: DiagnosticSpan,
+    pub replacement: String,
+    /// `MachineApplicable`, `MaybeIncorrect`, `HasPlaceholders` or `Unspecified`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub applicability: Option<String>,
+}
+
+/// A note or help attached to a diagnostic
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DiagnosticChild {
+    pub level: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub span: Option<DiagnosticSpan>,
+}
+
+/// One compiler error, warning or lint
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// `error`, `warning`, `note`, `help` or `failure-note`
+    pub level: String,
+    /// Error code or lint name, e.g. `E0308` or `clippy::needless_return`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    pub message: String,
+    /// Primary span, if the diagnostic points at source
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub span: Option<DiagnosticSpan>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<DiagnosticChild>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<Suggestion>,
+    /// Human-readable rendering as rustc prints it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rendered: Option<String>,
+    /// Package name and version, e.g. `demo 0.1.0`
+    pub package: String,
+    /// Target name and kind, e.g. `demo (lib)`
+    pub target: String,
+}
+
+/// A compiled unit reported by cargo
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Artifact {
+    pub package: String,
+    pub target: String,
+    /// Whether the unit was up to date and not rebuilt
+    pub fresh: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub executable: Option<String>,
+}
+
+/// Everything parsed from one operation's cargo output
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CargoReport {
+    /// `build-finished.success`; `None` if cargo never got that far
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+    pub errors: usize,
+    pub warnings: usize,
+    pub diagnostics: Vec<Diagnostic>,
+    pub artifacts: Vec<Artifact>,
+    /// Changes against the previous run in the same scope, if there was one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delta: Option<DiagnosticDelta>,
+    /// Per-test results of `test` and `nextest` runs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tests: Option<TestReport>,
+    /// Pass rates over repeated runs of `detect_flaky`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flaky: Option<FlakyReport>,
+    /// Test inventory of `list_tests`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test_list: Option<Vec<ListedTest>>,
+    /// Panics in the program started by `run`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub panics: Vec<Panic>,
+    /// Where the time of a `timings: true` build or check went
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timings: Option<TimingReport>,
+    /// Advisories found by `audit`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audit: Option<AuditReport>,
+    /// Policy violations found by `deny`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deny: Option<DenyReport>,
+    /// Breaking changes and the required release type found by `semver_checks`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub semver: Option<SemverReport>,
+    /// Declared but unused dependencies found by `unused_deps`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unused_deps: Option<UnusedDepsReport>,
+    /// Per-lint counts of a clippy run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lints: Option<LintSummary>,
+    /// Per-file coverage of a `coverage` run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<CoverageReport>,
+    /// First paragraph of `rustc --explain` per distinct error code
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub explanations: BTreeMap<String, String>,
+}
+
+impl CargoReport {
+    /// Whether the stream contained any compiler records or tool reports at all
+    pub fn is_empty(&self) -> bool {
+        self.success.is_none()
+            && self.diagnostics.is_empty()
+            && self.artifacts.is_empty()
+            && self.tests.is_none()
+            && self.flaky.is_none()
+            && self.test_list.is_none()
+            && self.panics.is_empty()
+            && self.timings.is_none()
+            && self.audit.is_none()
+            && self.deny.is_none()
+            && self.semver.is_none()
+            && self.unused_deps.is_none()
+            && self.lints.is_none()
+            && self.coverage.is_none()
+            && self.explanations.is_empty()
+    }
+
+    /// Short count line, e.g. "1 error, 2 warnings"
+    pub fn summary(&self) -> String {
+        format!(
+            "{} error{}, {} warning{}",
+            self.errors,
+            if self.errors == 1 { "" } else { "s" },
+            self.warnings,
+            if self.warnings == 1 { "" } else { "s" },
+        )
+    }
+}
+
+impl Diagnostic {
+    /// Errors and warnings; notes and help messages are not tracked across runs
+    fn is_tracked(&self) -> bool {
+        self.level == "warning" || self.level.starts_with("error")
+    }
+
+    /// Identity across runs. Lines are left out because edits elsewhere in a file shift them.
+    fn identity(&self) -> (&str, Option<&str>, &str, Option<&str>) {
+        (
+            &self.level,
+            self.code.as_deref(),
+            &self.message,
+            self.span.as_ref().map(|s| s.file.as_str()),
+        )
+    }
+
+    /// Whether two diagnostics are the same report emitted for different targets of a package
+    fn is_duplicate_of(&self, other: &Diagnostic) -> bool {
+        self.package == other.package
+            && self.level == other.level
+            && self.code == other.code
+            && self.message == other.message
+            && self.span == other.span
+            && self.rendered == other.rendered
+    }
+}
+
+/// Which runs are compared with each other: same directory, tool and target selection
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DiagnosticScope {
+    pub working_directory: String,
+    pub tool: String,
+    /// Normalized cargo arguments that select packages, targets, features and profile
+    pub selection: String,
+}
+
+impl DiagnosticScope {
+    /// Build a scope from the cargo arguments of a run. Arguments that do not change what is
+    /// compiled (message format, timings, job count, target dir, test-binary arguments) are
+    /// ignored.
+    pub fn new<I, S>(tool: &str, working_directory: &str, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut selection: Vec<String> = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let arg = arg.as_ref();
+            match arg {
+                "--" => break,
+                "--jobs" | "-j" | "--target-dir" => {
+                    args.next();
+                }
+                _ if arg.starts_with("--message-format")
+                    || arg.starts_with("--timings")
+                    || arg.starts_with("--jobs=")
+                    || arg.starts_with("--target-dir=") => {}
+                _ => selection.push(arg.to_string()),
+            }
+        }
+        Self {
+            working_directory: working_directory.to_string(),
+            tool: tool.to_string(),
+            selection: selection.join(" "),
+        }
+    }
+}
+
+/// A diagnostic as listed in a delta
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeltaEntry {
+    pub level: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    pub message: String,
+    /// Line in the run the entry was taken from (the previous run for fixed entries)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+}
+
+impl From<&Diagnostic> for DeltaEntry {
+    fn from(d: &Diagnostic) -> Self {
+        Self {
+            level: d.level.clone(),
+            code: d.code.clone(),
+            message: d.message.clone(),
+            line: d.span.as_ref().map(|s| s.line_start),
+        }
+    }
+}
+
+impl DeltaEntry {
+    fn render(&self) -> String {
+        let code = self
+            .code
+            .as_ref()
+            .map(|c| format!("[{c}]"))
+            .unwrap_or_default();
+        let line = self
+            .line
+            .map(|l| format!(" (line {l})"))
+            .unwrap_or_default();
+        format!("{}{code}: {}{line}", self.level, self.message)
+    }
+}
+
+/// Changes within one file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileDelta {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fixed: Vec<DeltaEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub new: Vec<DeltaEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unchanged: Vec<DeltaEntry>,
+}
+
+/// Key used for diagnostics without a source location
+const NO_FILE: &str = "(no location)";
+
+/// Errors and warnings fixed, introduced and unchanged since the previous run in a scope
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiagnosticDelta {
+    pub previous_operation_id: String,
+    pub fixed: usize,
+    pub new: usize,
+    pub unchanged: usize,
+    /// Per-file changes keyed by path, in path order
+    pub files: BTreeMap<String, FileDelta>,
+}
+
+impl DiagnosticDelta {
+    /// Compare two runs. Diagnostics match on level, code, message and file; each previous
+    /// diagnostic matches at most one current one, so repeated diagnostics are counted.
+    pub fn between(
+        previous_operation_id: &str,
+        previous: &[Diagnostic],
+        current: &[Diagnostic],
+    ) -> Self {
+        let mut delta = Self {
+            previous_operation_id: previous_operation_id.to_string(),
+            ..Self::default()
+        };
+        let mut remaining: Vec<&Diagnostic> = previous.iter().filter(|d| d.is_tracked()).collect();
+        let file_of = |d: &Diagnostic| {
+            d.span
+                .as_ref()
+                .map(|s| s.file.clone())
+                .unwrap_or_else(|| NO_FILE.to_string())
+        };
+
+        for diagnostic in current.iter().filter(|d| d.is_tracked()) {
+            let file = delta.files.entry(file_of(diagnostic)).or_default();
+            match remaining
+                .iter()
+                .position(|p| p.identity() == diagnostic.identity())
+            {
+                Some(index) => {
+                    remaining.remove(index);
+                    file.unchanged.push(diagnostic.into());
+                    delta.unchanged += 1;
+                }
+                None => {
+                    file.new.push(diagnostic.into());
+                    delta.new += 1;
+                }
+            }
+        }
+        for diagnostic in remaining {
+            let file = delta.files.entry(file_of(diagnostic)).or_default();
+            file.fixed.push(diagnostic.into());
+            delta.fixed += 1;
+        }
+        delta
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fixed + self.new + self.unchanged == 0
+    }
+
+    /// Text section grouped by file, e.g.
+    /// "Changes since op_check_1: 1 fixed, 0 new, 1 unchanged" followed by one line per entry
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "Changes since {}: {} fixed, {} new, {} unchanged",
+            self.previous_operation_id, self.fixed, self.new, self.unchanged
+        );
+        for (file, changes) in &self.files {
+            out.push_str(&format!("\n  {file}"));
+            for (marker, entries) in [
+                ("- fixed", &changes.fixed),
+                ("+ new", &changes.new),
+                ("= unchanged", &changes.unchanged),
+            ] {
+                for entry in entries {
+                    out.push_str(&format!("\n    {marker} {}", entry.render()));
+                }
+            }
+        }
+        out
+    }
+}
+
+// Raw cargo/rustc JSON shapes; only the fields we use
+
+#[derive(Deserialize)]
+struct RawMessage {
+    reason: String,
+    #[serde(default)]
+    package_id: String,
+    target: Option<RawTarget>,
+    message: Option<RawDiagnostic>,
+    #[serde(default)]
+    fresh: bool,
+    executable: Option<String>,
+    success: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct RawTarget {
+    name: String,
+    #[serde(default)]
+    kind: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct RawDiagnostic {
+    message: String,
+    level: String,
+    code: Option<RawCode>,
+    #[serde(default)]
+    spans: Vec<RawSpan>,
+    #[serde(default)]
+    children: Vec<RawDiagnostic>,
+    rendered: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawCode {
+    code: String,
+    explanation: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawSpan {
+    file_name: String,
+    byte_start: usize,
+    byte_end: usize,
+    line_start: usize,
+    line_end: usize,
+    column_start: usize,
+    column_end: usize,
+    #[serde(default)]
+    is_primary: bool,
+    label: Option<String>,
+    suggested_replacement: Option<String>,
+    suggestion_applicability: Option<String>,
+}
+
+impl RawSpan {
+    fn to_span(&self) -> DiagnosticSpan {
+        DiagnosticSpan {
+            file: self.file_name.clone(),
+            line_start: self.line_start,
+            line_end: self.line_end,
+            column_start: self.column_start,
+            column_end: self.column_end,
+            byte_start: self.byte_start,
+            byte_end: self.byte_end,
+            label: self.label.clone(),
+        }
+    }
+}
+
+fn primary_span(spans: &[RawSpan]) -> Option<DiagnosticSpan> {
+    spans
+        .iter()
+        .find(|s| s.is_primary)
+        .or_else(|| spans.first())
+        .map(RawSpan::to_span)
+}
+
+/// Collect suggested replacements from a diagnostic's children, recursively
+fn collect_suggestions(children: &[RawDiagnostic], out: &mut Vec<Suggestion>) {
+    for child in children {
+        for span in &child.spans {
+            if let Some(replacement) = &span.suggested_replacement {
+                out.push(Suggestion {
+                    message: child.message.clone(),
+                    span: span.to_span(),
+                    replacement: replacement.clone(),
+                    applicability: span.suggestion_applicability.clone(),
+                });
+            }
+        }
+        collect_suggestions(&child.children, out);
+    }
+}
+
+/// `path+file:///tmp/demo#0.1.0` or `registry+...#serde@1.0.0` -> `demo 0.1.0` / `serde 1.0.0`
+fn package_name(package_id: &str) -> String {
+    let Some((source, fragment)) = package_id.rsplit_once('#') else {
+        return package_id.to_string();
+    };
+    match fragment.split_once('@') {
+        Some((name, version)) => format!("{name} {version}"),
+        None => {
+            let name = source.rsplit('/').next().unwrap_or(source);
+            format!("{name} {fragment}")
+        }
+    }
+}
+
+fn target_label(target: &Option<RawTarget>) -> String {
+    match target {
+        Some(t) if !t.kind.is_empty() => format!("{} ({})", t.name, t.kind.join(", ")),
+        Some(t) => t.name.clone(),
+        None => String::new(),
+    }
+}
+
+/// Messages that only summarize other diagnostics
+fn is_summary_message(level: &str, message: &str) -> bool {
+    level == "failure-note"
+        || message.starts_with("aborting due to")
+        || (level == "warning" && message.ends_with("emitted"))
+}
+
+/// Parse cargo's JSON stdout. Returns the report and the text a human would have seen on
+/// stdout: each diagnostic's rendering plus all non-JSON lines, in their original order.
+pub fn parse_cargo_messages(stdout: &str) -> (CargoReport, String) {
+    let mut parser = CargoMessageParser::default();
+    let text: Vec<String> = stdout
+        .lines()
+        .filter_map(|line| parser.line(line))
+        .collect();
+    (parser.finish(), text.join("\n"))
+}
+
+/// [`parse_cargo_messages`] one line at a time, for output read while cargo runs
+#[derive(Debug, Default)]
+pub struct CargoMessageParser {
+    report: CargoReport,
+}
+
+impl CargoMessageParser {
+    /// Take one line of cargo's stdout into the report and return the text a human would have
+    /// seen for it, if any
+    pub fn line(&mut self, line: &str) -> Option<String> {
+        let report = &mut self.report;
+        let trimmed = line.trim_start();
+        let raw = if trimmed.starts_with("{\"reason\":") {
+            serde_json::from_str::<RawMessage>(trimmed).ok()
+        } else {
+            None
+        };
+        let Some(raw) = raw else {
+            return Some(line.to_string());
+        };
+
+        match raw.reason.as_str() {
+            "compiler-message" => {
+                let msg = raw.message?;
+                if is_summary_message(&msg.level, &msg.message) {
+                    return msg.rendered.map(|rendered| rendered.trim_end().to_string());
+                }
+                let mut suggestions = Vec::new();
+                collect_suggestions(&msg.children, &mut suggestions);
+                if let Some(RawCode {
+                    code,
+                    explanation: Some(explanation),
+                }) = &msg.code
+                    && is_error_code(code)
+                {
+                    report
+                        .explanations
+                        .entry(code.clone())
+                        .or_insert_with(|| excerpt(explanation));
+                }
+                let diagnostic = Diagnostic {
+                    level: msg.level,
+                    code: msg.code.map(|c| c.code),
+                    message: msg.message,
+                    span: primary_span(&msg.spans),
+                    children: msg
+                        .children
+                        .iter()
+                        .map(|c| DiagnosticChild {
+                            level: c.level.clone(),
+                            message: c.message.clone(),
+                            span: primary_span(&c.spans),
+                        })
+                        .collect(),
+                    suggestions,
+                    rendered: msg.rendered,
+                    package: package_name(&raw.package_id),
+                    target: target_label(&raw.target),
+                };
+                // The lib and its unit-test build report the same diagnostic twice
+                if report
+                    .diagnostics
+                    .iter()
+                    .any(|d| diagnostic.is_duplicate_of(d))
+                {
+                    return None;
+                }
+                let rendered = diagnostic
+                    .rendered
+                    .as_ref()
+                    .map(|rendered| rendered.trim_end().to_string());
+                match diagnostic.level.as_str() {
+                    "error" | "error: internal compiler error" => report.errors += 1,
+                    "warning" => report.warnings += 1,
+                    _ => {}
+                }
+                report.diagnostics.push(diagnostic);
+                return rendered;
+            }
+            "compiler-artifact" => report.artifacts.push(Artifact {
+                package: package_name(&raw.package_id),
+                target: target_label(&raw.target),
+                fresh: raw.fresh,
+                executable: raw.executable,
+            }),
+            "build-finished" => report.success = raw.success,
+            // build-script-executed and future record types carry nothing we report
+            _ => {}
+        }
+        None
+    }
+
+    /// The report of all lines taken so far
+    pub fn finish(self) -> CargoReport {
+        self.report
+    }
+}
+
+/// Whether user-supplied cargo arguments already choose a message format
+pub fn has_message_format(args: &Option<Vec<String>>) -> bool {
+    args.as_ref()
+        .is_some_and(|args| args.iter().any(|a| a.starts_with("--message-format")))
+}
+
+/// Recent reports by operation ID, for structured results and follow-up tools
+#[derive(Debug, Default)]
+pub struct DiagnosticsStore {
+    reports: RwLock<VecDeque<(String, CargoReport, SystemTime)>>,
+    /// Last operation ID and diagnostics per scope, the baseline for the next delta
+    baselines: RwLock<HashMap<DiagnosticScope, (String, Vec<Diagnostic>)>>,
+    /// Tests detect_flaky saw both pass and fail, by working directory and test name
+    known_flaky: RwLock<HashMap<(String, String), KnownFlaky>>,
+    explanations: ExplanationCache,
+    /// Set for stores of runs the user does not see, which must not move their baselines
+    without_deltas: bool,
+}
+
+impl DiagnosticsStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A store that keeps reports but never computes a delta or records a baseline, for
+    /// background runs whose results may never be shown
+    pub fn without_deltas() -> Self {
+        Self {
+            without_deltas: true,
+            ..Self::default()
+        }
+    }
+
+    /// Full `rustc --explain` text of an error code for the toolchain of `working_directory`
+    pub async fn explain(&self, working_directory: &str, code: &str) -> Result<String, String> {
+        self.explanations.get(working_directory, code).await
+    }
+
+    /// Add excerpts for error codes whose JSON messages carried no explanation
+    pub async fn fill_explanations(&self, working_directory: &str, report: &mut CargoReport) {
+        let missing: BTreeSet<String> = report
+            .diagnostics
+            .iter()
+            .filter_map(|d| d.code.clone())
+            .filter(|code| is_error_code(code) && !report.explanations.contains_key(code))
+            .collect();
+        for code in missing {
+            if let Ok(explanation) = self.explain(working_directory, &code).await {
+                report.explanations.insert(code, excerpt(&explanation));
+            }
+        }
+    }
+
+    /// Store the report for an operation, evicting the oldest beyond the retention limit
+    pub async fn record(&self, operation_id: &str, report: CargoReport) {
+        let mut reports = self.reports.write().await;
+        reports.retain(|(id, _, _)| id != operation_id);
+        reports.push_back((operation_id.to_string(), report, SystemTime::now()));
+        while reports.len() > MAX_STORED_REPORTS {
+            reports.pop_front();
+        }
+    }
+
+    /// Attach test results to an operation's report, creating the report if the run produced
+    /// no compiler messages (as with nextest)
+    pub async fn record_tests(&self, operation_id: &str, tests: TestReport) {
+        self.update(operation_id, |report| report.tests = Some(tests))
+            .await;
+    }
+
+    /// Attach a `--timings` analysis to an operation's report
+    pub async fn record_timings(&self, operation_id: &str, timings: TimingReport) {
+        self.update(operation_id, |report| report.timings = Some(timings))
+            .await;
+    }
+
+    /// Attach parsed `cargo audit` advisories to an operation's report
+    pub async fn record_audit(&self, operation_id: &str, audit: AuditReport) {
+        self.update(operation_id, |report| report.audit = Some(audit))
+            .await;
+    }
+
+    /// Attach parsed `cargo deny` violations to an operation's report
+    pub async fn record_deny(&self, operation_id: &str, deny: DenyReport) {
+        self.update(operation_id, |report| report.deny = Some(deny))
+            .await;
+    }
+
+    /// Attach a parsed `cargo semver-checks` report to an operation's report
+    pub async fn record_semver(&self, operation_id: &str, semver: SemverReport) {
+        self.update(operation_id, |report| report.semver = Some(semver))
+            .await;
+    }
+
+    /// Attach the unused dependencies found by cargo-machete or cargo-udeps to an operation's report
+    pub async fn record_unused_deps(&self, operation_id: &str, unused_deps: UnusedDepsReport) {
+        self.update(operation_id, |report| {
+            report.unused_deps = Some(unused_deps)
+        })
+        .await;
+    }
+
+    /// Attach a clippy lint summary to an operation's report
+    pub async fn record_lints(&self, operation_id: &str, lints: LintSummary) {
+        self.update(operation_id, |report| report.lints = Some(lints))
+            .await;
+    }
+
+    /// Attach a coverage report to an operation's report
+    pub async fn record_coverage(&self, operation_id: &str, coverage: CoverageReport) {
+        self.update(operation_id, |report| report.coverage = Some(coverage))
+            .await;
+    }
+
+    /// Change an operation's report in place, creating an empty one first if there is none
+    async fn update(&self, operation_id: &str, change: impl FnOnce(&mut CargoReport)) {
+        let mut reports = self.reports.write().await;
+        if let Some((_, report, _)) = reports.iter_mut().find(|(id, _, _)| id == operation_id) {
+            change(report);
+            return;
+        }
+        drop(reports);
+        let mut report = CargoReport::default();
+        change(&mut report);
+        self.record(operation_id, report).await;
+    }
+
+    /// Store a detect_flaky report and update the known-flaky tests of `working_directory`:
+    /// flaky tests are added, tests that behaved consistently are cleared
+    pub async fn record_flaky(
+        &self,
+        working_directory: &str,
+        operation_id: &str,
+        report: FlakyReport,
+    ) {
+        {
+            let mut known = self.known_flaky.write().await;
+            for test in &report.tests {
+                let key = (working_directory.to_string(), test.name.clone());
+                if test.flaky {
+                    known.insert(
+                        key,
+                        KnownFlaky {
+                            passed: test.passed,
+                            runs: test.passed + test.failed,
+                            operation_id: operation_id.to_string(),
+                        },
+                    );
+                } else {
+                    known.remove(&key);
+                }
+            }
+        }
+        let report = CargoReport {
+            flaky: Some(report),
+            ..CargoReport::default()
+        };
+        self.record(operation_id, report).await;
+    }
+
+    /// Mark the tests of a run that are known to be flaky in `working_directory`
+    pub async fn annotate_flaky(&self, working_directory: &str, tests: &mut TestReport) {
+        let known = self.known_flaky.read().await;
+        if known.is_empty() {
+            return;
+        }
+        for test in &mut tests.tests {
+            test.known_flaky = known
+                .get(&(working_directory.to_string(), test.name.clone()))
+                .cloned();
+        }
+    }
+
+    /// Compare a report with the previous one in the same scope and make it the new baseline.
+    /// Returns `None` for the first run in a scope or when neither run had any diagnostics.
+    pub async fn delta(
+        &self,
+        scope: &DiagnosticScope,
+        operation_id: &str,
+        report: &CargoReport,
+    ) -> Option<DiagnosticDelta> {
+        if self.without_deltas {
+            return None;
+        }
+        let previous = self.baselines.write().await.insert(
+            scope.clone(),
+            (operation_id.to_string(), report.diagnostics.clone()),
+        );
+        let (previous_id, previous) = previous?;
+        let delta = DiagnosticDelta::between(&previous_id, &previous, &report.diagnostics);
+        (!delta.is_empty()).then_some(delta)
+    }
+
+    pub async fn get(&self, operation_id: &str) -> Option<CargoReport> {
+        self.get_with_time(operation_id)
+            .await
+            .map(|(report, _)| report)
+    }
+
+    /// The most recent `semver_checks` report for `working_directory` and its operation id
+    pub async fn latest_semver(&self, working_directory: &str) -> Option<(String, SemverReport)> {
+        self.reports
+            .read()
+            .await
+            .iter()
+            .rev()
+            .filter_map(|(id, report, _)| Some((id, report.semver.as_ref()?)))
+            .find(|(_, semver)| semver.working_directory == working_directory)
+            .map(|(id, semver)| (id.clone(), semver.clone()))
+    }
+
+    /// The report for an operation and when it was recorded
+    pub async fn get_with_time(&self, operation_id: &str) -> Option<(CargoReport, SystemTime)> {
+        self.reports
+            .read()
+            .await
+            .iter()
+            .find(|(id, _, _)| id == operation_id)
+            .map(|(_, report, at)| (report.clone(), *at))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WARNING: &str = r#"{"reason":"compiler-message","package_id":"path+file:///tmp/demo#0.1.0","target":{"kind":["bin"],"name":"demo"},"message":{"rendered":"warning: unused variable: `x`\n --> src/main.rs:2:9\n\n","children":[{"children":[],"code":null,"level":"help","message":"if this is intentional, prefix it with an underscore","rendered":null,"spans":[{"byte_end":21,"byte_start":20,"column_end":10,"column_start":9,"file_name":"src/main.rs","is_primary":true,"label":null,"line_end":2,"line_start":2,"suggested_replacement":"_x","suggestion_applicability":"MachineApplicable"}]}],"level":"warning","message":"unused variable: `x`","spans":[{"byte_end":21,"byte_start":20,"column_end":10,"column_start":9,"file_name":"src/main.rs","is_primary":true,"label":null,"line_end":2,"line_start":2,"suggested_replacement":null,"suggestion_applicability":null}],"code":{"code":"unused_variables","explanation":null}}}"#;
+    const ERROR: &str = r#"{"reason":"compiler-message","package_id":"registry+https://github.com/rust-lang/crates.io-index#demo@0.2.0","target":{"kind":["lib"],"name":"demo"},"message":{"rendered":"error[E0308]: mismatched types\n","children":[],"level":"error","message":"mismatched types","spans":[{"byte_end":32,"byte_start":29,"column_end":21,"column_start":18,"file_name":"src/lib.rs","is_primary":false,"label":"expected due to this","line_end":2,"line_start":2},{"byte_end":32,"byte_start":29,"column_end":21,"column_start":18,"file_name":"src/lib.rs","is_primary":true,"label":"expected `u32`, found `&str`","line_end":2,"line_start":2}],"code":{"code":"E0308","explanation":"..."}}}"#;
+    const NOTE: &str = r#"{"reason":"compiler-message","package_id":"path+file:///tmp/demo#0.1.0","target":{"kind":["lib"],"name":"demo"},"message":{"rendered":"For more information about this error, try `rustc --explain E0308`.\n","children":[],"level":"failure-note","message":"For more information about this error, try `rustc --explain E0308`.","spans":[],"code":null}}"#;
+    const ARTIFACT: &str = r#"{"reason":"compiler-artifact","package_id":"path+file:///tmp/demo#0.1.0","target":{"kind":["bin"],"name":"demo"},"fresh":true,"executable":"/tmp/demo/target/debug/demo"}"#;
+    const FINISHED: &str = r#"{"reason":"build-finished","success":false}"#;
+
+    #[test]
+    fn test_parse_diagnostics_and_suggestions() {
+        let stdout = [WARNING, ERROR, NOTE, ARTIFACT, FINISHED].join("\n");
+        let (report, text) = parse_cargo_messages(&stdout);
+
+        assert_eq!(report.success, Some(false));
+        assert_eq!((report.errors, report.warnings), (1, 1));
+        assert_eq!(report.diagnostics.len(), 2);
+
+        let warning = &report.diagnostics[0];
+        assert_eq!(warning.code.as_deref(), Some("unused_variables"));
+        assert_eq!(warning.package, "demo 0.1.0");
+        assert_eq!(warning.target, "demo (bin)");
+        assert_eq!(warning.suggestions.len(), 1);
+        assert_eq!(warning.suggestions[0].replacement, "_x");
+        assert_eq!(
+            warning.suggestions[0].applicability.as_deref(),
+            Some("MachineApplicable")
+        );
+
+        let error = &report.diagnostics[1];
+        assert_eq!(error.package, "demo 0.2.0");
+        let span = error.span.as_ref().unwrap();
+        assert_eq!((span.line_start, span.column_start), (2, 18));
+        assert_eq!(span.label.as_deref(), Some("expected `u32`, found `&str`"));
+
+        assert_eq!(report.artifacts.len(), 1);
+        assert!(report.artifacts[0].fresh);
+
+        assert!(text.starts_with("warning: unused variable: `x`\n --> src/main.rs:2:9"));
+        assert!(text.contains("error[E0308]: mismatched types"));
+        assert!(text.contains("rustc --explain E0308"));
+        assert!(!text.contains("\"reason\""));
+    }
+
+    #[test]
+    fn test_non_json_lines_are_kept_in_order() {
+        let stdout = format!("running 1 test\n{ARTIFACT}\ntest tests::a ... ok");
+        let (report, text) = parse_cargo_messages(&stdout);
+        assert_eq!(text, "running 1 test\ntest tests::a ... ok");
+        assert_eq!(report.artifacts.len(), 1);
+        assert_eq!(report.summary(), "0 errors, 0 warnings");
+    }
+
+    #[test]
+    fn test_report_with_tool_results_is_not_empty() {
+        assert!(CargoReport::default().is_empty());
+        let deny = CargoReport {
+            deny: Some(DenyReport::default()),
+            ..CargoReport::default()
+        };
+        assert!(!deny.is_empty());
+        let semver = CargoReport {
+            semver: Some(SemverReport::default()),
+            ..CargoReport::default()
+        };
+        assert!(!semver.is_empty());
+        let unused_deps = CargoReport {
+            unused_deps: Some(UnusedDepsReport {
+                tool: crate::unused_deps::UnusedDepsTool::Machete,
+                dependencies: Vec::new(),
+            }),
+            ..CargoReport::default()
+        };
+        assert!(!unused_deps.is_empty());
+        let explanations = CargoReport {
+            explanations: BTreeMap::from([("E0308".to_string(), "mismatched types".to_string())]),
+            ..CargoReport::default()
+        };
+        assert!(!explanations.is_empty());
+    }
+
+    #[test]
+    fn test_has_message_format() {
+        assert!(!has_message_format(&None));
+        assert!(has_message_format(&Some(vec![
+            "--message-format=short".to_string()
+        ])));
+    }
+
+    #[test]
+    fn test_duplicate_target_diagnostics_are_merged() {
+        let test_target = WARNING.replace(r#""kind":["bin"]"#, r#""kind":["test"]"#);
+        let stdout = [WARNING, test_target.as_str(), FINISHED].join("\n");
+        let (report, text) = parse_cargo_messages(&stdout);
+        assert_eq!(report.warnings, 1);
+        assert_eq!(report.diagnostics.len(), 1);
+        assert_eq!(text.matches("unused variable").count(), 1);
+    }
+
+    #[test]
+    fn test_scope_ignores_output_only_arguments() {
+        let a = DiagnosticScope::new(
+            "build",
+            "/tmp/demo",
+            ["--message-format=json", "--lib", "--jobs", "4", "--release"],
+        );
+        let b = DiagnosticScope::new("build", "/tmp/demo", ["--lib", "--release"]);
+        assert_eq!(a, b);
+        assert_eq!(a.selection, "--lib --release");
+        assert_ne!(a, DiagnosticScope::new("build", "/tmp/demo", ["--tests"]));
+    }
+
+    #[tokio::test]
+    async fn test_delta_against_previous_run() {
+        let store = DiagnosticsStore::new();
+        let scope = DiagnosticScope::new("check", "/tmp/demo", Vec::<String>::new());
+        let (first, _) = parse_cargo_messages(&[WARNING, ERROR, FINISHED].join("\n"));
+        assert!(store.delta(&scope, "op_check_1", &first).await.is_none());
+
+        // The error is fixed; the warning moved down a line and a new warning appeared
+        let moved = WARNING.replace(r#""line_start":2"#, r#""line_start":3"#);
+        let new = WARNING.replace("unused variable: `x`", "unused variable: `y`");
+        let (second, _) = parse_cargo_messages(&[moved.as_str(), new.as_str()].join("\n"));
+        let delta = store.delta(&scope, "op_check_2", &second).await.unwrap();
+
+        assert_eq!((delta.fixed, delta.new, delta.unchanged), (1, 1, 1));
+        assert_eq!(delta.previous_operation_id, "op_check_1");
+        assert_eq!(
+            delta.files["src/lib.rs"].fixed[0].code.as_deref(),
+            Some("E0308")
+        );
+        let main = &delta.files["src/main.rs"];
+        assert_eq!(main.unchanged[0].line, Some(3));
+        assert_eq!(main.new[0].message, "unused variable: `y`");
+
+        let text = delta.render();
+        assert!(text.starts_with("Changes since op_check_1: 1 fixed, 1 new, 1 unchanged"));
+        assert!(
+            text.contains("\n  src/lib.rs\n    - fixed error[E0308]: mismatched types (line 2)")
+        );
+
+        // Other scopes keep their own baseline
+        let other = DiagnosticScope::new("clippy", "/tmp/demo", Vec::<String>::new());
+        assert!(store.delta(&other, "op_clippy_3", &second).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_store_without_deltas_keeps_no_baseline() {
+        let store = DiagnosticsStore::without_deltas();
+        let scope = DiagnosticScope::new("check", "/tmp/demo", Vec::<String>::new());
+        let (report, _) = parse_cargo_messages(&[WARNING, FINISHED].join("\n"));
+        assert!(store.delta(&scope, "op_check_1", &report).await.is_none());
+        assert!(store.delta(&scope, "op_check_2", &report).await.is_none());
+        assert!(store.baselines.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_store_evicts_oldest() {
+        let store = DiagnosticsStore::new();
+        for i in 0..MAX_STORED_REPORTS + 1 {
+            store
+                .record(&format!("op_{i}"), CargoReport::default())
+                .await;
+        }
+        assert!(store.get("op_0").await.is_none());
+        assert!(
+            store
+                .get(&format!("op_{MAX_STORED_REPORTS}"))
+                .await
+                .is_some()
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! Outlines of `cargo expand` output
+//!
+//! Expanding a whole crate easily produces thousands of lines, most of them derive and format
+//! machinery the agent is not debugging. When the expanded source is longer than
+//! [`MAX_INLINE_EXPANDED_LINES`] and no `item` was requested, `expand` saves the source for
+//! `get_output` and returns an outline of the expanded items with their line ranges, so the agent
+//! can ask for the one item it needs. The outline relies on the 4-space indentation `cargo expand`
+//! formats its output with.
+
+/// Expanded source longer than this is outlined unless an item was requested
+pub const MAX_INLINE_EXPANDED_LINES: usize = 400;
+
+/// Outline entries listed before the rest are counted
+const MAX_OUTLINE_ITEMS: usize = 200;
+
+/// A module-level item of the expanded source
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpandedItem {
+    /// Path within the crate, e.g. `parser::Token`; impls are named like `impl Display for Token`
+    pub path: String,
+    pub kind: &'static str,
+    /// 1-based, inclusive
+    pub first_line: usize,
+    pub last_line: usize,
+}
+
+/// Module-level items of expanded source, in order, including those of nested modules
+pub fn outline(source: &str) -> Vec<ExpandedItem> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut modules: Vec<String> = Vec::new();
+    let mut items = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        let depth = indent / 4;
+        modules.truncate(depth);
+        if depth != modules.len() {
+            continue;
+        }
+        let Some((kind, name)) = parse_item(trimmed) else {
+            continue;
+        };
+        let last_line = if trimmed.ends_with(';') || trimmed.ends_with('}') {
+            index
+        } else {
+            lines[index + 1..]
+                .iter()
+                .position(|l| {
+                    l.len() - l.trim_start().len() == indent
+                        && (l.trim_start().starts_with('}') || l.trim_start().starts_with(')'))
+                })
+                .map_or(index, |offset| index + 1 + offset)
+        };
+        let path = modules
+            .iter()
+            .map(String::as_str)
+            .chain([name.as_str()])
+            .collect::<Vec<_>>()
+            .join("::");
+        items.push(ExpandedItem {
+            path,
+            kind,
+            first_line: index + 1,
+            last_line: last_line + 1,
+        });
+        if kind == "mod" && trimmed.ends_with('{') {
+            modules.push(name);
+        }
+    }
+    items
+}
+
+/// The kind and name of the item declared by a line, if it declares one
+fn parse_item(line: &str) -> Option<(&'static str, String)> {
+    let mut rest = line;
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("pub(") {
+            rest = &after[after.find(')')? + 1..];
+        } else if let Some(after) = ["pub ", "default ", "async ", "unsafe "]
+            .iter()
+            .find_map(|q| rest.strip_prefix(q))
+        {
+            rest = after;
+        } else if rest.starts_with("extern \"") {
+            let after = &rest["extern \"".len()..];
+            rest = &after[after.find('"')? + 1..];
+        } else if let Some(after) = rest.strip_prefix("const ")
+            && ["fn ", "unsafe ", "async ", "extern "]
+                .iter()
+                .any(|q| after.starts_with(q))
+        {
+            rest = after;
+        } else {
+            break;
+        }
+    }
+
+    if let Some(after) = rest.strip_prefix("impl") {
+        let after = skip_generics(after)?;
+        let header = after.split(" where").next()?.trim_end_matches('{').trim();
+        return (!header.is_empty()).then(|| ("impl", format!("impl {header}")));
+    }
+    if let Some(after) = rest.strip_prefix("macro_rules! ") {
+        return Some(("macro_rules", identifier(after)?));
+    }
+    for kind in [
+        "fn", "struct", "enum", "union", "trait", "mod", "const", "static", "type",
+    ] {
+        if let Some(after) = rest
+            .strip_prefix(kind)
+            .and_then(|after| after.strip_prefix(' '))
+        {
+            let after = after.strip_prefix("mut ").unwrap_or(after);
+            return Some((kind, identifier(after)?));
+        }
+    }
+    None
+}
+
+/// `after` without a leading `<...>` generic parameter list; `None` unless it continues with a
+/// space, as in `impl<T> Trait for T`
+fn skip_generics(after: &str) -> Option<&str> {
+    if !after.starts_with('<') {
+        return after.strip_prefix(' ');
+    }
+    let mut depth = 0usize;
+    for (i, c) in after.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => {
+                depth -= 1;
+                if depth == 0 {
+                    return after[i + 1..].strip_prefix(' ');
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn identifier(text: &str) -> Option<String> {
+    let name: String = text
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Outline text for expanded source of `total_lines` lines
+pub fn render_outline(items: &[ExpandedItem], total_lines: usize) -> String {
+    let mut text = format!(
+        "Expanded source has {total_lines} lines; outline of its {} items (pass `item` with a path below to expand just that item):",
+        items.len()
+    );
+    for item in items.iter().take(MAX_OUTLINE_ITEMS) {
+        let lines = if item.first_line == item.last_line {
+            format!("line {}", item.first_line)
+        } else {
+            format!("lines {}-{}", item.first_line, item.last_line)
+        };
+        text.push_str(&format!("\n  {} {} ({lines})", item.kind, item.path));
+    }
+    if items.len() > MAX_OUTLINE_ITEMS {
+        text.push_str(&format!(
+            "\n  ... and {} more",
+            items.len() - MAX_OUTLINE_ITEMS
+        ));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPANDED: &str = r#"#![feature(prelude_import)]
+#[prelude_import]
+use std::prelude::rust_2021::*;
+#[macro_use]
+extern crate std;
+mod parser {
+    pub struct Token {
+        pub text: String,
+    }
+    #[automatically_derived]
+    impl ::core::fmt::Debug for Token {
+        #[inline]
+        fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+            ::core::fmt::Formatter::write_str(f, "Token")
+        }
+    }
+    pub(crate) const LIMIT: usize = 3;
+}
+impl<T: Clone> Wrapper<T> where T: Send {
+    pub const fn new() -> Self {
+        Self
+    }
+}
+fn main() {
+    let _ = parser::LIMIT;
+}
+"#;
+
+    #[test]
+    fn test_outlines_module_level_items_with_line_ranges() {
+        let items: Vec<(String, &str, usize, usize)> = outline(EXPANDED)
+            .into_iter()
+            .map(|i| (i.path, i.kind, i.first_line, i.last_line))
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                ("parser".to_string(), "mod", 6, 18),
+                ("parser::Token".to_string(), "struct", 7, 9),
+                (
+                    "parser::impl ::core::fmt::Debug for Token".to_string(),
+                    "impl",
+                    11,
+                    16
+                ),
+                ("parser::LIMIT".to_string(), "const", 17, 17),
+                ("impl Wrapper<T>".to_string(), "impl", 19, 23),
+                ("main".to_string(), "fn", 24, 26),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parses_qualified_declarations() {
+        assert_eq!(
+            parse_item("pub(in crate::a) unsafe extern \"C\" fn raw() {"),
+            Some(("fn", "raw".to_string()))
+        );
+        assert_eq!(
+            parse_item("static mut COUNT: u32 = 0;"),
+            Some(("static", "COUNT".to_string()))
+        );
+        assert_eq!(
+            parse_item("macro_rules! square {"),
+            Some(("macro_rules", "square".to_string()))
+        );
+        assert_eq!(parse_item("let x = 1;"), None);
+        assert_eq!(parse_item("fnord();"), None);
+    }
+
+    #[test]
+    fn test_renders_outline() {
+        let items = outline("fn a() {}\nstruct B;\n");
+        assert_eq!(
+            render_outline(&items, 2),
+            "Expanded source has 2 lines; outline of its 2 items (pass `item` with a path below to expand just that item):\n  fn a (line 1)\n  struct B (line 2)"
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! Explanations of rustc error codes
+//!
+//! Results of `build`, `check`, `clippy` and `test` carry a one-paragraph excerpt for each
+//! distinct error code (`E0502`), taken from the `code.explanation` field of cargo's JSON
+//! messages. Where that field is missing, and for the `explain` tool, the text comes from
+//! `rustc --explain`. Explanations change between compiler releases, so they are cached per
+//! toolchain, identified by the `rustc --version` of the working directory.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use tokio::sync::RwLock;
+
+/// Longest excerpt included in a result, in characters
+const MAX_EXCERPT_CHARS: usize = 300;
+
+/// `E0502`: rustc error codes, as opposed to lint names such as `unused_variables`
+pub fn is_error_code(code: &str) -> bool {
+    code.len() == 5 && code.starts_with('E') && code[1..].chars().all(|c| c.is_ascii_digit())
+}
+
+/// Accept `E0502`, `e0502` or `0502`
+pub fn normalize_error_code(code: &str) -> Option<String> {
+    let code = code.trim().to_ascii_uppercase();
+    let code = if code.starts_with('E') {
+        code
+    } else {
+        format!("E{code}")
+    };
+    is_error_code(&code).then_some(code)
+}
+
+/// The first prose paragraph of an explanation on one line, shortened to
+/// [`MAX_EXCERPT_CHARS`]. Headings such as "#### Note: this error code is no longer emitted"
+/// and code blocks are skipped.
+pub fn excerpt(explanation: &str) -> String {
+    let paragraph = explanation
+        .split("\n\n")
+        .map(str::trim)
+        .find(|p| !p.is_empty() && !p.starts_with('#') && !p.starts_with("```"))
+        .unwrap_or_default();
+    let line = paragraph.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() <= MAX_EXCERPT_CHARS {
+        return line;
+    }
+    let cut: String = line.chars().take(MAX_EXCERPT_CHARS).collect();
+    match cut.rsplit_once(' ') {
+        Some((head, _)) => format!("{head} ..."),
+        None => format!("{cut}..."),
+    }
+}
+
+/// `rustc --version` of the toolchain used in `working_directory`, or `None` if rustc fails.
+/// Checked once per directory, since `rust-toolchain.toml` can pin a different toolchain.
+pub async fn rustc_version(working_directory: &str) -> Option<String> {
+    static CACHE: OnceLock<Mutex<HashMap<String, Option<String>>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(Default::default);
+    if let Some(known) = cache.lock().unwrap().get(working_directory) {
+        return known.clone();
+    }
+    let version = tokio::process::Command::new("rustc")
+        .arg("--version")
+        .current_dir(working_directory)
+        .output()
+        .await
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string());
+    cache
+        .lock()
+        .unwrap()
+        .insert(working_directory.to_string(), version.clone());
+    version
+}
+
+/// Full `rustc --explain` texts by toolchain version and error code
+#[derive(Debug, Default)]
+pub struct ExplanationCache {
+    explanations: RwLock<HashMap<(String, String), String>>,
+}
+
+impl ExplanationCache {
+    /// The explanation of `code` for the toolchain of `working_directory`, running
+    /// `rustc --explain` on first use
+    pub async fn get(&self, working_directory: &str, code: &str) -> Result<String, String> {
+        let toolchain = rustc_version(working_directory).await.unwrap_or_default();
+        let key = (toolchain, code.to_string());
+        if let Some(explanation) = self.explanations.read().await.get(&key) {
+            return Ok(explanation.clone());
+        }
+        let output = tokio::process::Command::new("rustc")
+            .args(["--explain", code])
+            .current_dir(working_directory)
+            .output()
+            .await
+            .map_err(|e| format!("Failed to execute rustc --explain: {e}"))?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        let explanation = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        self.explanations
+            .write()
+            .await
+            .insert(key, explanation.clone());
+        Ok(explanation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes() {
+        assert!(is_error_code("E0502"));
+        assert!(!is_error_code("unused_variables"));
+        assert!(!is_error_code("E05"));
+        assert_eq!(normalize_error_code("e0502").as_deref(), Some("E0502"));
+        assert_eq!(normalize_error_code("0308").as_deref(), Some("E0308"));
+        assert_eq!(normalize_error_code("clippy::unwrap_used"), None);
+    }
+
+    #[test]
+    fn test_excerpt_takes_first_paragraph() {
+        let explanation = "A variable already borrowed with a certain mutability (either mutable or\nimmutable) was borrowed again with a different mutability.\n\nErroneous code example:\n\n```\nfn foo() {}\n```\n";
+        assert_eq!(
+            excerpt(explanation),
+            "A variable already borrowed with a certain mutability (either mutable or immutable) was borrowed again with a different mutability."
+        );
+        let retired = "#### Note: this error code is no longer emitted by the compiler.\n\nA lifetime was missing.\n";
+        assert_eq!(excerpt(retired), "A lifetime was missing.");
+        let long = "word ".repeat(100);
+        let short = excerpt(&long);
+        assert!(short.ends_with(" ...") && short.len() <= MAX_EXCERPT_CHARS + 4);
+    }
+}
@@ -0,0 +1,349 @@
+//! Fingerprints of a workspace's sources
+//!
+//! A source fingerprint hashes the relative path, size and modification time of every file under
+//! the directories a build reads: the workspace root and the root of every path dependency, found
+//! with `cargo metadata --no-deps`. It skips `.git/`, cargo's `target/` and anything matched by
+//! `.gitignore` files (the same paths watch sessions ignore). Two equal fingerprints mean no source
+//! file was added, removed or written in between, so a result computed for the first still holds.
+//! A request fingerprint adds what else decides a result: `Cargo.lock` (which may be ignored or
+//! live in a parent workspace), the toolchain, the `CARGO*`/`RUST*` environment cargo inherits and
+//! the request arguments.
+
+use crate::watch::is_target_dir_name;
+use ignore::WalkBuilder;
+use serde_json::Value;
+use std::collections::{BTreeSet, VecDeque};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Fingerprint of the sources a build of `working_directory` reads. `None` if the walk could not
+/// run.
+pub async fn source_fingerprint(working_directory: &str) -> Option<u64> {
+    let roots = source_roots(working_directory).await;
+    tokio::task::spawn_blocking(move || files_fingerprint(&roots, |_| true))
+        .await
+        .ok()
+}
+
+/// The workspace root of `working_directory` and the root of every package reachable from it
+/// through path dependencies, with roots nested in another one dropped. `cargo metadata --no-deps`
+/// neither resolves registry dependencies nor writes `Cargo.lock`, so path dependencies of path
+/// dependencies are followed by asking again for each new manifest. Falls back to
+/// `working_directory` alone when it holds no cargo project.
+pub async fn source_roots(working_directory: &str) -> Vec<PathBuf> {
+    let mut roots = BTreeSet::new();
+    let mut seen = BTreeSet::new();
+    let mut pending = VecDeque::from([None::<PathBuf>]);
+    while let Some(manifest) = pending.pop_front() {
+        let mut cmd = tokio::process::Command::new("cargo");
+        cmd.args(["metadata", "--no-deps", "--format-version", "1"])
+            .current_dir(working_directory);
+        if let Some(manifest) = &manifest {
+            cmd.arg("--manifest-path").arg(manifest);
+        }
+        let Some(metadata) = cmd
+            .output()
+            .await
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| serde_json::from_slice::<Value>(&output.stdout).ok())
+        else {
+            continue;
+        };
+        if let Some(root) = metadata["workspace_root"].as_str() {
+            roots.insert(PathBuf::from(root));
+        }
+        let packages = metadata["packages"].as_array().cloned().unwrap_or_default();
+        seen.extend(
+            packages
+                .iter()
+                .filter_map(|p| p["manifest_path"].as_str())
+                .filter_map(|p| Path::new(p).parent())
+                .map(Path::to_path_buf),
+        );
+        for path in packages
+            .iter()
+            .flat_map(|p| p["dependencies"].as_array().cloned().unwrap_or_default())
+            .filter_map(|dep| dep["path"].as_str().map(PathBuf::from))
+        {
+            if seen.insert(path.clone()) {
+                pending.push_back(Some(path.join("Cargo.toml")));
+            }
+        }
+    }
+    if roots.is_empty() {
+        return vec![PathBuf::from(working_directory)];
+    }
+    // Sorted order puts a parent before anything nested in it
+    let mut outer: Vec<PathBuf> = Vec::new();
+    for root in roots {
+        if !outer.iter().any(|parent| root.starts_with(parent)) {
+            outer.push(root);
+        }
+    }
+    outer
+}
+
+fn files_fingerprint(roots: &[PathBuf], include: impl Fn(&Path) -> bool) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for root in roots {
+        root.hash(&mut hasher);
+        hash_files(root, &include, &mut hasher);
+    }
+    hasher.finish()
+}
+
+fn hash_files(root: &Path, include: &impl Fn(&Path) -> bool, hasher: &mut DefaultHasher) {
+    let walk = WalkBuilder::new(root)
+        .hidden(false)
+        .require_git(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            name != ".git" && !(entry.depth() == 1 && is_target_dir_name(&name))
+        })
+        .build();
+    for entry in walk.flatten() {
+        if !entry.file_type().is_some_and(|t| t.is_file()) || !include(entry.path()) {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        entry
+            .path()
+            .strip_prefix(root)
+            .unwrap_or(entry.path())
+            .hash(hasher);
+        metadata.len().hash(hasher);
+        metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+            .hash(hasher);
+    }
+}
+
+/// The variables among `vars` that change what cargo and rustc do (`CARGO_*`, `RUSTFLAGS`,
+/// `RUSTC_WRAPPER`, `RUSTUP_TOOLCHAIN`, ...), sorted by name
+fn build_environment(
+    vars: impl Iterator<Item = (std::ffi::OsString, std::ffi::OsString)>,
+) -> Vec<(std::ffi::OsString, std::ffi::OsString)> {
+    let mut relevant: Vec<_> = vars
+        .filter(|(name, _)| {
+            let name = name.to_string_lossy();
+            name.starts_with("CARGO") || name.starts_with("RUST")
+        })
+        .collect();
+    relevant.sort();
+    relevant
+}
+
+/// Fingerprint of running `tool` with the arguments `request_key` in a working directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestFingerprint {
+    working_directory: String,
+    tool: String,
+    request_key: String,
+    /// The source files alone, without `Cargo.lock`
+    pub sources: u64,
+    /// Sources, `Cargo.lock` contents, toolchain, environment and request together
+    pub key: u64,
+}
+
+impl RequestFingerprint {
+    /// `None` when the toolchain cannot be determined
+    pub async fn compute(working_directory: &str, tool: &str, request_key: &str) -> Option<Self> {
+        let toolchain = tokio::process::Command::new("rustc")
+            .arg("-vV")
+            .current_dir(working_directory)
+            .output()
+            .await
+            .ok()
+            .filter(|output| output.status.success())?
+            .stdout;
+        let roots = source_roots(working_directory).await;
+        let root = PathBuf::from(working_directory);
+        let (sources, lock) = tokio::task::spawn_blocking(move || {
+            let lock = root
+                .ancestors()
+                .map(|dir| dir.join("Cargo.lock"))
+                .find(|path| path.is_file())
+                .and_then(|path| std::fs::read(path).ok());
+            let sources = files_fingerprint(&roots, |path| !path.ends_with("Cargo.lock"));
+            (sources, lock)
+        })
+        .await
+        .ok()?;
+
+        let mut hasher = DefaultHasher::new();
+        working_directory.hash(&mut hasher);
+        tool.hash(&mut hasher);
+        request_key.hash(&mut hasher);
+        toolchain.hash(&mut hasher);
+        build_environment(std::env::vars_os()).hash(&mut hasher);
+        sources.hash(&mut hasher);
+        lock.hash(&mut hasher);
+        Some(Self {
+            working_directory: working_directory.to_string(),
+            tool: tool.to_string(),
+            request_key: request_key.to_string(),
+            sources,
+            key: hasher.finish(),
+        })
+    }
+
+    /// The fingerprint after the request ran, which may have written `Cargo.lock`. `None` if a
+    /// source file changed meanwhile, since the result then belongs to neither state.
+    pub async fn after_run(&self) -> Option<Self> {
+        Self::compute(&self.working_directory, &self.tool, &self.request_key)
+            .await
+            .filter(|after| after.sources == self.sources)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fingerprint_changes_with_sources_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let fingerprint = || source_fingerprint(root.to_str().unwrap());
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src").join("lib.rs"), "pub fn a() {}\n").unwrap();
+        std::fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        let before = fingerprint().await;
+        assert!(before.is_some());
+        assert_eq!(before, fingerprint().await);
+
+        std::fs::create_dir_all(root.join("target").join("debug")).unwrap();
+        std::fs::write(root.join("target").join("debug").join("out"), "x").unwrap();
+        std::fs::write(root.join("build.log"), "ignored").unwrap();
+        assert_eq!(before, fingerprint().await);
+
+        std::fs::write(root.join("src").join("lib.rs"), "pub fn ab() {}\n").unwrap();
+        assert_ne!(before, fingerprint().await);
+
+        let edited = fingerprint().await;
+        std::fs::write(root.join("src").join("new.rs"), "").unwrap();
+        assert_ne!(edited, fingerprint().await);
+    }
+
+    #[test]
+    fn test_build_environment_keeps_cargo_and_rust_variables() {
+        let vars = [
+            ("RUSTFLAGS", "-Dwarnings"),
+            ("HOME", "/home/me"),
+            ("CARGO_TARGET_DIR", "/tmp/target"),
+            ("PATH", "/usr/bin"),
+            ("RUSTUP_TOOLCHAIN", "nightly"),
+        ]
+        .map(|(name, value)| (name.into(), value.into()));
+        let names: Vec<_> = build_environment(vars.into_iter())
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, ["CARGO_TARGET_DIR", "RUSTFLAGS", "RUSTUP_TOOLCHAIN"]);
+    }
+
+    #[tokio::test]
+    async fn test_fingerprint_covers_workspace_root_and_path_dependencies() {
+        let write_crate = |dir: &Path, manifest: &str| {
+            std::fs::create_dir_all(dir.join("src")).unwrap();
+            std::fs::write(dir.join("Cargo.toml"), manifest).unwrap();
+            std::fs::write(dir.join("src").join("lib.rs"), "").unwrap();
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("workspace");
+        let member = workspace.join("member");
+        let helper = dir.path().join("helper");
+        let nested = dir.path().join("nested");
+        std::fs::create_dir_all(&workspace).unwrap();
+        std::fs::write(
+            workspace.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"member\"]\nresolver = \"2\"\n",
+        )
+        .unwrap();
+        write_crate(
+            &member,
+            "[package]\nname = \"member\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nhelper = { path = \"../../helper\" }\n",
+        );
+        write_crate(
+            &helper,
+            "[package]\nname = \"helper\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nnested = { path = \"../nested\" }\n",
+        );
+        write_crate(
+            &nested,
+            "[package]\nname = \"nested\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        );
+        let member = member.to_str().unwrap();
+
+        let canonical = |path: &Path| path.canonicalize().unwrap();
+        let mut roots: Vec<PathBuf> = source_roots(member)
+            .await
+            .iter()
+            .map(|p| canonical(p))
+            .collect();
+        roots.sort();
+        let mut expected = vec![
+            canonical(&workspace),
+            canonical(&helper),
+            canonical(&nested),
+        ];
+        expected.sort();
+        assert_eq!(roots, expected);
+
+        // Edits outside the member directory still change its fingerprint
+        let mut before = source_fingerprint(member).await;
+        for (file, comment) in [
+            (workspace.join("Cargo.toml"), "# edited"),
+            (nested.join("src").join("lib.rs"), "// edited"),
+        ] {
+            let mut contents = std::fs::read_to_string(&file).unwrap();
+            contents.push_str(comment);
+            std::fs::write(&file, contents).unwrap();
+            let after = source_fingerprint(member).await;
+            assert_ne!(
+                before,
+                after,
+                "editing {} kept the fingerprint",
+                file.display()
+            );
+            before = after;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_fingerprint_covers_lock_file_and_arguments() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path();
+        let member = workspace.join("member");
+        std::fs::create_dir_all(member.join("src")).unwrap();
+        std::fs::write(member.join("src").join("lib.rs"), "").unwrap();
+        let member = member.to_str().unwrap();
+        let key = |tool: &'static str, args: &'static str| async move {
+            RequestFingerprint::compute(member, tool, args)
+                .await
+                .map(|f| f.key)
+        };
+
+        let first = RequestFingerprint::compute(member, "check", "args")
+            .await
+            .expect("rustc should be available to the tests");
+        assert_eq!(Some(first.key), key("check", "args").await);
+        assert_ne!(Some(first.key), key("clippy", "args").await);
+        assert_ne!(Some(first.key), key("check", "other").await);
+
+        // Writing the lock file, as a first build does, keeps the sources but changes the key
+        std::fs::write(workspace.join("Cargo.lock"), "version = 4\n").unwrap();
+        let after = first.after_run().await.expect("sources are unchanged");
+        assert_ne!(after.key, first.key);
+
+        std::fs::write(Path::new(member).join("src").join("lib.rs"), "//").unwrap();
+        assert_eq!(first.after_run().await, None);
+    }
+}
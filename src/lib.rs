@@ -1,14 +1,34 @@
 //! Model Control Protocol (MCP) for Cargo with asynchronous respon handling to allow the LLM to continue processing while waiting for responses.
 
+pub mod audit;
 pub mod callback_system;
 pub mod cargo_tools;
+pub mod condense;
 pub mod constants;
+pub mod coverage;
+pub mod cpu_budget;
+pub mod deny;
+pub mod diagnostics;
+pub mod expand;
+pub mod explain;
+pub mod fingerprint;
+pub mod lints;
 pub mod logging;
 pub mod mcp_callback;
 pub mod operation_monitor;
+pub mod output_store;
+pub mod result_cache;
+pub mod semver;
 pub mod shell_pool;
+pub mod speculative;
+pub mod suggestions;
+pub mod target_isolation;
 pub mod terminal_output;
 pub mod test_cargo_tools;
+pub mod test_results;
 pub mod test_utils;
 pub mod timestamp;
+pub mod timings;
 pub mod tool_hints;
+pub mod unused_deps;
+pub mod watch;
@@ -0,0 +1,451 @@
+//! Lint summaries of clippy runs
+//!
+//! `clippy` results count the diagnostics of each lint in the recorded [`CargoReport`] and list
+//! them with an example location, the level they were emitted at and the lint's default level
+//! and clippy group. Default levels and groups come from `clippy-driver -W help`, cached per
+//! toolchain. With `lint_advice`, the summary also proposes a `[lints]` table that allows the
+//! noisy lints, or orders the lints by how soon they are worth fixing.
+
+use crate::diagnostics::CargoReport;
+use crate::explain::{is_error_code, rustc_version};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, OnceLock};
+
+/// Lints with fewer occurrences are left out of a proposed `[lints]` table
+const NOISY_LINT_MIN_COUNT: usize = 3;
+
+/// What to propose on top of the lint summary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LintAdvice {
+    /// A `[lints.clippy]` / `[lints.rust]` table for Cargo.toml that allows the noisy lints
+    LintsTable,
+    /// Lints in the order they are worth fixing: likely bugs, then performance, then lints
+    /// with machine-applicable fixes
+    FixPriority,
+}
+
+/// Default level and group of a lint, as `-W help` lists them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LintInfo {
+    pub default_level: String,
+    /// Clippy group such as `style` or `pedantic`; `None` for rustc lints
+    pub group: Option<String>,
+}
+
+/// Occurrences of one lint in one run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LintCount {
+    /// As in the diagnostic, e.g. `clippy::needless_return` or `unused_variables`
+    pub lint: String,
+    pub count: usize,
+    /// Level the diagnostics were emitted at, `warning` or `error`
+    pub level: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_level: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// `file:line:column` of the first occurrence
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub example: Option<String>,
+    /// Occurrences with a machine-applicable suggestion (see apply_suggestions)
+    pub machine_applicable: usize,
+}
+
+impl LintCount {
+    /// Rust lint name without the tool prefix, e.g. `needless_return`
+    fn name(&self) -> &str {
+        self.lint.strip_prefix("clippy::").unwrap_or(&self.lint)
+    }
+
+    fn is_clippy(&self) -> bool {
+        self.lint.starts_with("clippy::")
+    }
+
+    /// Lower ranks are fixed first: likely bugs, then compiler lints, then performance
+    fn priority_rank(&self) -> usize {
+        match self.group.as_deref() {
+            Some("correctness") => 0,
+            Some("suspicious") => 1,
+            None => 2,
+            Some("perf") => 3,
+            Some("complexity") => 4,
+            Some("style") => 5,
+            Some("cargo") => 6,
+            Some("pedantic") => 7,
+            Some("nursery") => 8,
+            _ => 9,
+        }
+    }
+
+    fn priority_reason(&self) -> String {
+        let reason = match self.group.as_deref() {
+            Some("correctness") => "likely a bug",
+            Some("suspicious") => "probably wrong or useless code",
+            None => "compiler lint",
+            Some("perf") => "slower than needed",
+            Some("complexity") => "simpler code available",
+            Some("style") => "idiomatic style",
+            Some("cargo") => "manifest hygiene",
+            Some("pedantic" | "nursery" | "restriction") => "opt-in strictness",
+            Some(_) => "other",
+        };
+        if self.machine_applicable == self.count {
+            format!("{reason}; all auto-fixable with apply_suggestions")
+        } else if self.machine_applicable > 0 {
+            format!(
+                "{reason}; {} of {} auto-fixable with apply_suggestions",
+                self.machine_applicable, self.count
+            )
+        } else {
+            reason.to_string()
+        }
+    }
+}
+
+/// Lint counts of one clippy run, with the advice asked for
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LintSummary {
+    /// Most frequent first
+    pub lints: Vec<LintCount>,
+    /// Proposed `[lints]` table for Cargo.toml (`lint_advice: "lints_table"`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lints_table: Option<String>,
+    /// Lint names in the order to fix them (`lint_advice: "fix_priority"`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fix_priority: Vec<String>,
+}
+
+impl LintSummary {
+    /// Count the lint diagnostics of `report`; error codes and uncoded messages are skipped
+    pub fn from_report(report: &CargoReport, info: &HashMap<String, LintInfo>) -> Self {
+        let mut by_lint: BTreeMap<&str, LintCount> = BTreeMap::new();
+        for diagnostic in &report.diagnostics {
+            let Some(code) = diagnostic.code.as_deref() else {
+                continue;
+            };
+            if is_error_code(code) || !matches!(diagnostic.level.as_str(), "warning" | "error") {
+                continue;
+            }
+            let entry = by_lint.entry(code).or_insert_with(|| {
+                let info = info.get(code);
+                LintCount {
+                    lint: code.to_string(),
+                    count: 0,
+                    level: diagnostic.level.clone(),
+                    default_level: info.map(|i| i.default_level.clone()),
+                    group: info.and_then(|i| i.group.clone()),
+                    example: diagnostic
+                        .span
+                        .as_ref()
+                        .map(|s| format!("{}:{}:{}", s.file, s.line_start, s.column_start)),
+                    machine_applicable: 0,
+                }
+            });
+            entry.count += 1;
+            if diagnostic
+                .suggestions
+                .iter()
+                .any(|s| s.applicability.as_deref() == Some("MachineApplicable"))
+            {
+                entry.machine_applicable += 1;
+            }
+        }
+        let mut lints: Vec<LintCount> = by_lint.into_values().collect();
+        lints.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.lint.cmp(&b.lint)));
+        LintSummary {
+            lints,
+            ..Default::default()
+        }
+    }
+
+    /// Fill in the proposal `advice` asks for. `workspace` selects `[workspace.lints.*]`.
+    pub fn advise(&mut self, advice: LintAdvice, workspace: bool) {
+        match advice {
+            LintAdvice::LintsTable => self.lints_table = Some(self.propose_lints_table(workspace)),
+            LintAdvice::FixPriority => {
+                let mut ordered: Vec<&LintCount> = self.lints.iter().collect();
+                ordered.sort_by_key(|l| {
+                    (
+                        l.priority_rank(),
+                        l.machine_applicable < l.count,
+                        std::cmp::Reverse(l.count),
+                    )
+                });
+                self.fix_priority = ordered
+                    .iter()
+                    .map(|l| format!("{} ({}): {}", l.lint, l.count, l.priority_reason()))
+                    .collect();
+            }
+        }
+    }
+
+    /// `[lints.clippy]` and `[lints.rust]` entries allowing every lint with at least
+    /// [`NOISY_LINT_MIN_COUNT`] occurrences. Correctness and suspicious lints are never
+    /// proposed; they usually point at real bugs.
+    fn propose_lints_table(&self, workspace: bool) -> String {
+        let prefix = if workspace {
+            "workspace.lints"
+        } else {
+            "lints"
+        };
+        let noisy: Vec<&LintCount> = self
+            .lints
+            .iter()
+            .filter(|l| l.count >= NOISY_LINT_MIN_COUNT && l.priority_rank() > 1)
+            .collect();
+        if noisy.is_empty() {
+            return format!(
+                "# No lint fires {NOISY_LINT_MIN_COUNT} or more times outside the correctness and suspicious groups"
+            );
+        }
+        let mut out = Vec::new();
+        for (tool, clippy) in [("clippy", true), ("rust", false)] {
+            let entries: Vec<String> = noisy
+                .iter()
+                .filter(|l| l.is_clippy() == clippy)
+                .map(|l| format!("{} = \"allow\"  # {} occurrence(s)", l.name(), l.count))
+                .collect();
+            if !entries.is_empty() {
+                out.push(format!("[{prefix}.{tool}]\n{}", entries.join("\n")));
+            }
+        }
+        if workspace {
+            out.push("# Members opt in with:\n# [lints]\n# workspace = true".to_string());
+        }
+        out.join("\n\n")
+    }
+
+    /// `Lint summary:` line, one row per lint, then the proposal if one was made
+    pub fn render(&self) -> String {
+        let total: usize = self.lints.iter().map(|l| l.count).sum();
+        let mut out = format!(
+            "Lint summary: {total} diagnostic(s) from {} lint(s)",
+            self.lints.len()
+        );
+        for lint in &self.lints {
+            let default = match (&lint.default_level, &lint.group) {
+                (Some(level), Some(group)) => format!(" (default {level}, {group})"),
+                (Some(level), None) => format!(" (default {level})"),
+                _ => String::new(),
+            };
+            out.push_str(&format!(
+                "\n  {:>5}  {} [{}{default}]",
+                lint.count, lint.lint, lint.level
+            ));
+            if let Some(example) = &lint.example {
+                out.push_str(&format!(" e.g. {example}"));
+            }
+        }
+        if let Some(table) = &self.lints_table {
+            out.push_str(&format!("\n\nProposed Cargo.toml lints:\n{table}"));
+        }
+        if !self.fix_priority.is_empty() {
+            out.push_str("\n\nFix priority:");
+            for (n, entry) in self.fix_priority.iter().enumerate() {
+                out.push_str(&format!("\n  {}. {entry}", n + 1));
+            }
+        }
+        out
+    }
+}
+
+/// Parse `clippy-driver -W help` (or `rustc -W help`) into default levels and clippy groups,
+/// keyed by lint name as diagnostics report it (`clippy::needless_return`)
+pub fn parse_lint_help(help: &str) -> HashMap<String, LintInfo> {
+    let mut lints: HashMap<String, LintInfo> = HashMap::new();
+    let mut in_groups = false;
+    for line in help.lines() {
+        if line.starts_with("Lint checks") {
+            in_groups = false;
+            continue;
+        }
+        if line.starts_with("Lint groups") {
+            in_groups = true;
+            continue;
+        }
+        let Some((name, rest)) = line.trim().split_once(char::is_whitespace) else {
+            continue;
+        };
+        let name = name.replace('-', "_");
+        let rest = rest.trim_start();
+        if !in_groups {
+            let level = rest.split_whitespace().next().unwrap_or_default();
+            if matches!(level, "allow" | "warn" | "deny" | "forbid") {
+                lints.entry(name).or_default().default_level = level.to_string();
+            }
+        } else if let Some(group) = name.strip_prefix("clippy::")
+            && group != "all"
+        {
+            for member in rest.split(", ") {
+                if let Some(info) = lints.get_mut(&member.trim().replace('-', "_")) {
+                    info.group = Some(group.to_string());
+                }
+            }
+        }
+    }
+    lints
+}
+
+/// Default levels and groups of the lints known to the toolchain of `working_directory`.
+/// Empty if `clippy-driver` cannot be run.
+pub async fn lint_info(working_directory: &str) -> HashMap<String, LintInfo> {
+    static CACHE: OnceLock<Mutex<HashMap<String, HashMap<String, LintInfo>>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(Default::default);
+    let toolchain = rustc_version(working_directory).await.unwrap_or_default();
+    if let Some(known) = cache.lock().unwrap().get(&toolchain) {
+        return known.clone();
+    }
+    let info = match tokio::process::Command::new("clippy-driver")
+        .args(["-W", "help"])
+        .current_dir(working_directory)
+        .output()
+        .await
+    {
+        Ok(output) if output.status.success() => {
+            parse_lint_help(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => HashMap::new(),
+    };
+    cache.lock().unwrap().insert(toolchain, info.clone());
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::{Diagnostic, DiagnosticSpan, Suggestion};
+
+    const HELP: &str = "
+Lint checks provided by rustc:
+
+                          name  default  meaning
+                          ----  -------  -------
+              unused-variables  warn     detect variables which are not used in any way
+
+Lint checks loaded by this crate:
+
+                          name  default  meaning
+                          ----  -------  -------
+       clippy::needless-return  warn     using a return statement like `return expr;` where an expression would suffice
+             clippy::eq-op      deny     equal operands on both sides of a comparison or bitwise combination
+     clippy::must-use-candidate  allow   function or method that could take a `#[must_use]` attribute
+
+Lint groups loaded by this crate:
+
+                          name  sub-lints
+                          ----  ---------
+                   clippy::all  clippy::needless-return, clippy::eq-op
+                 clippy::style  clippy::needless-return
+           clippy::correctness  clippy::eq-op
+              clippy::pedantic  clippy::must-use-candidate
+";
+
+    fn diagnostic(code: &str, line: usize, fixable: bool) -> Diagnostic {
+        let span = DiagnosticSpan {
+            file: "src/lib.rs".into(),
+            line_start: line,
+            line_end: line,
+            column_start: 5,
+            column_end: 9,
+            byte_start: 0,
+            byte_end: 4,
+            label: None,
+        };
+        Diagnostic {
+            level: "warning".into(),
+            code: Some(code.into()),
+            message: "lint".into(),
+            span: Some(span.clone()),
+            children: Vec::new(),
+            suggestions: if fixable {
+                vec![Suggestion {
+                    message: "remove".into(),
+                    span,
+                    replacement: String::new(),
+                    applicability: Some("MachineApplicable".into()),
+                }]
+            } else {
+                Vec::new()
+            },
+            rendered: None,
+            package: "demo 0.1.0".into(),
+            target: "demo (lib)".into(),
+        }
+    }
+
+    fn report() -> CargoReport {
+        let mut diagnostics: Vec<Diagnostic> = (1..=4)
+            .map(|line| diagnostic("clippy::needless_return", line, true))
+            .collect();
+        diagnostics.extend((10..=12).map(|line| diagnostic("unused_variables", line, false)));
+        diagnostics.push(diagnostic("clippy::eq_op", 20, false));
+        diagnostics.push(diagnostic("E0308", 30, false));
+        CargoReport {
+            diagnostics,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_lint_help() {
+        let info = parse_lint_help(HELP);
+        assert_eq!(info["unused_variables"].default_level, "warn");
+        assert_eq!(info["unused_variables"].group, None);
+        assert_eq!(info["clippy::eq_op"].default_level, "deny");
+        assert_eq!(info["clippy::eq_op"].group.as_deref(), Some("correctness"));
+        assert_eq!(
+            info["clippy::must_use_candidate"].group.as_deref(),
+            Some("pedantic")
+        );
+    }
+
+    #[test]
+    fn test_summary_counts_lints() {
+        let summary = LintSummary::from_report(&report(), &parse_lint_help(HELP));
+        let counts: Vec<(&str, usize)> = summary
+            .lints
+            .iter()
+            .map(|l| (l.lint.as_str(), l.count))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("clippy::needless_return", 4),
+                ("unused_variables", 3),
+                ("clippy::eq_op", 1)
+            ]
+        );
+        assert_eq!(summary.lints[0].machine_applicable, 4);
+        assert!(summary.render().contains(
+            "      4  clippy::needless_return [warning (default warn, style)] e.g. src/lib.rs:1:5"
+        ));
+    }
+
+    #[test]
+    fn test_lints_table_allows_noisy_lints() {
+        let mut summary = LintSummary::from_report(&report(), &parse_lint_help(HELP));
+        summary.advise(LintAdvice::LintsTable, false);
+        assert_eq!(
+            summary.lints_table.as_deref(),
+            Some(
+                "[lints.clippy]\nneedless_return = \"allow\"  # 4 occurrence(s)\n\n[lints.rust]\nunused_variables = \"allow\"  # 3 occurrence(s)"
+            )
+        );
+    }
+
+    #[test]
+    fn test_fix_priority_puts_bugs_first() {
+        let mut summary = LintSummary::from_report(&report(), &parse_lint_help(HELP));
+        summary.advise(LintAdvice::FixPriority, false);
+        assert_eq!(
+            summary.fix_priority,
+            vec![
+                "clippy::eq_op (1): likely a bug",
+                "unused_variables (3): compiler lint",
+                "clippy::needless_return (4): idiomatic style; all auto-fixable with apply_suggestions",
+            ]
+        );
+    }
+}
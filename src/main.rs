@@ -3,6 +3,7 @@
 use anyhow::Result;
 use async_cargo_mcp::{
     cargo_tools::AsyncCargo,
+    condense::Verbosity,
     operation_monitor::{MonitorConfig, OperationMonitor},
 };
 use clap::Parser;
@@ -49,6 +50,23 @@ struct Args {
     )]
     max_shells: Option<usize>,
 
+    /// Keep every shell pool at --shell-pool-size instead of adapting to demand
+    #[arg(
+        long,
+        help = "Disable adaptive shell pool sizing; every directory keeps exactly --shell-pool-size idle shells"
+    )]
+    fixed_shell_pool_size: bool,
+
+    /// Working directories whose shell pools are filled at startup (can be used multiple times)
+    #[arg(
+        long,
+        value_name = "DIR",
+        action = clap::ArgAction::Append,
+        value_delimiter = ',',
+        help = "Prewarm shell pools for these working directories at startup instead of on first use. Use the same absolute paths passed as working_directory. Example: --prewarm-dir /src/app,/src/lib"
+    )]
+    prewarm_dir: Vec<std::path::PathBuf>,
+
     /// Disable shell pools and use direct command spawning
     #[arg(
         long,
@@ -80,6 +98,66 @@ struct Args {
     help = "Disable specific tools by name. Accepts comma-separated list or repeat flag. Example: --disable build,test,clippy --disable audit"
     )]
     disable: Vec<String>,
+
+    /// Give specific tools their own CARGO_TARGET_DIR (can be used multiple times)
+    #[arg(
+        long,
+        value_name = "TOOL",
+        action = clap::ArgAction::Append,
+        value_delimiter = ',',
+        help = "Run the listed tools (check, clippy, doc) in their own target dir under target/async_cargo_mcp/<tool> so they do not block build/test on the target dir lock. Example: --isolate-target-dir clippy,doc"
+    )]
+    isolate_target_dir: Vec<String>,
+
+    /// Disk budget for isolated target dirs per project in MiB (default: 4096, 0 = unlimited)
+    #[arg(
+        long,
+        value_name = "MIB",
+        help = "Maximum combined size of isolated target dirs per project in MiB; least recently used dirs are pruned (default: 4096, 0 = unlimited)"
+    )]
+    isolated_target_max_mib: Option<u64>,
+
+    /// Inline output limit per operation result in KiB (default: 64, 0 = unlimited)
+    #[arg(
+        long,
+        value_name = "KIB",
+        help = "Maximum size of an operation result returned inline, in KiB. Larger output is saved to a per-operation file, summarized as head/tail, and readable with get_output (default: 64, 0 = unlimited)"
+    )]
+    max_inline_output_kib: Option<usize>,
+
+    /// Default output condensation for requests that do not set `verbosity`
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Verbosity::Full,
+        help = "Default output condensation: 'summary' (errors, one line per warning, key result lines), 'normal' (drop progress lines and duplicate diagnostics) or 'full' (unchanged). Requests can override it with `verbosity`"
+    )]
+    verbosity: Verbosity,
+
+    /// Total parallel compile jobs shared by all concurrent cargo operations
+    #[arg(
+        long,
+        value_name = "JOBS",
+        help = "Share one jobserver with this many job slots across all concurrent cargo invocations; also caps the `jobs` parameter of build/test (default: unlimited, each cargo uses all CPUs)"
+    )]
+    jobs_budget: Option<u32>,
+
+    /// Check workspaces in the background after their sources change
+    #[arg(
+        long,
+        help = "Watch every working_directory named in a request and run a debounced, low-priority `cargo check` in target/async_cargo_mcp/speculative after each change. An explicit check returns that result instantly while the sources are unchanged"
+    )]
+    speculative_check: bool,
+
+    /// Tools whose results are reused for identical requests on unchanged sources
+    #[arg(
+        long,
+        value_name = "TOOL",
+        value_delimiter = ',',
+        default_value = "none",
+        help = "Return the earlier result of an identical check, clippy or test request when no source file, Cargo.lock, toolchain or CARGO*/RUST* environment variable changed since, marked with the operation it came from. Requests can bypass it with `force: true`. Off by default (`none`). Example: --cache-results check,clippy"
+    )]
+    cache_results: Vec<String>,
 }
 
 #[tokio::main]
@@ -139,6 +217,16 @@ async fn main() -> Result<()> {
         shell_pool_config.max_total_shells = max_shells;
     }
 
+    if args.fixed_shell_pool_size {
+        shell_pool_config.adaptive_sizing = false;
+        info!("Adaptive shell pool sizing disabled via CLI flag");
+    }
+
+    if !args.prewarm_dir.is_empty() {
+        info!("Prewarming shell pools for: {:?}", args.prewarm_dir);
+        shell_pool_config.prewarm_directories = args.prewarm_dir.clone();
+    }
+
     if args.disable_shell_pools {
         shell_pool_config.enabled = false;
         info!("Shell pools disabled via CLI flag - using direct command spawning");
@@ -153,6 +241,13 @@ async fn main() -> Result<()> {
         info!("Shell pools disabled");
     }
 
+    // Create the shared jobserver before any shell or cargo process is spawned
+    if let Some(jobs) = args.jobs_budget
+        && let Err(e) = async_cargo_mcp::cpu_budget::init(jobs)
+    {
+        tracing::warn!("Failed to create shared jobserver, running without a jobs budget: {e}");
+    }
+
     let synchronous_mode = args.synchronous;
 
     if synchronous_mode {
@@ -186,12 +281,61 @@ async fn main() -> Result<()> {
         );
     }
 
+    use async_cargo_mcp::target_isolation::TargetIsolationConfig;
+    let mut target_isolation = TargetIsolationConfig::new(&args.isolate_target_dir);
+    if let Some(mib) = args.isolated_target_max_mib {
+        target_isolation =
+            target_isolation.with_max_total_bytes((mib > 0).then(|| mib * 1024 * 1024));
+    }
+    if target_isolation.is_enabled() {
+        info!(
+            "Isolated target dirs for: {}",
+            target_isolation.isolated_tools().join(", ")
+        );
+    }
+
+    use async_cargo_mcp::output_store::{DEFAULT_MAX_INLINE_BYTES, OutputStore};
+    let max_inline_bytes = args
+        .max_inline_output_kib
+        .map(|kib| kib * 1024)
+        .unwrap_or(DEFAULT_MAX_INLINE_BYTES);
+    let output_store = OutputStore::new(OutputStore::default_dir(), max_inline_bytes)
+        .with_default_verbosity(args.verbosity);
+    info!(
+        "Operation output above {} bytes is spilled to {}",
+        max_inline_bytes,
+        output_store.dir().display()
+    );
+
+    use async_cargo_mcp::speculative::SpeculativeChecks;
+    let speculative_checks = if args.speculative_check {
+        info!("Speculative background checks enabled");
+        SpeculativeChecks::enabled()
+    } else {
+        SpeculativeChecks::disabled()
+    };
+
+    use async_cargo_mcp::result_cache::ResultCache;
+    let result_cache = ResultCache::new(&args.cache_results);
+    if result_cache.cached_tools().is_empty() {
+        info!("Result caching disabled");
+    } else {
+        info!(
+            "Results cached for: {}",
+            result_cache.cached_tools().join(", ")
+        );
+    }
+
     let service = AsyncCargo::new_with_config_and_disabled(
         monitor.clone(),
         shell_pool_manager,
         synchronous_mode,
         disabled_tools,
     )
+    .with_target_isolation(target_isolation)
+    .with_output_store(output_store.clone())
+    .with_speculative_checks(speculative_checks)
+    .with_result_cache(result_cache)
     .serve(stdio())
     .await
     .inspect_err(|e| {
@@ -204,12 +348,17 @@ async fn main() -> Result<()> {
     // Shutdown the monitor
     monitor.shutdown().await;
 
+    // Spilled output belongs to this process's operations only
+    if let Err(e) = output_store.clear() {
+        tracing::warn!("Failed to remove spilled output: {e}");
+    }
+
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Args;
+    use super::{Args, Verbosity};
     use clap::Parser;
 
     #[test]
@@ -221,4 +370,67 @@ mod tests {
             "Expected comma-separated list to split into individual tool names"
         );
     }
+
+    #[test]
+    fn test_isolate_target_dir_parsing() {
+        let args = Args::parse_from([
+            "prog",
+            "--isolate-target-dir",
+            "clippy,doc",
+            "--isolated-target-max-mib",
+            "512",
+        ]);
+        assert_eq!(args.isolate_target_dir, vec!["clippy", "doc"]);
+        assert_eq!(args.isolated_target_max_mib, Some(512));
+    }
+
+    #[test]
+    fn test_shell_pool_sizing_flags() {
+        let args = Args::parse_from([
+            "prog",
+            "--fixed-shell-pool-size",
+            "--prewarm-dir",
+            "/a,/b",
+            "--prewarm-dir",
+            "/c",
+        ]);
+        assert!(args.fixed_shell_pool_size);
+        assert_eq!(
+            args.prewarm_dir,
+            vec![
+                std::path::PathBuf::from("/a"),
+                std::path::PathBuf::from("/b"),
+                std::path::PathBuf::from("/c")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_jobs_budget_parsing() {
+        let args = Args::parse_from(["prog", "--jobs-budget", "6"]);
+        assert_eq!(args.jobs_budget, Some(6));
+        assert_eq!(Args::parse_from(["prog"]).jobs_budget, None);
+    }
+
+    #[test]
+    fn test_verbosity_parsing() {
+        let args = Args::parse_from(["prog", "--verbosity", "summary"]);
+        assert_eq!(args.verbosity, Verbosity::Summary);
+        assert_eq!(Args::parse_from(["prog"]).verbosity, Verbosity::Full);
+    }
+
+    #[test]
+    fn test_speculative_check_flag() {
+        assert!(Args::parse_from(["prog", "--speculative-check"]).speculative_check);
+        assert!(!Args::parse_from(["prog"]).speculative_check);
+    }
+
+    #[test]
+    fn test_cache_results_parsing() {
+        assert_eq!(Args::parse_from(["prog"]).cache_results, vec!["none"]);
+        let args = Args::parse_from(["prog", "--cache-results", "check,test"]);
+        assert_eq!(args.cache_results, vec!["check", "test"]);
+        let args = Args::parse_from(["prog", "--cache-results", "none"]);
+        assert_eq!(args.cache_results, vec!["none"]);
+    }
 }
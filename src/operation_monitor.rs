@@ -15,8 +15,9 @@ use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 /// Represents the current state of an operation
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub enum OperationState {
+    #[default]
     Pending,
     Running,
     Completed,
@@ -25,12 +26,6 @@ pub enum OperationState {
     TimedOut,
 }
 
-impl Default for OperationState {
-    fn default() -> Self {
-        Self::Pending
-    }
-}
-
 impl OperationState {
     /// Check if this state represents an active (non-terminal) operation
     pub fn is_active(&self) -> bool {
@@ -154,6 +149,8 @@ pub struct OperationInfo {
     pub first_wait_time: Option<Instant>,
     pub timeout_duration: Option<Duration>,
     pub working_directory: Option<String>,
+    /// Isolated `CARGO_TARGET_DIR` used by this operation, if any
+    pub target_directory: Option<String>,
     pub result: Option<Result<String, String>>,
     pub cancellation_token: CancellationToken,
 }
@@ -176,6 +173,7 @@ impl OperationInfo {
             first_wait_time: None,
             timeout_duration,
             working_directory,
+            target_directory: None,
             result: None,
             cancellation_token: CancellationToken::new(),
         }
@@ -422,6 +420,21 @@ impl OperationMonitor {
         }
     }
 
+    /// Record the isolated target directory an operation builds into
+    pub async fn set_target_directory(
+        &self,
+        operation_id: &str,
+        target_directory: String,
+    ) -> Result<(), String> {
+        let mut operations = self.operations.write().await;
+        if let Some(operation) = operations.get_mut(operation_id) {
+            operation.target_directory = Some(target_directory);
+            Ok(())
+        } else {
+            Err(format!("Operation not found: {operation_id}"))
+        }
+    }
+
     /// Complete an operation with a result
     pub async fn complete_operation(
         &self,
@@ -801,6 +814,7 @@ impl OperationMonitor {
                     "Invalid operation ID: empty or whitespace-only ID provided".to_string(),
                 )),
                 working_directory: None,
+                target_directory: None,
                 cancellation_token: CancellationToken::new(),
             };
             return Ok(vec![info]);
@@ -851,6 +865,7 @@ impl OperationMonitor {
                         To see current operations, use wait without specifying an operation ID."
                     ))),
                     working_directory: None,
+                    target_directory: None,
                     cancellation_token: CancellationToken::new(),
                 };
                 return Ok(vec![info]);
@@ -0,0 +1,782 @@
+//! Bounded operation output with spill-to-disk and paged retrieval
+//!
+//! A failing workspace test run can print megabytes, which would otherwise be kept in the
+//! operation history and sent to the client in a single message. Results larger than the inline
+//! limit are written in full to a per-operation log file and replaced by a head/tail summary that
+//! states how much was elided and where the full log lives. The `get_output` tool then pages
+//! through the full log by byte offset or line range, or greps within it.
+//!
+//! Commands are read with [`OutputStore::capture`] rather than buffered whole: each stream keeps
+//! its first and last lines in memory, and once the output outgrows the capture limit everything
+//! is written to the operation's log as it arrives.
+
+use crate::condense::{self, CondenseOptions, Verbosity};
+use std::collections::{HashSet, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tracing::{debug, warn};
+
+/// Default number of bytes returned inline before output is spilled to disk (64 KiB)
+pub const DEFAULT_MAX_INLINE_BYTES: usize = 64 * 1024;
+
+/// Default number of bytes of each output stream kept in memory while a command runs (4 MiB)
+pub const DEFAULT_CAPTURE_BYTES: usize = 4 * 1024 * 1024;
+
+/// Default page size for `get_output` byte-offset paging (16 KiB)
+pub const DEFAULT_PAGE_BYTES: usize = 16 * 1024;
+
+/// Marker that starts the elision notice inside a bounded result
+pub const TRUNCATION_MARKER: &str = "... [output truncated:";
+
+/// Stores oversized operation output on disk and produces bounded summaries
+#[derive(Debug, Clone)]
+pub struct OutputStore {
+    dir: PathBuf,
+    /// Results up to this size are returned unchanged; `0` disables bounding
+    max_inline_bytes: usize,
+    /// Condensation level for requests that do not set `verbosity`
+    default_verbosity: Verbosity,
+    /// Bytes of each stream [`Self::capture`] keeps in memory; `0` keeps everything
+    capture_bytes: usize,
+    /// Operations whose log was written while they ran; it holds more than any result built
+    /// from the captured head and tail, so the next [`Self::save`] keeps it instead of writing
+    streamed: Arc<Mutex<HashSet<String>>>,
+}
+
+impl Default for OutputStore {
+    fn default() -> Self {
+        Self::new(Self::default_dir(), DEFAULT_MAX_INLINE_BYTES)
+    }
+}
+
+impl OutputStore {
+    /// Create a store that spills into `dir` once output exceeds `max_inline_bytes`
+    pub fn new(dir: impl Into<PathBuf>, max_inline_bytes: usize) -> Self {
+        Self {
+            dir: dir.into(),
+            max_inline_bytes,
+            default_verbosity: Verbosity::Full,
+            capture_bytes: match max_inline_bytes {
+                0 => 0,
+                bytes => bytes.max(DEFAULT_CAPTURE_BYTES),
+            },
+            streamed: Arc::default(),
+        }
+    }
+
+    /// Set how many bytes of each stream a running command may keep in memory (`0` = all)
+    pub fn with_capture_bytes(mut self, capture_bytes: usize) -> Self {
+        self.capture_bytes = capture_bytes;
+        self
+    }
+
+    /// Set the condensation level used when a request does not choose one
+    pub fn with_default_verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.default_verbosity = verbosity;
+        self
+    }
+
+    /// Per-process spill directory under the system temp dir
+    pub fn default_dir() -> PathBuf {
+        std::env::temp_dir()
+            .join("async_cargo_mcp")
+            .join("outputs")
+            .join(std::process::id().to_string())
+    }
+
+    /// Inline limit in bytes (`0` = unlimited)
+    pub fn max_inline_bytes(&self) -> usize {
+        self.max_inline_bytes
+    }
+
+    /// Path of the full log for an operation
+    pub fn spill_path(&self, operation_id: &str) -> PathBuf {
+        let safe_id: String = operation_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir.join(format!("{safe_id}.log"))
+    }
+
+    /// Bound both arms of an operation result
+    pub fn bound_result(
+        &self,
+        operation_id: &str,
+        result: Result<String, String>,
+    ) -> Result<String, String> {
+        match result {
+            Ok(text) => Ok(self.bound(operation_id, text)),
+            Err(text) => Err(self.bound(operation_id, text)),
+        }
+    }
+
+    /// Condense and bound both arms of an operation result
+    pub fn finish_result(
+        &self,
+        operation_id: &str,
+        result: Result<String, String>,
+        options: &CondenseOptions,
+    ) -> Result<String, String> {
+        match result {
+            Ok(text) => Ok(self.finish(operation_id, text, options)),
+            Err(text) => Err(self.finish(operation_id, text, options)),
+        }
+    }
+
+    /// Condense `text` according to `options`, then bound it. When anything is removed the full
+    /// text is saved first and the result ends with a note saying what was elided.
+    pub fn finish(&self, operation_id: &str, text: String, options: &CondenseOptions) -> String {
+        let verbosity = options.verbosity.unwrap_or(self.default_verbosity);
+        let Some(condensed) =
+            condense::condense_to_budget(&text, verbosity, options.max_output_tokens)
+        else {
+            return self.bound_to(operation_id, text, self.token_limit(options), false);
+        };
+
+        let saved = self.save(operation_id, &text);
+        let location = if saved {
+            format!("call get_output with operation_id=\"{operation_id}\"")
+        } else {
+            "the full output could not be saved; retry".to_string()
+        };
+        let condensed_text = format!(
+            "{}\n[condensed to {} verbosity: elided {}; for the full output {location} or set verbosity: \"full\"]",
+            condensed.text,
+            condensed.verbosity.as_str(),
+            condensed.describe(),
+        );
+        self.bound_to(
+            operation_id,
+            condensed_text,
+            self.token_limit(options),
+            saved,
+        )
+    }
+
+    /// Inline limit in bytes, tightened by a request's token budget
+    fn token_limit(&self, options: &CondenseOptions) -> usize {
+        match options.max_output_tokens {
+            Some(tokens) if self.max_inline_bytes == 0 => tokens.saturating_mul(4),
+            Some(tokens) => self.max_inline_bytes.min(tokens.saturating_mul(4)),
+            None => self.max_inline_bytes,
+        }
+    }
+
+    /// Return `text` unchanged if it fits inline; otherwise write it to the operation's log file
+    /// and return the first and last parts with a notice describing what was elided
+    pub fn bound(&self, operation_id: &str, text: String) -> String {
+        self.bound_to(operation_id, text, self.max_inline_bytes, false)
+    }
+
+    /// Bound `text` to `max_bytes`. `already_saved` means the operation's log holds a fuller
+    /// version of the output that must not be overwritten.
+    fn bound_to(
+        &self,
+        operation_id: &str,
+        text: String,
+        max_bytes: usize,
+        already_saved: bool,
+    ) -> String {
+        if max_bytes == 0 || text.len() <= max_bytes {
+            return text;
+        }
+
+        let saved = already_saved || self.save(operation_id, &text);
+        let path = self.spill_path(operation_id);
+
+        // Head gets slightly more room than the tail: the command header and first errors
+        // usually matter most, the tail holds the final summary
+        let head = head_at_line_boundary(&text, max_bytes * 3 / 5);
+        let tail = tail_at_line_boundary(&text, max_bytes * 2 / 5);
+        let elided = text.len() - head.len() - tail.len();
+        let total_lines = text.lines().count();
+        let location = if saved {
+            format!(
+                "full output saved to {}. Use get_output with operation_id=\"{operation_id}\" to page or grep it",
+                path.display()
+            )
+        } else {
+            "full output could not be saved".to_string()
+        };
+
+        format!(
+            "{head}\n{TRUNCATION_MARKER} {elided} of {total} bytes ({total_lines} lines) elided; showing first {head_len} and last {tail_len} bytes; {location}] ...\n{tail}",
+            total = text.len(),
+            head_len = head.len(),
+            tail_len = tail.len(),
+        )
+    }
+
+    /// Write the full output of an operation to its log file. A log written while the operation
+    /// ran is kept, since `text` was built from its head and tail.
+    pub fn save(&self, operation_id: &str, text: &str) -> bool {
+        let path = self.spill_path(operation_id);
+        if self.take_streamed(operation_id) && path.is_file() {
+            return true;
+        }
+        match std::fs::create_dir_all(&self.dir)
+            .and_then(|_| std::fs::write(&path, text.as_bytes()))
+        {
+            Ok(()) => {
+                debug!(
+                    "Spilled {} bytes of output for {operation_id} to {}",
+                    text.len(),
+                    path.display()
+                );
+                true
+            }
+            Err(e) => {
+                warn!("Failed to spill output for {operation_id}: {e}");
+                false
+            }
+        }
+    }
+
+    /// Read the spilled log for an operation, if one exists
+    pub fn read_spilled(&self, operation_id: &str) -> Option<String> {
+        let path = self.spill_path(operation_id);
+        std::fs::read(&path)
+            .ok()
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// Whether the operation's log was written while it ran, forgetting it either way
+    fn take_streamed(&self, operation_id: &str) -> bool {
+        self.streamed
+            .lock()
+            .is_ok_and(|mut streamed| streamed.remove(operation_id))
+    }
+
+    /// Run `cmd` to completion, reading stdout and stderr as they are produced, like
+    /// [`tokio::process::Command::output`] but with bounded memory
+    pub async fn capture(
+        &self,
+        operation_id: &str,
+        cmd: &mut tokio::process::Command,
+    ) -> std::io::Result<CapturedOutput> {
+        self.capture_with(operation_id, cmd, |line| Some(line.to_string()))
+            .await
+    }
+
+    /// [`Self::capture`], passing each stdout line (without its newline) through `stdout_line`
+    /// first. Its result replaces the line in the captured stdout and the log; `None` drops it.
+    /// Parsers of machine-readable output use this to keep only the text a human would read.
+    pub async fn capture_with(
+        &self,
+        operation_id: &str,
+        cmd: &mut tokio::process::Command,
+        mut stdout_line: impl FnMut(&str) -> Option<String>,
+    ) -> std::io::Result<CapturedOutput> {
+        let mut child = cmd
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let mut stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        let mut stderr = BufReader::new(child.stderr.take().expect("stderr is piped"));
+        let mut log = StreamLog::new(self.spill_path(operation_id), self.capture_bytes);
+        let mut out = StreamBuffer::new(self.capture_bytes);
+        let mut err = StreamBuffer::new(self.capture_bytes);
+        let (mut out_line, mut err_line) = (Vec::new(), Vec::new());
+        let (mut out_open, mut err_open) = (true, true);
+
+        // read_until keeps a partial line in its buffer when the other branch wins, so no
+        // output is lost by alternating between the streams. A later call that reaches the end
+        // returns 0 with that last unterminated line still in the buffer.
+        while out_open || err_open {
+            tokio::select! {
+                read = stdout.read_until(b'\n', &mut out_line), if out_open => {
+                    if read? == 0 && out_line.is_empty() {
+                        out_open = false;
+                        continue;
+                    }
+                    let line = String::from_utf8_lossy(&out_line);
+                    if let Some(mut text) = stdout_line(line.trim_end_matches(['\n', '\r'])) {
+                        text.push('\n');
+                        log.write(text.as_bytes());
+                        out.push(text.into_bytes());
+                    }
+                    out_line.clear();
+                }
+                read = stderr.read_until(b'\n', &mut err_line), if err_open => {
+                    if read? == 0 && err_line.is_empty() {
+                        err_open = false;
+                        continue;
+                    }
+                    if !err_line.ends_with(b"\n") {
+                        err_line.push(b'\n');
+                    }
+                    log.write(&err_line);
+                    err.push(std::mem::take(&mut err_line));
+                }
+            }
+        }
+        let status = child.wait().await?;
+
+        let saved = log.finish();
+        if let Ok(mut streamed) = self.streamed.lock() {
+            // Entries whose result fit inline were never taken by save; drop those whose log
+            // has since gone so the set stays as small as the log directory
+            streamed.retain(|id| self.spill_path(id).is_file());
+            if saved.is_some() {
+                streamed.insert(operation_id.to_string());
+            } else {
+                streamed.remove(operation_id);
+            }
+        }
+        let location = match &saved {
+            Some(path) => format!(
+                "full output saved to {}. Use get_output with operation_id=\"{operation_id}\" to page or grep it",
+                path.display()
+            ),
+            None => "full output could not be saved".to_string(),
+        };
+        Ok(CapturedOutput {
+            status,
+            stdout: out.into_bytes("stdout", &location),
+            stderr: err.into_bytes("stderr", &location),
+        })
+    }
+
+    /// Remove all spilled logs
+    pub fn clear(&self) -> std::io::Result<()> {
+        if let Ok(mut streamed) = self.streamed.lock() {
+            streamed.clear();
+        }
+        if self.dir.exists() {
+            std::fs::remove_dir_all(&self.dir)?;
+        }
+        Ok(())
+    }
+
+    /// Spill directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+/// Exit status and output of a command run by [`OutputStore::capture`]. A stream that outgrew
+/// the capture limit holds its first and last lines around a truncation notice.
+#[derive(Debug)]
+pub struct CapturedOutput {
+    pub status: ExitStatus,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+/// One stream's first lines, up to 3/5 of the limit, and its last lines, up to 2/5
+struct StreamBuffer {
+    limit: usize,
+    head: Vec<u8>,
+    tail: VecDeque<Vec<u8>>,
+    tail_bytes: usize,
+    total_bytes: usize,
+    total_lines: usize,
+}
+
+impl StreamBuffer {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            head: Vec::new(),
+            tail: VecDeque::new(),
+            tail_bytes: 0,
+            total_bytes: 0,
+            total_lines: 0,
+        }
+    }
+
+    fn push(&mut self, line: Vec<u8>) {
+        self.total_bytes += line.len();
+        self.total_lines += 1;
+        if self.limit == 0
+            || (self.tail.is_empty() && self.head.len() + line.len() <= self.limit * 3 / 5)
+        {
+            self.head.extend_from_slice(&line);
+            return;
+        }
+        self.tail_bytes += line.len();
+        self.tail.push_back(line);
+        while self.head.len() + self.tail_bytes > self.limit && self.tail.len() > 1 {
+            let dropped = self.tail.pop_front().expect("tail has lines");
+            self.tail_bytes -= dropped.len();
+        }
+    }
+
+    fn into_bytes(self, stream: &str, location: &str) -> Vec<u8> {
+        let mut bytes = self.head;
+        let elided = self.total_bytes - bytes.len() - self.tail_bytes;
+        if elided > 0 {
+            bytes.extend_from_slice(
+                format!(
+                    "{TRUNCATION_MARKER} {elided} of {} bytes ({} lines) of {stream} elided while the command ran; {location}] ...\n",
+                    self.total_bytes, self.total_lines,
+                )
+                .as_bytes(),
+            );
+        }
+        bytes.extend(self.tail.into_iter().flatten());
+        bytes
+    }
+}
+
+/// Both streams in the order they arrived, held in memory until they outgrow the limit and
+/// written to the operation's log from then on
+struct StreamLog {
+    path: PathBuf,
+    limit: usize,
+    pending: Vec<u8>,
+    file: Option<std::io::BufWriter<std::fs::File>>,
+    failed: bool,
+}
+
+impl StreamLog {
+    fn new(path: PathBuf, limit: usize) -> Self {
+        Self {
+            path,
+            limit,
+            pending: Vec::new(),
+            file: None,
+            failed: false,
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        if self.limit == 0 || self.failed {
+            return;
+        }
+        if let Some(file) = &mut self.file {
+            if let Err(e) = file.write_all(bytes) {
+                warn!("Failed to write output log {}: {e}", self.path.display());
+                self.failed = true;
+            }
+            return;
+        }
+        self.pending.extend_from_slice(bytes);
+        if self.pending.len() <= self.limit {
+            return;
+        }
+        let opened = self
+            .path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::File::create(&self.path))
+            .map(std::io::BufWriter::new)
+            .and_then(|mut file| file.write_all(&self.pending).map(|_| file));
+        match opened {
+            Ok(file) => {
+                debug!("Streaming output to {}", self.path.display());
+                self.file = Some(file);
+            }
+            Err(e) => {
+                warn!("Failed to create output log {}: {e}", self.path.display());
+                self.failed = true;
+            }
+        }
+        self.pending = Vec::new();
+    }
+
+    /// Path of the log if the output was written to it in full
+    fn finish(self) -> Option<PathBuf> {
+        let mut file = self.file?;
+        match file.flush() {
+            Ok(()) if !self.failed => Some(self.path),
+            Ok(()) => None,
+            Err(e) => {
+                warn!("Failed to write output log {}: {e}", self.path.display());
+                None
+            }
+        }
+    }
+}
+
+/// Largest prefix of at most `max` bytes ending at a line boundary (or a char boundary if the
+/// first line alone is longer)
+fn head_at_line_boundary(text: &str, max: usize) -> &str {
+    if text.len() <= max {
+        return text;
+    }
+    let cut = floor_char_boundary(text, max);
+    match text[..cut].rfind('\n') {
+        Some(nl) if nl > 0 => &text[..nl],
+        _ => &text[..cut],
+    }
+}
+
+/// Largest suffix of at most `max` bytes starting at a line boundary (or a char boundary if the
+/// last line alone is longer)
+fn tail_at_line_boundary(text: &str, max: usize) -> &str {
+    if text.len() <= max {
+        return text;
+    }
+    let mut start = text.len() - max;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    match text[start..].find('\n') {
+        Some(nl) if start + nl + 1 < text.len() => &text[start + nl + 1..],
+        _ => &text[start..],
+    }
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// One page of output returned by [`page_by_bytes`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputPage {
+    pub text: String,
+    /// Byte offset of the first byte in `text`
+    pub start: usize,
+    /// Byte offset just past `text`; pass as the next `offset` to continue
+    pub end: usize,
+    pub total_bytes: usize,
+}
+
+/// Page through `text` by byte offset, snapping to char boundaries
+pub fn page_by_bytes(text: &str, offset: usize, limit: usize) -> OutputPage {
+    let total = text.len();
+    let mut start = offset.min(total);
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    let end = floor_char_boundary(text, start.saturating_add(limit).min(total));
+    OutputPage {
+        text: text[start..end].to_string(),
+        start,
+        end,
+        total_bytes: total,
+    }
+}
+
+/// Lines `start_line..=end_line` (1-based, inclusive), each prefixed with its line number
+pub fn lines_in_range(text: &str, start_line: usize, end_line: usize) -> Vec<String> {
+    text.lines()
+        .enumerate()
+        .skip(start_line.saturating_sub(1))
+        .take_while(|(i, _)| *i < end_line)
+        .map(|(i, line)| format!("{:>6}: {line}", i + 1))
+        .collect()
+}
+
+/// Lines containing `pattern` (case-insensitive), with `context` lines around each match.
+/// Non-adjacent groups are separated by `--`, like `grep -C`.
+pub fn grep_lines(text: &str, pattern: &str, context: usize, max_matches: usize) -> Vec<String> {
+    let needle = pattern.to_lowercase();
+    let lines: Vec<&str> = text.lines().collect();
+    let matches: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.to_lowercase().contains(&needle))
+        .map(|(i, _)| i)
+        .take(max_matches)
+        .collect();
+
+    let mut out = Vec::new();
+    let mut last_printed: Option<usize> = None;
+    for &m in &matches {
+        let from = m.saturating_sub(context);
+        let to = (m + context).min(lines.len().saturating_sub(1));
+        let from = match last_printed {
+            Some(last) if from <= last => last + 1,
+            Some(_) => {
+                out.push("--".to_string());
+                from
+            }
+            None => from,
+        };
+        for (i, line) in lines.iter().enumerate().take(to + 1).skip(from) {
+            let sep = if i == m { ':' } else { '-' };
+            out.push(format!("{:>6}{sep} {line}", i + 1));
+        }
+        last_printed = Some(to);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbered_lines(n: usize) -> String {
+        (1..=n)
+            .map(|i| format!("line {i}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_small_output_is_unchanged() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = OutputStore::new(tmp.path(), 1024);
+        assert_eq!(store.bound("op_test_1", "short".to_string()), "short");
+        assert!(store.read_spilled("op_test_1").is_none());
+    }
+
+    #[test]
+    fn test_large_output_is_spilled_with_head_and_tail() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = OutputStore::new(tmp.path(), 200);
+        let text = numbered_lines(500);
+
+        let bounded = store.bound("op_test_2", text.clone());
+        // Head and tail fit the 200 byte budget; only the notice comes on top
+        let notice_len = bounded
+            .lines()
+            .find(|l| l.starts_with(TRUNCATION_MARKER))
+            .unwrap()
+            .len();
+        assert!(
+            bounded.len() <= 200 + notice_len + 2,
+            "bounded output too large: {bounded}"
+        );
+        assert!(bounded.starts_with("line 1\n"));
+        assert!(bounded.ends_with("line 500"));
+        assert!(bounded.contains(TRUNCATION_MARKER));
+        assert!(bounded.contains(&format!("of {} bytes (500 lines)", text.len())));
+        assert!(bounded.contains("get_output"));
+        assert_eq!(store.read_spilled("op_test_2").unwrap(), text);
+    }
+
+    #[test]
+    fn test_zero_limit_disables_bounding() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = OutputStore::new(tmp.path(), 0);
+        let text = numbered_lines(1000);
+        assert_eq!(store.bound("op", text.clone()), text);
+    }
+
+    #[test]
+    fn test_condensed_output_keeps_full_text_on_disk() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = OutputStore::new(tmp.path(), DEFAULT_MAX_INLINE_BYTES);
+        let text = "+ Build completed successfully.\nOutput:    Compiling demo v0.1.0\n    Finished `dev` profile";
+        let options = CondenseOptions::new(Some(Verbosity::Normal), None);
+
+        let condensed = store.finish("op_test_3", text.to_string(), &options);
+        assert!(!condensed.contains("Compiling"));
+        assert!(condensed.contains("Finished"));
+        assert!(condensed.contains("elided 1 progress line"));
+        assert!(condensed.contains("operation_id=\"op_test_3\""));
+        assert_eq!(store.read_spilled("op_test_3").unwrap(), text);
+
+        // Full verbosity leaves small output alone and writes nothing
+        let full = store.finish("op_test_4", text.to_string(), &CondenseOptions::default());
+        assert_eq!(full, text);
+        assert!(store.read_spilled("op_test_4").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_capture_keeps_small_output_in_memory() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = OutputStore::new(tmp.path(), 1024);
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args(["-c", "echo out; echo err >&2; exit 3"]);
+        let output = store.capture("op_capture_1", &mut cmd).await.unwrap();
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+        assert!(store.read_spilled("op_capture_1").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_capture_streams_large_output_to_log() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = OutputStore::new(tmp.path(), 200).with_capture_bytes(1000);
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args(["-c", "seq 1 2000; echo done >&2"]);
+        let output = store.capture("op_capture_2", &mut cmd).await.unwrap();
+        assert!(output.status.success());
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let notice = stdout
+            .lines()
+            .find(|l| l.starts_with(TRUNCATION_MARKER))
+            .expect("stdout should be truncated");
+        assert!(stdout.len() <= 1000 + notice.len() + 1, "{stdout}");
+        assert!(stdout.starts_with("1\n2\n"));
+        assert!(stdout.ends_with("1999\n2000\n"));
+        assert!(notice.contains("(2000 lines) of stdout"), "{notice}");
+        assert!(notice.contains("operation_id=\"op_capture_2\""), "{notice}");
+        assert_eq!(output.stderr, b"done\n");
+
+        // Lines of the two streams are interleaved in the order they were read
+        let expected: String = (1..=2000).map(|i| format!("{i}\n")).collect();
+        let log = store.read_spilled("op_capture_2").unwrap();
+        assert_eq!(log.replacen("done\n", "", 1), expected);
+        assert!(log.contains("done\n"));
+        // The result built from the captured text must not replace the full log
+        assert!(store.save("op_capture_2", "summary"));
+        assert_eq!(store.read_spilled("op_capture_2").unwrap(), log);
+        // The streamed entry is used up by that save, so a later one replaces the log
+        assert!(store.streamed.lock().unwrap().is_empty());
+        assert!(store.save("op_capture_2", "replaced"));
+        assert_eq!(store.read_spilled("op_capture_2").unwrap(), "replaced");
+    }
+
+    #[tokio::test]
+    async fn test_capture_with_maps_stdout_lines() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = OutputStore::new(tmp.path(), 1024);
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args(["-c", "printf 'keep\\r\\ndrop\\nlast'"]);
+        let mut seen = Vec::new();
+        let output = store
+            .capture_with("op_capture_3", &mut cmd, |line| {
+                seen.push(line.to_string());
+                (line != "drop").then(|| line.to_uppercase())
+            })
+            .await
+            .unwrap();
+        assert_eq!(seen, vec!["keep", "drop", "last"]);
+        assert_eq!(output.stdout, b"KEEP\nLAST\n");
+    }
+
+    #[test]
+    fn test_page_by_bytes_respects_char_boundaries() {
+        let text = "aé€b";
+        let page = page_by_bytes(text, 2, 3);
+        assert_eq!(page.text, "€");
+        assert_eq!((page.start, page.end, page.total_bytes), (3, 6, 7));
+        let past_end = page_by_bytes(text, 100, 10);
+        assert!(past_end.text.is_empty());
+    }
+
+    #[test]
+    fn test_lines_in_range_is_one_based_inclusive() {
+        let text = numbered_lines(10);
+        let lines = lines_in_range(&text, 3, 4);
+        assert_eq!(lines, vec!["     3: line 3", "     4: line 4"]);
+    }
+
+    #[test]
+    fn test_grep_lines_with_context() {
+        let text = "a\nerror one\nb\nc\nd\nerror two\ne";
+        let out = grep_lines(text, "ERROR", 1, 10);
+        assert_eq!(
+            out,
+            vec![
+                "     1- a",
+                "     2: error one",
+                "     3- b",
+                "--",
+                "     5- d",
+                "     6: error two",
+                "     7- e",
+            ]
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Reuse of results for repeated requests on unchanged workspaces
+//!
+//! Agents often run the same `check`, `clippy` or `test` twice without editing anything in
+//! between. Each result of a cacheable tool is stored under a fingerprint of the workspace
+//! sources, `Cargo.lock`, the toolchain and the request arguments (see
+//! [`crate::fingerprint::RequestFingerprint`]); an identical repeat request returns the stored
+//! result marked with the operation that produced it, unless the request sets `force: true`.
+//! Caching is configured per tool and never applies to `run`.
+
+use std::collections::{HashSet, VecDeque};
+use tokio::sync::RwLock;
+use tracing::warn;
+
+/// Tools whose results can be cached
+pub const CACHEABLE_TOOLS: &[&str] = &["check", "clippy", "test"];
+
+/// Results kept; the oldest is evicted beyond this
+const MAX_CACHED_RESULTS: usize = 64;
+
+/// A stored result and where it came from
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedResult {
+    pub operation_id: String,
+    /// Uncondensed, so each request can condense it to its own verbosity
+    pub result: Result<String, String>,
+    pub finished_at: String,
+}
+
+impl CachedResult {
+    /// First line of a result returned from the cache
+    pub fn note(&self) -> String {
+        format!(
+            "Cached from {} at {} (no source, Cargo.lock, toolchain or argument changes since; set force: true to rerun).",
+            self.operation_id, self.finished_at
+        )
+    }
+}
+
+/// Results by request fingerprint, for the tools caching is enabled for
+#[derive(Debug)]
+pub struct ResultCache {
+    tools: HashSet<String>,
+    entries: RwLock<VecDeque<(u64, CachedResult)>>,
+}
+
+impl Default for ResultCache {
+    /// Caching for every cacheable tool
+    fn default() -> Self {
+        Self::new(CACHEABLE_TOOLS)
+    }
+}
+
+impl ResultCache {
+    /// Cache the results of the given tools (case-insensitive). Unsupported names are ignored
+    /// with a warning.
+    pub fn new<I, S>(tools: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut enabled = HashSet::new();
+        for tool in tools {
+            let name = tool.as_ref().trim().to_ascii_lowercase();
+            if name.is_empty() || name == "none" {
+                continue;
+            }
+            if CACHEABLE_TOOLS.contains(&name.as_str()) {
+                enabled.insert(name);
+            } else {
+                warn!(
+                    "Ignoring result caching for unsupported tool '{name}' (supported: {})",
+                    CACHEABLE_TOOLS.join(", ")
+                );
+            }
+        }
+        Self {
+            tools: enabled,
+            entries: RwLock::new(VecDeque::new()),
+        }
+    }
+
+    pub fn is_enabled(&self, tool: &str) -> bool {
+        self.tools.contains(&tool.to_ascii_lowercase())
+    }
+
+    /// Sorted list of tools with caching, for logging
+    pub fn cached_tools(&self) -> Vec<String> {
+        let mut tools: Vec<String> = self.tools.iter().cloned().collect();
+        tools.sort();
+        tools
+    }
+
+    pub async fn get(&self, fingerprint: u64) -> Option<CachedResult> {
+        self.entries
+            .read()
+            .await
+            .iter()
+            .find(|(key, _)| *key == fingerprint)
+            .map(|(_, cached)| cached.clone())
+    }
+
+    /// Store a result, replacing any earlier one for the same fingerprint
+    pub async fn store(&self, fingerprint: u64, cached: CachedResult) {
+        let mut entries = self.entries.write().await;
+        entries.retain(|(key, _)| *key != fingerprint);
+        entries.push_back((fingerprint, cached));
+        while entries.len() > MAX_CACHED_RESULTS {
+            entries.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(operation_id: &str) -> CachedResult {
+        CachedResult {
+            operation_id: operation_id.to_string(),
+            result: Ok("+ Check operation completed successfully in /p.".to_string()),
+            finished_at: "12:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_only_cacheable_tools_are_enabled() {
+        let cache = ResultCache::new(["Check", "run", "none", ""]);
+        assert!(cache.is_enabled("check"));
+        assert!(!cache.is_enabled("run"));
+        assert!(!cache.is_enabled("test"));
+        assert_eq!(cache.cached_tools(), vec!["check"]);
+        assert_eq!(
+            ResultCache::default().cached_tools(),
+            vec!["check", "clippy", "test"]
+        );
+        assert!(ResultCache::new(["none"]).cached_tools().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_store_replaces_and_evicts_oldest() {
+        let cache = ResultCache::default();
+        assert_eq!(cache.get(1).await, None);
+        cache.store(1, cached("op_check_1")).await;
+        cache.store(1, cached("op_check_2")).await;
+        assert_eq!(cache.get(1).await, Some(cached("op_check_2")));
+
+        for key in 2..=(MAX_CACHED_RESULTS as u64 + 1) {
+            cache.store(key, cached("op_check_n")).await;
+        }
+        assert_eq!(cache.get(1).await, None);
+        assert!(cache.get(2).await.is_some());
+    }
+
+    #[test]
+    fn test_note_names_the_source_operation() {
+        assert_eq!(
+            cached("op_check_7").note(),
+            "Cached from op_check_7 at 12:00:00 (no source, Cargo.lock, toolchain or argument changes since; set force: true to rerun)."
+        );
+    }
+}
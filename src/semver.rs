@@ -0,0 +1,348 @@
+//! Structured `cargo semver-checks` results
+//!
+//! `semver_checks` compares the current API against a baseline (a git revision, a local
+//! checkout or a registry version) with `cargo semver-checks`, which has no machine-readable
+//! output. Its report is parsed from text: one [`SemverChange`] per `--- failure` or `--- warning`
+//! block with the lint, its description and where it fired, and per package the versions compared
+//! and the release type the `Summary` line says is required. `bump_version` with
+//! `bump_type: "auto"` turns the strictest requirement into a `cargo set-version --bump` level.
+
+use serde::{Deserialize, Serialize};
+
+/// Release type a set of API changes requires
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum SemverBump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl SemverBump {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SemverBump::Patch => "patch",
+            SemverBump::Minor => "minor",
+            SemverBump::Major => "major",
+        }
+    }
+
+    /// The `cargo set-version --bump` level that satisfies this requirement for `version`.
+    /// Before 1.0 cargo treats the minor number as the major one, so a breaking change bumps
+    /// the minor number and an addition only needs a patch release.
+    pub fn set_version_level(&self, version: &str) -> &'static str {
+        if version.starts_with("0.") {
+            match self {
+                SemverBump::Major => "minor",
+                SemverBump::Minor | SemverBump::Patch => "patch",
+            }
+        } else {
+            self.as_str()
+        }
+    }
+}
+
+/// One lint that fired
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SemverChange {
+    /// Lint id, e.g. `function_missing`
+    pub lint: String,
+    /// `failure` or `warning`
+    pub level: String,
+    /// Header text, e.g. `pub fn removed or renamed`
+    pub summary: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /// Semver reference explaining why the change breaks users
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    /// The items the lint fired for, e.g. `function my_crate::foo, previously in file src/lib.rs:1`
+    pub locations: Vec<String>,
+}
+
+/// The comparison of one package
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SemverPackage {
+    pub name: String,
+    pub baseline_version: String,
+    pub current_version: String,
+    /// What the version change between baseline and current amounts to, e.g. `minor change`
+    pub declared_change: String,
+    /// From the package's `Summary` line; `None` if cargo-semver-checks did not finish it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required_bump: Option<SemverBump>,
+}
+
+/// Everything parsed from one `cargo semver-checks` run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SemverReport {
+    pub working_directory: String,
+    pub packages: Vec<SemverPackage>,
+    pub changes: Vec<SemverChange>,
+    /// The strictest requirement over all packages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required_bump: Option<SemverBump>,
+}
+
+impl SemverReport {
+    /// `cargo set-version --bump` level that satisfies the strictest requirement, given the
+    /// current versions. `None` when no package finished checking.
+    pub fn set_version_level(&self) -> Option<&'static str> {
+        let bump = self.required_bump?;
+        let pre_1_0 = self
+            .packages
+            .iter()
+            .all(|p| p.current_version.starts_with("0."));
+        Some(bump.set_version_level(if pre_1_0 { "0." } else { "1." }))
+    }
+
+    /// `Semver:` line with the required bump, one line per package, then each change
+    pub fn render(&self) -> String {
+        let failures = self.changes.iter().filter(|c| c.level == "failure").count();
+        let mut out = format!(
+            "Semver: minimum required bump {} ({failures} breaking change(s), {} warning(s))",
+            self.required_bump.map_or("unknown", |b| b.as_str()),
+            self.changes.len() - failures
+        );
+        for package in &self.packages {
+            out.push_str(&format!(
+                "\n  {} {} -> {} ({}): requires {}",
+                package.name,
+                package.baseline_version,
+                package.current_version,
+                package.declared_change,
+                package.required_bump.map_or("unknown", |b| b.as_str())
+            ));
+        }
+        if let Some(level) = self.set_version_level() {
+            out.push_str(&format!(
+                "\n  bump_version with bump_type \"auto\" would run cargo set-version --bump {level}"
+            ));
+        }
+        for change in &self.changes {
+            out.push_str(&format!(
+                "\n- {} {}: {}",
+                change.level, change.lint, change.summary
+            ));
+            for location in &change.locations {
+                out.push_str(&format!("\n    {location}"));
+            }
+            if let Some(reference) = &change.reference {
+                out.push_str(&format!("\n    ref: {reference}"));
+            }
+        }
+        out
+    }
+}
+
+/// Which part of a change block the parser is in
+enum Section {
+    None,
+    Description,
+    Locations,
+}
+
+/// Parse the text report of `cargo semver-checks`. Returns `None` if no package was checked,
+/// e.g. when the baseline could not be built.
+pub fn parse_semver_output(output: &str, working_directory: &str) -> Option<SemverReport> {
+    let mut report = SemverReport {
+        working_directory: working_directory.to_string(),
+        ..SemverReport::default()
+    };
+    let mut section = Section::None;
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if let Some(rest) = trimmed.strip_prefix("Checking ")
+            && let Some(package) = parse_checking(rest)
+        {
+            report.packages.push(package);
+            section = Section::None;
+        } else if let Some(rest) = trimmed.strip_prefix("Summary ") {
+            let bump = if rest.contains("requires new major") {
+                Some(SemverBump::Major)
+            } else if rest.contains("requires new minor") {
+                Some(SemverBump::Minor)
+            } else if rest.contains("no semver update required") {
+                Some(SemverBump::Patch)
+            } else {
+                None
+            };
+            if let Some(package) = report
+                .packages
+                .iter_mut()
+                .rev()
+                .find(|p| p.required_bump.is_none())
+            {
+                package.required_bump = bump;
+            }
+            section = Section::None;
+        } else if let Some(header) = trimmed
+            .strip_prefix("--- ")
+            .and_then(|h| h.strip_suffix(" ---"))
+            && let Some((level, rest)) = header.split_once(' ')
+            && (level == "failure" || level == "warning")
+        {
+            let (lint, summary) = rest.split_once(": ").unwrap_or((rest, ""));
+            report.changes.push(SemverChange {
+                lint: lint.to_string(),
+                level: level.to_string(),
+                summary: summary.to_string(),
+                description: String::new(),
+                reference: None,
+                locations: Vec::new(),
+            });
+            section = Section::None;
+        } else if let Some(change) = report.changes.last_mut() {
+            match trimmed {
+                "Description:" => section = Section::Description,
+                "Failed in:" => section = Section::Locations,
+                "" => section = Section::None,
+                _ => {
+                    if let Some(reference) = trimmed.strip_prefix("ref: ") {
+                        change.reference = Some(reference.to_string());
+                    } else if trimmed.starts_with("impl: ") {
+                        // Link to the lint's implementation; not useful to the agent
+                    } else {
+                        match section {
+                            Section::Description => {
+                                if !change.description.is_empty() {
+                                    change.description.push(' ');
+                                }
+                                change.description.push_str(trimmed);
+                            }
+                            Section::Locations => change.locations.push(trimmed.to_string()),
+                            Section::None => {}
+                        }
+                    }
+                }
+            }
+        }
+    }
+    if report.packages.is_empty() {
+        return None;
+    }
+    report.required_bump = report.packages.iter().filter_map(|p| p.required_bump).max();
+    Some(report)
+}
+
+/// `my_crate v0.1.0 -> v0.2.0 (minor change)`
+fn parse_checking(rest: &str) -> Option<SemverPackage> {
+    let (name, rest) = rest.split_once(' ')?;
+    let (baseline, rest) = rest.split_once(" -> ")?;
+    let (current, declared) = rest.split_once(' ').unwrap_or((rest, ""));
+    Some(SemverPackage {
+        name: name.to_string(),
+        baseline_version: baseline.trim_start_matches('v').to_string(),
+        current_version: current.trim_start_matches('v').to_string(),
+        declared_change: declared
+            .trim()
+            .trim_start_matches('(')
+            .trim_end_matches(')')
+            .to_string(),
+        required_bump: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OUTPUT: &str = "     Parsing my_crate v0.3.0 (current)
+      Parsed [   1.234s] (current)
+     Parsing my_crate v0.3.0 (baseline)
+      Parsed [   0.987s] (baseline)
+    Checking my_crate v0.3.0 -> v0.3.0 (no change)
+     Checked [   0.012s] 88 checks: 86 pass, 2 fail, 0 warn, 0 skip
+
+--- failure function_missing: pub fn removed or renamed ---
+
+Description:
+A publicly-visible function cannot be imported by its prior path. A `pub use` may have been removed,
+or the function itself may have been renamed or removed entirely.
+        ref: https://doc.rust-lang.org/cargo/reference/semver.html#item-remove
+       impl: https://github.com/obi1kenobi/cargo-semver-checks/tree/v0.41.0/src/lints/function_missing.ron
+
+Failed in:
+  function my_crate::parse, previously in file /tmp/base/src/lib.rs:3
+
+--- warning function_must_use_added: function #[must_use] added ---
+
+Description:
+A function is now #[must_use].
+        ref: https://doc.rust-lang.org/cargo/reference/semver.html#attr-must-use
+       impl: https://github.com/obi1kenobi/cargo-semver-checks/tree/v0.41.0/src/lints/function_must_use_added.ron
+
+Failed in:
+  function my_crate::render in /tmp/current/src/lib.rs:10
+
+     Summary semver requires new major version: 1 major and 0 minor checks failed
+    Finished [   2.345s] my_crate
+";
+
+    #[test]
+    fn test_parses_changes_and_required_bump() {
+        let report = parse_semver_output(OUTPUT, "/p").unwrap();
+        assert_eq!(report.working_directory, "/p");
+        assert_eq!(report.required_bump, Some(SemverBump::Major));
+        assert_eq!(
+            report.packages,
+            vec![SemverPackage {
+                name: "my_crate".to_string(),
+                baseline_version: "0.3.0".to_string(),
+                current_version: "0.3.0".to_string(),
+                declared_change: "no change".to_string(),
+                required_bump: Some(SemverBump::Major),
+            }]
+        );
+        assert_eq!(report.changes.len(), 2);
+        let missing = &report.changes[0];
+        assert_eq!(missing.lint, "function_missing");
+        assert_eq!(missing.level, "failure");
+        assert_eq!(missing.summary, "pub fn removed or renamed");
+        assert!(
+            missing
+                .description
+                .starts_with("A publicly-visible function")
+        );
+        assert!(missing.description.ends_with("removed entirely."));
+        assert_eq!(
+            missing.reference.as_deref(),
+            Some("https://doc.rust-lang.org/cargo/reference/semver.html#item-remove")
+        );
+        assert_eq!(
+            missing.locations,
+            vec!["function my_crate::parse, previously in file /tmp/base/src/lib.rs:3"]
+        );
+        assert_eq!(report.changes[1].level, "warning");
+    }
+
+    #[test]
+    fn test_maps_requirement_to_set_version_level() {
+        let mut report = parse_semver_output(OUTPUT, "/p").unwrap();
+        assert_eq!(report.set_version_level(), Some("minor"));
+        report.packages[0].current_version = "1.4.0".to_string();
+        assert_eq!(report.set_version_level(), Some("major"));
+        report.required_bump = Some(SemverBump::Minor);
+        assert_eq!(report.set_version_level(), Some("minor"));
+        assert_eq!(SemverBump::Minor.set_version_level("0.9.1"), "patch");
+    }
+
+    #[test]
+    fn test_no_update_required_and_unchecked_output() {
+        let output = "    Checking lib v1.0.0 -> v1.0.1 (patch change)\n     Summary no semver update required\n";
+        let report = parse_semver_output(output, "/p").unwrap();
+        assert_eq!(report.required_bump, Some(SemverBump::Patch));
+        assert!(report.changes.is_empty());
+        assert!(
+            report
+                .render()
+                .starts_with("Semver: minimum required bump patch (0 breaking change(s), 0 warning(s))\n  lib 1.0.0 -> 1.0.1 (patch change): requires patch")
+        );
+        assert_eq!(
+            parse_semver_output("error: failed to build rustdoc for crate", "/p"),
+            None
+        );
+    }
+}
@@ -9,6 +9,7 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, Command};
@@ -28,6 +29,11 @@ pub struct ShellPoolConfig {
     pub shell_spawn_timeout: Duration,
     pub command_timeout: Duration,
     pub health_check_interval: Duration,
+    /// Grow pools of busy directories toward `max_total_shells` and shrink them again when demand drops.
+    /// `shells_per_directory` is the baseline size each pool returns to.
+    pub adaptive_sizing: bool,
+    /// Directories whose pools are created and filled at startup instead of on first use
+    pub prewarm_directories: Vec<PathBuf>,
 }
 
 impl Default for ShellPoolConfig {
@@ -41,6 +47,8 @@ impl Default for ShellPoolConfig {
             shell_spawn_timeout: Duration::from_secs(5),
             command_timeout: Duration::from_secs(300),
             health_check_interval: Duration::from_secs(60),
+            adaptive_sizing: true,
+            prewarm_directories: Vec::new(),
         }
     }
 }
@@ -179,7 +187,10 @@ impl PrewarmedShell {
         );
 
         // Spawn bash process with JSON communication
-        let mut process = Command::new("bash")
+        let mut command = Command::new("bash");
+        // Commands run in this shell share the server-wide jobserver, if one is configured
+        crate::cpu_budget::configure_command(&mut command);
+        let mut process = command
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped()) // capture stderr for diagnostics
@@ -496,6 +507,24 @@ impl Drop for PrewarmedShell {
     }
 }
 
+/// Demand counters for a single pool
+#[derive(Debug, Default)]
+struct PoolMetrics {
+    /// Requests served by an idle prewarmed shell
+    hits: u64,
+    /// Requests that had to spawn a new shell
+    misses: u64,
+    /// Requests rejected because the manager was at capacity (caller falls back to direct spawn)
+    fallbacks: u64,
+    /// Total time callers spent waiting for a shell
+    total_wait: Duration,
+    /// Shells currently checked out
+    in_use: usize,
+    peak_in_use: usize,
+    /// Misses since the last rebalance, used to decide whether to shrink
+    recent_misses: u64,
+}
+
 /// Pool of shells for a specific working directory
 #[derive(Debug)]
 pub struct ShellPool {
@@ -503,6 +532,10 @@ pub struct ShellPool {
     shells: Mutex<Vec<PrewarmedShell>>,
     config: ShellPoolConfig,
     last_accessed: Mutex<Instant>,
+    /// Number of idle shells this pool keeps; starts at `shells_per_directory`
+    target_size: AtomicUsize,
+    metrics: Mutex<PoolMetrics>,
+    prewarmed: bool,
 }
 
 impl ShellPool {
@@ -510,17 +543,27 @@ impl ShellPool {
     pub fn new(working_dir: impl AsRef<Path>, config: ShellPoolConfig) -> Self {
         let working_dir = working_dir.as_ref().to_path_buf();
         info!("Creating shell pool for directory: {:?}", working_dir);
+        let prewarmed = config.prewarm_directories.contains(&working_dir);
 
         Self {
             working_dir,
             shells: Mutex::new(Vec::new()),
+            target_size: AtomicUsize::new(config.shells_per_directory),
             config,
             last_accessed: Mutex::new(Instant::now()),
+            metrics: Mutex::new(PoolMetrics::default()),
+            prewarmed,
         }
     }
 
     /// Get a shell from the pool, creating one if necessary
     pub async fn get_shell(&self) -> Result<PrewarmedShell, ShellError> {
+        self.acquire().await.map(|(shell, _hit)| shell)
+    }
+
+    /// Get a shell and report whether it came from the idle list (hit) or was spawned (miss)
+    async fn acquire(&self) -> Result<(PrewarmedShell, bool), ShellError> {
+        let started = Instant::now();
         let mut last_accessed = self.last_accessed.lock().await;
         *last_accessed = Instant::now();
         drop(last_accessed);
@@ -528,10 +571,12 @@ impl ShellPool {
         let mut shells = self.shells.lock().await;
 
         // Try to find a healthy shell
+        let mut reused = None;
         while let Some(shell) = shells.pop() {
             if shell.is_healthy() {
                 debug!("Reusing healthy shell {} from pool", shell.id());
-                return Ok(shell);
+                reused = Some(shell);
+                break;
             } else {
                 debug!("Discarding unhealthy shell {} from pool", shell.id());
                 // Shell is unhealthy, let it drop and try next
@@ -540,20 +585,132 @@ impl ShellPool {
 
         drop(shells);
 
-        // No healthy shells available, create a new one
-        debug!(
-            "Creating new shell for pool (directory: {:?})",
-            self.working_dir
-        );
-        PrewarmedShell::new(&self.working_dir, &self.config).await
+        let hit = reused.is_some();
+        let shell = match reused {
+            Some(shell) => shell,
+            None => {
+                // No healthy shells available, create a new one
+                debug!(
+                    "Creating new shell for pool (directory: {:?})",
+                    self.working_dir
+                );
+                PrewarmedShell::new(&self.working_dir, &self.config).await?
+            }
+        };
+
+        let mut metrics = self.metrics.lock().await;
+        if hit {
+            metrics.hits += 1;
+        } else {
+            metrics.misses += 1;
+            metrics.recent_misses += 1;
+        }
+        metrics.total_wait += started.elapsed();
+        metrics.in_use += 1;
+        metrics.peak_in_use = metrics.peak_in_use.max(metrics.in_use);
+        Ok((shell, hit))
+    }
+
+    /// Record a request that could not be served because the manager was at capacity
+    async fn record_fallback(&self) {
+        self.metrics.lock().await.fallbacks += 1;
+    }
+
+    /// Number of shells currently checked out from this pool
+    async fn in_use(&self) -> usize {
+        self.metrics.lock().await.in_use
+    }
+
+    /// Number of idle shells this pool currently aims to keep
+    pub fn target_size(&self) -> usize {
+        self.target_size.load(Ordering::Relaxed)
+    }
+
+    /// Spawn shells until the pool holds `target_size` idle shells. Returns how many were added.
+    pub async fn fill(&self) -> usize {
+        let mut added = 0;
+        while self.shell_count().await < self.target_size() {
+            match PrewarmedShell::new(&self.working_dir, &self.config).await {
+                Ok(shell) => {
+                    self.shells.lock().await.push(shell);
+                    added += 1;
+                }
+                Err(e) => {
+                    warn!("Failed to prewarm shell for {:?}: {}", self.working_dir, e);
+                    break;
+                }
+            }
+        }
+        added
+    }
+
+    /// Shrink the target by one step toward the baseline when the last interval saw no misses,
+    /// or all the way when the pool has been idle past `shell_idle_timeout`, then drop surplus
+    /// idle shells. Returns how many shells were dropped.
+    async fn shrink_if_cold(&self) -> usize {
+        let baseline = self.config.shells_per_directory;
+        let recent_misses = std::mem::take(&mut self.metrics.lock().await.recent_misses);
+        let target = self.target_size();
+        let new_target = if self.is_idle().await {
+            // Non-prewarmed pools release all idle processes; prewarmed ones keep the baseline
+            if self.prewarmed { baseline } else { 0 }
+        } else if recent_misses == 0 && target > baseline {
+            target - 1
+        } else {
+            target
+        };
+        if new_target != target {
+            debug!(
+                "Shrinking pool for {:?} from {} to {} shells",
+                self.working_dir, target, new_target
+            );
+            self.target_size.store(new_target, Ordering::Relaxed);
+        }
+
+        let mut shells = self.shells.lock().await;
+        let mut dropped = 0;
+        while shells.len() > new_target {
+            if let Some(mut shell) = shells.pop() {
+                shell.shutdown().await;
+                dropped += 1;
+            }
+        }
+        dropped
+    }
+
+    /// Snapshot of this pool's size and demand counters
+    pub async fn stats(&self) -> PoolDirectoryStats {
+        let idle_shells = self.shell_count().await;
+        let metrics = self.metrics.lock().await;
+        let served = metrics.hits + metrics.misses;
+        PoolDirectoryStats {
+            working_dir: self.working_dir.clone(),
+            idle_shells,
+            in_use: metrics.in_use,
+            peak_in_use: metrics.peak_in_use,
+            target_size: self.target_size(),
+            hits: metrics.hits,
+            misses: metrics.misses,
+            fallbacks: metrics.fallbacks,
+            avg_wait: if served > 0 {
+                metrics.total_wait / served as u32
+            } else {
+                Duration::ZERO
+            },
+            prewarmed: self.prewarmed,
+        }
     }
 
     /// Return a shell to the pool
     pub async fn return_shell(&self, shell: PrewarmedShell) {
+        {
+            let mut metrics = self.metrics.lock().await;
+            metrics.in_use = metrics.in_use.saturating_sub(1);
+        }
         let mut shells = self.shells.lock().await;
 
         // Only return healthy shells and respect pool size limits
-        if shell.is_healthy() && shells.len() < self.config.shells_per_directory {
+        if shell.is_healthy() && shells.len() < self.target_size() {
             debug!("Returning shell {} to pool", shell.id());
             shells.push(shell);
         } else {
@@ -654,6 +811,13 @@ impl ShellPoolManager {
                 }
             });
 
+            if !self.config.prewarm_directories.is_empty() {
+                let manager_for_prewarm = Arc::clone(&self);
+                tokio::spawn(async move {
+                    manager_for_prewarm.prewarm_configured_directories().await;
+                });
+            }
+
             info!("Started background tasks for shell pool monitoring");
         }
     }
@@ -667,15 +831,6 @@ impl ShellPoolManager {
 
         let working_dir = working_dir.as_ref().to_path_buf();
 
-        // Check if we're at capacity
-        {
-            let total_shells = self.total_shells.lock().await;
-            if *total_shells >= self.config.max_total_shells {
-                warn!("Shell pool manager at capacity ({} shells)", *total_shells);
-                return None;
-            }
-        }
-
         // Get or create pool for this directory
         let pool = {
             let pools = self.pools.read().await;
@@ -687,12 +842,27 @@ impl ShellPoolManager {
             }
         };
 
+        // Check if we're at capacity
+        {
+            let total_shells = self.total_shells.lock().await;
+            if *total_shells >= self.config.max_total_shells {
+                warn!("Shell pool manager at capacity ({} shells)", *total_shells);
+                drop(total_shells);
+                pool.record_fallback().await;
+                return None;
+            }
+        }
+
         // Get shell from pool
-        match pool.get_shell().await {
-            Ok(shell) => {
+        match pool.acquire().await {
+            Ok((shell, hit)) => {
                 let mut total_shells = self.total_shells.lock().await;
                 *total_shells += 1;
                 debug!("Got shell from pool, total shells: {}", *total_shells);
+                drop(total_shells);
+                if !hit {
+                    self.grow_if_hot(&pool).await;
+                }
                 Some(shell)
             }
             Err(e) => {
@@ -723,6 +893,46 @@ impl ShellPoolManager {
         }
     }
 
+    /// After a miss, raise the pool's target when concurrent demand exceeds it, as long as the
+    /// targets of all pools stay within `max_total_shells`
+    async fn grow_if_hot(&self, pool: &ShellPool) {
+        if !self.config.adaptive_sizing {
+            return;
+        }
+        let target = pool.target_size();
+        if pool.in_use().await <= target {
+            return;
+        }
+        let reserved: usize = {
+            let pools = self.pools.read().await;
+            pools.values().map(|p| p.target_size()).sum()
+        };
+        if reserved < self.config.max_total_shells {
+            pool.target_size.store(target + 1, Ordering::Relaxed);
+            debug!(
+                "Growing pool for {:?} to {} shells",
+                pool.working_dir(),
+                target + 1
+            );
+        }
+    }
+
+    /// Create pools for the configured prewarm directories and fill them to their baseline size
+    pub async fn prewarm_configured_directories(&self) -> usize {
+        if !self.config.enabled {
+            return 0;
+        }
+        let mut added = 0;
+        for dir in &self.config.prewarm_directories {
+            let pool = self.create_pool_for_dir(dir).await;
+            added += pool.fill().await;
+        }
+        if added > 0 {
+            info!("Prewarmed {} shells for configured directories", added);
+        }
+        added
+    }
+
     /// Create a new pool for the specified working directory
     async fn create_pool_for_dir(&self, working_dir: &Path) -> Arc<ShellPool> {
         let mut pools = self.pools.write().await;
@@ -794,25 +1004,30 @@ impl ShellPoolManager {
         let pools = self.pools.read().await;
         let total_shells = *self.total_shells.lock().await;
 
+        let mut directories = Vec::with_capacity(pools.len());
+        for pool in pools.values() {
+            directories.push(pool.stats().await);
+        }
+        directories.sort_by(|a, b| a.working_dir.cmp(&b.working_dir));
+
         ShellPoolStats {
             total_pools: pools.len(),
             total_shells,
             max_shells: self.config.max_total_shells,
+            directories,
         }
     }
 
-    /// Clean up idle shells across all pools
+    /// Clean up idle shells across all pools, shrinking pools whose demand has dropped
     async fn cleanup_idle_shells(&self) {
         let pools = self.pools.read().await;
         let mut cleaned_count = 0;
 
         for (path, pool) in pools.iter() {
-            let before_count = pool.shell_count().await;
-            // Shells will be cleaned up based on their idle timeout
-            // This is a placeholder - actual cleanup logic would be in ShellPool
             debug!("Checking pool {:?} for idle shells", path);
-            let after_count = pool.shell_count().await;
-            cleaned_count += before_count.saturating_sub(after_count);
+            if self.config.adaptive_sizing {
+                cleaned_count += pool.shrink_if_cold().await;
+            }
         }
 
         if cleaned_count > 0 {
@@ -835,8 +1050,40 @@ impl ShellPoolManager {
 #[derive(Debug, Clone)]
 pub struct ShellPoolStats {
     pub total_pools: usize,
+    /// Shells currently checked out across all pools
     pub total_shells: usize,
     pub max_shells: usize,
+    /// Per-directory breakdown, sorted by directory
+    pub directories: Vec<PoolDirectoryStats>,
+}
+
+/// Size and demand statistics for one working directory's pool
+#[derive(Debug, Clone)]
+pub struct PoolDirectoryStats {
+    pub working_dir: PathBuf,
+    pub idle_shells: usize,
+    pub in_use: usize,
+    pub peak_in_use: usize,
+    pub target_size: usize,
+    pub hits: u64,
+    pub misses: u64,
+    /// Requests that fell back to direct spawn because the manager was at capacity
+    pub fallbacks: u64,
+    /// Average time callers waited for a shell (including spawn time on a miss)
+    pub avg_wait: Duration,
+    pub prewarmed: bool,
+}
+
+impl PoolDirectoryStats {
+    /// Fraction of all requests (including fallbacks) served by an idle shell
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses + self.fallbacks;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
 }
 
 #[cfg(test)]
@@ -888,4 +1135,97 @@ mod tests {
         let pool = ShellPool::new(temp_dir.path(), config);
         assert_eq!(pool.working_dir(), temp_dir.path());
     }
+
+    #[tokio::test]
+    async fn test_pool_tracks_hits_and_misses() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ShellPoolManager::new(ShellPoolConfig::default());
+
+        let shell = manager.get_shell(temp_dir.path()).await.unwrap();
+        manager.return_shell(shell).await;
+        let shell = manager.get_shell(temp_dir.path()).await.unwrap();
+        manager.return_shell(shell).await;
+
+        let stats = manager.get_stats().await;
+        assert_eq!(stats.directories.len(), 1);
+        let dir = &stats.directories[0];
+        assert_eq!((dir.hits, dir.misses, dir.in_use), (1, 1, 0));
+        assert_eq!(dir.idle_shells, 1);
+        assert!((dir.hit_rate() - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn test_hot_pool_grows_and_cold_pool_shrinks() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = ShellPoolConfig {
+            shells_per_directory: 1,
+            max_total_shells: 4,
+            ..Default::default()
+        };
+        let manager = ShellPoolManager::new(config);
+
+        // Three concurrent users exceed the baseline of one shell
+        let mut checked_out = Vec::new();
+        for _ in 0..3 {
+            checked_out.push(manager.get_shell(temp_dir.path()).await.unwrap());
+        }
+        let stats = manager.get_stats().await;
+        assert_eq!(stats.directories[0].target_size, 3);
+        for shell in checked_out {
+            manager.return_shell(shell).await;
+        }
+        assert_eq!(manager.get_stats().await.directories[0].idle_shells, 3);
+
+        // The interval that saw the misses keeps its size
+        manager.cleanup_idle_shells().await;
+        assert_eq!(manager.get_stats().await.directories[0].target_size, 3);
+
+        // Intervals without misses shrink one step at a time back to the baseline
+        manager.cleanup_idle_shells().await;
+        let dir = &manager.get_stats().await.directories[0];
+        assert_eq!((dir.target_size, dir.idle_shells), (2, 2));
+        manager.cleanup_idle_shells().await;
+        manager.cleanup_idle_shells().await;
+        let dir = &manager.get_stats().await.directories[0];
+        assert_eq!((dir.target_size, dir.idle_shells), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_fixed_size_pool_does_not_grow() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = ShellPoolConfig {
+            shells_per_directory: 1,
+            adaptive_sizing: false,
+            ..Default::default()
+        };
+        let manager = ShellPoolManager::new(config);
+
+        let a = manager.get_shell(temp_dir.path()).await.unwrap();
+        let b = manager.get_shell(temp_dir.path()).await.unwrap();
+        manager.return_shell(a).await;
+        manager.return_shell(b).await;
+
+        let dir = &manager.get_stats().await.directories[0];
+        assert_eq!((dir.target_size, dir.idle_shells), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_prewarm_configured_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = ShellPoolConfig {
+            prewarm_directories: vec![temp_dir.path().to_path_buf()],
+            ..Default::default()
+        };
+        let manager = ShellPoolManager::new(config);
+
+        assert_eq!(manager.prewarm_configured_directories().await, 2);
+        let dir = &manager.get_stats().await.directories[0];
+        assert!(dir.prewarmed);
+        assert_eq!(dir.idle_shells, 2);
+
+        // First request is served by a prewarmed shell
+        let shell = manager.get_shell(temp_dir.path()).await.unwrap();
+        manager.return_shell(shell).await;
+        assert_eq!(manager.get_stats().await.directories[0].hits, 1);
+    }
 }
@@ -0,0 +1,167 @@
+//! Speculative background `check` of the workspaces the server has seen
+//!
+//! With `--speculative-check`, every `working_directory` named in a request is watched the way
+//! watch sessions watch theirs. After a debounced batch of changes the workspace is checked in
+//! the background: in its own target dir (`target/async_cargo_mcp/speculative`) so it never holds
+//! the lock a foreground build waits for, one workspace at a time, and only once no foreground
+//! operation is running in that directory. The result is kept with the source fingerprint taken
+//! before the check started, and an explicit `check` of the default selection whose sources still
+//! have that fingerprint returns it without running cargo.
+//!
+//! Speculative runs record their diagnostics in a store of their own that keeps no delta
+//! baselines, so a result nobody looked at never counts as the user's previous run. The delta is
+//! computed against the user's baseline when the result is returned.
+
+use crate::diagnostics::DiagnosticsStore;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::{Mutex, MutexGuard, RwLock};
+use tokio::task::JoinHandle;
+
+/// Quiet period after the last change before a speculative check starts
+pub const SPECULATIVE_DEBOUNCE_MS: u64 = 1000;
+
+/// Isolated target dir name, under `target/async_cargo_mcp/`
+pub const SPECULATIVE_TARGET_DIR: &str = "speculative";
+
+/// A finished speculative check
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeculativeResult {
+    pub operation_id: String,
+    /// Source fingerprint taken before the check started
+    pub fingerprint: u64,
+    pub result: Result<String, String>,
+    pub finished_at: String,
+}
+
+/// Watched workspaces and their latest speculative check
+#[derive(Debug, Default)]
+pub struct SpeculativeChecks {
+    enabled: bool,
+    /// Tasks checking each watched working directory
+    workspaces: Mutex<HashMap<String, JoinHandle<()>>>,
+    results: RwLock<HashMap<String, SpeculativeResult>>,
+    /// Held while a speculative check runs, so only one runs at a time
+    slot: Mutex<()>,
+    counter: AtomicU64,
+    /// Reports of speculative runs, kept apart from the user's delta baselines
+    diagnostics: Arc<DiagnosticsStore>,
+}
+
+impl SpeculativeChecks {
+    /// Speculative checks turned off; `watch` never starts anything
+    pub fn disabled() -> Self {
+        Self {
+            diagnostics: Arc::new(DiagnosticsStore::without_deltas()),
+            ..Self::default()
+        }
+    }
+
+    pub fn enabled() -> Self {
+        Self {
+            enabled: true,
+            ..Self::disabled()
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Where speculative runs record their reports
+    pub fn diagnostics(&self) -> Arc<DiagnosticsStore> {
+        self.diagnostics.clone()
+    }
+
+    /// Start watching `working_directory` with the task `start` spawns, unless it is already
+    /// watched. Returns whether a new task was started.
+    pub async fn watch(
+        &self,
+        working_directory: &str,
+        start: impl FnOnce() -> JoinHandle<()>,
+    ) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let mut workspaces = self.workspaces.lock().await;
+        if workspaces.contains_key(working_directory) {
+            return false;
+        }
+        workspaces.insert(working_directory.to_string(), start());
+        true
+    }
+
+    pub async fn is_watched(&self, working_directory: &str) -> bool {
+        self.workspaces.lock().await.contains_key(working_directory)
+    }
+
+    /// Wait until no other speculative check is running
+    pub async fn acquire_slot(&self) -> MutexGuard<'_, ()> {
+        self.slot.lock().await
+    }
+
+    pub fn next_operation_id(&self) -> String {
+        format!(
+            "op_speculative_check_{}",
+            self.counter.fetch_add(1, Ordering::SeqCst)
+        )
+    }
+
+    pub async fn record(&self, working_directory: &str, result: SpeculativeResult) {
+        self.results
+            .write()
+            .await
+            .insert(working_directory.to_string(), result);
+    }
+
+    pub async fn latest(&self, working_directory: &str) -> Option<SpeculativeResult> {
+        self.results.read().await.get(working_directory).cloned()
+    }
+
+    /// The latest result for `working_directory` if it was computed for sources with `fingerprint`
+    pub async fn cached(
+        &self,
+        working_directory: &str,
+        fingerprint: u64,
+    ) -> Option<SpeculativeResult> {
+        self.latest(working_directory)
+            .await
+            .filter(|r| r.fingerprint == fingerprint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_watches_each_workspace_once_when_enabled() {
+        let disabled = SpeculativeChecks::disabled();
+        assert!(!disabled.watch("/p", || tokio::spawn(async {})).await);
+        assert!(!disabled.is_watched("/p").await);
+
+        let checks = SpeculativeChecks::enabled();
+        assert!(checks.watch("/p", || tokio::spawn(async {})).await);
+        assert!(!checks.watch("/p", || panic!("already watched")).await);
+        assert!(checks.is_watched("/p").await);
+        assert!(!checks.is_watched("/q").await);
+    }
+
+    #[tokio::test]
+    async fn test_cached_result_requires_matching_fingerprint() {
+        let checks = SpeculativeChecks::enabled();
+        assert_eq!(checks.cached("/p", 1).await, None);
+        let result = SpeculativeResult {
+            operation_id: checks.next_operation_id(),
+            fingerprint: 1,
+            result: Ok("+ Check operation completed successfully in /p.".to_string()),
+            finished_at: "12:00:00".to_string(),
+        };
+        checks.record("/p", result.clone()).await;
+        assert_eq!(checks.cached("/p", 1).await, Some(result));
+        assert_eq!(checks.cached("/p", 2).await, None);
+        assert_eq!(checks.cached("/q", 1).await, None);
+        assert_eq!(checks.next_operation_id(), "op_speculative_check_1");
+    }
+}
@@ -0,0 +1,640 @@
+//! Applying rustc's suggested replacements with a diff preview and rollback
+//!
+//! Diagnostics recorded from a build/check/clippy/test run carry the replacements rustc
+//! proposes, each with a byte range and an applicability level. `apply_suggestions` selects
+//! them by lint, file and applicability, shows a unified diff, and writes every touched file
+//! or none of them. The original contents are backed up so `undo_suggestions` can restore them.
+
+use crate::diagnostics::{CargoReport, Suggestion};
+use serde::{Deserialize, Serialize};
+use similar::TextDiff;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tracing::warn;
+
+/// How certain rustc is that a suggestion is correct, most certain first
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Default,
+    Serialize,
+    Deserialize,
+    schemars::JsonSchema,
+)]
+pub enum Applicability {
+    /// Safe to apply automatically
+    #[default]
+    MachineApplicable,
+    /// Probably right, but may not compile or may change behavior
+    MaybeIncorrect,
+    /// Contains placeholders such as `(...)` that must be filled in
+    HasPlaceholders,
+    /// Unknown
+    Unspecified,
+}
+
+impl Applicability {
+    fn parse(value: Option<&str>) -> Applicability {
+        match value {
+            Some("MachineApplicable") => Applicability::MachineApplicable,
+            Some("MaybeIncorrect") => Applicability::MaybeIncorrect,
+            Some("HasPlaceholders") => Applicability::HasPlaceholders,
+            _ => Applicability::Unspecified,
+        }
+    }
+}
+
+/// Which suggestions to apply
+#[derive(Debug, Clone, Default)]
+pub struct SuggestionFilter {
+    /// Lint names or error codes; `clippy::` may be omitted
+    pub lints: Option<Vec<String>>,
+    /// Files as reported by rustc (relative to the workspace root) or trailing path components of them
+    pub files: Option<Vec<String>>,
+    /// Least certain applicability to accept
+    pub applicability: Applicability,
+}
+
+impl SuggestionFilter {
+    fn matches_lint(&self, code: Option<&str>) -> bool {
+        let Some(lints) = &self.lints else {
+            return true;
+        };
+        let Some(code) = code else {
+            return false;
+        };
+        lints.iter().any(|lint| {
+            code == lint
+                || code
+                    .strip_prefix("clippy::")
+                    .is_some_and(|short| short == lint)
+        })
+    }
+
+    fn matches_file(&self, file: &str) -> bool {
+        match &self.files {
+            // Whole path components only, so "lib.rs" does not select "src/stdlib.rs"
+            Some(files) => files
+                .iter()
+                .any(|f| Path::new(file).ends_with(Path::new(f))),
+            None => true,
+        }
+    }
+}
+
+/// Planned new contents for one file
+#[derive(Debug, Clone)]
+pub struct FileEdit {
+    pub path: PathBuf,
+    /// Path as reported by rustc, used in the diff header
+    pub display: String,
+    pub original: String,
+    pub updated: String,
+    /// Number of replacements applied to this file
+    pub replacements: usize,
+}
+
+/// Edits selected from a report, plus the reasons some suggestions were left out
+#[derive(Debug, Clone, Default)]
+pub struct EditPlan {
+    pub files: Vec<FileEdit>,
+    pub skipped: Vec<String>,
+}
+
+impl EditPlan {
+    pub fn replacements(&self) -> usize {
+        self.files.iter().map(|f| f.replacements).sum()
+    }
+
+    /// Unified diff of all planned edits
+    pub fn diff(&self) -> String {
+        self.files
+            .iter()
+            .map(|f| {
+                TextDiff::from_lines(&f.original, &f.updated)
+                    .unified_diff()
+                    .context_radius(3)
+                    .header(&format!("a/{}", f.display), &format!("b/{}", f.display))
+                    .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("")
+    }
+}
+
+/// Replacements that must be applied together (the parts of one multipart suggestion)
+struct SuggestionGroup<'a> {
+    code: Option<&'a str>,
+    parts: Vec<&'a Suggestion>,
+}
+
+/// Select suggestions from `report` and compute the resulting file contents.
+/// Files are resolved against `working_directory` and its ancestors, since rustc reports
+/// paths relative to the workspace root. Files modified after `recorded_at` are refused,
+/// because the byte offsets no longer match.
+pub fn plan_edits(
+    report: &CargoReport,
+    working_directory: &Path,
+    filter: &SuggestionFilter,
+    recorded_at: SystemTime,
+) -> EditPlan {
+    let mut plan = EditPlan::default();
+
+    // Group the parts of each suggestion; rustc reports the same suggestion once per target
+    // (lib and test), so identical groups are only kept once
+    let mut groups: Vec<SuggestionGroup> = Vec::new();
+    for diagnostic in &report.diagnostics {
+        let mut by_message: BTreeMap<&str, Vec<&Suggestion>> = BTreeMap::new();
+        for suggestion in &diagnostic.suggestions {
+            by_message
+                .entry(suggestion.message.as_str())
+                .or_default()
+                .push(suggestion);
+        }
+        for parts in by_message.into_values() {
+            if !groups.iter().any(|g| g.parts == parts) {
+                groups.push(SuggestionGroup {
+                    code: diagnostic.code.as_deref(),
+                    parts,
+                });
+            }
+        }
+    }
+
+    let mut by_file: BTreeMap<String, Vec<&SuggestionGroup>> = BTreeMap::new();
+    for group in &groups {
+        let applicability = group
+            .parts
+            .iter()
+            .map(|s| Applicability::parse(s.applicability.as_deref()))
+            .max()
+            .unwrap_or_default();
+        if applicability > filter.applicability
+            || !filter.matches_lint(group.code)
+            || !group
+                .parts
+                .iter()
+                .all(|s| filter.matches_file(&s.span.file))
+        {
+            continue;
+        }
+        // Multipart suggestions spanning several files are rare; keep the plan per file simple
+        let file = &group.parts[0].span.file;
+        if group.parts.iter().any(|s| &s.span.file != file) {
+            plan.skipped.push(format!(
+                "{}: suggestion spans several files",
+                group.code.unwrap_or("suggestion")
+            ));
+            continue;
+        }
+        by_file.entry(file.clone()).or_default().push(group);
+    }
+
+    for (file, groups) in by_file {
+        let Some(path) = resolve_path(working_directory, &file) else {
+            plan.skipped.push(format!(
+                "{file}: not found under {}",
+                working_directory.display()
+            ));
+            continue;
+        };
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+        if modified.is_some_and(|m| m > recorded_at) {
+            plan.skipped.push(format!(
+                "{file}: modified after the diagnostics were recorded; rerun check first"
+            ));
+            continue;
+        }
+        let original = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) => {
+                plan.skipped.push(format!("{file}: {e}"));
+                continue;
+            }
+        };
+
+        match apply_groups(&original, &groups) {
+            Ok((updated, replacements, overlaps)) => {
+                if overlaps > 0 {
+                    plan.skipped.push(format!(
+                        "{file}: {overlaps} suggestion(s) overlap an earlier one; rerun check and apply again"
+                    ));
+                }
+                if replacements > 0 {
+                    plan.files.push(FileEdit {
+                        path,
+                        display: file,
+                        original,
+                        updated,
+                        replacements,
+                    });
+                }
+            }
+            Err(e) => plan.skipped.push(format!("{file}: {e}")),
+        }
+    }
+    plan
+}
+
+/// Apply non-overlapping groups to `original`. Returns the new text, the number of
+/// replacements applied and the number of groups skipped because they overlap.
+fn apply_groups(
+    original: &str,
+    groups: &[&SuggestionGroup],
+) -> Result<(String, usize, usize), String> {
+    let mut accepted: Vec<&Suggestion> = Vec::new();
+    let mut overlaps = 0;
+    for group in groups {
+        let conflicts = group.parts.iter().any(|part| {
+            accepted.iter().any(|taken| {
+                let (a, b) = (&part.span, &taken.span);
+                (a.byte_start < b.byte_end && b.byte_start < a.byte_end)
+                    || a.byte_start == b.byte_start
+            })
+        });
+        if conflicts {
+            overlaps += 1;
+        } else {
+            accepted.extend(group.parts.iter().copied());
+        }
+    }
+
+    accepted.sort_by_key(|s| (s.span.byte_start, s.span.byte_end));
+    let mut updated = String::with_capacity(original.len());
+    let mut cursor = 0;
+    for suggestion in &accepted {
+        let (start, end) = (suggestion.span.byte_start, suggestion.span.byte_end);
+        if end > original.len()
+            || !original.is_char_boundary(start)
+            || !original.is_char_boundary(end)
+        {
+            return Err("suggestion offsets do not match the file; rerun check".to_string());
+        }
+        updated.push_str(&original[cursor..start]);
+        updated.push_str(&suggestion.replacement);
+        cursor = end;
+    }
+    updated.push_str(&original[cursor..]);
+    Ok((updated, accepted.len(), overlaps))
+}
+
+/// Find `file` relative to `working_directory` or one of its ancestors
+fn resolve_path(working_directory: &Path, file: &str) -> Option<PathBuf> {
+    let file = Path::new(file);
+    if file.is_absolute() {
+        return file.is_file().then(|| file.to_path_buf());
+    }
+    working_directory
+        .ancestors()
+        .map(|dir| dir.join(file))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Files changed by one `apply_suggestions` call
+#[derive(Debug, Clone)]
+struct AppliedChange {
+    apply_id: String,
+    /// (file, backup of the original, contents written)
+    files: Vec<(PathBuf, PathBuf, String)>,
+}
+
+/// Backups of applied suggestions, newest last
+#[derive(Debug)]
+pub struct SuggestionBackups {
+    dir: PathBuf,
+    applied: Mutex<Vec<AppliedChange>>,
+}
+
+impl Default for SuggestionBackups {
+    fn default() -> Self {
+        Self::new(Self::default_dir())
+    }
+}
+
+impl SuggestionBackups {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            applied: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Per-process backup directory under the system temp dir
+    pub fn default_dir() -> PathBuf {
+        std::env::temp_dir()
+            .join("async_cargo_mcp")
+            .join("backups")
+            .join(std::process::id().to_string())
+    }
+
+    /// Write all planned edits, or none of them. Originals are backed up first.
+    pub fn apply(&self, apply_id: &str, plan: &EditPlan) -> Result<(), String> {
+        let backup_dir = self.dir.join(apply_id);
+        std::fs::create_dir_all(&backup_dir)
+            .map_err(|e| format!("Failed to create backup dir {}: {e}", backup_dir.display()))?;
+
+        let mut files = Vec::new();
+        for (index, edit) in plan.files.iter().enumerate() {
+            let backup = backup_dir.join(format!("{index}.orig"));
+            std::fs::write(&backup, &edit.original)
+                .map_err(|e| format!("Failed to back up {}: {e}", edit.path.display()))?;
+            files.push((edit.path.clone(), backup, edit.updated.clone()));
+        }
+
+        let targets: Vec<(PathBuf, String)> = plan
+            .files
+            .iter()
+            .map(|e| (e.path.clone(), e.updated.clone()))
+            .collect();
+        write_all_or_restore(&targets, &files)?;
+
+        self.applied
+            .lock()
+            .expect("suggestion backups lock poisoned")
+            .push(AppliedChange {
+                apply_id: apply_id.to_string(),
+                files,
+            });
+        Ok(())
+    }
+
+    /// Restore the files changed by `apply_id` (default: the most recent apply).
+    /// Files edited since the apply are left alone unless `force` is set.
+    /// Returns the apply ID that was undone and the restored files.
+    pub fn undo(
+        &self,
+        apply_id: Option<&str>,
+        force: bool,
+    ) -> Result<(String, Vec<PathBuf>), String> {
+        let mut applied = self
+            .applied
+            .lock()
+            .expect("suggestion backups lock poisoned");
+        let index = match apply_id {
+            Some(id) => applied
+                .iter()
+                .position(|c| c.apply_id == id)
+                .ok_or_else(|| format!("No applied suggestions with apply_id '{id}'"))?,
+            None => applied
+                .len()
+                .checked_sub(1)
+                .ok_or_else(|| "No applied suggestions to undo".to_string())?,
+        };
+        let change = &applied[index];
+
+        if !force {
+            let edited: Vec<String> = change
+                .files
+                .iter()
+                .filter(|(path, _, written)| {
+                    std::fs::read_to_string(path).map_or(true, |now| &now != written)
+                })
+                .map(|(path, _, _)| path.display().to_string())
+                .collect();
+            if !edited.is_empty() {
+                return Err(format!(
+                    "Files changed since suggestions were applied: {}. Use force: true to restore them anyway",
+                    edited.join(", ")
+                ));
+            }
+        }
+
+        let mut targets = Vec::new();
+        for (path, backup, _) in &change.files {
+            let original = std::fs::read_to_string(backup)
+                .map_err(|e| format!("Failed to read backup {}: {e}", backup.display()))?;
+            targets.push((path.clone(), original));
+        }
+        write_all_or_restore(&targets, &[])?;
+
+        let change = applied.remove(index);
+        let backup_dir = self.dir.join(&change.apply_id);
+        if let Err(e) = std::fs::remove_dir_all(&backup_dir) {
+            warn!("Failed to remove backup dir {}: {e}", backup_dir.display());
+        }
+        Ok((
+            change.apply_id,
+            change.files.into_iter().map(|(path, _, _)| path).collect(),
+        ))
+    }
+}
+
+/// Write every `(path, contents)` via a temp file and rename. If a rename fails, files already
+/// replaced are restored from `backups` (entries of `(path, backup, _)`).
+fn write_all_or_restore(
+    targets: &[(PathBuf, String)],
+    backups: &[(PathBuf, PathBuf, String)],
+) -> Result<(), String> {
+    let temp_path = |path: &Path| {
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(".async_cargo_mcp.tmp");
+        path.with_file_name(name)
+    };
+
+    // Stage everything first so a write failure leaves all files untouched
+    for (index, (path, contents)) in targets.iter().enumerate() {
+        if let Err(e) = std::fs::write(temp_path(path), contents) {
+            for (staged, _) in &targets[..=index] {
+                let _ = std::fs::remove_file(temp_path(staged));
+            }
+            return Err(format!("Failed to write {}: {e}", path.display()));
+        }
+    }
+
+    for (index, (path, _)) in targets.iter().enumerate() {
+        if let Err(e) = std::fs::rename(temp_path(path), path) {
+            for (done, _) in &targets[..index] {
+                if let Some((_, backup, _)) = backups.iter().find(|(p, _, _)| p == done) {
+                    let _ = std::fs::copy(backup, done);
+                }
+            }
+            for (pending, _) in &targets[index..] {
+                let _ = std::fs::remove_file(temp_path(pending));
+            }
+            return Err(format!("Failed to replace {}: {e}", path.display()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::{Diagnostic, DiagnosticSpan};
+    use std::time::Duration;
+
+    fn suggestion(file: &str, start: usize, end: usize, replacement: &str) -> Suggestion {
+        Suggestion {
+            message: format!("replace with {replacement}"),
+            span: DiagnosticSpan {
+                file: file.to_string(),
+                line_start: 1,
+                line_end: 1,
+                column_start: start + 1,
+                column_end: end + 1,
+                byte_start: start,
+                byte_end: end,
+                label: None,
+            },
+            replacement: replacement.to_string(),
+            applicability: Some("MachineApplicable".to_string()),
+        }
+    }
+
+    fn diagnostic(code: &str, suggestions: Vec<Suggestion>) -> Diagnostic {
+        Diagnostic {
+            level: "warning".to_string(),
+            code: Some(code.to_string()),
+            message: code.to_string(),
+            span: None,
+            children: Vec::new(),
+            suggestions,
+            rendered: None,
+            package: "demo 0.1.0".to_string(),
+            target: "demo (lib)".to_string(),
+        }
+    }
+
+    fn later() -> SystemTime {
+        SystemTime::now() + Duration::from_secs(60)
+    }
+
+    #[test]
+    fn test_plan_filters_dedupes_and_diffs() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir(tmp.path().join("src")).unwrap();
+        std::fs::write(tmp.path().join("src/lib.rs"), "let x = 1;\nlet y = 2;\n").unwrap();
+
+        let unused = diagnostic(
+            "unused_variables",
+            vec![suggestion("src/lib.rs", 4, 5, "_x")],
+        );
+        let report = CargoReport {
+            diagnostics: vec![
+                unused.clone(),
+                // Same warning reported again for the test target
+                unused,
+                diagnostic(
+                    "clippy::let_and_return",
+                    vec![suggestion("src/lib.rs", 15, 16, "_y")],
+                ),
+            ],
+            ..Default::default()
+        };
+
+        let filter = SuggestionFilter {
+            lints: Some(vec!["unused_variables".to_string()]),
+            ..Default::default()
+        };
+        let plan = plan_edits(&report, tmp.path(), &filter, later());
+        assert_eq!(plan.replacements(), 1);
+        assert_eq!(plan.files[0].updated, "let _x = 1;\nlet y = 2;\n");
+        assert!(plan.diff().contains("-let x = 1;\n+let _x = 1;"));
+
+        // The clippy:: prefix is optional in the lint filter
+        let filter = SuggestionFilter {
+            lints: Some(vec!["let_and_return".to_string()]),
+            ..Default::default()
+        };
+        let plan = plan_edits(&report, tmp.path(), &filter, later());
+        assert_eq!(plan.files[0].updated, "let x = 1;\nlet _y = 2;\n");
+    }
+
+    #[test]
+    fn test_file_filter_matches_whole_path_components() {
+        let filter = SuggestionFilter {
+            files: Some(vec!["lib.rs".to_string(), "src/a.rs".to_string()]),
+            ..Default::default()
+        };
+        assert!(filter.matches_file("lib.rs"));
+        assert!(filter.matches_file("src/lib.rs"));
+        assert!(filter.matches_file("crates/demo/src/a.rs"));
+        assert!(!filter.matches_file("src/stdlib.rs"));
+        assert!(!filter.matches_file("src/data.rs"));
+        assert!(!filter.matches_file("other/a.rs"));
+    }
+
+    #[test]
+    fn test_plan_refuses_stale_files_and_skips_overlaps() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(tmp.path().join("a.rs"), "abcdef").unwrap();
+        let report = CargoReport {
+            diagnostics: vec![
+                diagnostic("one", vec![suggestion("a.rs", 1, 3, "X")]),
+                diagnostic("two", vec![suggestion("a.rs", 2, 4, "Y")]),
+            ],
+            ..Default::default()
+        };
+
+        let plan = plan_edits(&report, tmp.path(), &SuggestionFilter::default(), later());
+        assert_eq!(plan.files[0].updated, "aXdef");
+        assert!(plan.skipped[0].contains("overlap"));
+
+        let stale = plan_edits(
+            &report,
+            tmp.path(),
+            &SuggestionFilter::default(),
+            SystemTime::UNIX_EPOCH,
+        );
+        assert!(stale.files.is_empty());
+        assert!(stale.skipped[0].contains("modified after"));
+    }
+
+    #[test]
+    fn test_applicability_filter() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(tmp.path().join("a.rs"), "abc").unwrap();
+        let mut maybe = suggestion("a.rs", 0, 1, "Z");
+        maybe.applicability = Some("MaybeIncorrect".to_string());
+        let report = CargoReport {
+            diagnostics: vec![diagnostic("lint", vec![maybe])],
+            ..Default::default()
+        };
+
+        let strict = plan_edits(&report, tmp.path(), &SuggestionFilter::default(), later());
+        assert!(strict.files.is_empty());
+        let lenient = SuggestionFilter {
+            applicability: Applicability::MaybeIncorrect,
+            ..Default::default()
+        };
+        let plan = plan_edits(&report, tmp.path(), &lenient, later());
+        assert_eq!(plan.files[0].updated, "Zbc");
+    }
+
+    #[test]
+    fn test_apply_and_undo() {
+        let tmp = tempfile::tempdir().unwrap();
+        let file = tmp.path().join("a.rs");
+        std::fs::write(&file, "old").unwrap();
+        let plan = EditPlan {
+            files: vec![FileEdit {
+                path: file.clone(),
+                display: "a.rs".to_string(),
+                original: "old".to_string(),
+                updated: "new".to_string(),
+                replacements: 1,
+            }],
+            skipped: Vec::new(),
+        };
+        let backups = SuggestionBackups::new(tmp.path().join("backups"));
+
+        backups.apply("op_apply_1", &plan).unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "new");
+
+        // Edited after apply: refused without force
+        std::fs::write(&file, "newer").unwrap();
+        assert!(backups.undo(None, false).is_err());
+        let (apply_id, restored) = backups.undo(None, true).unwrap();
+        assert_eq!(apply_id, "op_apply_1");
+        assert_eq!(restored, vec![file.clone()]);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "old");
+        assert!(backups.undo(None, false).is_err());
+    }
+}
@@ -0,0 +1,295 @@
+//! Per-tool target directory isolation
+//!
+//! Cargo serializes every invocation that shares a target directory on a single file lock,
+//! so a long `clippy` or `doc` run blocks a concurrent `build` or `test` in the same project.
+//! When enabled for a tool, this module points that tool at its own `CARGO_TARGET_DIR` under
+//! `target/async_cargo_mcp/<tool>` and keeps the combined size of those directories within a
+//! configurable budget by removing the least recently used ones. Each use touches a
+//! [`LAST_USED_STAMP`] file in the tool's directory; the directory's own mtime is no guide, as
+//! cargo writes into nested paths without changing it.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::{debug, info, warn};
+
+/// Directory (relative to the working directory) holding all isolated target dirs
+pub const ISOLATED_TARGET_ROOT: &str = "target/async_cargo_mcp";
+
+/// Tools that honour target directory isolation
+pub const ISOLATABLE_TOOLS: &[&str] = &["check", "clippy", "doc"];
+
+/// File in each isolated target dir recording when a tool last used it
+pub const LAST_USED_STAMP: &str = ".async_cargo_mcp_last_used";
+
+/// Default disk budget for all isolated target dirs of one working directory (4 GiB)
+pub const DEFAULT_MAX_ISOLATED_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+
+/// Opt-in policy describing which tools get their own target directory
+#[derive(Debug, Clone)]
+pub struct TargetIsolationConfig {
+    tools: HashSet<String>,
+    /// Combined size limit for isolated target dirs per working directory; `None` disables pruning
+    pub max_total_bytes: Option<u64>,
+}
+
+impl Default for TargetIsolationConfig {
+    fn default() -> Self {
+        Self {
+            tools: HashSet::new(),
+            max_total_bytes: Some(DEFAULT_MAX_ISOLATED_BYTES),
+        }
+    }
+}
+
+impl TargetIsolationConfig {
+    /// Enable isolation for the given tool names (case-insensitive). Unsupported names are ignored
+    /// with a warning.
+    pub fn new<I, S>(tools: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut config = Self::default();
+        for tool in tools {
+            let name = tool.as_ref().trim().to_ascii_lowercase();
+            if name.is_empty() {
+                continue;
+            }
+            if ISOLATABLE_TOOLS.contains(&name.as_str()) {
+                config.tools.insert(name);
+            } else {
+                warn!(
+                    "Ignoring target dir isolation for unsupported tool '{name}' (supported: {})",
+                    ISOLATABLE_TOOLS.join(", ")
+                );
+            }
+        }
+        config
+    }
+
+    /// Override the disk budget; `None` keeps isolated target dirs forever
+    pub fn with_max_total_bytes(mut self, max_total_bytes: Option<u64>) -> Self {
+        self.max_total_bytes = max_total_bytes;
+        self
+    }
+
+    /// Whether any tool is isolated
+    pub fn is_enabled(&self) -> bool {
+        !self.tools.is_empty()
+    }
+
+    /// Whether the named tool runs in its own target directory
+    pub fn is_isolated(&self, tool: &str) -> bool {
+        self.tools.contains(&tool.to_ascii_lowercase())
+    }
+
+    /// Sorted list of isolated tools, for logging
+    pub fn isolated_tools(&self) -> Vec<String> {
+        let mut tools: Vec<String> = self.tools.iter().cloned().collect();
+        tools.sort();
+        tools
+    }
+
+    /// Target directory to use for `tool` in `working_directory`, or `None` when the tool
+    /// shares the default target directory
+    pub fn target_dir_for(&self, working_directory: &str, tool: &str) -> Option<PathBuf> {
+        self.is_isolated(tool)
+            .then(|| isolated_target_dir(working_directory, tool))
+    }
+}
+
+/// Path of the isolated target directory for `tool` under `working_directory`
+pub fn isolated_target_dir(working_directory: &str, tool: &str) -> PathBuf {
+    Path::new(working_directory)
+        .join(ISOLATED_TARGET_ROOT)
+        .join(tool.to_ascii_lowercase())
+}
+
+/// Record that a tool is about to use `dir`, creating it if needed
+pub fn mark_used(dir: &Path) {
+    if let Err(e) = write_stamp(dir, SystemTime::now()) {
+        warn!(
+            "Failed to mark isolated target dir {} as used: {e}",
+            dir.display()
+        );
+    }
+}
+
+fn write_stamp(dir: &Path, at: SystemTime) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let millis = at
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    std::fs::write(dir.join(LAST_USED_STAMP), millis.to_string())
+}
+
+/// When `dir` was last used according to its stamp, falling back to the directory's mtime for
+/// dirs created before stamps were written
+fn last_used(dir: &Path) -> SystemTime {
+    std::fs::read_to_string(dir.join(LAST_USED_STAMP))
+        .ok()
+        .and_then(|millis| millis.trim().parse::<u64>().ok())
+        .map(|millis| SystemTime::UNIX_EPOCH + Duration::from_millis(millis))
+        .or_else(|| std::fs::metadata(dir).and_then(|m| m.modified()).ok())
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+/// Recursively compute the size of a directory in bytes. Missing paths count as empty.
+pub fn dir_size_bytes(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(ft) if ft.is_dir() => dir_size_bytes(&entry.path()),
+            Ok(ft) if ft.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Remove least recently used isolated target dirs until the combined size fits `max_total_bytes`.
+/// The directory belonging to `keep_tool` is never removed so the running tool keeps its cache.
+/// Returns the names of the removed directories.
+pub fn enforce_disk_budget(
+    working_directory: &str,
+    keep_tool: &str,
+    max_total_bytes: u64,
+) -> Vec<String> {
+    let root = Path::new(working_directory).join(ISOLATED_TARGET_ROOT);
+    let Ok(entries) = std::fs::read_dir(&root) else {
+        return Vec::new();
+    };
+
+    // (name, size, last used)
+    let mut dirs: Vec<(String, u64, SystemTime)> = entries
+        .flatten()
+        .filter(|e| e.file_type().map(|ft| ft.is_dir()).unwrap_or(false))
+        .map(|e| {
+            (
+                e.file_name().to_string_lossy().to_string(),
+                dir_size_bytes(&e.path()),
+                last_used(&e.path()),
+            )
+        })
+        .collect();
+
+    let mut total: u64 = dirs.iter().map(|(_, size, _)| size).sum();
+    debug!(
+        "Isolated target dirs in {working_directory} use {total} bytes (budget {max_total_bytes})"
+    );
+    if total <= max_total_bytes {
+        return Vec::new();
+    }
+
+    // Least recently used first
+    dirs.sort_by_key(|(_, _, last_used)| *last_used);
+    let keep = keep_tool.to_ascii_lowercase();
+    let mut removed = Vec::new();
+    for (name, size, _) in dirs {
+        if total <= max_total_bytes {
+            break;
+        }
+        if name == keep {
+            continue;
+        }
+        match std::fs::remove_dir_all(root.join(&name)) {
+            Ok(()) => {
+                info!("Pruned isolated target dir '{name}' ({size} bytes) in {working_directory}");
+                total = total.saturating_sub(size);
+                removed.push(name);
+            }
+            Err(e) => warn!("Failed to prune isolated target dir '{name}': {e}"),
+        }
+    }
+    if total > max_total_bytes {
+        warn!(
+            "Isolated target dir for '{keep}' alone exceeds the disk budget ({total} > {max_total_bytes} bytes)"
+        );
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_isolates_nothing() {
+        let config = TargetIsolationConfig::default();
+        assert!(!config.is_enabled());
+        assert!(config.target_dir_for("/tmp/project", "clippy").is_none());
+    }
+
+    #[test]
+    fn test_new_normalizes_and_filters_tools() {
+        let config = TargetIsolationConfig::new(["Clippy", " doc ", "build", ""]);
+        assert!(config.is_isolated("clippy"));
+        assert!(config.is_isolated("DOC"));
+        assert!(!config.is_isolated("build"));
+        assert_eq!(config.isolated_tools(), vec!["clippy", "doc"]);
+    }
+
+    #[test]
+    fn test_target_dir_layout() {
+        let config = TargetIsolationConfig::new(["check"]);
+        let dir = config.target_dir_for("/tmp/project", "check").unwrap();
+        assert_eq!(
+            dir,
+            PathBuf::from("/tmp/project/target/async_cargo_mcp/check")
+        );
+    }
+
+    #[test]
+    fn test_enforce_disk_budget_prunes_other_tools_first() {
+        let tmp = tempfile::tempdir().unwrap();
+        let wd = tmp.path().to_str().unwrap();
+        for (tool, bytes) in [("doc", 600usize), ("clippy", 600usize)] {
+            let dir = isolated_target_dir(wd, tool);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("artifact"), vec![0u8; bytes]).unwrap();
+        }
+
+        let removed = enforce_disk_budget(wd, "clippy", 1000);
+        assert_eq!(removed, vec!["doc"]);
+        assert!(isolated_target_dir(wd, "clippy").exists());
+        assert!(!isolated_target_dir(wd, "doc").exists());
+
+        // Under budget: nothing more is removed, even the kept tool
+        assert!(enforce_disk_budget(wd, "clippy", 1000).is_empty());
+        assert_eq!(dir_size_bytes(&isolated_target_dir(wd, "clippy")), 600);
+    }
+
+    #[test]
+    fn test_enforce_disk_budget_orders_by_last_use_stamp() {
+        let tmp = tempfile::tempdir().unwrap();
+        let wd = tmp.path().to_str().unwrap();
+        let now = SystemTime::now();
+        for (tool, used_secs_ago) in [("check", 60), ("doc", 10)] {
+            let dir = isolated_target_dir(wd, tool);
+            write_stamp(&dir, now - Duration::from_secs(used_secs_ago)).unwrap();
+            std::fs::create_dir_all(dir.join("debug/deps")).unwrap();
+            std::fs::write(dir.join("debug/deps/artifact"), vec![0u8; 600]).unwrap();
+        }
+        // A new top-level entry makes check's directory mtime the newest, but its stamp is older
+        std::fs::write(isolated_target_dir(wd, "check").join("CACHEDIR.TAG"), "").unwrap();
+        std::fs::create_dir_all(isolated_target_dir(wd, "clippy")).unwrap();
+
+        let removed = enforce_disk_budget(wd, "clippy", 1000);
+        assert_eq!(removed, vec!["check"]);
+        assert!(isolated_target_dir(wd, "doc").exists());
+    }
+
+    #[test]
+    fn test_mark_used_creates_dir_and_stamp() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = isolated_target_dir(tmp.path().to_str().unwrap(), "doc");
+        let before = SystemTime::now() - Duration::from_secs(1);
+        mark_used(&dir);
+        assert!(dir.join(LAST_USED_STAMP).exists());
+        assert!(last_used(&dir) >= before);
+    }
+}
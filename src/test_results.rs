@@ -0,0 +1,1372 @@
+//! Structured test results from libtest and nextest output
+//!
+//! `cargo test` prints libtest's human-readable format on stable. On nightly the server asks the
+//! test harness for its JSON event stream instead (`-Z unstable-options --format json`), and
+//! `cargo nextest` can emit the same events with `--message-format libtest-json-plus`. Both are
+//! parsed into a [`TestReport`]: one [`TestCase`] per test with its status, duration, captured
+//! stdout and panic message/location. JSON events are rendered back into libtest's text format
+//! so the text result reads the same on every toolchain.
+//!
+//! Tests and `cargo run` execute with `RUST_BACKTRACE=1`, so a panic carries a short backtrace.
+//! Its frames outside std, core and alloc become the [`FailureLocation`]s of a failing test or
+//! of a [`Panic`] in `run` output, starting with the frame that panicked.
+//!
+//! [`FlakyReport`] aggregates repeated runs of the same tests for `detect_flaky`; tests that
+//! both passed and failed are remembered as [`KnownFlaky`] and annotated in later results.
+
+use crate::diagnostics::Artifact;
+use crate::explain::rustc_version;
+use serde::{Deserialize, Serialize};
+
+/// Arguments after `--` that switch libtest to its JSON event stream (nightly only)
+pub const LIBTEST_JSON_ARGS: &[&str] = &[
+    "-Z",
+    "unstable-options",
+    "--format",
+    "json",
+    "--report-time",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    Ignored,
+}
+
+/// Where a test panicked
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PanicLocation {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// One test and its outcome
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestCase {
+    /// Test path as libtest reports it, e.g. `tests::parses_input`
+    pub name: String,
+    /// Test binary, e.g. `unittests src/lib.rs` or `doc-tests demo`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub binary: Option<String>,
+    pub status: TestStatus,
+    /// Only reported by the JSON formats (or libtest's `--report-time`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<f64>,
+    /// Output the test printed before it failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stdout: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub panic_message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub panic_location: Option<PanicLocation>,
+    /// Reason given in `#[ignore = "..."]`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore_reason: Option<String>,
+    /// Set when detect_flaky saw this test both pass and fail
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub known_flaky: Option<KnownFlaky>,
+    /// Backtrace frames in user and dependency code, innermost first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failure_locations: Vec<FailureLocation>,
+}
+
+/// A backtrace frame outside the standard library
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailureLocation {
+    /// Function as the backtrace names it, e.g. `demo::tests::parses_input`
+    pub function: String,
+    /// Relative to the working directory for workspace files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    /// False for registry and git dependencies and for frames without debug info
+    pub in_workspace: bool,
+}
+
+impl FailureLocation {
+    /// `src/lib.rs:5:9 in demo::parse`, or just the function without debug info
+    pub fn render(&self) -> String {
+        match (&self.file, self.line, self.column) {
+            (Some(file), Some(line), Some(column)) => {
+                format!("{file}:{line}:{column} in {}", self.function)
+            }
+            _ => self.function.clone(),
+        }
+    }
+}
+
+/// A panic outside the test harness, e.g. in the program started by `cargo run`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Panic {
+    pub thread: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<PanicLocation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failure_locations: Vec<FailureLocation>,
+}
+
+impl Panic {
+    /// `Panic: thread 'main' at src/main.rs:2:5: message`, then one line per workspace frame
+    pub fn render(&self) -> String {
+        let mut out = format!("Panic: thread '{}'", self.thread);
+        if let Some(location) = &self.location {
+            out.push_str(&format!(
+                " at {}:{}:{}",
+                location.file, location.line, location.column
+            ));
+        }
+        if let Some(first) = self.message.as_deref().and_then(|m| m.lines().next()) {
+            out.push_str(&format!(": {first}"));
+        }
+        for frame in workspace_frames(&self.failure_locations) {
+            out.push_str(&format!("\n  at {}", frame.render()));
+        }
+        out
+    }
+}
+
+/// Frames worth showing inline: the first few in workspace files
+fn workspace_frames(frames: &[FailureLocation]) -> impl Iterator<Item = &FailureLocation> {
+    frames
+        .iter()
+        .filter(|f| f.in_workspace)
+        .take(MAX_RENDERED_FRAMES)
+}
+
+/// Workspace frames listed under each failure in the text result
+const MAX_RENDERED_FRAMES: usize = 5;
+
+/// A test detect_flaky saw both pass and fail
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownFlaky {
+    pub passed: usize,
+    pub runs: usize,
+    /// The detect_flaky operation that found it
+    pub operation_id: String,
+}
+
+/// All tests of one `cargo test` or `cargo nextest run` invocation
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TestReport {
+    pub passed: usize,
+    pub failed: usize,
+    pub ignored: usize,
+    pub filtered_out: usize,
+    /// Sum of the per-binary run times
+    pub duration_secs: f64,
+    pub tests: Vec<TestCase>,
+}
+
+impl TestReport {
+    pub fn is_empty(&self) -> bool {
+        self.tests.is_empty() && self.filtered_out == 0
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &TestCase> {
+        self.tests.iter().filter(|t| t.status == TestStatus::Failed)
+    }
+
+    /// Short count line, e.g. "5 passed, 1 failed, 2 ignored"
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "{} passed, {} failed, {} ignored",
+            self.passed, self.failed, self.ignored
+        );
+        if self.filtered_out > 0 {
+            summary.push_str(&format!(", {} filtered out", self.filtered_out));
+        }
+        summary
+    }
+
+    /// Summary line followed by one line per failing test with its panic location and message
+    pub fn render(&self) -> String {
+        let mut out = format!("Tests: {}", self.summary());
+        for test in self.failures() {
+            out.push_str(&format!("\n  FAILED {}", test.name));
+            if let Some(binary) = &test.binary {
+                out.push_str(&format!(" ({binary})"));
+            }
+            if let Some(location) = &test.panic_location {
+                out.push_str(&format!(
+                    " at {}:{}:{}",
+                    location.file, location.line, location.column
+                ));
+            }
+            if let Some(first) = test.panic_message.as_deref().and_then(|m| m.lines().next()) {
+                out.push_str(&format!(": {first}"));
+            }
+            if let Some(flaky) = &test.known_flaky {
+                out.push_str(&format!(
+                    " [known flaky: passed {}/{} in {}]",
+                    flaky.passed, flaky.runs, flaky.operation_id
+                ));
+            }
+            for frame in workspace_frames(&test.failure_locations) {
+                out.push_str(&format!("\n      at {}", frame.render()));
+            }
+        }
+        out
+    }
+
+    fn push(&mut self, test: TestCase) {
+        match test.status {
+            TestStatus::Passed => self.passed += 1,
+            TestStatus::Failed => self.failed += 1,
+            TestStatus::Ignored => self.ignored += 1,
+        }
+        self.tests.push(test);
+    }
+}
+
+/// libtest JSON event; nextest adds a `nextest` object and prefixes names with the binary ID
+#[derive(Deserialize)]
+struct RawEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    event: String,
+    name: Option<String>,
+    exec_time: Option<f64>,
+    stdout: Option<String>,
+    message: Option<String>,
+    test_count: Option<usize>,
+    passed: Option<usize>,
+    failed: Option<usize>,
+    ignored: Option<usize>,
+    measured: Option<usize>,
+    filtered_out: Option<usize>,
+}
+
+/// A test binary cargo ran, from its `Running ...` / `Doc-tests ...` stderr line
+struct BinaryRun {
+    /// e.g. `unittests src/lib.rs`, `tests/cli.rs` or `doc-tests demo`
+    label: String,
+    /// Executable path as printed by cargo; `None` for doc-tests
+    executable: Option<String>,
+    /// Crate whose doc-tests ran
+    doc_crate: Option<String>,
+}
+
+/// Test binaries in run order
+fn binary_runs(stderr: &str) -> Vec<BinaryRun> {
+    stderr
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if let Some(rest) = line.strip_prefix("Running ") {
+                let (label, executable) = match rest.split_once(" (") {
+                    Some((label, path)) => (label, path.strip_suffix(')').map(str::to_string)),
+                    None => (rest, None),
+                };
+                Some(BinaryRun {
+                    label: label.to_string(),
+                    executable,
+                    doc_crate: None,
+                })
+            } else {
+                line.strip_prefix("Doc-tests ").map(|krate| BinaryRun {
+                    label: format!("doc-tests {krate}"),
+                    executable: None,
+                    doc_crate: Some(krate.to_string()),
+                })
+            }
+        })
+        .collect()
+}
+
+/// Test binary labels in run order
+fn binary_labels(stderr: &str) -> Vec<String> {
+    binary_runs(stderr)
+        .into_iter()
+        .map(|run| run.label)
+        .collect()
+}
+
+/// Parse `file:line:column`
+fn parse_location(location: &str) -> Option<PanicLocation> {
+    let mut parts = location.rsplitn(3, ':');
+    let column = parts.next()?.parse().ok()?;
+    let line = parts.next()?.parse().ok()?;
+    Some(PanicLocation {
+        file: parts.next()?.to_string(),
+        line,
+        column,
+    })
+}
+
+/// Parse `thread 'name' (tid) panicked at src/lib.rs:5:49:` (or the pre-1.73
+/// `panicked at 'message', src/lib.rs:5:49`) into its location and any inline message
+fn parse_panic_line(line: &str) -> Option<(Option<PanicLocation>, Option<String>)> {
+    if !line.starts_with("thread '") {
+        return None;
+    }
+    let rest = &line[line.find(" panicked at ")? + " panicked at ".len()..];
+    let (message, location) = match rest.strip_prefix('\'') {
+        Some(quoted) => {
+            let (message, location) = quoted.rsplit_once("', ")?;
+            (Some(message.to_string()), location)
+        }
+        None => (None, rest.trim_end_matches(':')),
+    };
+    Some((parse_location(location), message))
+}
+
+/// The message and backtrace frames following the panic line at `index`
+fn parse_panic_body(
+    lines: &[&str],
+    index: usize,
+    location: Option<&PanicLocation>,
+) -> (String, Vec<FailureLocation>) {
+    let body = &lines[index + 1..];
+    let message_end = body
+        .iter()
+        .position(|l| {
+            l.starts_with("note: ")
+                || l.starts_with("stack backtrace:")
+                || parse_panic_line(l).is_some()
+        })
+        .unwrap_or(body.len());
+    let message = body[..message_end].join("\n");
+    let frames = match body.get(message_end) {
+        Some(line) if line.starts_with("stack backtrace:") => {
+            parse_backtrace(&body[message_end + 1..], location)
+        }
+        _ => Vec::new(),
+    };
+    (message, frames)
+}
+
+/// Parse the frames of a short backtrace, dropping std/core/alloc and test harness frames:
+///
+/// ```text
+///    2: demo::helper
+///              at ./src/main.rs:2:16
+/// ```
+///
+/// Backtraces print paths relative to the running binary's directory (`./src/lib.rs`), which
+/// for workspace members is the member's directory; the panic location's workspace-relative
+/// path is used where it names the same file.
+fn parse_backtrace(lines: &[&str], panic_location: Option<&PanicLocation>) -> Vec<FailureLocation> {
+    let mut frames: Vec<(String, Option<PanicLocation>)> = Vec::new();
+    for line in lines {
+        let trimmed = line.trim();
+        if let Some(at) = trimmed.strip_prefix("at ")
+            && let Some((_, location)) = frames.last_mut()
+        {
+            *location = parse_location(at);
+        } else if let Some((number, function)) = trimmed.split_once(": ")
+            && number.parse::<usize>().is_ok()
+        {
+            frames.push((function.to_string(), None));
+        } else {
+            break;
+        }
+    }
+
+    frames
+        .into_iter()
+        .filter(|(function, location)| {
+            !is_runtime_frame(function, location.as_ref().map(|l| l.file.as_str()))
+        })
+        .map(|(function, location)| {
+            let Some(location) = location else {
+                return FailureLocation {
+                    function,
+                    file: None,
+                    line: None,
+                    column: None,
+                    in_workspace: false,
+                };
+            };
+            let mut file = location
+                .file
+                .strip_prefix("./")
+                .unwrap_or(&location.file)
+                .to_string();
+            if let Some(panic) = panic_location
+                && panic.file.ends_with(&format!("/{file}"))
+            {
+                file = panic.file.clone();
+            }
+            let dependency = ["/.cargo/registry/", "/.cargo/git/", "/.rustup/"]
+                .iter()
+                .any(|dir| file.contains(dir));
+            FailureLocation {
+                function,
+                file: Some(file),
+                line: Some(location.line),
+                column: Some(location.column),
+                in_workspace: !dependency,
+            }
+        })
+        .collect()
+}
+
+/// Frames of the standard library, the panic runtime and the test harness
+fn is_runtime_frame(function: &str, file: Option<&str>) -> bool {
+    const RUNTIME_PATHS: &[&str] = &["std::", "core::", "alloc::", "test::", "__rustc::"];
+    if file.is_some_and(|f| f.starts_with("/rustc/") || f.contains("/library/")) {
+        return true;
+    }
+    let path = function.trim_start_matches('<');
+    path.starts_with("fn(")
+        || path.starts_with("rust_begin_unwind")
+        || path.starts_with("__rust")
+        || RUNTIME_PATHS.iter().any(|p| path.starts_with(p))
+}
+
+/// Split a failing test's output into what it printed, the panic message and the location
+fn split_failure_output(test: &mut TestCase, output: &str) {
+    let lines: Vec<&str> = output.lines().collect();
+    let panic = lines
+        .iter()
+        .enumerate()
+        .find_map(|(i, line)| parse_panic_line(line).map(|parsed| (i, parsed)));
+    let printed_end = panic.as_ref().map_or(lines.len(), |(i, _)| *i);
+    let printed = lines[..printed_end].join("\n");
+    if !printed.trim().is_empty() {
+        test.stdout = Some(printed.trim_end().to_string());
+    }
+    let Some((index, (location, inline_message))) = panic else {
+        return;
+    };
+    let (message, frames) = parse_panic_body(&lines, index, location.as_ref());
+    test.panic_location = location;
+    test.failure_locations = frames;
+    let message = inline_message.unwrap_or(message);
+    if !message.trim().is_empty() {
+        test.panic_message = Some(message.trim_end().to_string());
+    }
+}
+
+/// Every panic in a program's output, e.g. the stderr of `cargo run`
+pub fn parse_panics(output: &str) -> Vec<Panic> {
+    let lines: Vec<&str> = output.lines().collect();
+    lines
+        .iter()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let (location, inline_message) = parse_panic_line(line)?;
+            let thread = line["thread '".len()..].split('\'').next()?.to_string();
+            let (message, failure_locations) = parse_panic_body(&lines, index, location.as_ref());
+            let message = inline_message.unwrap_or(message);
+            Some(Panic {
+                thread,
+                message: (!message.trim().is_empty()).then(|| message.trim_end().to_string()),
+                location,
+                failure_locations,
+            })
+        })
+        .collect()
+}
+
+/// `test tests::name ... ok` / `... FAILED` / `... ignored, reason`, with an optional
+/// `<0.001s>` time suffix from `--report-time`
+fn parse_result_line(line: &str) -> Option<TestCase> {
+    let (name, outcome) = line.strip_prefix("test ")?.split_once(" ... ")?;
+    let (outcome, duration_secs) = match outcome.rsplit_once(" <") {
+        Some((outcome, time)) => (
+            outcome,
+            time.strip_suffix("s>").and_then(|t| t.parse().ok()),
+        ),
+        None => (outcome, None),
+    };
+    let (status, ignore_reason) = match outcome {
+        "ok" => (TestStatus::Passed, None),
+        "FAILED" => (TestStatus::Failed, None),
+        "ignored" => (TestStatus::Ignored, None),
+        other => (
+            TestStatus::Ignored,
+            Some(other.strip_prefix("ignored, ")?.to_string()),
+        ),
+    };
+    Some(TestCase {
+        name: name.to_string(),
+        binary: None,
+        status,
+        duration_secs,
+        stdout: None,
+        panic_message: None,
+        panic_location: None,
+        ignore_reason,
+        known_flaky: None,
+        failure_locations: Vec::new(),
+    })
+}
+
+/// Value of `N <label>` in a `test result:` line, e.g. `3 filtered out`
+fn count_in_result_line(line: &str, label: &str) -> Option<usize> {
+    line.split(';')
+        .find_map(|part| part.trim().strip_suffix(label)?.trim().parse().ok())
+}
+
+/// Libtest-style text for a finished JSON suite
+fn render_suite_end(event: &RawEvent, failures: &[(String, String)], out: &mut Vec<String>) {
+    if !failures.is_empty() {
+        out.push(String::new());
+        out.push("failures:".to_string());
+        for (name, stdout) in failures {
+            out.push(String::new());
+            out.push(format!("---- {name} stdout ----"));
+            out.push(stdout.trim_end().to_string());
+        }
+        out.push(String::new());
+        out.push("failures:".to_string());
+        out.extend(failures.iter().map(|(name, _)| format!("    {name}")));
+    }
+    out.push(String::new());
+    out.push(format!(
+        "test result: {}. {} passed; {} failed; {} ignored; {} measured; {} filtered out{}",
+        if event.event == "ok" { "ok" } else { "FAILED" },
+        event.passed.unwrap_or(0),
+        event.failed.unwrap_or(0),
+        event.ignored.unwrap_or(0),
+        event.measured.unwrap_or(0),
+        event.filtered_out.unwrap_or(0),
+        event
+            .exec_time
+            .map(|t| format!("; finished in {t:.2}s"))
+            .unwrap_or_default(),
+    ));
+    out.push(String::new());
+}
+
+/// Parse test output. `stderr` supplies the binary names cargo prints before each suite.
+/// Returns the report and the stdout text with JSON events rendered in libtest's text format.
+pub fn parse_test_output(stdout: &str, stderr: &str) -> (TestReport, String) {
+    let mut report = TestReport::default();
+    let mut text: Vec<String> = Vec::new();
+    let mut binaries = binary_labels(stderr).into_iter();
+    let mut binary: Option<String> = None;
+    // First test of the current suite, so failure sections attach to the right binary
+    let mut suite_start = 0;
+    // Failure section being collected: test name and lines
+    let mut section: Option<(String, Vec<String>)> = None;
+    // Failing tests of the current JSON suite, rendered when the suite ends
+    let mut json_failures: Vec<(String, String)> = Vec::new();
+
+    let finish_section =
+        |report: &mut TestReport, section: Option<(String, Vec<String>)>, start| {
+            if let Some((name, lines)) = section
+                && let Some(test) = report.tests[start..].iter_mut().find(|t| t.name == name)
+            {
+                split_failure_output(test, &lines.join("\n"));
+            }
+        };
+
+    for line in stdout.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('{')
+            && trimmed.contains("\"type\"")
+            && let Ok(event) = serde_json::from_str::<RawEvent>(trimmed)
+        {
+            match (event.kind.as_str(), event.event.as_str()) {
+                ("suite", "started") => {
+                    binary = binaries.next();
+                    suite_start = report.tests.len();
+                    json_failures.clear();
+                    let count = event.test_count.unwrap_or(0);
+                    text.push(String::new());
+                    text.push(format!(
+                        "running {count} test{}",
+                        if count == 1 { "" } else { "s" }
+                    ));
+                }
+                ("suite", _) => {
+                    report.filtered_out += event.filtered_out.unwrap_or(0);
+                    report.duration_secs += event.exec_time.unwrap_or(0.0);
+                    render_suite_end(&event, &json_failures, &mut text);
+                }
+                ("test", "ok" | "failed" | "ignored") => {
+                    let Some(full_name) = event.name else {
+                        continue;
+                    };
+                    // nextest names are `<binary-id>$<test name>`
+                    let (test_binary, name) = match full_name.split_once('$') {
+                        Some((id, name)) => (Some(id.to_string()), name.to_string()),
+                        None => (binary.clone(), full_name),
+                    };
+                    let status = match event.event.as_str() {
+                        "ok" => TestStatus::Passed,
+                        "failed" => TestStatus::Failed,
+                        _ => TestStatus::Ignored,
+                    };
+                    let mut test = TestCase {
+                        name: name.clone(),
+                        binary: test_binary,
+                        status,
+                        duration_secs: event.exec_time,
+                        stdout: None,
+                        panic_message: None,
+                        panic_location: None,
+                        ignore_reason: None,
+                        known_flaky: None,
+                        failure_locations: Vec::new(),
+                    };
+                    let outcome = match status {
+                        TestStatus::Passed => "ok".to_string(),
+                        TestStatus::Failed => "FAILED".to_string(),
+                        TestStatus::Ignored => match &event.message {
+                            Some(reason) => format!("ignored, {reason}"),
+                            None => "ignored".to_string(),
+                        },
+                    };
+                    text.push(format!("test {name} ... {outcome}"));
+                    if status == TestStatus::Ignored {
+                        test.ignore_reason = event.message;
+                    }
+                    if let Some(output) = &event.stdout {
+                        split_failure_output(&mut test, output);
+                        if status == TestStatus::Failed {
+                            json_failures.push((name, output.clone()));
+                        }
+                    }
+                    report.push(test);
+                }
+                // `started`, `timeout` and bench events carry nothing we report
+                _ => {}
+            }
+            continue;
+        }
+
+        if line.starts_with("running ") && (line.ends_with(" tests") || line.ends_with(" test")) {
+            finish_section(&mut report, section.take(), suite_start);
+            binary = binaries.next();
+            suite_start = report.tests.len();
+        } else if let Some(name) = line
+            .strip_prefix("---- ")
+            .and_then(|rest| rest.strip_suffix(" stdout ----"))
+        {
+            finish_section(&mut report, section.take(), suite_start);
+            section = Some((name.to_string(), Vec::new()));
+        } else if line == "failures:" || line.starts_with("test result: ") {
+            finish_section(&mut report, section.take(), suite_start);
+            if line.starts_with("test result: ") {
+                report.filtered_out += count_in_result_line(line, "filtered out").unwrap_or(0);
+                report.duration_secs += line
+                    .rsplit_once("finished in ")
+                    .and_then(|(_, t)| t.trim_end_matches('s').parse::<f64>().ok())
+                    .unwrap_or(0.0);
+            }
+        } else if let Some((_, lines)) = section.as_mut() {
+            lines.push(line.to_string());
+        } else if let Some(mut test) = parse_result_line(line) {
+            test.binary = binary.clone();
+            report.push(test);
+        }
+        text.push(line.to_string());
+    }
+    finish_section(&mut report, section.take(), suite_start);
+
+    (report, text.join("\n"))
+}
+
+/// Whether the toolchain used in `working_directory` accepts libtest's unstable JSON format
+pub async fn supports_libtest_json(working_directory: &str) -> bool {
+    rustc_version(working_directory)
+        .await
+        .is_some_and(|version| version.contains("-nightly"))
+}
+
+/// Whether test-binary arguments leave the output format to us
+pub fn wants_default_format(test_args: &[String]) -> bool {
+    !test_args.iter().any(|a| {
+        a.starts_with("--format")
+            || a == "-Z"
+            || a == "--list"
+            || a == "--help"
+            || a == "-q"
+            || a == "--quiet"
+    })
+}
+
+/// One distinct way a test failed over repeated runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailureCount {
+    /// Panic message, or a placeholder when the test failed without one
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<PanicLocation>,
+    pub count: usize,
+}
+
+/// Outcome of one test over repeated runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlakyTestStats {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub binary: Option<String>,
+    pub passed: usize,
+    pub failed: usize,
+    /// `passed / (passed + failed)`
+    pub pass_rate: f64,
+    /// Passed in some runs and failed in others
+    pub flaky: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<FailureCount>,
+}
+
+/// Aggregate of repeated runs of the same tests
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FlakyReport {
+    pub runs: usize,
+    pub parallel: bool,
+    /// Runs that could not be started or produced no test results (e.g. a crashed binary)
+    pub run_errors: usize,
+    /// Flaky tests first, then always-failing, then always-passing; by name within each
+    pub tests: Vec<FlakyTestStats>,
+}
+
+impl FlakyReport {
+    /// Aggregate per-run reports. Ignored tests are left out since they never ran.
+    pub fn from_runs(reports: &[TestReport], parallel: bool, run_errors: usize) -> Self {
+        let mut tests: Vec<FlakyTestStats> = Vec::new();
+        for report in reports {
+            for test in report
+                .tests
+                .iter()
+                .filter(|t| t.status != TestStatus::Ignored)
+            {
+                let index = match tests
+                    .iter()
+                    .position(|s| s.name == test.name && s.binary == test.binary)
+                {
+                    Some(index) => index,
+                    None => {
+                        tests.push(FlakyTestStats {
+                            name: test.name.clone(),
+                            binary: test.binary.clone(),
+                            passed: 0,
+                            failed: 0,
+                            pass_rate: 0.0,
+                            flaky: false,
+                            failures: Vec::new(),
+                        });
+                        tests.len() - 1
+                    }
+                };
+                let stats = &mut tests[index];
+                if test.status == TestStatus::Passed {
+                    stats.passed += 1;
+                    continue;
+                }
+                stats.failed += 1;
+                let message = test
+                    .panic_message
+                    .clone()
+                    .unwrap_or_else(|| "(failed without a panic message)".to_string());
+                match stats
+                    .failures
+                    .iter_mut()
+                    .find(|f| f.message == message && f.location == test.panic_location)
+                {
+                    Some(failure) => failure.count += 1,
+                    None => stats.failures.push(FailureCount {
+                        message,
+                        location: test.panic_location.clone(),
+                        count: 1,
+                    }),
+                }
+            }
+        }
+        for stats in &mut tests {
+            stats.pass_rate = stats.passed as f64 / (stats.passed + stats.failed) as f64;
+            stats.flaky = stats.passed > 0 && stats.failed > 0;
+            stats.failures.sort_by_key(|f| std::cmp::Reverse(f.count));
+        }
+        let rank = |s: &FlakyTestStats| match (s.flaky, s.failed > 0) {
+            (true, _) => 0,
+            (false, true) => 1,
+            (false, false) => 2,
+        };
+        tests.sort_by(|a, b| rank(a).cmp(&rank(b)).then_with(|| a.name.cmp(&b.name)));
+        Self {
+            runs: reports.len() + run_errors,
+            parallel,
+            run_errors,
+            tests,
+        }
+    }
+
+    pub fn flaky(&self) -> impl Iterator<Item = &FlakyTestStats> {
+        self.tests.iter().filter(|t| t.flaky)
+    }
+
+    /// Count line followed by each flaky or failing test and its distinct failures
+    pub fn render(&self) -> String {
+        let flaky = self.flaky().count();
+        let failing = self
+            .tests
+            .iter()
+            .filter(|t| !t.flaky && t.failed > 0)
+            .count();
+        let mut out = format!(
+            "{} test(s) over {} {} run(s): {flaky} flaky, {failing} always failing, {} always passing",
+            self.tests.len(),
+            self.runs,
+            if self.parallel {
+                "parallel"
+            } else {
+                "sequential"
+            },
+            self.tests.len() - flaky - failing,
+        );
+        if self.run_errors > 0 {
+            out.push_str(&format!(
+                " ({} run(s) produced no test results)",
+                self.run_errors
+            ));
+        }
+        for test in self.tests.iter().filter(|t| t.failed > 0) {
+            out.push_str(&format!(
+                "\n  {} {}",
+                if test.flaky { "FLAKY" } else { "FAILING" },
+                test.name
+            ));
+            if let Some(binary) = &test.binary {
+                out.push_str(&format!(" ({binary})"));
+            }
+            out.push_str(&format!(
+                ": passed {}/{} ({:.0}%)",
+                test.passed,
+                test.passed + test.failed,
+                test.pass_rate * 100.0
+            ));
+            for failure in &test.failures {
+                out.push_str(&format!("\n    {}x", failure.count));
+                if let Some(location) = &failure.location {
+                    out.push_str(&format!(
+                        " at {}:{}:{}",
+                        location.file, location.line, location.column
+                    ));
+                }
+                let first = failure.message.lines().next().unwrap_or_default();
+                out.push_str(&format!(": {first}"));
+            }
+        }
+        out
+    }
+}
+
+/// Where a test is defined
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+}
+
+/// A test found by `list_tests`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListedTest {
+    /// Name to pass to `test`/`nextest` filters, e.g. `tests::parses_input`
+    pub name: String,
+    /// Test binary, e.g. `unittests src/lib.rs`, `tests/cli.rs` or `doc-tests demo`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub binary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+    /// `test` or `bench`
+    pub kind: String,
+    pub ignored: bool,
+    /// Where the test is defined: always known for doc-tests, and for all tests on nightly
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<SourceLocation>,
+}
+
+/// libtest JSON discovery event (`--list --format json`, nightly)
+#[derive(Deserialize)]
+struct RawDiscovery {
+    #[serde(rename = "type")]
+    kind: String,
+    event: String,
+    name: Option<String>,
+    #[serde(default)]
+    ignore: bool,
+    source_path: Option<String>,
+    start_line: Option<usize>,
+}
+
+/// `src/lib.rs - add (line 3)` -> `src/lib.rs:3`
+fn doc_test_location(name: &str) -> Option<SourceLocation> {
+    let (rest, line) = name.rsplit_once(" (line ")?;
+    let (file, _) = rest.split_once(" - ")?;
+    Some(SourceLocation {
+        file: file.to_string(),
+        line: line.strip_suffix(')')?.parse().ok()?,
+    })
+}
+
+/// Package name of a test binary, from the artifacts cargo reported for the same run
+fn binary_package(run: &BinaryRun, artifacts: &[Artifact]) -> Option<String> {
+    let artifact = match (&run.executable, &run.doc_crate) {
+        (Some(executable), _) => artifacts.iter().find(|a| {
+            a.executable
+                .as_deref()
+                .is_some_and(|path| path.ends_with(executable.as_str()))
+        }),
+        (None, Some(krate)) => artifacts
+            .iter()
+            .find(|a| a.target.starts_with(&format!("{krate} (")) && a.target.contains("lib")),
+        (None, None) => None,
+    }?;
+    artifact
+        .package
+        .split_whitespace()
+        .next()
+        .map(str::to_string)
+}
+
+/// Parse `cargo test -- --list` output: libtest's `name: test` lines with an
+/// `N tests, M benchmarks` line after each binary, or JSON discovery events on nightly
+pub fn parse_test_list(stdout: &str, stderr: &str, artifacts: &[Artifact]) -> Vec<ListedTest> {
+    let runs = binary_runs(stderr);
+    let binary_at = |index: usize| runs.get(index);
+    let mut tests = Vec::new();
+    // Index of the binary whose listing is being read
+    let mut block = 0;
+
+    for line in stdout.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('{')
+            && let Ok(event) = serde_json::from_str::<RawDiscovery>(trimmed)
+        {
+            match (event.kind.as_str(), event.event.as_str()) {
+                ("suite", "completed") => block += 1,
+                ("test" | "bench", "discovered") => {
+                    let Some(name) = event.name else { continue };
+                    let run = binary_at(block);
+                    let location = match (event.source_path, event.start_line) {
+                        (Some(file), Some(line)) => Some(SourceLocation { file, line }),
+                        _ => doc_test_location(&name),
+                    };
+                    tests.push(ListedTest {
+                        binary: run.map(|r| r.label.clone()),
+                        package: run.and_then(|r| binary_package(r, artifacts)),
+                        kind: event.kind,
+                        ignored: event.ignore,
+                        location,
+                        name,
+                    });
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        if line.ends_with(" benchmarks") || line.ends_with(" benchmark") {
+            block += 1;
+        } else if let Some((name, kind)) = line.rsplit_once(": ")
+            && (kind == "test" || kind == "bench")
+        {
+            let run = binary_at(block);
+            tests.push(ListedTest {
+                name: name.to_string(),
+                binary: run.map(|r| r.label.clone()),
+                package: run.and_then(|r| binary_package(r, artifacts)),
+                kind: kind.to_string(),
+                ignored: false,
+                location: doc_test_location(name),
+            });
+        }
+    }
+    tests
+}
+
+/// Parse `cargo nextest list --message-format json`
+pub fn parse_nextest_list(stdout: &str) -> Vec<ListedTest> {
+    let Some(list) = stdout
+        .lines()
+        .find_map(|line| serde_json::from_str::<serde_json::Value>(line.trim()).ok())
+    else {
+        return Vec::new();
+    };
+    let mut tests = Vec::new();
+    let Some(suites) = list["rust-suites"].as_object() else {
+        return tests;
+    };
+    for (binary_id, suite) in suites {
+        let package = suite["package-name"]
+            .as_str()
+            .map(str::to_string)
+            .or_else(|| binary_id.split("::").next().map(str::to_string));
+        let Some(cases) = suite["testcases"].as_object() else {
+            continue;
+        };
+        for (name, case) in cases {
+            tests.push(ListedTest {
+                name: name.clone(),
+                binary: Some(binary_id.clone()),
+                package: package.clone(),
+                kind: "test".to_string(),
+                ignored: case["ignored"].as_bool().unwrap_or(false),
+                location: None,
+            });
+        }
+    }
+    tests
+}
+
+/// One line per binary followed by its tests
+pub fn render_test_list(tests: &[ListedTest]) -> String {
+    let mut out = String::new();
+    let mut current: Option<(&Option<String>, &Option<String>)> = None;
+    for test in tests {
+        if current != Some((&test.binary, &test.package)) {
+            current = Some((&test.binary, &test.package));
+            out.push_str(test.binary.as_deref().unwrap_or("(unknown binary)"));
+            if let Some(package) = &test.package {
+                out.push_str(&format!(" [{package}]"));
+            }
+            out.push('\n');
+        }
+        out.push_str(&format!("  {}", test.name));
+        if test.kind != "test" {
+            out.push_str(&format!(" ({})", test.kind));
+        }
+        if test.ignored {
+            out.push_str(" (ignored)");
+        }
+        if let Some(location) = &test.location
+            && !test.name.starts_with(&location.file)
+        {
+            out.push_str(&format!(" at {}:{}", location.file, location.line));
+        }
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+/// libtest options that take a value, so the value is not mistaken for a name filter
+const LIBTEST_VALUE_FLAGS: &[&str] = &[
+    "--skip",
+    "--test-threads",
+    "--format",
+    "--color",
+    "--logfile",
+    "--shuffle-seed",
+    "-Z",
+];
+
+/// Test-binary arguments that run exactly `names`: the options of `test_args` are kept, its
+/// name filters are replaced and `--exact` is added
+pub fn exact_test_args(test_args: &[String], names: &[String]) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut iter = test_args.iter();
+    while let Some(arg) = iter.next() {
+        if !arg.starts_with('-') {
+            continue;
+        }
+        args.push(arg.clone());
+        if LIBTEST_VALUE_FLAGS.contains(&arg.as_str())
+            && let Some(value) = iter.next()
+        {
+            args.push(value.clone());
+        }
+    }
+    if !args.iter().any(|a| a == "--exact") {
+        args.push("--exact".to_string());
+    }
+    args.extend(names.iter().cloned());
+    args
+}
+
+/// Nextest arguments with any filterset (`-E`, `--filterset`, `--filter-expr`) removed
+pub fn without_filterset(args: &[String]) -> Vec<String> {
+    let mut kept = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-E" | "--filterset" | "--filter-expr" => {
+                iter.next();
+            }
+            _ if arg.starts_with("--filterset=") || arg.starts_with("--filter-expr=") => {}
+            _ => kept.push(arg.clone()),
+        }
+    }
+    kept
+}
+
+/// Nextest filterset matching exactly the given tests, e.g.
+/// `(binary_id(demo::integration) & test(=adds)) | test(=tests::fails)`
+pub fn nextest_filterset(tests: &[&TestCase]) -> String {
+    tests
+        .iter()
+        .map(|t| match &t.binary {
+            Some(binary) => format!("(binary_id({binary}) & test(={}))", t.name),
+            None => format!("test(={})", t.name),
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STABLE_STDOUT: &str = "\nrunning 4 tests\ntest tests::fails ... FAILED\ntest tests::ok ... ok\ntest tests::slow ... ignored, slow\ntest tests::slow2 ... ignored\n\nfailures:\n\n---- tests::fails stdout ----\nhello out\n\nthread 'tests::fails' (7793) panicked at src/lib.rs:5:49:\nassertion `left == right` failed: math\n  left: 2\n right: 3\nnote: run with `RUST_BACKTRACE=1` environment variable to display a backtrace\n\n\nfailures:\n    tests::fails\n\ntest result: FAILED. 1 passed; 1 failed; 2 ignored; 0 measured; 3 filtered out; finished in 0.02s\n";
+    const STDERR: &str = "   Compiling demo v0.1.0 (/tmp/demo)\n     Running unittests src/lib.rs (target/debug/deps/demo-e8bb00aca4fa22b6)\n";
+    const NIGHTLY_STDOUT: &str = r#"{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "event": "started", "name": "tests::fails" }
+{ "type": "test", "name": "tests::fails", "event": "failed", "exec_time": 0.010192264, "stdout": "hello out\n\nthread 'tests::fails' (7851) panicked at src/lib.rs:5:49:\nassertion `left == right` failed: math\n  left: 2\n right: 3\nstack backtrace:\n   0: __rustc::rust_begin_unwind\n" }
+{ "type": "test", "name": "tests::ok", "event": "ok", "exec_time": 0.000001411 }
+{ "type": "test", "name": "tests::slow", "event": "ignored", "message": "slow" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 1, "measured": 0, "filtered_out": 0, "exec_time": 0.010636296 }"#;
+
+    fn assert_failure(test: &TestCase) {
+        assert_eq!(test.status, TestStatus::Failed);
+        assert_eq!(test.stdout.as_deref(), Some("hello out"));
+        assert_eq!(
+            test.panic_message.as_deref(),
+            Some("assertion `left == right` failed: math\n  left: 2\n right: 3")
+        );
+        assert_eq!(
+            test.panic_location,
+            Some(PanicLocation {
+                file: "src/lib.rs".to_string(),
+                line: 5,
+                column: 49
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_stable_text_output() {
+        let (report, text) = parse_test_output(STABLE_STDOUT, STDERR);
+        assert_eq!(text, STABLE_STDOUT.trim_end_matches('\n'));
+        assert_eq!(
+            report.summary(),
+            "1 passed, 1 failed, 2 ignored, 3 filtered out"
+        );
+        assert_eq!(report.tests.len(), 4);
+        assert_failure(&report.tests[0]);
+        assert_eq!(
+            report.tests[0].binary.as_deref(),
+            Some("unittests src/lib.rs")
+        );
+        assert_eq!(report.tests[2].ignore_reason.as_deref(), Some("slow"));
+        assert!((report.duration_secs - 0.02).abs() < 1e-9);
+        assert_eq!(
+            report.render(),
+            "Tests: 1 passed, 1 failed, 2 ignored, 3 filtered out\n  FAILED tests::fails (unittests src/lib.rs) at src/lib.rs:5:49: assertion `left == right` failed: math"
+        );
+    }
+
+    #[test]
+    fn test_parse_libtest_json_events() {
+        let (report, text) = parse_test_output(NIGHTLY_STDOUT, STDERR);
+        assert_eq!(report.summary(), "1 passed, 1 failed, 1 ignored");
+        assert_failure(&report.tests[0]);
+        assert_eq!(report.tests[1].duration_secs, Some(0.000001411));
+        assert!(text.contains("running 3 tests\ntest tests::fails ... FAILED"));
+        assert!(text.contains("test tests::slow ... ignored, slow"));
+        assert!(text.contains("---- tests::fails stdout ----\nhello out"));
+        assert!(text.contains("test result: FAILED. 1 passed; 1 failed; 1 ignored"));
+        assert!(!text.contains("\"type\""));
+    }
+
+    #[test]
+    fn test_nextest_names_carry_the_binary() {
+        let stdout = r#"{"type":"test","event":"ok","name":"demo::integration$adds","exec_time":0.004,"nextest":{"crate":"demo","test_binary":"integration","kind":"test"}}"#;
+        let (report, _) = parse_test_output(stdout, "");
+        assert_eq!(report.tests[0].name, "adds");
+        assert_eq!(report.tests[0].binary.as_deref(), Some("demo::integration"));
+    }
+
+    #[test]
+    fn test_old_panic_format() {
+        let (location, message) =
+            parse_panic_line("thread 'a' panicked at 'boom', src/main.rs:3:5").unwrap();
+        assert_eq!(message.as_deref(), Some("boom"));
+        assert_eq!(location.unwrap().line, 3);
+    }
+
+    const BACKTRACE_STDERR: &str = "   Compiling bt v0.1.0 (/tmp/bt)\n     Running `target/debug/bt`\n\nthread 'main' (7177) panicked at crates/bt/src/main.rs:2:16:\ntoo big: 2\nstack backtrace:\n   0: __rustc::rust_begin_unwind\n             at /rustc/5980761/library/std/src/panicking.rs:689:5\n   1: core::panicking::panic_fmt\n             at /rustc/5980761/library/core/src/panicking.rs:80:14\n   2: bt::helper\n             at ./src/main.rs:2:16\n   3: serde_json::de::from_str\n             at /root/.cargo/registry/src/index.crates.io-1949cf8c6b5b557f/serde_json-1.0.140/src/de.rs:2699:5\n   4: bt::main\n             at ./src/main.rs:7:5\n   5: core::ops::function::FnOnce::call_once\n             at /rustc/5980761/library/core/src/ops/function.rs:250:5\nnote: Some details are omitted, run with `RUST_BACKTRACE=full` for a verbose backtrace.\n";
+
+    #[test]
+    fn test_parse_panic_with_backtrace() {
+        let panics = parse_panics(BACKTRACE_STDERR);
+        assert_eq!(panics.len(), 1);
+        let panic = &panics[0];
+        assert_eq!(panic.thread, "main");
+        assert_eq!(panic.message.as_deref(), Some("too big: 2"));
+        let frames: Vec<(&str, Option<&str>, bool)> = panic
+            .failure_locations
+            .iter()
+            .map(|f| (f.function.as_str(), f.file.as_deref(), f.in_workspace))
+            .collect();
+        assert_eq!(
+            frames,
+            vec![
+                ("bt::helper", Some("crates/bt/src/main.rs"), true),
+                (
+                    "serde_json::de::from_str",
+                    Some(
+                        "/root/.cargo/registry/src/index.crates.io-1949cf8c6b5b557f/serde_json-1.0.140/src/de.rs"
+                    ),
+                    false
+                ),
+                ("bt::main", Some("crates/bt/src/main.rs"), true),
+            ]
+        );
+        assert_eq!(
+            panic.render(),
+            "Panic: thread 'main' at crates/bt/src/main.rs:2:16: too big: 2\n  at crates/bt/src/main.rs:2:16 in bt::helper\n  at crates/bt/src/main.rs:7:5 in bt::main"
+        );
+    }
+
+    #[test]
+    fn test_failing_test_backtrace_frames() {
+        let stdout = "\nrunning 1 test\ntest tests::fails ... FAILED\n\nfailures:\n\n---- tests::fails stdout ----\n\nthread 'tests::fails' (7256) panicked at src/main.rs:2:16:\ntoo big: 5\nstack backtrace:\n   0: __rustc::rust_begin_unwind\n             at /rustc/5980761/library/std/src/panicking.rs:689:5\n   1: bt::helper\n             at ./src/main.rs:2:16\n   2: bt::tests::fails\n             at ./src/main.rs:12:18\n   3: <fn() -> core::result::Result<(), alloc::string::String> as core::ops::function::FnOnce<()>>::call_once\n   4: bt::uninstrumented\nnote: Some details are omitted, run with `RUST_BACKTRACE=full` for a verbose backtrace.\n\n\nfailures:\n    tests::fails\n\ntest result: FAILED. 0 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.01s\n";
+        let (report, _) = parse_test_output(stdout, STDERR);
+        let failed = report.failures().next().unwrap();
+        assert_eq!(failed.panic_message.as_deref(), Some("too big: 5"));
+        let functions: Vec<&str> = failed
+            .failure_locations
+            .iter()
+            .map(|f| f.function.as_str())
+            .collect();
+        assert_eq!(
+            functions,
+            vec!["bt::helper", "bt::tests::fails", "bt::uninstrumented"]
+        );
+        assert!(!failed.failure_locations[2].in_workspace);
+        assert!(report.render().ends_with(
+            "too big: 5\n      at src/main.rs:2:16 in bt::helper\n      at src/main.rs:12:18 in bt::tests::fails"
+        ));
+    }
+
+    #[test]
+    fn test_exact_args_keep_options_and_replace_filters() {
+        let original: Vec<String> = ["parser", "--test-threads", "1", "--nocapture"]
+            .map(String::from)
+            .to_vec();
+        let names = vec!["tests::a".to_string(), "tests::b".to_string()];
+        assert_eq!(
+            exact_test_args(&original, &names),
+            [
+                "--test-threads",
+                "1",
+                "--nocapture",
+                "--exact",
+                "tests::a",
+                "tests::b"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nextest_filterset() {
+        let (report, _) = parse_test_output(STABLE_STDOUT, STDERR);
+        let mut nextest = report.tests[0].clone();
+        nextest.binary = Some("demo::integration".to_string());
+        nextest.name = "adds".to_string();
+        let mut plain = report.tests[0].clone();
+        plain.binary = None;
+        assert_eq!(
+            nextest_filterset(&[&nextest, &plain]),
+            "(binary_id(demo::integration) & test(=adds)) | test(=tests::fails)"
+        );
+        let args: Vec<String> = ["-p", "demo", "-E", "test(foo)", "--filterset=all()"]
+            .map(String::from)
+            .to_vec();
+        assert_eq!(without_filterset(&args), ["-p", "demo"]);
+    }
+
+    #[test]
+    fn test_flaky_report_from_runs() {
+        let (failing, _) = parse_test_output(STABLE_STDOUT, STDERR);
+        let passing_stdout =
+            STABLE_STDOUT.replace("tests::fails ... FAILED", "tests::fails ... ok");
+        let passing_stdout = &passing_stdout[..passing_stdout.find("\nfailures:").unwrap()];
+        let (passing, _) = parse_test_output(passing_stdout, STDERR);
+
+        let report = FlakyReport::from_runs(&[failing.clone(), passing, failing], true, 1);
+        assert_eq!(report.runs, 4);
+        assert_eq!(report.tests.len(), 2);
+        let flaky = &report.tests[0];
+        assert_eq!(flaky.name, "tests::fails");
+        assert!(flaky.flaky);
+        assert_eq!((flaky.passed, flaky.failed), (1, 2));
+        assert_eq!(flaky.failures.len(), 1);
+        assert_eq!(flaky.failures[0].count, 2);
+        assert!(!report.tests[1].flaky);
+
+        let text = report.render();
+        assert!(text.starts_with(
+            "2 test(s) over 4 parallel run(s): 1 flaky, 0 always failing, 1 always passing (1 run(s) produced no test results)"
+        ));
+        assert!(text.contains(
+            "FLAKY tests::fails (unittests src/lib.rs): passed 1/3 (33%)\n    2x at src/lib.rs:5:49: assertion `left == right` failed: math"
+        ));
+    }
+
+    const LIST_STDERR: &str = "     Running unittests src/lib.rs (target/debug/deps/demo-e8bb00aca4fa22b6)\n     Running tests/it.rs (target/debug/deps/it-271324b1dc75c57a)\n   Doc-tests demo\n";
+
+    fn artifacts() -> Vec<Artifact> {
+        ["demo-e8bb00aca4fa22b6", "it-271324b1dc75c57a"]
+            .iter()
+            .map(|exe| Artifact {
+                package: "demo 0.1.0".to_string(),
+                target: "demo (lib)".to_string(),
+                fresh: true,
+                executable: Some(format!("/tmp/demo/target/debug/deps/{exe}")),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_text_test_list() {
+        let stdout = "tests::ok: test\ntests::slow: test\n\n2 tests, 0 benchmarks\ninteg: test\n\n1 test, 0 benchmarks\nsrc/lib.rs - add (line 1): test\n\n1 test, 0 benchmarks\nall doctests ran in 0.29s; merged doctests compilation took 0.29s\n";
+        let tests = parse_test_list(stdout, LIST_STDERR, &artifacts());
+        assert_eq!(tests.len(), 4);
+        assert_eq!(tests[0].binary.as_deref(), Some("unittests src/lib.rs"));
+        assert_eq!(tests[0].package.as_deref(), Some("demo"));
+        assert_eq!(tests[2].binary.as_deref(), Some("tests/it.rs"));
+        let doc = &tests[3];
+        assert_eq!(doc.binary.as_deref(), Some("doc-tests demo"));
+        assert_eq!(doc.package.as_deref(), Some("demo"));
+        assert_eq!(
+            doc.location,
+            Some(SourceLocation {
+                file: "src/lib.rs".to_string(),
+                line: 1
+            })
+        );
+        assert_eq!(
+            render_test_list(&tests),
+            "unittests src/lib.rs [demo]\n  tests::ok\n  tests::slow\ntests/it.rs [demo]\n  integ\ndoc-tests demo [demo]\n  src/lib.rs - add (line 1)"
+        );
+    }
+
+    #[test]
+    fn test_parse_json_test_list() {
+        let stdout = r#"{ "type": "suite", "event": "discovery" }
+{ "type": "test", "event": "discovered", "name": "tests::slow", "ignore": true, "ignore_message": "", "source_path": "src/lib.rs", "start_line": 8, "start_col": 26, "end_line": 8, "end_col": 30 }
+{ "type": "suite", "event": "completed", "tests": 1, "benchmarks": 0, "total": 1, "ignored": 1 }
+{ "type": "suite", "event": "discovery" }
+{ "type": "test", "event": "discovered", "name": "integ", "ignore": false, "ignore_message": "", "source_path": "tests/it.rs", "start_line": 1, "start_col": 12, "end_line": 1, "end_col": 17 }
+{ "type": "suite", "event": "completed", "tests": 1, "benchmarks": 0, "total": 1, "ignored": 0 }"#;
+        let tests = parse_test_list(stdout, LIST_STDERR, &artifacts());
+        assert!(tests[0].ignored);
+        assert_eq!(tests[0].location.as_ref().unwrap().line, 8);
+        assert_eq!(tests[1].binary.as_deref(), Some("tests/it.rs"));
+        assert!(render_test_list(&tests).contains("  tests::slow (ignored) at src/lib.rs:8"));
+    }
+
+    #[test]
+    fn test_parse_nextest_list() {
+        let stdout = r#"{"rust-build-meta":{},"test-count":2,"rust-suites":{"demo":{"package-name":"demo","binary-id":"demo","kind":"lib","testcases":{"tests::ok":{"ignored":false,"filter-match":{"status":"matches"}},"tests::slow":{"ignored":true,"filter-match":{"status":"mismatch","reason":"ignored"}}}}}}"#;
+        let tests = parse_nextest_list(stdout);
+        assert_eq!(tests.len(), 2);
+        assert_eq!(tests[1].name, "tests::slow");
+        assert!(tests[1].ignored);
+        assert_eq!(tests[0].package.as_deref(), Some("demo"));
+    }
+}
@@ -0,0 +1,247 @@
+//! Build timing analysis from cargo's `--timings` report
+//!
+//! With `timings: true`, `build` and `check` pass `--timings`. Cargo prints
+//! `Timing report saved to <path>` and writes an HTML report with the per-unit data embedded as
+//! a `UNIT_DATA` JSON array (start, duration, frontend/codegen sections and the units each one
+//! unblocked). The machine-readable `--timings=json` variant was removed from cargo, so that
+//! array is parsed into a [`TimingReport`]: total time, the slowest units, the frontend/codegen
+//! split and the critical path of units that kept the build waiting.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Units listed under "slowest" in a timing report
+pub const SLOWEST_UNITS: usize = 10;
+
+/// One compilation unit: a crate target in one mode (build, build script run, test, ...)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnitTiming {
+    /// As cargo's report names it, e.g. `serde v1.0.219` or `demo v0.1.0 demo "bin"`
+    pub unit: String,
+    pub start_secs: f64,
+    pub duration_secs: f64,
+    /// Time until metadata was ready for dependents (parsing, type checking, borrow checking)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frontend_secs: Option<f64>,
+    /// Time spent generating and optimizing code after the frontend
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub codegen_secs: Option<f64>,
+}
+
+impl UnitTiming {
+    fn render(&self) -> String {
+        let mut out = format!("{:.2}s  {}", self.duration_secs, self.unit);
+        if let (Some(frontend), Some(codegen)) = (self.frontend_secs, self.codegen_secs) {
+            out.push_str(&format!(
+                " (frontend {frontend:.2}s, codegen {codegen:.2}s)"
+            ));
+        }
+        out
+    }
+}
+
+/// Where the time of one `cargo build --timings` went
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimingReport {
+    /// Wall time from the start of the build until the last unit finished
+    pub total_secs: f64,
+    pub units: usize,
+    /// Frontend and codegen time summed over the units that report the split
+    pub frontend_secs: f64,
+    pub codegen_secs: f64,
+    /// Longest units first
+    pub slowest: Vec<UnitTiming>,
+    /// Chain of units, each waiting for the one before it, that ends with the last unit to finish
+    pub critical_path: Vec<UnitTiming>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub html_path: Option<String>,
+}
+
+impl TimingReport {
+    /// `Timings:` line followed by the slowest units, the critical path and the report path
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "Timings: {:.2}s total for {} unit(s); frontend {:.2}s, codegen {:.2}s summed over units",
+            self.total_secs, self.units, self.frontend_secs, self.codegen_secs
+        );
+        if !self.slowest.is_empty() {
+            out.push_str("\n  Slowest units:");
+            for unit in &self.slowest {
+                out.push_str(&format!("\n    {}", unit.render()));
+            }
+        }
+        if !self.critical_path.is_empty() {
+            let chain: Vec<String> = self
+                .critical_path
+                .iter()
+                .map(|u| format!("{} ({:.2}s)", u.unit, u.duration_secs))
+                .collect();
+            out.push_str(&format!("\n  Critical path: {}", chain.join(" -> ")));
+        }
+        if let Some(path) = &self.html_path {
+            out.push_str(&format!("\n  Report: {path}"));
+        }
+        out
+    }
+}
+
+#[derive(Deserialize)]
+struct RawUnit {
+    i: usize,
+    name: String,
+    version: String,
+    #[serde(default)]
+    target: String,
+    start: f64,
+    duration: f64,
+    /// Older cargo reports the frontend as the time until the rmeta file was written
+    rmeta_time: Option<f64>,
+    #[serde(default)]
+    unblocked_units: Vec<usize>,
+    #[serde(default)]
+    unblocked_rmeta_units: Vec<usize>,
+    sections: Option<Vec<(String, RawSection)>>,
+}
+
+#[derive(Deserialize)]
+struct RawSection {
+    start: f64,
+    end: f64,
+}
+
+impl RawUnit {
+    fn timing(&self) -> UnitTiming {
+        let section = |name: &str| {
+            self.sections.as_ref().and_then(|sections| {
+                sections
+                    .iter()
+                    .find(|(n, _)| n == name)
+                    .map(|(_, s)| s.end - s.start)
+            })
+        };
+        let (frontend_secs, codegen_secs) = match (section("frontend"), section("codegen")) {
+            (Some(frontend), Some(codegen)) => (Some(frontend), Some(codegen)),
+            _ => match self.rmeta_time {
+                Some(rmeta) => (Some(rmeta), Some((self.duration - rmeta).max(0.0))),
+                None => (None, None),
+            },
+        };
+        UnitTiming {
+            unit: format!("{} v{}{}", self.name, self.version, self.target),
+            start_secs: self.start,
+            duration_secs: self.duration,
+            frontend_secs,
+            codegen_secs,
+        }
+    }
+}
+
+/// `Timing report saved to <path>` from cargo's stderr
+pub fn report_path(stderr: &str) -> Option<String> {
+    stderr.lines().find_map(|line| {
+        line.trim()
+            .strip_prefix("Timing report saved to ")
+            .map(|path| path.trim().to_string())
+    })
+}
+
+/// Parse the `UNIT_DATA` array of a cargo timing HTML report
+pub fn parse_timing_html(html: &str) -> Option<TimingReport> {
+    let start = html.find("const UNIT_DATA = ")? + "const UNIT_DATA = ".len();
+    let units: Vec<RawUnit> = serde_json::Deserializer::from_str(&html[start..])
+        .into_iter()
+        .next()?
+        .ok()?;
+
+    let timings: Vec<UnitTiming> = units.iter().map(RawUnit::timing).collect();
+    let end = |u: &UnitTiming| u.start_secs + u.duration_secs;
+    let total_secs = timings.iter().map(end).fold(0.0, f64::max);
+
+    let mut slowest = timings.clone();
+    slowest.sort_by(|a, b| b.duration_secs.total_cmp(&a.duration_secs));
+    slowest.truncate(SLOWEST_UNITS);
+
+    // A unit lists the units that could start once it finished; walk those edges back from the
+    // last unit to finish
+    let index: HashMap<usize, usize> = units.iter().enumerate().map(|(n, u)| (u.i, n)).collect();
+    let mut unblocked_by: HashMap<usize, usize> = HashMap::new();
+    for (n, unit) in units.iter().enumerate() {
+        for dependent in unit
+            .unblocked_units
+            .iter()
+            .chain(&unit.unblocked_rmeta_units)
+        {
+            if let Some(&d) = index.get(dependent) {
+                unblocked_by.insert(d, n);
+            }
+        }
+    }
+    let mut critical_path = Vec::new();
+    let mut current =
+        (0..timings.len()).max_by(|&a, &b| end(&timings[a]).total_cmp(&end(&timings[b])));
+    while let Some(n) = current
+        && critical_path.len() < timings.len()
+    {
+        critical_path.push(timings[n].clone());
+        current = unblocked_by.get(&n).copied();
+    }
+    critical_path.reverse();
+
+    Some(TimingReport {
+        total_secs,
+        units: timings.len(),
+        frontend_secs: timings.iter().filter_map(|u| u.frontend_secs).sum(),
+        codegen_secs: timings.iter().filter_map(|u| u.codegen_secs).sum(),
+        slowest,
+        critical_path,
+        html_path: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HTML: &str = r#"<script>
+DURATION = 1;
+const UNIT_DATA = [
+  {"i": 2, "name": "b", "version": "0.1.0", "mode": "todo", "target": "", "features": [], "start": 0.01, "duration": 0.4, "unblocked_units": [], "unblocked_rmeta_units": [],
+   "sections": [["frontend", {"start": 0.0, "end": 0.1}], ["codegen", {"start": 0.1, "end": 0.4}]]},
+  {"i": 0, "name": "a", "version": "0.1.0", "mode": "todo", "target": "", "features": [], "start": 0.02, "duration": 0.5, "unblocked_units": [1], "unblocked_rmeta_units": [],
+   "sections": [["frontend", {"start": 0.0, "end": 0.2}], ["codegen", {"start": 0.2, "end": 0.5}]]},
+  {"i": 1, "name": "app", "version": "0.1.0", "mode": "todo", "target": " app \"bin\"", "features": [], "start": 0.52, "duration": 0.3, "rmeta_time": 0.1, "unblocked_units": [], "unblocked_rmeta_units": [], "sections": null}
+];
+const CONCURRENCY_DATA = [];
+</script>"#;
+
+    #[test]
+    fn test_parse_timing_html() {
+        let report = parse_timing_html(HTML).unwrap();
+        assert_eq!(report.units, 3);
+        assert!((report.total_secs - 0.82).abs() < 1e-9);
+        assert!((report.frontend_secs - 0.4).abs() < 1e-9);
+        assert!((report.codegen_secs - 0.8).abs() < 1e-9);
+        let slowest: Vec<&str> = report.slowest.iter().map(|u| u.unit.as_str()).collect();
+        assert_eq!(
+            slowest,
+            vec!["a v0.1.0", "b v0.1.0", "app v0.1.0 app \"bin\""]
+        );
+        let path: Vec<&str> = report
+            .critical_path
+            .iter()
+            .map(|u| u.unit.as_str())
+            .collect();
+        assert_eq!(path, vec!["a v0.1.0", "app v0.1.0 app \"bin\""]);
+    }
+
+    #[test]
+    fn test_report_path() {
+        let stderr = "   Compiling app v0.1.0 (/tmp/app)\n      Timing report saved to /tmp/app/target/cargo-timings/cargo-timing-20261018T144525403Z-773f4f467c9a6380.html\n    Finished `dev` profile\n";
+        assert_eq!(
+            report_path(stderr).as_deref(),
+            Some(
+                "/tmp/app/target/cargo-timings/cargo-timing-20261018T144525403Z-773f4f467c9a6380.html"
+            )
+        );
+    }
+}
@@ -0,0 +1,353 @@
+//! Unused dependency detection with cargo-machete or cargo-udeps
+//!
+//! cargo-machete searches the sources for each dependency's crate name, so it is fast and runs on
+//! stable but can miss uses hidden in macros. cargo-udeps builds the crate on nightly and asks the
+//! compiler which dependencies were never loaded, which is accurate but slow. machete prints plain
+//! text without the manifest section; udeps prints JSON with normal, development and build lists.
+//! Both are turned into [`UnusedDependency`]s, and the section of each (and its `[target.*]`
+//! table, if any) is looked up in `cargo metadata` so `fix` can hand them to `cargo remove`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Which detector `unused_deps` runs
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum UnusedDepsTool {
+    /// cargo-machete: fast, stable, text search
+    #[default]
+    Machete,
+    /// cargo-udeps: accurate, needs a nightly toolchain
+    Udeps,
+}
+
+impl UnusedDepsTool {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnusedDepsTool::Machete => "cargo-machete",
+            UnusedDepsTool::Udeps => "cargo-udeps",
+        }
+    }
+}
+
+/// Manifest section a dependency is declared in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyKind {
+    Normal,
+    Dev,
+    Build,
+}
+
+impl DependencyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DependencyKind::Normal => "normal",
+            DependencyKind::Dev => "dev",
+            DependencyKind::Build => "build",
+        }
+    }
+
+    /// The `kind` of a dependency in `cargo metadata` output
+    fn from_metadata(kind: &Value) -> Self {
+        match kind.as_str() {
+            Some("dev") => DependencyKind::Dev,
+            Some("build") => DependencyKind::Build,
+            _ => DependencyKind::Normal,
+        }
+    }
+}
+
+/// A dependency one package declares but does not use
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnusedDependency {
+    pub package: String,
+    pub manifest_path: String,
+    /// Name as written in the manifest (the rename, if the dependency is renamed)
+    pub name: String,
+    pub kind: DependencyKind,
+    /// Platform of a `[target.'cfg(...)'.dependencies]` table
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+}
+
+/// Everything one `unused_deps` run found
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnusedDepsReport {
+    pub tool: UnusedDepsTool,
+    pub dependencies: Vec<UnusedDependency>,
+}
+
+impl UnusedDepsReport {
+    /// Count line, then one line per package listing its unused dependencies with their section
+    pub fn render(&self) -> String {
+        let mut by_package: BTreeMap<(&str, &str), Vec<String>> = BTreeMap::new();
+        for dep in &self.dependencies {
+            let section = match &dep.target {
+                Some(target) => format!("{}, target {target}", dep.kind.as_str()),
+                None => dep.kind.as_str().to_string(),
+            };
+            by_package
+                .entry((&dep.package, &dep.manifest_path))
+                .or_default()
+                .push(format!("{} ({section})", dep.name));
+        }
+        let mut out = format!(
+            "Unused dependencies: {} in {} package(s) (found by {})",
+            self.dependencies.len(),
+            by_package.len(),
+            self.tool.as_str()
+        );
+        for ((package, manifest_path), deps) in by_package {
+            out.push_str(&format!(
+                "\n  {package} ({manifest_path}): {}",
+                deps.join(", ")
+            ));
+        }
+        out
+    }
+}
+
+/// A finding before its section is known: package, manifest path and dependency name, plus the
+/// kind when the detector reports it (udeps)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub package: String,
+    pub manifest_path: String,
+    pub name: String,
+    pub kind: Option<DependencyKind>,
+}
+
+/// Parse cargo-machete's text report:
+///
+/// ```text
+/// cargo-machete found the following unused dependencies in /p:
+/// my_crate -- /p/Cargo.toml:
+///     serde
+/// ```
+pub fn parse_machete_output(output: &str) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut current: Option<(String, String)> = None;
+    for line in output.lines() {
+        if line.starts_with(char::is_whitespace) {
+            let name = line.trim();
+            if let Some((package, manifest_path)) = &current
+                && !name.is_empty()
+            {
+                findings.push(Finding {
+                    package: package.clone(),
+                    manifest_path: manifest_path.clone(),
+                    name: name.to_string(),
+                    kind: None,
+                });
+            }
+        } else if let Some((package, manifest)) = line.split_once(" -- ")
+            && let Some(manifest_path) = manifest.trim_end().strip_suffix(':')
+        {
+            current = Some((package.to_string(), manifest_path.to_string()));
+        } else {
+            current = None;
+        }
+    }
+    findings
+}
+
+/// Parse `cargo udeps --output json`. Returns `None` if the output holds no udeps report.
+pub fn parse_udeps_json(output: &str) -> Option<Vec<Finding>> {
+    let json: Value = output
+        .lines()
+        .find_map(|line| serde_json::from_str(line).ok())
+        .or_else(|| serde_json::from_str(output.trim()).ok())?;
+    let packages = json.get("unused_deps")?.as_object()?;
+    let mut findings = Vec::new();
+    for (package_id, unused) in packages {
+        // Keys look like "my_crate 0.1.0 (path+file:///p)"
+        let package = package_id.split(' ').next().unwrap_or(package_id);
+        let manifest_path = unused["manifest_path"].as_str().unwrap_or_default();
+        for (key, kind) in [
+            ("normal", DependencyKind::Normal),
+            ("development", DependencyKind::Dev),
+            ("build", DependencyKind::Build),
+        ] {
+            for name in unused[key].as_array().into_iter().flatten() {
+                if let Some(name) = name.as_str() {
+                    findings.push(Finding {
+                        package: package.to_string(),
+                        manifest_path: manifest_path.to_string(),
+                        name: name.to_string(),
+                        kind: Some(kind),
+                    });
+                }
+            }
+        }
+    }
+    Some(findings)
+}
+
+/// Give each finding its section from `cargo metadata --no-deps` output. A dependency declared in
+/// several sections (e.g. both normal and dev) yields one entry per section unless the detector
+/// named the section. Findings that cannot be matched are kept as normal dependencies.
+pub fn resolve_sections(findings: Vec<Finding>, metadata: Option<&Value>) -> Vec<UnusedDependency> {
+    let mut resolved = Vec::new();
+    for finding in findings {
+        let declared: Vec<(DependencyKind, Option<String>, String)> = metadata
+            .and_then(|m| m["packages"].as_array())
+            .into_iter()
+            .flatten()
+            .filter(|p| p["name"].as_str() == Some(finding.package.as_str()))
+            .flat_map(|p| {
+                let manifest_path = p["manifest_path"].as_str().unwrap_or_default().to_string();
+                p["dependencies"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter(|d| {
+                        let name = d["rename"].as_str().or_else(|| d["name"].as_str());
+                        // crates.io treats `-` and `_` in names alike
+                        name.map(|n| n.replace('-', "_")) == Some(finding.name.replace('-', "_"))
+                    })
+                    .map(move |d| {
+                        (
+                            DependencyKind::from_metadata(&d["kind"]),
+                            d["target"].as_str().map(str::to_string),
+                            manifest_path.clone(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .filter(|(kind, _, _)| finding.kind.is_none_or(|k| k == *kind))
+            .collect();
+        if declared.is_empty() {
+            resolved.push(UnusedDependency {
+                package: finding.package,
+                manifest_path: finding.manifest_path,
+                name: finding.name,
+                kind: finding.kind.unwrap_or(DependencyKind::Normal),
+                target: None,
+            });
+            continue;
+        }
+        for (kind, target, manifest_path) in declared {
+            resolved.push(UnusedDependency {
+                package: finding.package.clone(),
+                manifest_path,
+                name: finding.name.clone(),
+                kind,
+                target,
+            });
+        }
+    }
+    resolved.dedup();
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MACHETE: &str = "Analyzing dependencies of crates in this directory...
+cargo-machete found the following unused dependencies in /p:
+app -- /p/app/Cargo.toml:
+\tregex
+\tserde-json
+
+core -- /p/core/Cargo.toml:
+\tlog
+
+If you believe cargo-machete has detected an unused dependency incorrectly,
+you can add the dependency to the list of dependencies to ignore in the
+`[package.metadata.cargo-machete]` section of the appropriate Cargo.toml.
+";
+
+    const METADATA: &str = r#"{"packages":[
+        {"name":"app","manifest_path":"/p/app/Cargo.toml","dependencies":[
+            {"name":"regex","rename":null,"kind":null,"target":null},
+            {"name":"regex","rename":null,"kind":"dev","target":null},
+            {"name":"serde_json","rename":"serde-json","kind":null,"target":"cfg(unix)"}
+        ]},
+        {"name":"core","manifest_path":"/p/core/Cargo.toml","dependencies":[
+            {"name":"log","rename":null,"kind":"build","target":null}
+        ]}
+    ]}"#;
+
+    #[test]
+    fn test_parses_machete_report() {
+        let findings = parse_machete_output(MACHETE);
+        let names: Vec<(&str, &str, &str)> = findings
+            .iter()
+            .map(|f| {
+                (
+                    f.package.as_str(),
+                    f.manifest_path.as_str(),
+                    f.name.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("app", "/p/app/Cargo.toml", "regex"),
+                ("app", "/p/app/Cargo.toml", "serde-json"),
+                ("core", "/p/core/Cargo.toml", "log"),
+            ]
+        );
+        assert!(
+            parse_machete_output(
+                "cargo-machete didn't find any unused dependencies in /p. Good job!\n"
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_resolves_sections_from_metadata() {
+        let metadata: Value = serde_json::from_str(METADATA).unwrap();
+        let deps = resolve_sections(parse_machete_output(MACHETE), Some(&metadata));
+        let sections: Vec<(&str, DependencyKind, Option<&str>)> = deps
+            .iter()
+            .map(|d| (d.name.as_str(), d.kind, d.target.as_deref()))
+            .collect();
+        assert_eq!(
+            sections,
+            vec![
+                ("regex", DependencyKind::Normal, None),
+                ("regex", DependencyKind::Dev, None),
+                ("serde-json", DependencyKind::Normal, Some("cfg(unix)")),
+                ("log", DependencyKind::Build, None),
+            ]
+        );
+        let report = UnusedDepsReport {
+            tool: UnusedDepsTool::Machete,
+            dependencies: deps,
+        };
+        assert_eq!(
+            report.render(),
+            "Unused dependencies: 4 in 2 package(s) (found by cargo-machete)\n  app (/p/app/Cargo.toml): regex (normal), regex (dev), serde-json (normal, target cfg(unix))\n  core (/p/core/Cargo.toml): log (build)"
+        );
+    }
+
+    #[test]
+    fn test_parses_udeps_json_with_kinds() {
+        let output = r#"{"success":false,"unused_deps":{"app 0.1.0 (path+file:///p/app)":{"manifest_path":"/p/app/Cargo.toml","normal":[],"development":["regex"],"build":[]}},"note":"Note: They might be false-positive."}"#;
+        let metadata: Value = serde_json::from_str(METADATA).unwrap();
+        let deps = resolve_sections(parse_udeps_json(output).unwrap(), Some(&metadata));
+        assert_eq!(
+            deps,
+            vec![UnusedDependency {
+                package: "app".to_string(),
+                manifest_path: "/p/app/Cargo.toml".to_string(),
+                name: "regex".to_string(),
+                kind: DependencyKind::Dev,
+                target: None,
+            }]
+        );
+        assert_eq!(parse_udeps_json("error: no nightly toolchain"), None);
+
+        // Without metadata the detector's section is kept
+        let deps = resolve_sections(parse_udeps_json(output).unwrap(), None);
+        assert_eq!(deps[0].kind, DependencyKind::Dev);
+    }
+}
@@ -0,0 +1,623 @@
+//! Watch sessions: re-run a command chain whenever files in a directory change
+//!
+//! `watch_start` watches a directory recursively (inotify on Linux, via `notify`) and ignores
+//! changes under `.git/` and `target/` and paths matched by the `.gitignore` files found when the
+//! session starts. Once changes have been quiet for the debounce interval, the session runs its
+//! chain (e.g. check → test) once, stopping at the first failing step. Each session keeps its
+//! latest run for `watch_status`; the outcome of a run is compared with the previous one so
+//! progress notifications are only sent when it changes.
+
+use crate::diagnostics::CargoReport;
+use ignore::WalkBuilder;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock, mpsc};
+use tokio::task::JoinHandle;
+
+/// Quiet period after the last change before the chain runs, when the request does not say
+pub const DEFAULT_DEBOUNCE_MS: u64 = 500;
+
+/// Changed paths listed per run before the rest are only counted
+const MAX_LISTED_CHANGES: usize = 3;
+
+/// One command of a watch chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchStep {
+    Check,
+    Clippy,
+    Build,
+    Test,
+}
+
+impl WatchStep {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WatchStep::Check => "check",
+            WatchStep::Clippy => "clippy",
+            WatchStep::Build => "build",
+            WatchStep::Test => "test",
+        }
+    }
+
+    /// Chain used when the request does not give one
+    pub fn default_chain() -> Vec<WatchStep> {
+        vec![WatchStep::Check, WatchStep::Test]
+    }
+}
+
+/// Render a chain as `check → test`
+pub fn render_chain(steps: &[WatchStep]) -> String {
+    steps
+        .iter()
+        .map(|s| s.as_str())
+        .collect::<Vec<_>>()
+        .join(" → ")
+}
+
+/// Decides which changed paths can affect a build
+#[derive(Debug, Clone)]
+pub struct ChangeFilter {
+    root: PathBuf,
+    /// Deepest first, so a nested `.gitignore` overrides the ones above it
+    gitignores: Vec<Gitignore>,
+}
+
+impl ChangeFilter {
+    /// Collect the `.gitignore` files under `root` and `.git/info/exclude`
+    pub fn new(root: &Path) -> Self {
+        let mut gitignores: Vec<Gitignore> = WalkBuilder::new(root)
+            .hidden(false)
+            .filter_entry(|entry| !matches!(entry.file_name().to_str(), Some(".git" | "target")))
+            .build()
+            .flatten()
+            .filter(|entry| {
+                entry.file_name() == ".gitignore" && entry.file_type().is_some_and(|t| t.is_file())
+            })
+            .map(|entry| Gitignore::new(entry.path()).0)
+            .collect();
+        let exclude = root.join(".git").join("info").join("exclude");
+        if exclude.is_file() {
+            let mut builder = GitignoreBuilder::new(root);
+            builder.add(exclude);
+            if let Ok(gitignore) = builder.build() {
+                gitignores.push(gitignore);
+            }
+        }
+        gitignores.sort_by_key(|g| std::cmp::Reverse(g.path().components().count()));
+        Self {
+            root: root.to_path_buf(),
+            gitignores,
+        }
+    }
+
+    /// Whether a change to `path` should trigger a run
+    pub fn is_relevant(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        if relative.components().any(|c| c.as_os_str() == ".git") {
+            return false;
+        }
+        let first = relative
+            .components()
+            .next()
+            .map(|c| c.as_os_str().to_string_lossy());
+        if first.is_some_and(|name| is_target_dir_name(&name)) {
+            return false;
+        }
+        let is_dir = path.is_dir();
+        for gitignore in &self.gitignores {
+            if !path.starts_with(gitignore.path()) {
+                continue;
+            }
+            let matched = gitignore.matched_path_or_any_parents(path, is_dir);
+            if matched.is_ignore() {
+                return false;
+            }
+            if matched.is_whitelist() {
+                return true;
+            }
+        }
+        true
+    }
+}
+
+/// Whether a top-level directory name is cargo's build output: `target`, or the temporary
+/// `targetXXXXXX` directory cargo creates and renames to `target`
+pub fn is_target_dir_name(name: &str) -> bool {
+    name == "target" || (name.starts_with("target") && name.len() == "target".len() + 6)
+}
+
+/// Watch `root` recursively and forward the paths of relevant changes. Changes stop arriving
+/// when the returned watcher is dropped.
+pub fn watch_directory(
+    root: &Path,
+) -> notify::Result<(RecommendedWatcher, mpsc::UnboundedReceiver<PathBuf>)> {
+    let filter = ChangeFilter::new(root);
+    let (tx, rx) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        for path in event.paths {
+            if filter.is_relevant(&path) {
+                let _ = tx.send(path);
+            }
+        }
+    })?;
+    watcher.watch(root, RecursiveMode::Recursive)?;
+    Ok((watcher, rx))
+}
+
+/// Wait for the next change, then keep collecting until no change arrives for `debounce`.
+/// Returns the distinct changed paths in arrival order, or `None` once the watcher is gone.
+pub async fn next_batch(
+    rx: &mut mpsc::UnboundedReceiver<PathBuf>,
+    debounce: Duration,
+) -> Option<Vec<PathBuf>> {
+    let mut paths = vec![rx.recv().await?];
+    while let Ok(Some(path)) = tokio::time::timeout(debounce, rx.recv()).await {
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    Some(paths)
+}
+
+/// Result of one step of a run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StepResult {
+    pub step: WatchStep,
+    /// `get_output` reads the full output under this ID when `output` was condensed
+    pub operation_id: String,
+    pub success: bool,
+    /// e.g. "1 error, 0 warnings" or "4 passed, 1 failed, 0 ignored"
+    pub summary: String,
+    /// Condensed output of the step
+    pub output: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub report: Option<CargoReport>,
+}
+
+impl StepResult {
+    pub fn new(
+        step: WatchStep,
+        operation_id: &str,
+        success: bool,
+        output: String,
+        report: Option<CargoReport>,
+    ) -> Self {
+        let summary = match &report {
+            Some(CargoReport {
+                tests: Some(tests), ..
+            }) if step == WatchStep::Test => tests.summary(),
+            Some(report) => report.summary(),
+            None => if success { "ok" } else { "failed" }.to_string(),
+        };
+        Self {
+            step,
+            operation_id: operation_id.to_string(),
+            success,
+            summary,
+            output,
+            report,
+        }
+    }
+
+    fn outcome(&self) -> StepOutcome {
+        let report = self.report.as_ref();
+        StepOutcome {
+            step: self.step,
+            success: self.success,
+            errors: report.map_or(0, |r| r.errors),
+            warnings: report.map_or(0, |r| r.warnings),
+            failed_tests: report
+                .and_then(|r| r.tests.as_ref())
+                .map(|t| t.failures().map(|f| f.name.clone()).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// What a notification is about: pass/fail, diagnostic counts and failing tests per step
+#[derive(Debug, Clone, PartialEq)]
+struct StepOutcome {
+    step: WatchStep,
+    success: bool,
+    errors: usize,
+    warnings: usize,
+    failed_tests: Vec<String>,
+}
+
+/// One execution of a session's chain
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WatchRun {
+    /// 1-based
+    pub number: u64,
+    /// Changed paths relative to the watched directory; empty for the run at start
+    pub changed: Vec<String>,
+    pub finished_at: String,
+    pub duration_ms: u64,
+    pub steps: Vec<StepResult>,
+    /// Steps not run because an earlier one failed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<WatchStep>,
+}
+
+impl WatchRun {
+    pub fn success(&self) -> bool {
+        self.skipped.is_empty() && self.steps.iter().all(|s| s.success)
+    }
+
+    /// Whether this run ended differently from `previous` (always true for the first run)
+    pub fn outcome_changed(&self, previous: Option<&WatchRun>) -> bool {
+        let outcome = |run: &WatchRun| -> Vec<StepOutcome> {
+            run.steps.iter().map(StepResult::outcome).collect()
+        };
+        previous.is_none_or(|previous| outcome(previous) != outcome(self))
+    }
+
+    /// `Run #3 after changes to src/lib.rs: + check (0 errors, 1 warning), - test (...)`
+    pub fn summary(&self) -> String {
+        let trigger = if self.changed.is_empty() {
+            "at start".to_string()
+        } else {
+            let mut listed = self
+                .changed
+                .iter()
+                .take(MAX_LISTED_CHANGES)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ");
+            if self.changed.len() > MAX_LISTED_CHANGES {
+                listed.push_str(&format!(
+                    " (+{} more)",
+                    self.changed.len() - MAX_LISTED_CHANGES
+                ));
+            }
+            format!("after changes to {listed}")
+        };
+        let mut steps: Vec<String> = self
+            .steps
+            .iter()
+            .map(|s| {
+                format!(
+                    "{} {} ({})",
+                    if s.success { "+" } else { "-" },
+                    s.step.as_str(),
+                    s.summary
+                )
+            })
+            .collect();
+        steps.extend(
+            self.skipped
+                .iter()
+                .map(|s| format!("{} skipped", s.as_str())),
+        );
+        format!(
+            "Run #{} {trigger} finished at {} in {:.1}s: {}",
+            self.number,
+            self.finished_at,
+            self.duration_ms as f64 / 1000.0,
+            steps.join(", ")
+        )
+    }
+
+    /// Summary line followed by the condensed output of each step
+    pub fn render(&self) -> String {
+        let mut out = self.summary();
+        for step in &self.steps {
+            out.push_str(&format!(
+                "\n\n[{}] {} ({})\n{}",
+                step.step.as_str(),
+                if step.success { "passed" } else { "failed" },
+                step.operation_id,
+                step.output.trim_end()
+            ));
+        }
+        out
+    }
+}
+
+/// A running watch session as reported by `watch_status`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WatchSession {
+    pub session_id: String,
+    pub working_directory: String,
+    pub steps: Vec<WatchStep>,
+    pub debounce_ms: u64,
+    pub started_at: String,
+    /// Whether the chain is executing right now
+    pub running: bool,
+    pub runs: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest: Option<WatchRun>,
+}
+
+impl WatchSession {
+    pub fn new(
+        session_id: &str,
+        working_directory: &str,
+        steps: Vec<WatchStep>,
+        debounce_ms: u64,
+        started_at: String,
+    ) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            working_directory: working_directory.to_string(),
+            steps,
+            debounce_ms,
+            started_at,
+            running: false,
+            runs: 0,
+            latest: None,
+        }
+    }
+
+    /// One line: ID, directory, chain, run count and the outcome of the latest run
+    pub fn summary(&self) -> String {
+        let state = match (&self.latest, self.running) {
+            (_, true) => "running".to_string(),
+            (None, false) => "waiting for changes".to_string(),
+            (Some(run), false) if run.success() => format!("run #{} passed", run.number),
+            (Some(run), false) => format!("run #{} failed", run.number),
+        };
+        format!(
+            "Watch session {} on {}: {} (debounce {}ms), {} run(s), {state}",
+            self.session_id,
+            self.working_directory,
+            render_chain(&self.steps),
+            self.debounce_ms,
+            self.runs
+        )
+    }
+
+    /// Summary line followed by the latest run
+    pub fn render(&self) -> String {
+        match &self.latest {
+            Some(run) => format!("{}\n{}", self.summary(), run.render()),
+            None => self.summary(),
+        }
+    }
+}
+
+/// Active watch sessions and the tasks driving them
+#[derive(Debug, Default)]
+pub struct WatchSessions {
+    sessions: RwLock<HashMap<String, WatchSession>>,
+    tasks: Mutex<HashMap<String, JoinHandle<()>>>,
+}
+
+impl WatchSessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn insert(&self, session: WatchSession) {
+        self.sessions
+            .write()
+            .await
+            .insert(session.session_id.clone(), session);
+    }
+
+    /// Keep the task of a session so `stop` can end it
+    pub async fn attach_task(&self, session_id: &str, task: JoinHandle<()>) {
+        self.tasks.lock().await.insert(session_id.to_string(), task);
+    }
+
+    pub async fn get(&self, session_id: &str) -> Option<WatchSession> {
+        self.sessions.read().await.get(session_id).cloned()
+    }
+
+    /// All sessions, oldest first
+    pub async fn list(&self) -> Vec<WatchSession> {
+        let mut sessions: Vec<WatchSession> =
+            self.sessions.read().await.values().cloned().collect();
+        sessions.sort_by(|a, b| {
+            (a.started_at.as_str(), a.session_id.as_str())
+                .cmp(&(b.started_at.as_str(), b.session_id.as_str()))
+        });
+        sessions
+    }
+
+    /// The session already watching `working_directory`, if any
+    pub async fn find_by_directory(&self, working_directory: &str) -> Option<WatchSession> {
+        self.sessions
+            .read()
+            .await
+            .values()
+            .find(|s| s.working_directory == working_directory)
+            .cloned()
+    }
+
+    pub async fn set_running(&self, session_id: &str, running: bool) {
+        if let Some(session) = self.sessions.write().await.get_mut(session_id) {
+            session.running = running;
+        }
+    }
+
+    /// Store a finished run and return the run it replaces
+    pub async fn record_run(&self, session_id: &str, run: WatchRun) -> Option<WatchRun> {
+        let mut sessions = self.sessions.write().await;
+        let session = sessions.get_mut(session_id)?;
+        session.running = false;
+        session.runs = run.number;
+        session.latest.replace(run)
+    }
+
+    /// End a session: abort its task, which drops the file watcher, and forget it
+    pub async fn stop(&self, session_id: &str) -> Option<WatchSession> {
+        if let Some(task) = self.tasks.lock().await.remove(session_id) {
+            task.abort();
+        }
+        self.sessions.write().await.remove(session_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_results::{TestCase, TestReport, TestStatus};
+
+    fn run(number: u64, steps: Vec<StepResult>) -> WatchRun {
+        WatchRun {
+            number,
+            changed: vec!["src/lib.rs".to_string()],
+            finished_at: "12:00:00".to_string(),
+            duration_ms: 1500,
+            steps,
+            skipped: Vec::new(),
+        }
+    }
+
+    fn check(errors: usize, warnings: usize) -> StepResult {
+        let report = CargoReport {
+            success: Some(errors == 0),
+            errors,
+            warnings,
+            ..CargoReport::default()
+        };
+        StepResult::new(
+            WatchStep::Check,
+            "op_watch_1_run1_check",
+            errors == 0,
+            String::new(),
+            Some(report),
+        )
+    }
+
+    fn test_step(failed: &[&str]) -> StepResult {
+        let tests = TestReport {
+            passed: 3,
+            failed: failed.len(),
+            tests: failed
+                .iter()
+                .map(|name| TestCase {
+                    name: name.to_string(),
+                    binary: None,
+                    status: TestStatus::Failed,
+                    duration_secs: None,
+                    stdout: None,
+                    panic_message: None,
+                    panic_location: None,
+                    ignore_reason: None,
+                    known_flaky: None,
+                    failure_locations: Vec::new(),
+                })
+                .collect(),
+            ..TestReport::default()
+        };
+        let report = CargoReport {
+            tests: Some(tests),
+            ..CargoReport::default()
+        };
+        StepResult::new(
+            WatchStep::Test,
+            "op_watch_1_run1_test",
+            failed.is_empty(),
+            String::new(),
+            Some(report),
+        )
+    }
+
+    #[test]
+    fn outcome_changes_only_when_results_differ() {
+        let first = run(1, vec![check(0, 1), test_step(&["a::fails"])]);
+        assert!(first.outcome_changed(None));
+
+        let same = run(2, vec![check(0, 1), test_step(&["a::fails"])]);
+        assert!(!same.outcome_changed(Some(&first)));
+
+        let fixed = run(3, vec![check(0, 1), test_step(&[])]);
+        assert!(fixed.outcome_changed(Some(&same)));
+
+        let new_warning = run(4, vec![check(0, 2), test_step(&[])]);
+        assert!(new_warning.outcome_changed(Some(&fixed)));
+    }
+
+    #[test]
+    fn summary_lists_steps_and_skipped_steps() {
+        let mut failed = run(2, vec![check(1, 0)]);
+        failed.skipped = vec![WatchStep::Test];
+        assert!(!failed.success());
+        assert_eq!(
+            failed.summary(),
+            "Run #2 after changes to src/lib.rs finished at 12:00:00 in 1.5s: - check (1 error, 0 warnings), test skipped"
+        );
+
+        let passed = run(3, vec![check(0, 0), test_step(&[])]);
+        assert!(passed.success());
+        assert!(
+            passed.summary().ends_with(
+                "+ check (0 errors, 0 warnings), + test (3 passed, 0 failed, 0 ignored)"
+            ),
+            "{}",
+            passed.summary()
+        );
+    }
+
+    #[test]
+    fn summary_counts_changes_beyond_the_listed_ones() {
+        let mut many = run(1, vec![check(0, 0)]);
+        many.changed = (1..=5).map(|i| format!("src/m{i}.rs")).collect();
+        assert!(
+            many.summary()
+                .contains("after changes to src/m1.rs, src/m2.rs, src/m3.rs (+2 more)")
+        );
+    }
+
+    #[test]
+    fn filter_skips_target_git_and_gitignored_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join(".gitignore"), "*.log\n/generated\n").unwrap();
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+        std::fs::write(
+            root.join("sub").join(".gitignore"),
+            "!keep.log\nscratch.rs\n",
+        )
+        .unwrap();
+
+        let filter = ChangeFilter::new(root);
+        assert!(filter.is_relevant(&root.join("src").join("lib.rs")));
+        assert!(filter.is_relevant(&root.join("Cargo.toml")));
+        assert!(!filter.is_relevant(&root.join("target").join("debug").join("x")));
+        assert!(!filter.is_relevant(&root.join("targetZo0z5v").join("CACHEDIR.TAG")));
+        assert!(filter.is_relevant(&root.join("targets.rs")));
+        assert!(!filter.is_relevant(&root.join(".git").join("index")));
+        assert!(!filter.is_relevant(&root.join("build.log")));
+        assert!(!filter.is_relevant(&root.join("generated").join("out.rs")));
+        assert!(!filter.is_relevant(&root.join("sub").join("scratch.rs")));
+        assert!(filter.is_relevant(&root.join("sub").join("keep.log")));
+        assert!(!filter.is_relevant(Path::new("/elsewhere/src/lib.rs")));
+    }
+
+    #[tokio::test]
+    async fn next_batch_collects_changes_until_quiet() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        tx.send(PathBuf::from("a.rs")).unwrap();
+        tx.send(PathBuf::from("b.rs")).unwrap();
+        tx.send(PathBuf::from("a.rs")).unwrap();
+        let batch = next_batch(&mut rx, Duration::from_millis(20)).await;
+        assert_eq!(
+            batch,
+            Some(vec![PathBuf::from("a.rs"), PathBuf::from("b.rs")])
+        );
+
+        tx.send(PathBuf::from("c.rs")).unwrap();
+        drop(tx);
+        assert_eq!(
+            next_batch(&mut rx, Duration::from_millis(20)).await,
+            Some(vec![PathBuf::from("c.rs")])
+        );
+        assert_eq!(next_batch(&mut rx, Duration::from_millis(20)).await, None);
+    }
+}
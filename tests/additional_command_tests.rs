@@ -373,6 +373,12 @@ async fn test_availability_report() {
     assert!(report.contains("clippy"));
     assert!(report.contains("nextest"));
     assert!(report.contains("cargo-audit"));
+    assert!(report.contains("cargo-llvm-cov"));
+    assert!(report.contains("cargo-expand"));
+    assert!(report.contains("cargo-deny"));
+    assert!(report.contains("cargo-semver-checks"));
+    assert!(report.contains("cargo-machete"));
+    assert!(report.contains("cargo-udeps"));
     assert!(report.contains("Recommendations"));
 }
 
@@ -383,11 +389,17 @@ async fn test_component_availability() {
 
     println!("Component availability: {availability:?}");
 
-    // Should check for cargo (always true), clippy, nextest, and cargo-audit
+    // Should check for cargo (always true), clippy, nextest, cargo-audit, cargo-llvm-cov, cargo-expand, cargo-deny, cargo-semver-checks, cargo-machete and cargo-udeps
     assert!(availability.contains_key("cargo"));
     assert!(availability.contains_key("clippy"));
     assert!(availability.contains_key("nextest"));
     assert!(availability.contains_key("cargo-audit"));
+    assert!(availability.contains_key("cargo-llvm-cov"));
+    assert!(availability.contains_key("cargo-expand"));
+    assert!(availability.contains_key("cargo-deny"));
+    assert!(availability.contains_key("cargo-semver-checks"));
+    assert!(availability.contains_key("cargo-machete"));
+    assert!(availability.contains_key("cargo-udeps"));
 
     // Cargo should always be available if we got this far
     assert_eq!(availability.get("cargo"), Some(&true));
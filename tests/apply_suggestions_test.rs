@@ -0,0 +1,96 @@
+//! apply_suggestions applies rustc's machine-applicable fixes from a check run; undo restores them
+use anyhow::Result;
+mod common;
+use common::test_project::create_project_with_warning;
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn test_apply_and_undo_suggestions() -> Result<()> {
+    let temp = create_project_with_warning().await?;
+    let project_path = temp.path().to_str().unwrap();
+    let main_rs = temp.path().join("src/main.rs");
+    let original = std::fs::read_to_string(&main_rs)?;
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"]);
+            },
+        ))?)
+        .await?;
+
+    let check = client
+        .call_tool(CallToolRequestParam {
+            name: "check".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let check_text = check
+        .content
+        .first()
+        .and_then(|c| c.as_text())
+        .map(|t| t.text.clone())
+        .unwrap_or_default();
+    let operation_id = check_text
+        .split("apply_suggestions with operation_id=\"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .expect("check result should point at apply_suggestions")
+        .to_string();
+
+    let preview = client
+        .call_tool(CallToolRequestParam {
+            name: "apply_suggestions".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "operation_id": operation_id.clone(),
+                "lints": ["unused_variables"],
+                "dry_run": true
+            })),
+        })
+        .await?;
+    let preview_text = format!("{:?}", preview.content);
+    assert!(
+        preview_text.contains("Dry run")
+            && preview_text.contains("+    let _unused_variable = 42;"),
+        "Expected a diff preview: {preview_text}"
+    );
+    assert_eq!(std::fs::read_to_string(&main_rs)?, original);
+
+    let applied = client
+        .call_tool(CallToolRequestParam {
+            name: "apply_suggestions".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "operation_id": operation_id
+            })),
+        })
+        .await?;
+    let applied_text = format!("{:?}", applied.content);
+    assert!(
+        applied_text.contains("Applied 1 suggestion(s) in 1 file(s)"),
+        "Expected suggestions to be applied: {applied_text}"
+    );
+    assert!(std::fs::read_to_string(&main_rs)?.contains("let _unused_variable = 42;"));
+
+    let undo = client
+        .call_tool(CallToolRequestParam {
+            name: "undo_suggestions".into(),
+            arguments: Some(object!({})),
+        })
+        .await?;
+    let undo_text = format!("{:?}", undo.content);
+    assert!(undo_text.contains("Reverted"), "Expected undo: {undo_text}");
+    assert_eq!(std::fs::read_to_string(&main_rs)?, original);
+
+    let _ = client.cancel().await;
+    Ok(())
+}
@@ -0,0 +1,154 @@
+//! audit turns a recorded `cargo audit --json` report into structured advisories with the path
+//! that pulls each affected crate into the project and the smallest fix
+use anyhow::Result;
+mod common;
+use common::fake_tool::{fake_cargo_subcommand, fixture, path_with};
+use common::test_project::create_basic_project;
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn test_audit_reports_recorded_advisories_with_dependency_paths() -> Result<()> {
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+
+    // Path crates stand in for the advisories' packages so cargo tree can find them offline:
+    // test_project -> chrono 0.4.19 -> time 0.1.45, and test_project -> atty 0.2.14
+    let deps = tempfile::tempdir()?;
+    for (name, version, dependencies) in [
+        ("time", "0.1.45", String::new()),
+        (
+            "chrono",
+            "0.4.19",
+            format!("time = {{ path = {:?} }}\n", deps.path().join("time")),
+        ),
+        ("atty", "0.2.14", String::new()),
+    ] {
+        let dir = deps.path().join(name);
+        tokio::fs::create_dir_all(dir.join("src")).await?;
+        tokio::fs::write(
+            dir.join("Cargo.toml"),
+            format!(
+                "[package]\nname = \"{name}\"\nversion = \"{version}\"\nedition = \"2021\"\n\n[dependencies]\n{dependencies}"
+            ),
+        )
+        .await?;
+        tokio::fs::write(dir.join("src/lib.rs"), "").await?;
+    }
+    let manifest = tokio::fs::read_to_string(temp.path().join("Cargo.toml")).await?;
+    let manifest = manifest.replace("[dependencies]\n", "");
+    tokio::fs::write(
+        temp.path().join("Cargo.toml"),
+        format!(
+            "{manifest}\n[dependencies]\nchrono = {{ path = {:?} }}\natty = {{ path = {:?} }}\n",
+            deps.path().join("chrono"),
+            deps.path().join("atty"),
+        ),
+    )
+    .await?;
+
+    let fake = fake_cargo_subcommand(
+        "audit",
+        &format!(
+            r#"if [ "$2" = "--version" ]; then echo "cargo-audit 0.21.2"; exit 0; fi
+shift
+echo "$@" >> "{args}"
+echo "    Fetching advisory database from \`https://github.com/RustSec/advisory-db.git\`" >&2
+cat "{report}"
+exit 1
+"#,
+            args = temp.path().join("audit-args").display(),
+            report = fixture("audit_report.json").display(),
+        ),
+    )?;
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"])
+                    .env("PATH", path_with(fake.path()));
+            },
+        ))?)
+        .await?;
+
+    let text_of = |result: &rmcp::model::CallToolResult| {
+        result
+            .content
+            .first()
+            .and_then(|c| c.as_text())
+            .map(|t| t.text.clone())
+            .unwrap_or_default()
+    };
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "audit".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = text_of(&result);
+    assert!(text.contains("found security vulnerabilities"), "{text}");
+    assert!(
+        text.contains(
+            "Audit: 1 vulnerability (1 medium), 1 warning(s) in 4 dependencies\n  RUSTSEC-2020-0071 time 0.1.45: Potential segfault in the time crate [medium 5.9, CVE-2020-26235]\n    path: test_project 0.1.0 -> chrono 0.4.19 -> time 0.1.45\n    fix: time comes in through chrono 0.4.19; upgrade chrono 0.4.19 to a release that depends on time 0.2.23 or later"
+        ),
+        "{text}"
+    );
+    assert!(
+        text.contains(
+            "  RUSTSEC-2024-0375 atty 0.2.14 (unmaintained): `atty` is unmaintained\n    path: test_project 0.1.0 -> atty 0.2.14\n    fix: atty is unmaintained; consider an alternative crate"
+        ),
+        "{text}"
+    );
+
+    let structured = result
+        .structured_content
+        .expect("audit should return structuredContent");
+    let audit = &structured["audit"];
+    assert_eq!(audit["vulnerabilities"], 1, "{structured}");
+    assert_eq!(audit["warnings"], 1, "{structured}");
+    let time = &audit["advisories"][0];
+    assert_eq!(time["id"], "RUSTSEC-2020-0071");
+    assert_eq!(time["severity"], "medium");
+    assert_eq!(time["cvss_score"], 5.9);
+    assert_eq!(time["patched"][0], ">=0.2.23");
+    assert_eq!(
+        time["dependency_path"],
+        serde_json::json!(["test_project 0.1.0", "chrono 0.4.19", "time 0.1.45"])
+    );
+    assert_eq!(audit["advisories"][1]["kind"], "unmaintained");
+
+    // Vulnerabilities below min_severity are counted but not listed; warnings are kept
+    let text = text_of(
+        &client
+            .call_tool(CallToolRequestParam {
+                name: "audit".into(),
+                arguments: Some(object!({
+                    "working_directory": project_path,
+                    "min_severity": "high",
+                    "enable_async_notification": false
+                })),
+            })
+            .await?,
+    );
+    assert!(
+        text.contains("completed successfully")
+            && text.contains(
+                "Audit: 0 vulnerabilities, 1 warning(s) in 4 dependencies, 1 below min_severity not shown\n  RUSTSEC-2024-0375 atty 0.2.14 (unmaintained)"
+            ),
+        "{text}"
+    );
+
+    let args = tokio::fs::read_to_string(temp.path().join("audit-args")).await?;
+    assert_eq!(args, "--json\n--json\n");
+
+    let _ = client.cancel().await;
+    Ok(())
+}
@@ -0,0 +1,69 @@
+//! build and check with timings: true report where the compile time went
+use anyhow::Result;
+mod common;
+use common::test_project::create_basic_project;
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn test_build_and_check_timings() -> Result<()> {
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"]);
+            },
+        ))?)
+        .await?;
+
+    let text_of = |result: &rmcp::model::CallToolResult| {
+        result
+            .content
+            .first()
+            .and_then(|c| c.as_text())
+            .map(|t| t.text.clone())
+            .unwrap_or_default()
+    };
+
+    for tool in ["build", "check"] {
+        let result = client
+            .call_tool(CallToolRequestParam {
+                name: tool.into(),
+                arguments: Some(object!({
+                    "working_directory": project_path,
+                    "timings": true,
+                    "enable_async_notification": false
+                })),
+            })
+            .await?;
+        let text = text_of(&result);
+        assert!(
+            text.contains("Timings: ")
+                && text.contains("Slowest units:")
+                && text.contains("Critical path: test_project v0.1.0"),
+            "Expected a timing summary from {tool}: {text}"
+        );
+
+        let structured = result
+            .structured_content
+            .expect("a timed run should return structuredContent");
+        let timings = &structured["timings"];
+        assert!(timings["units"].as_u64().unwrap_or(0) >= 1, "{structured}");
+        let html = timings["html_path"]
+            .as_str()
+            .expect("the HTML report path should be included");
+        assert!(
+            std::path::Path::new(html).is_file(),
+            "{tool} timing report should exist at {html}"
+        );
+    }
+
+    let _ = client.cancel().await;
+    Ok(())
+}
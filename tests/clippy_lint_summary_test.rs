@@ -0,0 +1,107 @@
+//! clippy results summarize lints and propose a [lints] table or a fix order
+use anyhow::Result;
+mod common;
+use common::test_project::create_basic_project;
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+const NOISY_MAIN: &str = r#"fn one() -> i32 {
+    return 1;
+}
+
+fn two() -> i32 {
+    return 2;
+}
+
+fn three() -> i32 {
+    return 3;
+}
+
+fn main() {
+    let x = one() + two() + three();
+    if x == x {
+        println!("{x}");
+    }
+}
+"#;
+
+#[tokio::test]
+async fn test_clippy_lint_summary_and_advice() -> Result<()> {
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+    std::fs::write(temp.path().join("src/main.rs"), NOISY_MAIN)?;
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"]);
+            },
+        ))?)
+        .await?;
+
+    let text_of = |result: &rmcp::model::CallToolResult| {
+        result
+            .content
+            .first()
+            .and_then(|c| c.as_text())
+            .map(|t| t.text.clone())
+            .unwrap_or_default()
+    };
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "clippy".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "lint_advice": "lints_table",
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = text_of(&result);
+    assert!(
+        text.contains("Lint summary: ")
+            && text.contains("3  clippy::needless_return [warning (default warn, style)]"),
+        "Expected a lint summary: {text}"
+    );
+    assert!(
+        text.contains("[lints.clippy]\nneedless_return = \"allow\""),
+        "Expected a proposed [lints.clippy] table: {text}"
+    );
+    let structured = result
+        .structured_content
+        .expect("clippy should return structuredContent");
+    let lints = structured["lints"]["lints"].as_array().unwrap();
+    assert_eq!(lints[0]["lint"], "clippy::needless_return", "{structured}");
+    assert_eq!(lints[0]["count"], 3, "{structured}");
+    assert!(
+        !structured["lints"]["lints_table"]
+            .as_str()
+            .unwrap()
+            .contains("eq_op"),
+        "correctness lints should never be proposed for allowing: {structured}"
+    );
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "clippy".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "lint_advice": "fix_priority",
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = text_of(&result);
+    assert!(
+        text.contains("Fix priority:\n  1. clippy::eq_op (1): likely a bug"),
+        "Expected correctness lints first: {text}"
+    );
+
+    let _ = client.cancel().await;
+    Ok(())
+}
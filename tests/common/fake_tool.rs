@@ -0,0 +1,44 @@
+#![allow(dead_code)]
+
+//! Stand-ins for cargo subcommands that may not be installed where the tests run
+
+use anyhow::Result;
+use std::ffi::OsString;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Create a directory holding an executable `cargo-{name}` shell script with `body` after the
+/// shebang. cargo runs it as `cargo-{name} {name} <args>`, so `$2` is the first argument the
+/// tool sees. Put the directory on `PATH` with [`path_with`]; cargo searches `PATH` in order
+/// whenever it contains `~/.cargo/bin`, so the script shadows an installed tool.
+pub fn fake_cargo_subcommand(name: &str, body: &str) -> Result<TempDir> {
+    let dir = tempfile::Builder::new()
+        .prefix(&format!("fake_cargo_{name}_"))
+        .tempdir()?;
+    add_cargo_subcommand(dir.path(), name, body)?;
+    Ok(dir)
+}
+
+/// Add another `cargo-{name}` script to a directory from [`fake_cargo_subcommand`]
+pub fn add_cargo_subcommand(dir: &Path, name: &str, body: &str) -> Result<()> {
+    let script = dir.join(format!("cargo-{name}"));
+    std::fs::write(&script, format!("#!/bin/sh\n{body}"))?;
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+/// `PATH` with `dir` searched first
+pub fn path_with(dir: &Path) -> OsString {
+    let current = std::env::var_os("PATH").unwrap_or_default();
+    std::env::join_paths(std::iter::once(dir.to_path_buf()).chain(std::env::split_paths(&current)))
+        .expect("PATH entries contain no separator")
+}
+
+/// A recorded tool output under `tests/fixtures`
+pub fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
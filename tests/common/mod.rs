@@ -1,3 +1,4 @@
+pub mod fake_tool;
 pub mod test_project;
 
 /// Strip ANSI escape sequences so tests are robust across local vs CI where
@@ -18,6 +18,8 @@ pub struct TestProjectOptions {
     pub with_binary_args_example: bool,
     /// Create a library with unit tests and an integration test.
     pub with_integration_tests: bool,
+    /// Unit tests in main: one passing, one failing (printing first) and one ignored.
+    pub with_failing_test: bool,
 }
 
 /// Create a temporary Cargo project with flexible content.
@@ -106,7 +108,10 @@ mod tests {
             )?;
         } else {
             // Binary-only variants: prepare main.rs content
-            let main_content = if opts.with_formatting_issues {
+            let main_content = if opts.with_failing_test {
+                // the assertion in `fails` is at src/main.rs:11:9
+                "fn main() {}\n\n#[cfg(test)]\nmod tests {\n    #[test]\n    fn passes() {}\n\n    #[test]\n    fn fails() {\n        println!(\"about to fail\");\n        assert_eq!(1 + 1, 3, \"math is broken\");\n    }\n\n    #[test]\n    #[ignore = \"slow\"]\n    fn slow() {}\n}\n".to_string()
+            } else if opts.with_formatting_issues {
                 // poor formatting on purpose
                 "fn main(){\nlet x=42;\n    let y =   43  ;\n        println!(\"Hello, world! {} {}\",x,y);\n}\n\n#[cfg(test)]\nmod tests {\n    #[test]\nfn it_works(  ) {\n        let result = 2+ 2;\n            assert_eq!( result,4 );\n    }\n}\n".to_string()
             } else if opts.with_warning {
@@ -211,6 +216,14 @@ pub async fn create_project_with_integration_tests() -> Result<TempDir> {
     .await
 }
 
+pub async fn create_project_with_failing_test() -> Result<TempDir> {
+    create_test_cargo_project(TestProjectOptions {
+        with_failing_test: true,
+        ..Default::default()
+    })
+    .await
+}
+
 /// Create a simple multi-package workspace suitable for version bump tests
 pub async fn create_workspace_project() -> Result<TempDir> {
     // TempDir creation in blocking thread
@@ -0,0 +1,166 @@
+//! coverage runs cargo llvm-cov when installed and answers uncovered-line queries
+use anyhow::Result;
+mod common;
+use common::test_project::create_basic_project;
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn test_coverage_reports_files_or_missing_tool() -> Result<()> {
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"]);
+            },
+        ))?)
+        .await?;
+
+    let text_of = |result: &rmcp::model::CallToolResult| {
+        result
+            .content
+            .first()
+            .and_then(|c| c.as_text())
+            .map(|t| t.text.clone())
+            .unwrap_or_default()
+    };
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "coverage".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "file": "src/main.rs",
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = text_of(&result);
+    if text.contains("cargo-llvm-cov is not installed") {
+        assert!(text.starts_with("- Coverage operation"), "{text}");
+    } else {
+        assert!(
+            text.contains("Coverage: lines ") && text.contains("Uncovered lines in src/main.rs"),
+            "Expected a coverage summary and an uncovered-lines answer: {text}"
+        );
+        let structured = result
+            .structured_content
+            .expect("coverage should return structuredContent");
+        assert!(
+            structured["coverage"]["files"].as_array().is_some(),
+            "{structured}"
+        );
+    }
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "coverage".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "operation_id": "op_coverage_unknown",
+                "file": "src/main.rs"
+            })),
+        })
+        .await?;
+    assert_eq!(
+        text_of(&result),
+        "- No coverage report recorded for operation op_coverage_unknown"
+    );
+
+    let _ = client.cancel().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_coverage_parses_llvm_cov_export() -> Result<()> {
+    use common::fake_tool::{fake_cargo_subcommand, fixture, path_with};
+
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+    // Writes the recorded export to --output-path with its paths moved into the working directory
+    let fake = fake_cargo_subcommand(
+        "llvm-cov",
+        &format!(
+            r#"if [ "$2" = "--version" ]; then echo "cargo-llvm-cov 0.6.16"; exit 0; fi
+out=""; prev=""
+for arg in "$@"; do
+  if [ "$prev" = "--output-path" ]; then out="$arg"; fi
+  prev="$arg"
+done
+echo "$@" > "{args}"
+sed "s#@DIR@#$(pwd -P)#g" "{export}" > "$out"
+echo "     Running unittests src/main.rs (target/llvm-cov-target/debug/deps/test_project-1)" >&2
+"#,
+            args = temp.path().join("llvm-cov-args").display(),
+            export = fixture("llvm_cov_export.json").display(),
+        ),
+    )?;
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"])
+                    .env("PATH", path_with(fake.path()));
+            },
+        ))?)
+        .await?;
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "coverage".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "file": "main.rs",
+                "test_name": "it_works",
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = result
+        .content
+        .first()
+        .and_then(|c| c.as_text())
+        .map(|t| t.text.clone())
+        .unwrap_or_default();
+    assert!(
+        text.contains(
+            "Coverage: lines 40.0% (4/10), regions 33.3% (1/3), functions 33.3% (1/3) over 1 file(s)"
+        ) && text.contains("src/main.rs (uncovered: 1-3)"),
+        "{text}"
+    );
+    // Registry sources are outside the working directory and left out
+    assert!(!text.contains("itoa"), "{text}");
+    assert!(
+        text.contains("Uncovered lines in src/main.rs (57.1% (4/7) of lines covered): 1-3"),
+        "{text}"
+    );
+
+    let args = tokio::fs::read_to_string(temp.path().join("llvm-cov-args")).await?;
+    assert!(
+        args.starts_with("llvm-cov --json --output-path ")
+            && args.trim_end().ends_with("-- it_works"),
+        "{args}"
+    );
+
+    let structured = result
+        .structured_content
+        .expect("coverage should return structuredContent");
+    let files = structured["coverage"]["files"]
+        .as_array()
+        .expect("coverage files");
+    assert_eq!(files.len(), 1, "{structured}");
+    assert_eq!(files[0]["file"], "src/main.rs", "{structured}");
+    assert_eq!(
+        files[0]["uncovered_lines"],
+        serde_json::json!([[1, 3]]),
+        "{structured}"
+    );
+
+    let _ = client.cancel().await;
+    Ok(())
+}
@@ -0,0 +1,194 @@
+//! deny scaffolds a deny.toml and checks license policy when cargo-deny is installed
+use anyhow::Result;
+mod common;
+use common::test_project::create_basic_project;
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn test_deny_init_and_check_or_missing_tool() -> Result<()> {
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"]);
+            },
+        ))?)
+        .await?;
+
+    let text_of = |result: &rmcp::model::CallToolResult| {
+        result
+            .content
+            .first()
+            .and_then(|c| c.as_text())
+            .map(|t| t.text.clone())
+            .unwrap_or_default()
+    };
+
+    let init = text_of(
+        &client
+            .call_tool(CallToolRequestParam {
+                name: "deny".into(),
+                arguments: Some(object!({
+                    "working_directory": project_path,
+                    "init": true
+                })),
+            })
+            .await?,
+    );
+    if init.contains("cargo-deny is not installed") {
+        assert!(init.starts_with("- Deny operation"), "{init}");
+        let _ = client.cancel().await;
+        return Ok(());
+    }
+    assert!(init.starts_with("+ Deny init"), "{init}");
+    assert!(temp.path().join("deny.toml").exists());
+
+    // The project has no dependencies, so only its own license can be checked
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "deny".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "checks": ["licenses", "bans", "sources"],
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = text_of(&result);
+    assert!(
+        text.contains("Deny:") && text.contains("from bans, licenses, sources"),
+        "{text}"
+    );
+    let structured = result
+        .structured_content
+        .expect("deny should return structuredContent");
+    assert!(
+        structured["deny"]["violations"].as_array().is_some(),
+        "{structured}"
+    );
+
+    let _ = client.cancel().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deny_init_and_policy_violations_from_recorded_output() -> Result<()> {
+    use common::fake_tool::{fake_cargo_subcommand, fixture, path_with};
+
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+    // `init` writes a config; `check` prints the recorded diagnostics to stderr as cargo-deny does
+    let fake = fake_cargo_subcommand(
+        "deny",
+        &format!(
+            r#"case "$2" in
+  --version) echo "cargo-deny 0.18.3"; exit 0 ;;
+  init) printf '[licenses]\nallow = ["MIT"]\n' > deny.toml; exit 0 ;;
+esac
+shift
+echo "$@" > "{args}"
+cat "{output}" >&2
+exit 1
+"#,
+            args = temp.path().join("deny-args").display(),
+            output = fixture("deny_check.jsonl").display(),
+        ),
+    )?;
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"])
+                    .env("PATH", path_with(fake.path()));
+            },
+        ))?)
+        .await?;
+
+    let text_of = |result: &rmcp::model::CallToolResult| {
+        result
+            .content
+            .first()
+            .and_then(|c| c.as_text())
+            .map(|t| t.text.clone())
+            .unwrap_or_default()
+    };
+    let init = || {
+        client.call_tool(CallToolRequestParam {
+            name: "deny".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "init": true
+            })),
+        })
+    };
+
+    let text = text_of(&init().await?);
+    assert!(
+        text.starts_with("+ Deny init") && text.contains("deny.toml"),
+        "{text}"
+    );
+    let config = tokio::fs::read_to_string(temp.path().join("deny.toml")).await?;
+    assert!(config.contains("[licenses]"), "{config}");
+
+    // A second init leaves the edited config alone
+    let text = text_of(&init().await?);
+    assert!(
+        text.starts_with("- Deny init") && text.contains("already exists"),
+        "{text}"
+    );
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "deny".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "checks": ["licenses", "bans"],
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = text_of(&result);
+    assert!(
+        text.starts_with("- Deny operation #") && text.contains("found policy violations"),
+        "{text}"
+    );
+    assert!(
+        text.contains("Deny: 1 error(s), 1 warning(s) from bans, licenses (bans: 0 error(s), 1 warning(s); licenses: 1 error(s), 0 warning(s))"),
+        "{text}"
+    );
+    assert!(
+        text.contains(
+            "- error[licenses/rejected] failed to satisfy license requirements: gpl-crate 1.2.0\n    path: test_project 0.1.0 -> middle 0.3.1 -> gpl-crate 1.2.0"
+        ),
+        "{text}"
+    );
+    assert!(!text.contains("license requirements satisfied"), "{text}");
+
+    let structured = result
+        .structured_content
+        .expect("deny should return structuredContent");
+    let violations = structured["deny"]["violations"]
+        .as_array()
+        .expect("violations should be an array");
+    assert_eq!(violations.len(), 2, "{structured}");
+    assert_eq!(violations[0]["check"], "licenses");
+    assert_eq!(violations[0]["krate"], "gpl-crate 1.2.0");
+    assert_eq!(violations[1]["check"], "bans");
+    assert_eq!(violations[1]["code"], "duplicate");
+    assert_eq!(violations[1]["krate"], "syn 1.0.109");
+
+    let args = tokio::fs::read_to_string(temp.path().join("deny-args")).await?;
+    assert_eq!(
+        args.trim(),
+        "--format json --color never check bans licenses"
+    );
+
+    let _ = client.cancel().await;
+    Ok(())
+}
@@ -0,0 +1,107 @@
+//! detect_flaky flags tests that both pass and fail, and later test results mark them
+use anyhow::Result;
+mod common;
+use common::test_project::create_basic_project;
+use rmcp::{
+    ServiceExt,
+    model::{CallToolRequestParam, CallToolResult},
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+/// Fails on every other run, counted in a file next to Cargo.toml
+const MAIN_WITH_ALTERNATING_TEST: &str = r#"fn main() {}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn steady() {}
+
+    #[test]
+    fn alternating() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/runs.txt");
+        let runs: u32 = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(0);
+        std::fs::write(path, (runs + 1).to_string()).unwrap();
+        assert!(runs % 2 == 0, "odd run");
+    }
+}
+"#;
+
+fn text_of(result: &CallToolResult) -> String {
+    result
+        .content
+        .first()
+        .and_then(|c| c.as_text())
+        .map(|t| t.text.clone())
+        .unwrap_or_default()
+}
+
+#[tokio::test]
+async fn test_detect_flaky_flags_alternating_test() -> Result<()> {
+    let temp = create_basic_project().await?;
+    std::fs::write(temp.path().join("src/main.rs"), MAIN_WITH_ALTERNATING_TEST)?;
+    let project_path = temp.path().to_str().unwrap();
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"]);
+            },
+        ))?)
+        .await?;
+
+    // Sequential runs 0, 1, 2: pass, fail, pass
+    let detect = client
+        .call_tool(CallToolRequestParam {
+            name: "detect_flaky".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "runs": 3,
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = text_of(&detect);
+    assert!(
+        text.contains(
+            "2 test(s) over 3 sequential run(s): 1 flaky, 0 always failing, 1 always passing"
+        ) && text.contains("FLAKY tests::alternating (unittests src/main.rs): passed 2/3 (67%)")
+            && text.contains("1x at src/main.rs:16:9: odd run"),
+        "Expected the alternating test to be flagged: {text}"
+    );
+    let flaky_id = text
+        .split_whitespace()
+        .find(|w| w.starts_with("op_detect_flaky_"))
+        .expect("result should name the operation")
+        .to_string();
+    let structured = detect
+        .structured_content
+        .expect("detect_flaky should return structuredContent");
+    assert_eq!(
+        structured["flaky"]["tests"][0]["name"],
+        "tests::alternating"
+    );
+    assert_eq!(structured["flaky"]["tests"][0]["flaky"], true);
+
+    // Run 3 fails again and is now marked as known-flaky
+    let test = client
+        .call_tool(CallToolRequestParam {
+            name: "test".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = text_of(&test);
+    assert!(
+        text.contains(&format!("[known flaky: passed 2/3 in {flaky_id}]")),
+        "Expected the failure to be annotated as known-flaky: {text}"
+    );
+
+    let _ = client.cancel().await;
+    Ok(())
+}
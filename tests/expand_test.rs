@@ -0,0 +1,222 @@
+//! expand shows macro-expanded source when cargo-expand is installed
+use anyhow::Result;
+mod common;
+use common::test_project::create_basic_project;
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn test_expand_item_or_missing_tool() -> Result<()> {
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+    tokio::fs::write(
+        temp.path().join("src/main.rs"),
+        "#[derive(Debug)]\nstruct Token;\n\nfn main() {\n    println!(\"{:?}\", Token);\n}\n",
+    )
+    .await?;
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"]);
+            },
+        ))?)
+        .await?;
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "expand".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "item": "main",
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = result
+        .content
+        .first()
+        .and_then(|c| c.as_text())
+        .map(|t| t.text.clone())
+        .unwrap_or_default();
+    if text.contains("cargo-expand is not installed") {
+        assert!(text.starts_with("- Expand operation"), "{text}");
+    } else {
+        assert!(
+            text.contains("completed successfully") && text.contains("fn main()"),
+            "Expected the expanded main function: {text}"
+        );
+        assert!(
+            !text.contains("println!"),
+            "println! should be expanded: {text}"
+        );
+    }
+
+    let _ = client.cancel().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_expand_filters_items_and_outlines_large_output() -> Result<()> {
+    use common::fake_tool::{fake_cargo_subcommand, path_with};
+
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+    // The last argument is the item path, if any; a whole-crate expansion is 462 lines long
+    let fake = fake_cargo_subcommand(
+        "expand",
+        &format!(
+            r##"if [ "$2" = "--version" ]; then echo "cargo-expand 1.0.100"; exit 0; fi
+echo "$@" >> "{args}"
+for item in "$@"; do :; done
+case "$item" in
+parser::Token)
+  echo "pub struct Token {{"
+  echo "    pub f1: u32,"
+  echo "}}"
+  ;;
+missing)
+  echo "WARNING: no such item: missing" >&2
+  ;;
+*)
+  echo "#![feature(prelude_import)]"
+  echo "#[prelude_import]"
+  echo "use std::prelude::rust_2024::*;"
+  echo "#[macro_use]"
+  echo "extern crate std;"
+  echo "mod parser {{"
+  echo "    pub struct Token {{"
+  i=1
+  while [ $i -le 450 ]; do echo "        pub f$i: u32,"; i=$((i + 1)); done
+  echo "    }}"
+  echo "}}"
+  echo "fn main() {{"
+  echo "    let _ = 1;"
+  echo "}}"
+  ;;
+esac
+"##,
+            args = temp.path().join("expand-args").display(),
+        ),
+    )?;
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"])
+                    .env("PATH", path_with(fake.path()));
+            },
+        ))?)
+        .await?;
+    let call = |name: &'static str, arguments: rmcp::model::JsonObject| {
+        client.call_tool(CallToolRequestParam {
+            name: name.into(),
+            arguments: Some(arguments),
+        })
+    };
+    let text_of = |result: &rmcp::model::CallToolResult| {
+        result
+            .content
+            .first()
+            .and_then(|c| c.as_text())
+            .map(|t| t.text.clone())
+            .unwrap_or_default()
+    };
+
+    let text = text_of(
+        &call(
+            "expand",
+            object!({
+                "working_directory": project_path,
+                "item": "parser::Token",
+                "enable_async_notification": false
+            }),
+        )
+        .await?,
+    );
+    assert!(
+        text.contains("completed successfully")
+            && text.contains("for item 'parser::Token'")
+            && text.contains("pub struct Token {\n    pub f1: u32,\n}"),
+        "{text}"
+    );
+
+    let text = text_of(
+        &call(
+            "expand",
+            object!({
+                "working_directory": project_path,
+                "item": "missing",
+                "enable_async_notification": false
+            }),
+        )
+        .await?,
+    );
+    assert!(
+        text.starts_with("- Expand operation #")
+            && text.contains("found nothing")
+            && text.contains("for item 'missing'")
+            && text.contains("WARNING: no such item: missing"),
+        "{text}"
+    );
+
+    let text = text_of(
+        &call(
+            "expand",
+            object!({
+                "working_directory": project_path,
+                "enable_async_notification": false
+            }),
+        )
+        .await?,
+    );
+    assert!(
+        text.contains("Expanded source has 462 lines; outline of its 3 items")
+            && text.contains("  mod parser (lines 6-459)")
+            && text.contains("  struct parser::Token (lines 7-458)")
+            && text.contains("  fn main (lines 460-462)"),
+        "{text}"
+    );
+    assert!(!text.contains("pub f1: u32"), "{text}");
+
+    // The outline's line ranges index the saved source
+    let operation_id = text
+        .split('#')
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .expect("operation id in the expand result")
+        .to_string();
+    let lines = text_of(
+        &call(
+            "get_output",
+            object!({
+                "operation_id": operation_id,
+                "start_line": 458,
+                "end_line": 460
+            }),
+        )
+        .await?,
+    );
+    assert!(
+        lines.contains("   458:     }")
+            && lines.contains("   459: }")
+            && lines.contains("   460: fn main() {"),
+        "{lines}"
+    );
+
+    let args = tokio::fs::read_to_string(temp.path().join("expand-args")).await?;
+    assert_eq!(
+        args.lines().collect::<Vec<_>>(),
+        vec![
+            "expand --color never parser::Token",
+            "expand --color never missing",
+            "expand --color never",
+        ]
+    );
+
+    let _ = client.cancel().await;
+    Ok(())
+}
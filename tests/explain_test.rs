@@ -0,0 +1,91 @@
+//! Results carry an excerpt of rustc's explanation per error code; explain returns the full text
+use anyhow::Result;
+mod common;
+use common::test_project::create_basic_project;
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn test_error_code_excerpts_and_explain_tool() -> Result<()> {
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+    tokio::fs::write(
+        temp.path().join("src/main.rs"),
+        "fn main() {\n    let a: u32 = \"one\";\n    let b: u32 = \"two\";\n    println!(\"{a} {b}\");\n}\n",
+    )
+    .await?;
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"]);
+            },
+        ))?)
+        .await?;
+
+    let text_of = |result: &rmcp::model::CallToolResult| {
+        result
+            .content
+            .first()
+            .and_then(|c| c.as_text())
+            .map(|t| t.text.clone())
+            .unwrap_or_default()
+    };
+
+    let checked = client
+        .call_tool(CallToolRequestParam {
+            name: "check".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = text_of(&checked);
+    assert_eq!(
+        text.matches("Error code E0308: Expected type did not match the received type.")
+            .count(),
+        1,
+        "Expected one excerpt for the repeated error code: {text}"
+    );
+    let structured = checked
+        .structured_content
+        .expect("check should return structuredContent");
+    assert_eq!(
+        structured["explanations"]["E0308"],
+        "Expected type did not match the received type."
+    );
+
+    let explained = client
+        .call_tool(CallToolRequestParam {
+            name: "explain".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "code": "e0502"
+            })),
+        })
+        .await?;
+    let text = text_of(&explained);
+    assert!(
+        text.starts_with("E0502:") && text.contains("borrowed again with a different mutability"),
+        "Expected the full explanation: {text}"
+    );
+
+    let invalid = client
+        .call_tool(CallToolRequestParam {
+            name: "explain".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "code": "unused_variables"
+            })),
+        })
+        .await;
+    assert!(invalid.is_err(), "A lint name is not an error code");
+
+    let _ = client.cancel().await;
+    Ok(())
+}
@@ -0,0 +1,102 @@
+//! Oversized results are truncated to head/tail and the full log is readable through get_output
+use anyhow::Result;
+mod common;
+use common::test_project::create_basic_project;
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn test_large_output_is_spilled_and_paged() -> Result<()> {
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args([
+                    "run",
+                    "--bin",
+                    "async_cargo_mcp",
+                    "--",
+                    "--max-inline-output-kib",
+                    "1",
+                ]);
+            },
+        ))?)
+        .await?;
+
+    // Metadata JSON for even a basic project is larger than 1 KiB
+    let metadata = client
+        .call_tool(CallToolRequestParam {
+            name: "metadata".into(),
+            arguments: Some(object!({ "working_directory": project_path })),
+        })
+        .await?;
+    let text = metadata
+        .content
+        .first()
+        .and_then(|c| c.as_text())
+        .map(|t| t.text.clone())
+        .unwrap_or_default();
+    assert!(
+        text.contains("[output truncated:"),
+        "Expected truncated output: {text}"
+    );
+    let operation_id = text
+        .split("operation_id=\"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .expect("truncation notice should name the operation")
+        .to_string();
+
+    let page = client
+        .call_tool(CallToolRequestParam {
+            name: "get_output".into(),
+            arguments: Some(object!({ "operation_id": operation_id.clone(), "limit": 200 })),
+        })
+        .await?;
+    let page_text = format!("{:?}", page.content);
+    assert!(
+        page_text.contains("bytes 0-200 of") && page_text.contains("offset=200"),
+        "Expected first page with continuation hint: {page_text}"
+    );
+
+    let grep = client
+        .call_tool(CallToolRequestParam {
+            name: "get_output".into(),
+            arguments: Some(object!({
+                "operation_id": operation_id.clone(),
+                "grep": "test_project",
+                "context": 0
+            })),
+        })
+        .await?;
+    let grep_text = format!("{:?}", grep.content);
+    assert!(
+        grep_text.contains("lines containing 'test_project'"),
+        "Expected grep matches: {grep_text}"
+    );
+
+    let lines = client
+        .call_tool(CallToolRequestParam {
+            name: "get_output".into(),
+            arguments: Some(object!({
+                "operation_id": operation_id,
+                "start_line": 1,
+                "end_line": 1
+            })),
+        })
+        .await?;
+    let lines_text = format!("{:?}", lines.content);
+    assert!(
+        lines_text.contains("(lines 1-1 of") && lines_text.contains("Metadata operation"),
+        "Expected the first line of the full log: {lines_text}"
+    );
+
+    let _ = client.cancel().await;
+    Ok(())
+}
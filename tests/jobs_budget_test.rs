@@ -0,0 +1,66 @@
+//! Cargo invocations must keep working when they share the server-wide jobserver (--jobs-budget)
+use anyhow::Result;
+mod common;
+use common::test_project::create_basic_project;
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn test_build_and_check_succeed_with_shared_jobserver() -> Result<()> {
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args([
+                    "run",
+                    "--bin",
+                    "async_cargo_mcp",
+                    "--",
+                    "--jobs-budget",
+                    "2",
+                ]);
+            },
+        ))?)
+        .await?;
+
+    // Requested jobs above the budget are capped rather than rejected
+    let build = client
+        .call_tool(CallToolRequestParam {
+            name: "build".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "jobs": 8,
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = format!("{:?}", build.content);
+    assert!(
+        text.contains("Build completed successfully"),
+        "Build should succeed with a shared jobserver: {text}"
+    );
+
+    let check = client
+        .call_tool(CallToolRequestParam {
+            name: "check".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = format!("{:?}", check.content);
+    assert!(
+        text.contains("Check operation completed successfully"),
+        "Check should succeed with a shared jobserver: {text}"
+    );
+
+    let _ = client.cancel().await;
+    Ok(())
+}
@@ -0,0 +1,88 @@
+//! list_tests enumerates tests without running them
+use anyhow::Result;
+mod common;
+use common::test_project::create_project_with_failing_test;
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn test_list_tests_reports_names_and_ignored() -> Result<()> {
+    let temp = create_project_with_failing_test().await?;
+    let project_path = temp.path().to_str().unwrap();
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"]);
+            },
+        ))?)
+        .await?;
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "list_tests".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = result
+        .content
+        .first()
+        .and_then(|c| c.as_text())
+        .map(|t| t.text.clone())
+        .unwrap_or_default();
+    assert!(
+        text.contains("3 test(s) in 1 binary (1 ignored)"),
+        "Expected three tests with one ignored: {text}"
+    );
+    assert!(
+        text.contains("  tests::passes")
+            && text.contains("  tests::fails")
+            && text.contains("  tests::slow (ignored)"),
+        "Expected every test by name: {text}"
+    );
+    assert!(
+        !text.contains("math is broken"),
+        "Listing must not run the tests: {text}"
+    );
+
+    let structured = result
+        .structured_content
+        .expect("listing should carry structured content");
+    let listed = structured["test_list"]
+        .as_array()
+        .expect("test_list should be an array");
+    assert_eq!(listed.len(), 3, "Unexpected listing: {structured}");
+
+    let filtered = client
+        .call_tool(CallToolRequestParam {
+            name: "list_tests".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "filter": "fail",
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let filtered_text = filtered
+        .content
+        .first()
+        .and_then(|c| c.as_text())
+        .map(|t| t.text.clone())
+        .unwrap_or_default();
+    assert!(
+        filtered_text.contains("1 test(s) in 1 binary (0 ignored), 1 of 3 match the filters")
+            && filtered_text.contains("  tests::fails")
+            && !filtered_text.contains("tests::passes"),
+        "Expected only the matching test: {filtered_text}"
+    );
+
+    let _ = client.cancel().await;
+    Ok(())
+}
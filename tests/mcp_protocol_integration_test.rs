@@ -98,11 +98,11 @@ async fn test_mcp_protocol_comprehensive() -> Result<()> {
     }
 
     // Verify we have exactly the expected number of tools (catches if new tools are added)
-    // We expect 28 total tools including new commands: fmt, tree, version, fetch, rustc, metadata, wait, sleep, cargo_lock_remediation, and bump_version
+    // We expect 43 total tools including new commands: fmt, tree, version, fetch, rustc, metadata, wait, sleep, cargo_lock_remediation, bump_version, get_output, apply_suggestions, undo_suggestions, rerun_failed, detect_flaky, list_tests, explain, coverage, expand, deny, semver_checks, unused_deps, watch_start, watch_status and watch_stop
     assert_eq!(
         tool_names.len(),
-        28,
-        "Expected exactly 28 tools, but found {}. Tools: {:?}",
+        43,
+        "Expected exactly 43 tools, but found {}. Tools: {:?}",
         tool_names.len(),
         tool_names
     );
@@ -1,3 +1,5 @@
+#![allow(clippy::duplicate_mod)]
+
 pub mod async_operations_test;
 pub mod bump_version_test;
 pub mod common;
@@ -0,0 +1,70 @@
+//! Requests can condense cargo output with `verbosity`; the full text stays available via get_output
+use anyhow::Result;
+mod common;
+use common::test_project::create_basic_project;
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn test_normal_verbosity_drops_progress_lines() -> Result<()> {
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"]);
+            },
+        ))?)
+        .await?;
+
+    let build = client
+        .call_tool(CallToolRequestParam {
+            name: "build".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "verbosity": "normal",
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = build
+        .content
+        .first()
+        .and_then(|c| c.as_text())
+        .map(|t| t.text.clone())
+        .unwrap_or_default();
+    assert!(
+        text.contains("Build completed successfully") && !text.contains("Compiling test_project"),
+        "Expected condensed build output: {text}"
+    );
+    assert!(
+        text.contains("[condensed to normal verbosity: elided"),
+        "Expected a condensation note: {text}"
+    );
+    let operation_id = text
+        .split("operation_id=\"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .expect("condensation note should name the operation")
+        .to_string();
+
+    let full = client
+        .call_tool(CallToolRequestParam {
+            name: "get_output".into(),
+            arguments: Some(object!({ "operation_id": operation_id, "grep": "Compiling" })),
+        })
+        .await?;
+    let full_text = format!("{:?}", full.content);
+    assert!(
+        full_text.contains("Compiling test_project"),
+        "Full output should keep progress lines: {full_text}"
+    );
+
+    let _ = client.cancel().await;
+    Ok(())
+}
@@ -0,0 +1,115 @@
+//! Failing tests and panicking programs report backtrace frames in the project as failure locations
+use anyhow::Result;
+mod common;
+use common::test_project::{create_basic_project, create_project_with_failing_test};
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn test_failure_locations_from_backtraces() -> Result<()> {
+    let temp = create_project_with_failing_test().await?;
+    let project_path = temp.path().to_str().unwrap();
+    let panicking = create_basic_project().await?;
+    let panicking_path = panicking.path().to_str().unwrap();
+    tokio::fs::write(
+        panicking.path().join("src/main.rs"),
+        "fn parse(input: &str) -> u32 {\n    input.parse().expect(\"not a number\")\n}\n\nfn main() {\n    println!(\"parsing\");\n    parse(\"x\");\n}\n",
+    )
+    .await?;
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"]);
+            },
+        ))?)
+        .await?;
+
+    let text_of = |result: &rmcp::model::CallToolResult| {
+        result
+            .content
+            .first()
+            .and_then(|c| c.as_text())
+            .map(|t| t.text.clone())
+            .unwrap_or_default()
+    };
+
+    let tested = client
+        .call_tool(CallToolRequestParam {
+            name: "test".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = text_of(&tested);
+    assert!(
+        text.contains("math is broken\n      at src/main.rs:11:9 in test_project::tests::fails"),
+        "Expected the test's frame under the failure: {text}"
+    );
+    let structured = tested
+        .structured_content
+        .expect("test should return structuredContent");
+    let failed = structured["tests"]["tests"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|t| t["status"] == "failed")
+        .expect("the failing test should be listed");
+    let first = &failed["failure_locations"][0];
+    assert_eq!(first["function"], "test_project::tests::fails");
+    assert_eq!(first["file"], "src/main.rs");
+    assert_eq!(first["line"], 11);
+    assert_eq!(first["in_workspace"], true);
+
+    let without = client
+        .call_tool(CallToolRequestParam {
+            name: "test".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "backtrace": false,
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let structured = without
+        .structured_content
+        .expect("test should return structuredContent");
+    assert!(
+        !structured.to_string().contains("failure_locations"),
+        "backtrace: false should leave out failure locations: {structured}"
+    );
+
+    let ran = client
+        .call_tool(CallToolRequestParam {
+            name: "run".into(),
+            arguments: Some(object!({
+                "working_directory": panicking_path,
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = text_of(&ran);
+    assert!(
+        text.contains("Panic: thread 'main' at src/main.rs:2:19: not a number")
+            && text.contains("  at src/main.rs:2:19 in test_project::parse")
+            && text.contains("  at src/main.rs:7:5 in test_project::main"),
+        "Expected the panic and its frames: {text}"
+    );
+    let structured = ran
+        .structured_content
+        .expect("a panicking run should return structuredContent");
+    assert_eq!(structured["panics"][0]["thread"], "main");
+    assert_eq!(
+        structured["panics"][0]["failure_locations"][1]["function"],
+        "test_project::main"
+    );
+
+    let _ = client.cancel().await;
+    Ok(())
+}
@@ -0,0 +1,82 @@
+//! rerun_failed runs only the tests that failed in an earlier test operation
+use anyhow::Result;
+mod common;
+use common::test_project::create_project_with_failing_test;
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn test_rerun_failed_runs_only_failures() -> Result<()> {
+    let temp = create_project_with_failing_test().await?;
+    let project_path = temp.path().to_str().unwrap();
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"]);
+            },
+        ))?)
+        .await?;
+
+    let text_of = |result: &rmcp::model::CallToolResult| {
+        result
+            .content
+            .first()
+            .and_then(|c| c.as_text())
+            .map(|t| t.text.clone())
+            .unwrap_or_default()
+    };
+
+    let unknown = client
+        .call_tool(CallToolRequestParam {
+            name: "rerun_failed".into(),
+            arguments: Some(object!({ "operation_id": "op_test_999" })),
+        })
+        .await?;
+    assert!(
+        text_of(&unknown).contains("no test or nextest run recorded"),
+        "Expected an unknown operation to be reported: {}",
+        text_of(&unknown)
+    );
+
+    let first = client
+        .call_tool(CallToolRequestParam {
+            name: "test".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let first_text = text_of(&first);
+    let test_id = first_text
+        .split("rerun_failed with operation_id=\"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .expect("a failing run should name its operation for rerun_failed")
+        .to_string();
+
+    let rerun = client
+        .call_tool(CallToolRequestParam {
+            name: "rerun_failed".into(),
+            arguments: Some(object!({
+                "operation_id": test_id.clone(),
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = text_of(&rerun);
+    assert!(
+        text.starts_with(&format!(
+            "Re-running 1 failed test(s) from {test_id}: tests::fails"
+        )) && text.contains("Tests: 0 passed, 1 failed, 0 ignored, 2 filtered out"),
+        "Expected only the failing test to run: {text}"
+    );
+
+    let _ = client.cancel().await;
+    Ok(())
+}
@@ -0,0 +1,84 @@
+//! An identical repeat check returns the cached result until the sources change or the request
+//! sets force
+use anyhow::Result;
+mod common;
+use common::test_project::create_basic_project;
+use rmcp::{
+    ServiceExt,
+    model::{CallToolRequestParam, CallToolResult},
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+fn text_of(result: &CallToolResult) -> String {
+    result
+        .content
+        .first()
+        .and_then(|c| c.as_text())
+        .map(|t| t.text.clone())
+        .unwrap_or_default()
+}
+
+#[tokio::test]
+async fn test_repeated_check_returns_cached_result() -> Result<()> {
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+    tokio::fs::write(
+        temp.path().join("src/main.rs"),
+        "fn main() {\n    let unused = 1;\n}\n",
+    )
+    .await?;
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args([
+                    "run",
+                    "--bin",
+                    "async_cargo_mcp",
+                    "--",
+                    "--cache-results",
+                    "check",
+                ]);
+            },
+        ))?)
+        .await?;
+
+    let check = |force: bool| {
+        client.call_tool(CallToolRequestParam {
+            name: "check".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "force": force,
+                "enable_async_notification": false
+            })),
+        })
+    };
+
+    let first = text_of(&check(false).await?);
+    assert!(
+        first.contains("Check operation completed successfully"),
+        "{first}"
+    );
+    assert!(!first.contains("Cached from"), "{first}");
+
+    let repeat = check(false).await?;
+    let text = text_of(&repeat);
+    assert!(text.starts_with("Cached from op_check_"), "{text}");
+    assert!(text.contains("unused variable"), "{text}");
+    let structured = repeat
+        .structured_content
+        .expect("cached check should keep its diagnostics");
+    assert_eq!(structured["warnings"], 1, "{structured}");
+
+    let forced = text_of(&check(true).await?);
+    assert!(!forced.contains("Cached from"), "{forced}");
+
+    tokio::fs::write(temp.path().join("src/main.rs"), "fn main() {}\n").await?;
+    let edited = text_of(&check(false).await?);
+    assert!(edited.contains("1 fixed"), "{edited}");
+    assert!(!edited.contains("Cached from"), "{edited}");
+
+    let _ = client.cancel().await;
+    Ok(())
+}
@@ -0,0 +1,291 @@
+//! semver_checks finds a removed function against a baseline checkout and drives
+//! bump_version's "auto" bump type when cargo-semver-checks is installed
+use anyhow::Result;
+mod common;
+use common::test_project::create_basic_project;
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn test_semver_checks_and_auto_bump_or_missing_tool() -> Result<()> {
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+    tokio::fs::write(
+        temp.path().join("src/lib.rs"),
+        "pub fn parse() {}\n\npub fn render() {}\n",
+    )
+    .await?;
+
+    // The baseline is a copy of the crate before parse was removed
+    let baseline = tempfile::tempdir()?;
+    tokio::fs::create_dir(baseline.path().join("src")).await?;
+    for file in ["Cargo.toml", "src/lib.rs", "src/main.rs"] {
+        tokio::fs::copy(temp.path().join(file), baseline.path().join(file)).await?;
+    }
+    tokio::fs::write(temp.path().join("src/lib.rs"), "pub fn render() {}\n").await?;
+
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"]);
+            },
+        ))?)
+        .await?;
+
+    let text_of = |result: &rmcp::model::CallToolResult| {
+        result
+            .content
+            .first()
+            .and_then(|c| c.as_text())
+            .map(|t| t.text.clone())
+            .unwrap_or_default()
+    };
+
+    // Without a semver_checks report there is nothing to base an automatic bump on
+    let auto = text_of(
+        &client
+            .call_tool(CallToolRequestParam {
+                name: "bump_version".into(),
+                arguments: Some(object!({
+                    "working_directory": project_path,
+                    "bump_type": "auto",
+                    "dry_run": true
+                })),
+            })
+            .await?,
+    );
+    assert!(
+        auto.starts_with("- Bump-version operation")
+            && auto.contains("needs a semver_checks report"),
+        "{auto}"
+    );
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "semver_checks".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "baseline_path": baseline.path().to_str().unwrap(),
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = text_of(&result);
+    if text.contains("cargo-semver-checks is not installed") {
+        assert!(text.starts_with("- Semver-checks operation"), "{text}");
+        let _ = client.cancel().await;
+        return Ok(());
+    }
+    assert!(
+        text.contains("Semver: minimum required bump major") && text.contains("function_missing"),
+        "{text}"
+    );
+    let structured = result
+        .structured_content
+        .expect("semver_checks should return structuredContent");
+    assert_eq!(
+        structured["semver"]["required_bump"], "major",
+        "{structured}"
+    );
+
+    // 0.1.0 with a breaking change needs a new minor version
+    let auto = text_of(
+        &client
+            .call_tool(CallToolRequestParam {
+                name: "bump_version".into(),
+                arguments: Some(object!({
+                    "working_directory": project_path,
+                    "bump_type": "auto",
+                    "dry_run": true
+                })),
+            })
+            .await?,
+    );
+    assert!(
+        auto.contains("Bump type minor chosen from semver_checks operation")
+            || auto.contains("cargo-edit"),
+        "{auto}"
+    );
+
+    let _ = client.cancel().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_semver_checks_report_drives_auto_bump_with_fake_tools() -> Result<()> {
+    use common::fake_tool::{add_cargo_subcommand, fake_cargo_subcommand, fixture, path_with};
+
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+    let semver_args = temp.path().join("semver-args");
+    let set_version_args = temp.path().join("set-version-args");
+    // A baseline_rev run finds a removed function; a baseline_version run finds nothing
+    let fake = fake_cargo_subcommand(
+        "semver-checks",
+        &format!(
+            r#"if [ "$2" = "--version" ]; then echo "cargo-semver-checks 0.41.0"; exit 0; fi
+shift
+echo "$@" >> "{args}"
+case "$*" in
+  *--baseline-rev*) cat "{major}" >&2; exit 1 ;;
+  *) cat "{patch}" >&2; exit 0 ;;
+esac
+"#,
+            args = semver_args.display(),
+            major = fixture("semver_checks_major.txt").display(),
+            patch = fixture("semver_checks_patch.txt").display(),
+        ),
+    )?;
+    add_cargo_subcommand(
+        fake.path(),
+        "set-version",
+        &format!(
+            r#"if [ "$2" = "--version" ]; then echo "cargo-set-version 0.13.1"; exit 0; fi
+shift
+echo "$@" >> "{args}"
+echo "   Upgrading test_project from 0.1.0 to 0.2.0" >&2
+echo "warning: aborting set-version due to dry run" >&2
+"#,
+            args = set_version_args.display(),
+        ),
+    )?;
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"])
+                    .env("PATH", path_with(fake.path()));
+            },
+        ))?)
+        .await?;
+
+    let text_of = |result: &rmcp::model::CallToolResult| {
+        result
+            .content
+            .first()
+            .and_then(|c| c.as_text())
+            .map(|t| t.text.clone())
+            .unwrap_or_default()
+    };
+    let operation_id = |text: &str| {
+        text.split('#')
+            .nth(1)
+            .and_then(|rest| rest.split_whitespace().next())
+            .map(str::to_string)
+            .unwrap_or_default()
+    };
+    let semver_checks = |baseline: serde_json::Value| {
+        let mut arguments = object!({
+            "working_directory": project_path,
+            "enable_async_notification": false
+        });
+        if let serde_json::Value::Object(baseline) = baseline {
+            arguments.extend(baseline);
+        }
+        client.call_tool(CallToolRequestParam {
+            name: "semver_checks".into(),
+            arguments: Some(arguments),
+        })
+    };
+    let auto_bump = |semver_operation_id: Option<String>| {
+        let mut arguments = object!({
+            "working_directory": project_path,
+            "bump_type": "auto",
+            "dry_run": true
+        });
+        if let Some(id) = semver_operation_id {
+            arguments.insert("semver_operation_id".into(), id.into());
+        }
+        client.call_tool(CallToolRequestParam {
+            name: "bump_version".into(),
+            arguments: Some(arguments),
+        })
+    };
+
+    let result = semver_checks(serde_json::json!({ "baseline_rev": "v0.1.0" })).await?;
+    let text = text_of(&result);
+    assert!(
+        text.starts_with("- Semver-checks operation #")
+            && text.contains("found changes the current version does not allow"),
+        "{text}"
+    );
+    assert!(
+        text.contains(
+            "Semver: minimum required bump major (1 breaking change(s), 0 warning(s))\n  test_project 0.1.0 -> 0.1.0 (no change): requires major\n  bump_version with bump_type \"auto\" would run cargo set-version --bump minor"
+        ),
+        "{text}"
+    );
+    assert!(
+        text.contains(
+            "- failure function_missing: pub fn removed or renamed\n    function test_project::parse, previously in file /tmp/baseline/src/lib.rs:1"
+        ),
+        "{text}"
+    );
+    let major_id = operation_id(&text);
+    assert!(major_id.starts_with("op_semver_checks_"), "{text}");
+    let structured = result
+        .structured_content
+        .expect("semver_checks should return structuredContent");
+    assert_eq!(
+        structured["semver"]["required_bump"], "major",
+        "{structured}"
+    );
+    assert_eq!(
+        structured["semver"]["changes"][0]["reference"],
+        "https://doc.rust-lang.org/cargo/reference/semver.html#item-remove"
+    );
+
+    let text = text_of(&semver_checks(serde_json::json!({ "baseline_version": "0.1.0" })).await?);
+    assert!(
+        text.starts_with("+ Semver-checks operation #")
+            && text.contains(
+                "Semver: minimum required bump patch (0 breaking change(s), 0 warning(s))"
+            ),
+        "{text}"
+    );
+    let patch_id = operation_id(&text);
+
+    // Without an operation id the latest report for the directory decides
+    let text = text_of(&auto_bump(None).await?);
+    assert!(
+        text.contains("completed successfully") && text.contains("(dry run - no changes made)"),
+        "{text}"
+    );
+    assert!(
+        text.contains(&format!(
+            "Bump type patch chosen from semver_checks operation {patch_id} (minimum required: patch)."
+        )),
+        "{text}"
+    );
+
+    // A breaking change before 1.0 needs a new minor version
+    let text = text_of(&auto_bump(Some(major_id.clone())).await?);
+    assert!(
+        text.contains(&format!(
+            "Bump type minor chosen from semver_checks operation {major_id} (minimum required: major)."
+        )),
+        "{text}"
+    );
+
+    let text = text_of(&auto_bump(Some("op_semver_checks_999".to_string())).await?);
+    assert!(
+        text.starts_with("- Bump-version operation")
+            && text.contains("needs a semver_checks report from operation op_semver_checks_999"),
+        "{text}"
+    );
+
+    let args = tokio::fs::read_to_string(&semver_args).await?;
+    assert_eq!(
+        args,
+        "--color never --baseline-rev v0.1.0\n--color never --baseline-version 0.1.0\n"
+    );
+    let args = tokio::fs::read_to_string(&set_version_args).await?;
+    assert_eq!(args, "--bump patch --dry-run\n--bump minor --dry-run\n");
+
+    let _ = client.cancel().await;
+    Ok(())
+}
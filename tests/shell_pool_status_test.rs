@@ -0,0 +1,55 @@
+//! The status overview reports per-directory shell pool statistics
+use anyhow::Result;
+mod common;
+use common::test_project::create_basic_project;
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn test_status_shows_shell_pool_stats() -> Result<()> {
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp", "--", "--prewarm-dir"])
+                    .arg(project_path);
+            },
+        ))?)
+        .await?;
+
+    // Build runs through the shell pool
+    client
+        .call_tool(CallToolRequestParam {
+            name: "build".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+
+    let status = client
+        .call_tool(CallToolRequestParam {
+            name: "status".into(),
+            arguments: Some(object!({ "working_directory": project_path })),
+        })
+        .await?;
+    let text = format!("{:?}", status.content);
+    assert!(
+        text.contains("Shell pools:"),
+        "Missing pool summary: {text}"
+    );
+    assert!(
+        text.contains(project_path) && text.contains("prewarmed") && text.contains("hit rate"),
+        "Missing per-directory pool stats: {text}"
+    );
+
+    let _ = client.cancel().await;
+    Ok(())
+}
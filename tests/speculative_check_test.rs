@@ -0,0 +1,101 @@
+//! With --speculative-check, an edited workspace is checked in the background and an explicit
+//! check returns that result while the sources are unchanged
+use anyhow::Result;
+mod common;
+use common::test_project::create_basic_project;
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use std::time::Duration;
+use tokio::process::Command;
+
+#[tokio::test]
+async fn test_check_returns_speculative_result_for_unchanged_sources() -> Result<()> {
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args([
+                    "run",
+                    "--bin",
+                    "async_cargo_mcp",
+                    "--",
+                    "--speculative-check",
+                ]);
+            },
+        ))?)
+        .await?;
+
+    let check = || {
+        client.call_tool(CallToolRequestParam {
+            name: "check".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "enable_async_notification": false
+            })),
+        })
+    };
+    let text_of = |result: &rmcp::model::CallToolResult| {
+        result
+            .content
+            .first()
+            .and_then(|c| c.as_text())
+            .map(|t| t.text.clone())
+            .unwrap_or_default()
+    };
+
+    // The first request names the workspace, which starts watching it
+    let text = text_of(&check().await?);
+    assert!(
+        text.contains("Check operation completed successfully"),
+        "{text}"
+    );
+    assert!(!text.contains("speculative check"), "{text}");
+
+    tokio::fs::write(
+        temp.path().join("src/main.rs"),
+        "fn main() {\n    let unused = 1;\n}\n",
+    )
+    .await?;
+
+    let mut last = String::new();
+    for _ in 0..120 {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let result = check().await?;
+        last = text_of(&result);
+        if last.starts_with("Sources unchanged since speculative check op_speculative_check_") {
+            assert!(last.contains("unused variable"), "{last}");
+            let structured = result
+                .structured_content
+                .expect("cached check should keep its diagnostics");
+            assert_eq!(structured["warnings"], 1, "{structured}");
+
+            // Returning the speculative result made it the baseline under this check's id; the
+            // speculative run itself never became one
+            let forced = text_of(
+                &client
+                    .call_tool(CallToolRequestParam {
+                        name: "check".into(),
+                        arguments: Some(object!({
+                            "working_directory": project_path,
+                            "force": true,
+                            "enable_async_notification": false
+                        })),
+                    })
+                    .await?,
+            );
+            assert!(
+                forced.contains("Changes since op_check_")
+                    && !forced.contains("Changes since op_speculative_check_"),
+                "{forced}"
+            );
+            let _ = client.cancel().await;
+            return Ok(());
+        }
+    }
+    panic!("check never returned a speculative result: {last}");
+}
@@ -0,0 +1,129 @@
+//! check/clippy return compiler diagnostics as structuredContent alongside the rendered text
+use anyhow::Result;
+mod common;
+use common::test_project::create_project_with_warning;
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn test_check_returns_structured_diagnostics() -> Result<()> {
+    let temp = create_project_with_warning().await?;
+    let project_path = temp.path().to_str().unwrap();
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"]);
+            },
+        ))?)
+        .await?;
+
+    let check = client
+        .call_tool(CallToolRequestParam {
+            name: "check".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+
+    let text = format!("{:?}", check.content);
+    assert!(
+        text.contains("unused variable: `unused_variable`")
+            && text.contains("Diagnostics: 0 errors, 1 warning")
+            && !text.contains("\\\"reason\\\""),
+        "Expected rendered diagnostics instead of raw JSON: {text}"
+    );
+
+    let structured = check
+        .structured_content
+        .expect("check should return structuredContent");
+    assert_eq!(structured["success"], true);
+    assert_eq!(structured["warnings"], 1);
+    let diagnostic = &structured["diagnostics"][0];
+    assert_eq!(diagnostic["level"], "warning");
+    assert_eq!(diagnostic["code"], "unused_variables");
+    assert_eq!(diagnostic["span"]["file"], "src/main.rs");
+    assert_eq!(diagnostic["span"]["line_start"], 2);
+    assert_eq!(
+        diagnostic["suggestions"][0]["replacement"],
+        "_unused_variable"
+    );
+    assert_eq!(
+        diagnostic["suggestions"][0]["applicability"],
+        "MachineApplicable"
+    );
+
+    let _ = client.cancel().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_repeated_check_reports_delta() -> Result<()> {
+    let temp = create_project_with_warning().await?;
+    let project_path = temp.path().to_str().unwrap();
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"]);
+            },
+        ))?)
+        .await?;
+    let check = || {
+        client.call_tool(CallToolRequestParam {
+            name: "check".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "enable_async_notification": false
+            })),
+        })
+    };
+
+    let first = check().await?;
+    assert!(
+        first
+            .structured_content
+            .as_ref()
+            .is_some_and(|s| s.get("delta").is_none()),
+        "The first run has no baseline: {:?}",
+        first.structured_content
+    );
+
+    // Fix the existing warning and introduce a different one
+    std::fs::write(
+        temp.path().join("src/main.rs"),
+        "fn main() {\n    let other = 1;\n    println!(\"Hello, world!\");\n}\n",
+    )?;
+    let second = check().await?;
+    let text = second
+        .content
+        .first()
+        .and_then(|c| c.as_text())
+        .map(|t| t.text.clone())
+        .unwrap_or_default();
+    assert!(
+        text.contains("1 fixed, 1 new, 0 unchanged")
+            && text
+                .contains("- fixed warning[unused_variables]: unused variable: `unused_variable`")
+            && text.contains("+ new warning[unused_variables]: unused variable: `other` (line 2)"),
+        "Expected a delta against the first check: {text}"
+    );
+
+    let delta = &second
+        .structured_content
+        .expect("check should return structuredContent")["delta"];
+    assert!(
+        delta["previous_operation_id"]
+            .as_str()
+            .is_some_and(|id| id.starts_with("op_check_"))
+    );
+    assert_eq!(delta["files"]["src/main.rs"]["new"][0]["line"], 2);
+
+    let _ = client.cancel().await;
+    Ok(())
+}
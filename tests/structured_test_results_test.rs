@@ -0,0 +1,166 @@
+//! test returns per-test results with panic message and location as structuredContent
+use anyhow::Result;
+mod common;
+use common::test_project::create_project_with_failing_test;
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn test_failing_test_is_reported_with_location() -> Result<()> {
+    let temp = create_project_with_failing_test().await?;
+    let project_path = temp.path().to_str().unwrap();
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"]);
+            },
+        ))?)
+        .await?;
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "test".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+
+    let text = result
+        .content
+        .first()
+        .and_then(|c| c.as_text())
+        .map(|t| t.text.clone())
+        .unwrap_or_default();
+    assert!(
+        text.contains("Tests: 1 passed, 1 failed, 1 ignored")
+            && text.contains(
+                "FAILED tests::fails (unittests src/main.rs) at src/main.rs:11:9: assertion `left == right` failed: math is broken"
+            ),
+        "Expected a test summary naming the failure: {text}"
+    );
+
+    let structured = result
+        .structured_content
+        .expect("test should return structuredContent");
+    let tests = &structured["tests"];
+    assert_eq!(tests["failed"], 1);
+    let failed = tests["tests"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|t| t["status"] == "failed")
+        .expect("the failing test should be listed");
+    assert_eq!(failed["name"], "tests::fails");
+    assert_eq!(failed["stdout"], "about to fail");
+    assert_eq!(failed["panic_location"]["file"], "src/main.rs");
+    assert_eq!(failed["panic_location"]["line"], 11);
+    assert!(
+        failed["panic_message"]
+            .as_str()
+            .is_some_and(|m| m.contains("math is broken"))
+    );
+
+    let _ = client.cancel().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_args_after_separator_reach_the_test_binary() -> Result<()> {
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"]);
+            },
+        ))?)
+        .await?;
+    let run_tests = |project_path: String, test_args: Vec<&'static str>| {
+        let client = &client;
+        async move {
+            let mut arguments = object!({
+                "working_directory": project_path,
+                "args": ["--", "tests::"],
+                "enable_async_notification": false
+            });
+            if !test_args.is_empty() {
+                arguments.insert("test_args".into(), test_args.into());
+            }
+            client
+                .call_tool(CallToolRequestParam {
+                    name: "test".into(),
+                    arguments: Some(arguments),
+                })
+                .await
+        }
+    };
+    let status_of = |structured: &serde_json::Value, name: &str| {
+        structured["tests"]["tests"]
+            .as_array()
+            .and_then(|tests| tests.iter().find(|t| t["name"] == name))
+            .map(|t| t["status"].clone())
+    };
+
+    // test_args join the filter after the user's `--` instead of becoming filters themselves
+    let temp = create_project_with_failing_test().await?;
+    let result = run_tests(
+        temp.path().to_str().unwrap().to_string(),
+        vec!["--include-ignored"],
+    )
+    .await?;
+    let structured = result
+        .structured_content
+        .expect("test should return structuredContent");
+    assert_eq!(
+        status_of(&structured, "tests::slow"),
+        Some("passed".into()),
+        "{structured}"
+    );
+    assert_eq!(structured["tests"]["failed"], 1, "{structured}");
+
+    // On nightly the JSON event arguments share the same `--`, so durations come through
+    let nightly = create_project_with_failing_test().await?;
+    tokio::fs::write(
+        nightly.path().join("rust-toolchain.toml"),
+        "[toolchain]\nchannel = \"nightly\"\n",
+    )
+    .await?;
+    let version = Command::new("rustc")
+        .arg("--version")
+        .current_dir(nightly.path())
+        .output()
+        .await?;
+    if !String::from_utf8_lossy(&version.stdout).contains("-nightly") {
+        let _ = client.cancel().await;
+        return Ok(());
+    }
+    let result = run_tests(nightly.path().to_str().unwrap().to_string(), Vec::new()).await?;
+    let structured = result
+        .structured_content
+        .expect("test should return structuredContent");
+    assert_eq!(
+        status_of(&structured, "tests::passes"),
+        Some("passed".into()),
+        "{structured}"
+    );
+    assert_eq!(
+        status_of(&structured, "tests::slow"),
+        Some("ignored".into()),
+        "{structured}"
+    );
+    let passes = structured["tests"]["tests"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|t| t["name"] == "tests::passes")
+        .unwrap();
+    assert!(passes["duration_secs"].is_number(), "{structured}");
+
+    let _ = client.cancel().await;
+    Ok(())
+}
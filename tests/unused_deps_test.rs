@@ -0,0 +1,208 @@
+//! unused_deps finds and removes a dependency nothing uses when cargo-machete is installed
+use anyhow::Result;
+mod common;
+use common::test_project::create_basic_project;
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn test_unused_deps_fix_or_missing_tool() -> Result<()> {
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+
+    // A path dependency keeps the test offline
+    let helper = tempfile::tempdir()?;
+    tokio::fs::create_dir(helper.path().join("src")).await?;
+    tokio::fs::write(
+        helper.path().join("Cargo.toml"),
+        "[package]\nname = \"helper\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )
+    .await?;
+    tokio::fs::write(helper.path().join("src/lib.rs"), "pub fn help() {}\n").await?;
+    let manifest = tokio::fs::read_to_string(temp.path().join("Cargo.toml")).await?;
+    let manifest = if manifest.contains("[dependencies]") {
+        manifest.replace(
+            "[dependencies]",
+            &format!(
+                "[dependencies]\nhelper = {{ path = {:?} }}",
+                helper.path().to_str().unwrap()
+            ),
+        )
+    } else {
+        format!(
+            "{manifest}\n[dependencies]\nhelper = {{ path = {:?} }}\n",
+            helper.path().to_str().unwrap()
+        )
+    };
+    tokio::fs::write(temp.path().join("Cargo.toml"), manifest).await?;
+
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"]);
+            },
+        ))?)
+        .await?;
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "unused_deps".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "fix": true,
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = result
+        .content
+        .first()
+        .and_then(|c| c.as_text())
+        .map(|t| t.text.clone())
+        .unwrap_or_default();
+    if text.contains("cargo-machete is not installed") {
+        assert!(text.starts_with("- Unused-deps operation"), "{text}");
+        let _ = client.cancel().await;
+        return Ok(());
+    }
+    assert!(
+        text.contains("helper (normal)") && text.contains("check passed"),
+        "{text}"
+    );
+    let structured = result
+        .structured_content
+        .expect("unused_deps should return structuredContent");
+    assert_eq!(
+        structured["unused_deps"]["dependencies"][0]["name"], "helper",
+        "{structured}"
+    );
+    let manifest = tokio::fs::read_to_string(temp.path().join("Cargo.toml")).await?;
+    assert!(!manifest.contains("helper"), "{manifest}");
+
+    let _ = client.cancel().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unused_deps_fix_removes_reported_dependencies_with_fake_machete() -> Result<()> {
+    use common::fake_tool::{fake_cargo_subcommand, path_with};
+
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+
+    // helper is declared as both a normal and a dev dependency and used by neither; kept is used
+    let deps = tempfile::tempdir()?;
+    for name in ["helper", "kept"] {
+        let dir = deps.path().join(name);
+        tokio::fs::create_dir_all(dir.join("src")).await?;
+        tokio::fs::write(
+            dir.join("Cargo.toml"),
+            format!("[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n"),
+        )
+        .await?;
+        tokio::fs::write(dir.join("src/lib.rs"), "pub fn help() {}\n").await?;
+    }
+    let path_of = |name: &str| deps.path().join(name).to_str().unwrap().to_string();
+    let manifest = tokio::fs::read_to_string(temp.path().join("Cargo.toml")).await?;
+    let manifest = manifest.replace("[dependencies]\n", "");
+    tokio::fs::write(
+        temp.path().join("Cargo.toml"),
+        format!(
+            "{manifest}\n[dependencies]\nhelper = {{ path = {helper:?} }}\nkept = {{ path = {kept:?} }}\n\n[dev-dependencies]\nhelper = {{ path = {helper:?} }}\n",
+            helper = path_of("helper"),
+            kept = path_of("kept"),
+        ),
+    )
+    .await?;
+    tokio::fs::write(
+        temp.path().join("src/main.rs"),
+        "fn main() {\n    kept::help();\n}\n",
+    )
+    .await?;
+
+    let fake = fake_cargo_subcommand(
+        "machete",
+        &format!(
+            r#"if [ "$2" = "--version" ]; then echo "cargo-machete 0.8.0"; exit 0; fi
+shift
+echo "$@" > "{args}"
+dir=$(pwd -P)
+echo "Analyzing dependencies of crates in this directory..."
+echo "cargo-machete found the following unused dependencies in $dir:"
+echo "test_project -- $dir/Cargo.toml:"
+printf '\thelper\n'
+echo ""
+echo "If you believe cargo-machete has detected an unused dependency incorrectly,"
+exit 1
+"#,
+            args = temp.path().join("machete-args").display(),
+        ),
+    )?;
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"])
+                    .env("PATH", path_with(fake.path()));
+            },
+        ))?)
+        .await?;
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "unused_deps".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "fix": true,
+                "args": ["--with-metadata"],
+                "enable_async_notification": false
+            })),
+        })
+        .await?;
+    let text = result
+        .content
+        .first()
+        .and_then(|c| c.as_text())
+        .map(|t| t.text.clone())
+        .unwrap_or_default();
+    assert!(
+        text.starts_with("+ Unused-deps operation #")
+            && text.contains("removed the unused dependencies")
+            && text.contains("check passed"),
+        "{text}"
+    );
+    assert!(
+        text.contains("Unused dependencies: 2 in 1 package(s) (found by cargo-machete)")
+            && text.contains("): helper (normal), helper (dev)"),
+        "{text}"
+    );
+
+    let manifest = tokio::fs::read_to_string(temp.path().join("Cargo.toml")).await?;
+    assert!(!manifest.contains("helper"), "{manifest}");
+    assert!(manifest.contains("kept = "), "{manifest}");
+
+    let structured = result
+        .structured_content
+        .expect("unused_deps should return structuredContent");
+    let dependencies = structured["unused_deps"]["dependencies"]
+        .as_array()
+        .expect("dependencies should be an array");
+    let kinds: Vec<(&str, &str)> = dependencies
+        .iter()
+        .map(|d| (d["name"].as_str().unwrap(), d["kind"].as_str().unwrap()))
+        .collect();
+    assert_eq!(kinds, vec![("helper", "normal"), ("helper", "dev")]);
+    // The confirming check recorded its own result under the same operation
+    assert_eq!(structured["success"], true, "{structured}");
+    assert_eq!(structured["errors"], 0, "{structured}");
+
+    let args = tokio::fs::read_to_string(temp.path().join("machete-args")).await?;
+    assert_eq!(args.trim(), "--with-metadata");
+
+    let _ = client.cancel().await;
+    Ok(())
+}
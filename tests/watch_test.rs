@@ -0,0 +1,210 @@
+//! watch_start runs its chain on start and after file changes; watch_status and watch_stop
+use anyhow::Result;
+mod common;
+use common::test_project::create_basic_project;
+use rmcp::{
+    ServiceExt,
+    model::CallToolRequestParam,
+    object,
+    transport::{ConfigureCommandExt, TokioChildProcess},
+};
+use std::time::Duration;
+use tokio::process::Command;
+
+#[tokio::test]
+async fn test_watch_session_reruns_check_on_change() -> Result<()> {
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args(["run", "--bin", "async_cargo_mcp"]);
+            },
+        ))?)
+        .await?;
+
+    let text_of = |result: &rmcp::model::CallToolResult| {
+        result
+            .content
+            .first()
+            .and_then(|c| c.as_text())
+            .map(|t| t.text.clone())
+            .unwrap_or_default()
+    };
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "watch_start".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "steps": ["check"],
+                "debounce_ms": 200
+            })),
+        })
+        .await?;
+    let text = text_of(&result);
+    assert!(text.starts_with("+ Started Watch session "), "{text}");
+    let session_id = text
+        .split_whitespace()
+        .nth(4)
+        .expect("session id in the start message")
+        .to_string();
+
+    // Polls watch_status until its text satisfies `done` while no run is in progress
+    let wait_for = |done: fn(&str) -> bool| {
+        let client = &client;
+        let session_id = session_id.clone();
+        async move {
+            let mut last = String::new();
+            for _ in 0..600 {
+                let result = client
+                    .call_tool(CallToolRequestParam {
+                        name: "watch_status".into(),
+                        arguments: Some(object!({ "session_id": session_id.clone() })),
+                    })
+                    .await
+                    .expect("watch_status");
+                let structured = result.structured_content.clone().unwrap_or_default();
+                last = text_of(&result);
+                if structured["watch"]["running"] == false && done(&last) {
+                    return result;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            panic!("watch session never reached the expected state: {last}");
+        }
+    };
+
+    let first = wait_for(|text| text.contains("Run #1 at start")).await;
+    let text = text_of(&first);
+    assert!(text.contains("+ check (0 errors"), "{text}");
+
+    // The first check may write Cargo.lock, which is a change like any other
+    tokio::fs::write(
+        temp.path().join("src/main.rs"),
+        "fn main() {\n    let x: u32 = \"not a number\";\n}\n",
+    )
+    .await?;
+    let broken = wait_for(|text| text.contains("- check (1 error")).await;
+    let changed = &broken.structured_content.as_ref().unwrap()["watch"]["latest"]["changed"];
+    assert!(
+        changed.as_array().unwrap().contains(&"src/main.rs".into()),
+        "{changed}"
+    );
+    let structured = broken.structured_content.unwrap();
+    assert_eq!(
+        structured["watch"]["latest"]["steps"][0]["report"]["diagnostics"][0]["code"], "E0308",
+        "{structured}"
+    );
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "watch_start".into(),
+            arguments: Some(object!({ "working_directory": project_path })),
+        })
+        .await?;
+    assert!(
+        text_of(&result).contains("is already watched by session"),
+        "{}",
+        text_of(&result)
+    );
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "watch_stop".into(),
+            arguments: Some(object!({ "session_id": session_id.clone() })),
+        })
+        .await?;
+    assert!(
+        text_of(&result).starts_with(&format!("+ Stopped watch session {session_id}")),
+        "{}",
+        text_of(&result)
+    );
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "watch_status".into(),
+            arguments: Some(object!({})),
+        })
+        .await?;
+    assert_eq!(
+        text_of(&result),
+        "No watch sessions. Start one with watch_start."
+    );
+
+    let _ = client.cancel().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_watch_marks_isolated_target_dir_and_enforces_budget() -> Result<()> {
+    let temp = create_basic_project().await?;
+    let project_path = temp.path().to_str().unwrap();
+    let isolated = temp.path().join("target/async_cargo_mcp");
+    // A stale clippy dir over the 1 MiB budget
+    tokio::fs::create_dir_all(isolated.join("clippy")).await?;
+    tokio::fs::write(isolated.join("clippy/blob"), vec![0u8; 2 * 1024 * 1024]).await?;
+
+    let client = ()
+        .serve(TokioChildProcess::new(Command::new("cargo").configure(
+            |cmd| {
+                cmd.args([
+                    "run",
+                    "--bin",
+                    "async_cargo_mcp",
+                    "--",
+                    "--isolate-target-dir",
+                    "check,clippy",
+                    "--isolated-target-max-mib",
+                    "1",
+                ]);
+            },
+        ))?)
+        .await?;
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "watch_start".into(),
+            arguments: Some(object!({
+                "working_directory": project_path,
+                "steps": ["check"],
+                "debounce_ms": 200
+            })),
+        })
+        .await?;
+    let session_id = result
+        .content
+        .first()
+        .and_then(|c| c.as_text())
+        .and_then(|t| t.text.split_whitespace().nth(4).map(str::to_string))
+        .expect("session id in the start message");
+
+    let mut finished = false;
+    for _ in 0..600 {
+        let result = client
+            .call_tool(CallToolRequestParam {
+                name: "watch_status".into(),
+                arguments: Some(object!({ "session_id": session_id.clone() })),
+            })
+            .await?;
+        let structured = result.structured_content.unwrap_or_default();
+        if structured["watch"]["running"] == false && !structured["watch"]["latest"].is_null() {
+            finished = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert!(finished, "the watch session never finished its first run");
+
+    assert!(
+        isolated.join("check/.async_cargo_mcp_last_used").is_file(),
+        "the watch run should stamp its isolated target dir"
+    );
+    assert!(
+        !isolated.join("clippy").exists(),
+        "the watch run should prune the stale isolated target dir"
+    );
+
+    let _ = client.cancel().await;
+    Ok(())
+}